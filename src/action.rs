@@ -0,0 +1,301 @@
+use super::*;
+
+/// Every action which the user can perform.
+///
+/// All input is mapped to an `Action` (see [`Action::from_key()`]), which is
+/// then performed by the [`Model`](crate::model::Model). This allows actions to
+/// be queued, and recorded into macros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Toggles playback, restarting it if the end has been reached.
+    TogglePlayback,
+    /// Stops and resets playback to the beginning.
+    Stop,
+    /// Moves the playback position by a number of frames of playback, or
+    /// backwards if negative.
+    Scrub(i32),
+    /// Marks a point of the A–B loop, or clears the loop if both points are
+    /// marked.
+    MarkLoopPoint,
+    /// Clears the A–B loop.
+    ClearLoop,
+    /// Adds a marker at the playback position, or removes the marker which is
+    /// already there.
+    ToggleMarker,
+    /// Removes every marker from the current capture.
+    ClearMarkers,
+    /// Moves the playback position to the next marker.
+    NextMarker,
+    /// Moves the playback position to the previous marker.
+    PreviousMarker,
+    /// Computes the current algorithm.
+    Compute,
+    /// Cancels the algorithm being computed.
+    CancelCompute,
+    /// Shuffles the wheel.
+    Shuffle,
+    /// Force-sorts the wheel.
+    ForceSort,
+    /// Cycles to the next algorithm.
+    NextAlgorithm,
+    /// Cycles to the previous algorithm.
+    PreviousAlgorithm,
+    /// Selects an algorithm.
+    SelectAlgorithm(SortingAlgorithm),
+    /// Shuffles the wheel, then runs the current algorithm.
+    ShuffleAndSort,
+    /// Shuffles the wheel, then runs the next algorithm.
+    ShuffleAndSortNext,
+    /// Shuffles the wheel, then runs the previous algorithm.
+    ShuffleAndSortPrevious,
+    /// Toggles between the moving-window and Fisher–Yates shuffles.
+    ToggleUniformShuffle,
+    IncreaseShuffleIntensity,
+    DecreaseShuffleIntensity,
+    /// Cycles to the next input distribution.
+    NextDistribution,
+    /// Cycles to the previous input distribution.
+    PreviousDistribution,
+    IncreaseResolution,
+    DecreaseResolution,
+    /// Toggles whether operations of a kind are highlighted and sonified.
+    ToggleOperationKind(OperationKind),
+    /// Only highlights and sonifies operations of a kind, or shows every
+    /// operation again if it is already the only kind shown.
+    SoloOperationKind(OperationKind),
+    /// Toggles whether operations of a kind are sonified, while still
+    /// highlighting them.
+    ToggleOperationSound(OperationKind),
+    /// Cycles to the next playback curve.
+    NextPlaybackCurve,
+    /// Cycles to the previous playback curve.
+    PreviousPlaybackCurve,
+    /// Cycles to the next color palette.
+    NextPalette,
+    /// Toggles colorblind-safe mode.
+    ToggleColorblindMode,
+    IncreaseOverlayIntensity,
+    DecreaseOverlayIntensity,
+    IncreaseSpeed,
+    DecreaseSpeed,
+    /// Sets the playback speed to one of [`Player::SPEED_PRESETS`].
+    SpeedPreset(usize),
+    /// Resets the playback speed to 1x.
+    ResetSpeed,
+    IncreaseAvOffset,
+    DecreaseAvOffset,
+    /// Estimates the audio/visual offset from the audio buffer size and the
+    /// frame time, without measuring anything.
+    EstimateAvOffset,
+    /// Toggles playing sorts back at the pace they were computed at.
+    ToggleRealTime,
+    /// Toggles audio mute.
+    ToggleAudio,
+    /// Cycles to the next scale which notes are quantized to.
+    NextScale,
+    /// Moves the root note of the scale up by a semitone.
+    NextRootNote,
+    /// Cycles to the next way of placing notes in the stereo field.
+    NextPanning,
+    /// Cycles to the next amplitude envelope preset.
+    NextEnvelope,
+    /// Cycles to the next tone control preset.
+    NextTonePreset,
+    /// Cycles to the next delay mode.
+    NextDelayMode,
+    /// Cycles to the next reverb mix level.
+    NextReverbMix,
+    /// Cycles to the next polyphony cap.
+    NextPolyphonyCap,
+    /// Cycles to the next behavior for new notes once every voice is in use.
+    NextVoiceSteal,
+    /// Toggles editing the wheel with the mouse.
+    ToggleEditing,
+    /// Clears the selected region, so that algorithms run on the whole wheel.
+    ClearRegion,
+    /// Toggles the target ring around the wheel.
+    ToggleTarget,
+    /// Toggles the persistent trail left by writes.
+    ToggleWriteTrail,
+    /// Toggles the heat map ring around the wheel.
+    ToggleHeatMap,
+    /// Toggles the cross-fade between the colors of swapped slices.
+    ToggleSwapAnimation,
+    /// Toggles the highlight of the slices in their final positions.
+    ToggleSortedRegion,
+    /// Toggles the pointers drawn around the wheel.
+    TogglePointers,
+    /// Toggles the outlines between the slices of the wheel.
+    ToggleOutlines,
+    /// Cycles to the next way of drawing the array.
+    NextView,
+    /// Cycles to the previous way of drawing the array.
+    PreviousView,
+    /// Saves the current capture to disk.
+    SaveCapture,
+    /// Loads the capture saved to disk.
+    LoadCapture,
+    /// Adds the current algorithm to the end of the playlist.
+    AddToPlaylist,
+    /// Removes the last algorithm from the playlist.
+    RemoveFromPlaylist,
+    /// Shuffles, sorts and plays each algorithm in the playlist in turn.
+    PlayPlaylist,
+    /// Toggles tour mode, where every algorithm is shuffled, sorted and played
+    /// in turn.
+    ToggleTour,
+    /// Toggles race mode, where the current algorithm becomes an opponent
+    /// which is raced against on a second wheel.
+    ToggleRace,
+    /// Replays the most recent capture in the history.
+    ReplayOlderCapture,
+    /// Replays the oldest capture in the history.
+    ReplayNewerCapture,
+    /// Exports the current capture to a text format.
+    ExportCapture(ExportFormat),
+    /// Saves the next frame to a PNG.
+    Screenshot,
+    /// Starts or stops exporting the current capture frame by frame.
+    ToggleFrameExport,
+    /// Starts or finishes recording a macro.
+    ToggleMacroRecording,
+    /// Queues the actions of the recorded macro.
+    PlayMacro,
+}
+
+impl Action {
+    /// The number of frames which are scrubbed through with Shift held.
+    const LARGE_SCRUB_FRAMES: i32 = 30;
+
+    /// Returns the action bound to `key`, if there is one.
+    pub const fn from_key(key: Key, shift: bool) -> Option<Self> {
+        let action = match key {
+            // "play/pause"
+            Key::Space => Self::TogglePlayback,
+            // "stop"
+            Key::Back | Key::Delete => Self::Stop,
+            Key::Left if shift => Self::Scrub(-Self::LARGE_SCRUB_FRAMES),
+            Key::Left => Self::Scrub(-1),
+            Key::Right if shift => Self::Scrub(Self::LARGE_SCRUB_FRAMES),
+            Key::Right => Self::Scrub(1),
+            // A–B loop
+            Key::B if shift => Self::ClearLoop,
+            Key::B => Self::MarkLoopPoint,
+            // markers
+            Key::Z if shift => Self::ClearMarkers,
+            Key::Z => Self::ToggleMarker,
+            Key::Up => Self::NextMarker,
+            Key::Down => Self::PreviousMarker,
+            // sound settings
+            Key::R if shift => Self::NextReverbMix,
+            Key::A if shift => Self::NextTonePreset,
+            Key::Y if shift => Self::NextPolyphonyCap,
+            Key::K if shift => Self::NextRootNote,
+            Key::L if shift => Self::NextDelayMode,
+            // view toggles
+            Key::F if shift => Self::ToggleSortedRegion,
+            Key::O if shift => Self::ToggleOutlines,
+            Key::I if shift => Self::TogglePointers,
+            // screenshots and frame export
+            Key::S if shift => Self::Screenshot,
+            Key::V if shift => Self::ToggleFrameExport,
+            // "recompute"
+            Key::R => Self::Compute,
+            // "abort"
+            Key::A => Self::CancelCompute,
+            // "shuffle"
+            Key::S => Self::Shuffle,
+            // "force-sort"
+            Key::F => Self::ForceSort,
+            Key::Return if shift => Self::PreviousAlgorithm,
+            Key::Return => Self::NextAlgorithm,
+            Key::N if shift => Self::ShuffleAndSortPrevious,
+            Key::N => Self::ShuffleAndSortNext,
+            Key::U if shift => Self::NextPanning,
+            Key::U => Self::ToggleUniformShuffle,
+            Key::Apostrophe => Self::IncreaseShuffleIntensity,
+            Key::Semicolon => Self::DecreaseShuffleIntensity,
+            Key::D if shift => Self::PreviousDistribution,
+            Key::D => Self::NextDistribution,
+            Key::Plus | Key::Equals => Self::IncreaseResolution,
+            Key::Underline | Key::Minus => Self::DecreaseResolution,
+            Key::G if shift => Self::PreviousPlaybackCurve,
+            Key::G => Self::NextPlaybackCurve,
+            Key::Period => Self::IncreaseSpeed,
+            Key::Comma => Self::DecreaseSpeed,
+            // speed presets
+            Key::Key1 => Self::SpeedPreset(0),
+            Key::Key2 => Self::SpeedPreset(1),
+            Key::Key3 => Self::SpeedPreset(2),
+            Key::Key4 => Self::SpeedPreset(3),
+            Key::Key5 => Self::SpeedPreset(4),
+            Key::Key0 => Self::ResetSpeed,
+            // operation filters
+            Key::Key6 if shift => {
+                Self::SoloOperationKind(OperationKind::Writes)
+            }
+            Key::Key6 => Self::ToggleOperationKind(OperationKind::Writes),
+            Key::Key7 if shift => Self::SoloOperationKind(OperationKind::Reads),
+            Key::Key7 => Self::ToggleOperationKind(OperationKind::Reads),
+            Key::Key8 if shift => Self::SoloOperationKind(OperationKind::Swaps),
+            Key::Key8 => Self::ToggleOperationKind(OperationKind::Swaps),
+            Key::Key9 if shift => {
+                Self::SoloOperationKind(OperationKind::Comparisons)
+            }
+            Key::Key9 => Self::ToggleOperationKind(OperationKind::Comparisons),
+            // operation sounds
+            Key::F6 => Self::ToggleOperationSound(OperationKind::Writes),
+            Key::F7 => Self::ToggleOperationSound(OperationKind::Reads),
+            Key::F8 => Self::ToggleOperationSound(OperationKind::Swaps),
+            Key::F9 => Self::ToggleOperationSound(OperationKind::Comparisons),
+            // audio/visual offset (and overlay intensity)
+            Key::RBracket if shift => Self::IncreaseOverlayIntensity,
+            Key::RBracket => Self::IncreaseAvOffset,
+            Key::LBracket if shift => Self::DecreaseOverlayIntensity,
+            Key::LBracket => Self::DecreaseAvOffset,
+            Key::C if shift => Self::NextPalette,
+            Key::C => Self::EstimateAvOffset,
+            Key::P if shift => Self::ToggleColorblindMode,
+            Key::P => Self::ToggleRealTime,
+            Key::M if shift => Self::NextScale,
+            Key::M => Self::ToggleAudio,
+            Key::E if shift => Self::NextEnvelope,
+            Key::E => Self::ToggleEditing,
+            Key::X if shift => Self::NextVoiceSteal,
+            Key::X => Self::ClearRegion,
+            Key::T if shift => Self::ToggleHeatMap,
+            Key::T => Self::ToggleTarget,
+            Key::W if shift => Self::ToggleSwapAnimation,
+            Key::W => Self::ToggleWriteTrail,
+            Key::Slash if shift => Self::PreviousView,
+            Key::Slash => Self::NextView,
+            // saving and loading
+            Key::O => Self::SaveCapture,
+            Key::I => Self::LoadCapture,
+            Key::Tab => Self::ToggleTour,
+            // "queue"
+            Key::Q if shift => Self::RemoveFromPlaylist,
+            Key::Q => Self::AddToPlaylist,
+            Key::Y => Self::PlayPlaylist,
+            // "versus"
+            Key::V => Self::ToggleRace,
+            // capture history
+            Key::H if shift => Self::ReplayNewerCapture,
+            Key::H => Self::ReplayOlderCapture,
+            Key::J if shift => Self::ExportCapture(ExportFormat::Csv),
+            Key::J => Self::ExportCapture(ExportFormat::Json),
+            // macros
+            Key::K => Self::ToggleMacroRecording,
+            Key::L => Self::PlayMacro,
+            _ => return None,
+        };
+
+        Some(action)
+    }
+
+    /// Whether this action controls macros, in which case it is never
+    /// recorded into one.
+    pub const fn is_macro_control(self) -> bool {
+        matches!(self, Self::ToggleMacroRecording | Self::PlayMacro)
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use nannou::rand::random_range;
+use crate::rng::random_range;
 
 /// A bogosort.
 #[derive(Debug)]
@@ -42,7 +42,9 @@ impl SortProcessor for Counting {
 
         for i in (0..n).rev() {
             let arr_i = arr.read(i);
-            self.output_arr[self.counting_arr[arr_i] - 1] = arr_i;
+            let pos = self.counting_arr[arr_i] - 1;
+            self.output_arr[pos] = arr_i;
+            arr.aux_write(pos, arr_i);
 
             self.counting_arr[arr_i] -= 1;
         }
@@ -5,6 +5,8 @@ pub struct Heap;
 
 impl Heap {
     fn heapify(arr: &mut SortArray, n: usize, i: usize) {
+        arr.point(Pointer::Root, Some(i));
+
         let mut max = i;
 
         let l = 2 * i + 1;
@@ -28,11 +30,15 @@ impl SortProcessor for Heap {
     fn process(&mut self, arr: &mut SortArray) {
         let len = arr.len();
 
+        arr.point(Pointer::High, Some(len - 1));
+
         for i in (0..len / 2).rev() {
             Self::heapify(arr, len, i);
         }
 
         for i in (1..len).rev() {
+            // the end of the heap, which is swapped with its largest element.
+            arr.point(Pointer::High, Some(i));
             arr.swap(0, i);
             Self::heapify(arr, i, 0);
         }
@@ -30,16 +30,22 @@ impl Merge {
         mid: usize,
         right: usize,
     ) {
+        arr.point(Pointer::Low, Some(left));
+        arr.point(Pointer::Mid, Some(mid + 1));
+        arr.point(Pointer::High, Some(right));
+
         let left_len = mid - left + 1;
         let right_len = right - mid;
 
         self.left = vec![0; left_len];
         for i in 0..left_len {
             self.left[i] = arr.read(left + i);
+            arr.aux_write(left + i, self.left[i]);
         }
         self.right = vec![0; right_len];
         for i in 0..right_len {
             self.right[i] = arr.read(mid + i + 1);
+            arr.aux_write(mid + i + 1, self.right[i]);
         }
 
         let (mut l, mut r) = (0, 0);
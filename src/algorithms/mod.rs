@@ -1,4 +1,5 @@
 use super::*;
+use clap::ValueEnum;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use std::cmp::Ordering::{Equal, Greater, Less};
@@ -20,6 +21,7 @@ mod heap;
 mod insertion;
 mod merge;
 mod pancake;
+mod parallel_quick;
 mod pigeonhole;
 mod quick;
 mod radix;
@@ -43,12 +45,13 @@ use heap::Heap;
 use insertion::Insertion;
 use merge::Merge;
 use pancake::Pancake;
+use parallel_quick::ParallelQuickSort;
 use pigeonhole::Pigeonhole;
 use quick::QuickSort;
 use radix::*;
 use selection::Selection;
 use shell::Shell;
-use shuffle::Shuffle;
+use shuffle::{FisherYates, Shuffle};
 use sleep::Sleep;
 use stooge::Stooge;
 use timsort::Timsort;
@@ -61,7 +64,9 @@ pub trait SortProcessor: Debug + Send + Sync {
 }
 
 /// A particular sorting algorithm.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, FromPrimitive)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, FromPrimitive, ValueEnum,
+)]
 pub enum SortingAlgorithm {
     #[default]
     Bogo,
@@ -86,6 +91,7 @@ pub enum SortingAlgorithm {
     Heap,
     Timsort,
     QuickSort,
+    ParallelQuickSort,
 
     RadixLSD2,
     RadixLSD5,
@@ -109,24 +115,80 @@ pub enum SortingAlgorithm {
     // it uses an input & output buffer.
     // Strand,
 
-    // NOTE: Shuffle MUST be the last variant in order for the cycling methods
-    // to function.
+    // NOTE: the shuffles MUST be the last variants (with FisherYates first) in
+    // order for the cycling methods to function.
+    #[value(skip)]
+    FisherYates,
+    #[value(skip)]
     Shuffle,
 }
 
 unsafe impl bytemuck::NoUninit for SortingAlgorithm {}
 
 impl SortingAlgorithm {
-    /// Cycles to the next sorting algorithm. This never cycles over
-    /// [`SortingAlgorithm::Shuffle`], and if the current algorithm is
-    /// [`SortingAlgorithm::Shuffle`] then this method will cycle to
-    /// [`SortingAlgorithm::Bubble`].
+    /// Whether this algorithm is a shuffle rather than a sort.
+    pub const fn is_shuffle(self) -> bool {
+        matches!(self, Self::Shuffle | Self::FisherYates)
+    }
+
+    /// The oscillator which this algorithm's operations of some kinds are
+    /// sonified with by default, in place of their usual sound, to suit how
+    /// the algorithm works — e.g. the digit passes of the radix sorts are
+    /// heard as sawtooth waves, and Bogosort's random swaps as noise.
+    #[allow(clippy::enum_glob_use)]
+    pub const fn sound_preset(
+        self,
+    ) -> Option<(OscillatorType, &'static [OperationKind])> {
+        use SortingAlgorithm::*;
+
+        match self {
+            Bogo => Some((
+                OscillatorType::Noise { pink: false },
+                &[OperationKind::Swaps],
+            )),
+            Pancake => Some((
+                OscillatorType::Pulse { width: 0.25 },
+                &[OperationKind::Swaps],
+            )),
+            Heap => Some((OscillatorType::Fm, &[OperationKind::Comparisons])),
+            Sleep => Some((OscillatorType::Fm, &[OperationKind::Writes])),
+            Counting | Pigeonhole | RadixLSD2 | RadixLSD5 | RadixLSD10
+            | RadixLSD32 | RadixLSD1000 | InPlaceRadixLSD2
+            | InPlaceRadixLSD10 | InPlaceRadixLSD32 | InPlaceRadixLSD1000
+            | RadixMSD2 | RadixMSD10 | RadixMSD32 | RadixMSD1000 => {
+                Some((OscillatorType::Saw, &[OperationKind::Writes]))
+            }
+            _ => None,
+        }
+    }
+
+    /// A stable name for the algorithm, which is used to identify it in saved
+    /// captures. Unlike its `Display` form, this only changes if the variant
+    /// is renamed.
+    pub fn name(self) -> String {
+        format!("{self:?}")
+    }
+
+    /// Every algorithm which is a sort, i.e. not a shuffle.
+    pub fn sorts() -> impl Iterator<Item = Self> {
+        (0..).map_while(Self::from_usize).filter(|algo| !algo.is_shuffle())
+    }
+
+    /// Returns the algorithm with the given [`name`](Self::name()), if there
+    /// is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        (0..).map_while(Self::from_usize).find(|algo| algo.name() == name)
+    }
+
+    /// Cycles to the next sorting algorithm. This never cycles over the
+    /// shuffles, and if the current algorithm is a shuffle then this method
+    /// will cycle to [`SortingAlgorithm::Bubble`].
     pub fn cycle_next(&mut self) {
-        if matches!(*self, Self::Shuffle) {
+        if self.is_shuffle() {
             *self = Self::Bubble;
         }
 
-        let max = Self::Shuffle as usize;
+        let max = Self::FisherYates as usize;
         let n = (*self as usize + 1) % max;
 
         if let Some(next) = FromPrimitive::from_usize(n) {
@@ -134,16 +196,15 @@ impl SortingAlgorithm {
         }
     }
 
-    /// Cycles to the previous sorting algorithm. This never cycles over
-    /// [`SortingAlgorithm::Shuffle`], and if the current algorithm is
-    /// [`SortingAlgorithm::Shuffle`] then this method will cycle to
-    /// [`SortingAlgorithm::Bubble`].
+    /// Cycles to the previous sorting algorithm. This never cycles over the
+    /// shuffles, and if the current algorithm is a shuffle then this method
+    /// will cycle to [`SortingAlgorithm::Bubble`].
     pub fn cycle_prev(&mut self) {
-        if matches!(*self, Self::Shuffle) {
+        if self.is_shuffle() {
             *self = Self::Bubble;
         }
 
-        let max = Self::Shuffle as usize;
+        let max = Self::FisherYates as usize;
         let s = *self as usize;
         let n = if s == 0 { max - 1 } else { s - 1 };
 
@@ -190,8 +251,10 @@ impl Display for SortingAlgorithm {
             Counting => write("Counting sort"),
             Pigeonhole => write("Pigeonhole sort"),
             QuickSort => write("QuickSort"),
+            ParallelQuickSort => write("Parallel QuickSort"),
             Sleep => write("Sleep sort (not stable)"),
             Shuffle => write("Shuffle"),
+            FisherYates => write("Fisher–Yates shuffle"),
             Bingo => write("Bingo sort"),
             // Bucket => write("Bucket sort"),
             Timsort => write("TimSort"),
@@ -206,8 +269,9 @@ pub struct Algorithms {
 }
 
 impl Algorithms {
-    /// Creates and initializes all sorting algorithms.
-    pub fn new() -> Self {
+    /// Creates and initializes all sorting algorithms. `shuffle_intensity` is
+    /// shared with the shuffles, and should be between `0.0` and `1.0`.
+    pub fn new(shuffle_intensity: &Arc<Atomic<f32>>) -> Self {
         let arr = [
             (SA::Bogo, Box::new(Bogo::new()) as Box<dyn SortProcessor>),
             (SA::Stooge, Box::new(Stooge::new())),
@@ -228,6 +292,7 @@ impl Algorithms {
             (SA::Heap, Box::new(Heap)),
             (SA::Timsort, Box::new(Timsort::new())),
             (SA::QuickSort, Box::new(QuickSort::new())),
+            (SA::ParallelQuickSort, Box::new(ParallelQuickSort::new())),
             (SA::RadixLSD2, Box::new(RadixLSD::new(2))),
             (SA::RadixLSD5, Box::new(RadixLSD::new(5))),
             (SA::RadixLSD10, Box::new(RadixLSD::new(10))),
@@ -242,7 +307,14 @@ impl Algorithms {
             (SA::RadixMSD32, Box::new(RadixMSD::new(32))),
             (SA::RadixMSD1000, Box::new(RadixMSD::new(1000))),
             (SA::Sleep, Box::new(Sleep::new())),
-            (SA::Shuffle, Box::new(Shuffle::new())),
+            (
+                SA::FisherYates,
+                Box::new(FisherYates::new(Arc::clone(shuffle_intensity))),
+            ),
+            (
+                SA::Shuffle,
+                Box::new(Shuffle::new(Arc::clone(shuffle_intensity))),
+            ),
         ];
 
         Self { algos: HashMap::from(arr) }
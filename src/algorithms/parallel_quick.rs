@@ -0,0 +1,126 @@
+use super::*;
+use crate::thread_pool::ThreadPool;
+use crossbeam_channel::unbounded;
+use std::panic::{self, AssertUnwindSafe};
+
+/// A parallel QuickSort, which partitions the array until there is a segment
+/// for each worker thread, and then sorts each segment concurrently.
+#[derive(Debug)]
+pub struct ParallelQuickSort {
+    thread_pool: ThreadPool,
+}
+
+impl ParallelQuickSort {
+    const NUM_THREADS: usize = 4;
+    /// Segments smaller than this are not split any further.
+    const MIN_SEGMENT_SIZE: isize = 16;
+
+    pub fn new() -> Self {
+        Self {
+            thread_pool: ThreadPool::build(
+                Self::NUM_THREADS,
+                None,
+                Some(&[
+                    "parallel quicksort #0",
+                    "parallel quicksort #1",
+                    "parallel quicksort #2",
+                    "parallel quicksort #3",
+                ]),
+            )
+            .expect("failed to allocate parallel quicksort threads"),
+        }
+    }
+
+    /// Partitions the array into (at most) one segment per thread. The
+    /// segments are inclusive ranges, kept in the order they appear in the
+    /// array.
+    fn split(arr: &mut SortArray) -> Vec<(isize, isize)> {
+        let mut segments = vec![(0, arr.len() as isize - 1)];
+
+        while segments.len() < Self::NUM_THREADS {
+            let Some((idx, &(low, high))) = segments
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, (low, high))| high - low)
+            else {
+                break;
+            };
+
+            if high - low < Self::MIN_SEGMENT_SIZE {
+                break;
+            }
+
+            let part = QuickSort::partition(arr, low, high);
+
+            segments.remove(idx);
+            segments.insert(idx, (part + 1, high));
+            segments.insert(idx, (low, part - 1));
+            segments.retain(|(low, high)| low <= high);
+        }
+
+        segments
+    }
+}
+
+impl SortProcessor for ParallelQuickSort {
+    fn process(&mut self, arr: &mut SortArray) {
+        let segments = Self::split(arr);
+        let num_segments = segments.len();
+        let (tx, rx) = unbounded();
+
+        for (i, &(low, high)) in segments.iter().enumerate() {
+            let start = low as usize;
+            let mut sub = arr.sub_array(start..high as usize + 1);
+            let tx = tx.clone();
+
+            // the segments share the operations left under the limit, so
+            // that they can't exceed it once they are merged.
+            sub.set_op_limit(
+                arr.remaining_ops().map(|remaining| remaining / num_segments),
+            );
+            let mut sub = Some(sub);
+
+            self.thread_pool.execute(move || {
+                if let Some(mut sub) = sub.take() {
+                    let high = sub.len() as isize - 1;
+
+                    // a cancelled segment keeps the operations it recorded
+                    // before it stopped, and is merged as usual.
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        QuickSort::sort(&mut sub, 0, high);
+                    }));
+                    if let Err(payload) = result {
+                        if !payload.is::<Cancelled>() {
+                            panic::resume_unwind(payload);
+                        }
+                    }
+
+                    _ = tx.send((i, start, sub));
+                }
+            });
+        }
+
+        drop(tx);
+
+        let mut results: Vec<_> = (0..num_segments)
+            .map(|_| rx.recv().expect("parallel quicksort thread panicked"))
+            .collect();
+
+        // the threads may finish in any order, so the results are re-ordered
+        // to keep the merged operations deterministic.
+        results.sort_unstable_by_key(|&(i, ..)| i);
+
+        arr.merge_sub_arrays(
+            results
+                .into_iter()
+                .map(|(_, start, sub)| (start, sub))
+                .collect(),
+        );
+
+        // a segment which reached the operation limit stops the whole sort,
+        // rather than letting it be verified as if it had finished.
+        if arr.is_truncated() {
+            panic::resume_unwind(Box::new(Cancelled));
+        }
+    }
+}
@@ -36,6 +36,11 @@ impl SortProcessor for Pigeonhole {
             self.holes[arr_i - min].push(arr_i);
         }
 
+        // the holes are laid out one after another in the auxiliary array.
+        for (i, &val) in self.holes.iter().flatten().enumerate() {
+            arr.aux_write(i, val);
+        }
+
         let mut idx = 0;
 
         for i in 0..range {
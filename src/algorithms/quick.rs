@@ -8,7 +8,11 @@ impl QuickSort {
         Self
     }
 
-    fn partition(arr: &mut SortArray, low: isize, high: isize) -> isize {
+    pub fn partition(arr: &mut SortArray, low: isize, high: isize) -> isize {
+        arr.point(Pointer::Low, Some(low as usize));
+        arr.point(Pointer::High, Some(high as usize));
+        arr.point(Pointer::Pivot, Some(high as usize));
+
         let pivot = arr.read(high as usize);
         let mut i = low - 1;
 
@@ -21,11 +25,12 @@ impl QuickSort {
         }
 
         arr.swap((i + 1) as usize, high as usize);
+        arr.point(Pointer::Pivot, Some((i + 1) as usize));
 
         i + 1
     }
 
-    fn sort(arr: &mut SortArray, low: isize, high: isize) {
+    pub fn sort(arr: &mut SortArray, low: isize, high: isize) {
         if low < high {
             let part = Self::partition(arr, low, high);
 
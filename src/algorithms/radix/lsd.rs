@@ -22,6 +22,7 @@ impl RadixLSD {
         for bin in bins {
             for &val in bin.iter() {
                 tmp[total] = val;
+                arr.aux_write(total, val);
                 total += 1;
             }
 
@@ -13,6 +13,11 @@ impl RadixMSD {
     fn transcribe(arr: &mut SortArray, bins: &[Vec<usize>], min: usize) {
         let total = bins.iter().fold(0, |acc, b| acc + b.len());
 
+        // the bins are laid out one after another in the auxiliary array.
+        for (i, &val) in bins.iter().flatten().enumerate() {
+            arr.aux_write(min + i, val);
+        }
+
         let mut tmp = 0;
         for bin in bins.iter().rev() {
             for &val in bin.iter().rev() {
@@ -1,14 +1,34 @@
 use super::*;
+use crate::rng::{random_range, with_rng};
+use nannou::rand::seq::index::sample;
+
+/// Picks a proportion of the positions in `0..len` at random — set by the
+/// shuffle `intensity` — in ascending order. The shuffles only shuffle the
+/// elements at these positions amongst themselves.
+fn shuffle_positions(len: usize, intensity: &Atomic<f32>) -> Vec<usize> {
+    let intensity = intensity.load(Relaxed).clamp(0.0, 1.0);
+    let k = (len as f32 * intensity).round() as usize;
+
+    let mut positions = with_rng(|rng| sample(rng, len, k)).into_vec();
+    positions.sort_unstable();
+
+    positions
+}
 
 /// A "moving-window" shuffle.
+///
+/// Only a proportion of the elements — set by the shuffle intensity — are
+/// shuffled amongst themselves, and the rest are left in place.
 #[derive(Debug, Clone)]
-pub struct Shuffle;
+pub struct Shuffle {
+    intensity: Arc<Atomic<f32>>,
+}
 
 impl Shuffle {
     const ITERS_PER_STEP: usize = 10;
 
-    pub const fn new() -> Self {
-        Self
+    pub const fn new(intensity: Arc<Atomic<f32>>) -> Self {
+        Self { intensity }
     }
 
     fn rand_above(len: usize, start: usize, size: usize) -> (usize, usize) {
@@ -29,7 +49,13 @@ impl Shuffle {
     fn rand_below(start: usize, size: usize) -> (usize, usize) {
         let rand = |in_win: bool| {
             let min = if in_win && start >= size { start - size } else { 0 };
-            random_range(min, start)
+
+            if start == 0 {
+                0
+            }
+            else {
+                random_range(min, start)
+            }
         };
 
         (rand(true), rand(true))
@@ -58,19 +84,25 @@ impl Shuffle {
 
 impl SortProcessor for Shuffle {
     fn process(&mut self, arr: &mut SortArray) {
-        let n = arr.len() - 1;
+        let positions = shuffle_positions(arr.len(), &self.intensity);
+
+        if positions.len() < 2 {
+            return;
+        }
+
+        let n = positions.len() - 1;
         let win_size = (n / 4).max(1);
         let step = 4;
 
         let mut head_bot = 0;
-        let mut head_top = arr.len() - 1;
+        let mut head_top = n;
 
         for _ in (0..n * 2).step_by(step) {
             for _ in 0..Self::ITERS_PER_STEP {
                 let (ab, bb) = Self::rand_above(n, head_bot, win_size);
                 let (at, bt) = Self::rand_below(head_top, win_size);
-                arr.swap(ab, bt);
-                arr.swap(at, bb);
+                arr.swap(positions[ab], positions[bt]);
+                arr.swap(positions[at], positions[bb]);
             }
 
             head_bot = (head_bot + step) % n;
@@ -78,3 +110,29 @@ impl SortProcessor for Shuffle {
         }
     }
 }
+
+/// A uniform Fisher–Yates shuffle, where every permutation is equally likely.
+///
+/// Only a proportion of the elements — set by the shuffle intensity — are
+/// shuffled amongst themselves, and the rest are left in place.
+#[derive(Debug, Clone)]
+pub struct FisherYates {
+    intensity: Arc<Atomic<f32>>,
+}
+
+impl FisherYates {
+    pub const fn new(intensity: Arc<Atomic<f32>>) -> Self {
+        Self { intensity }
+    }
+}
+
+impl SortProcessor for FisherYates {
+    fn process(&mut self, arr: &mut SortArray) {
+        let positions = shuffle_positions(arr.len(), &self.intensity);
+
+        for i in (1..positions.len()).rev() {
+            let j = random_range(0, i + 1);
+            arr.swap(positions[i], positions[j]);
+        }
+    }
+}
@@ -1,50 +1,144 @@
 use super::*;
+use crate::thread_pool::ThreadPool;
+use crossbeam_channel::unbounded;
 use std::{
-    thread::{sleep, spawn},
-    time::Duration,
+    thread::sleep,
+    time::{Duration, Instant},
 };
 
+/// A sleep sort, which distributes the elements across a fixed number of
+/// worker threads rather than spawning a thread per element.
+///
+/// Each worker holds a "timer wheel" of its elements, where each slot is a
+/// point in time at which those elements wake up. The workers all push to the
+/// same output array as their elements wake, so the ordering between workers
+/// is still subject to the usual sleep sort races.
 #[derive(Debug)]
 pub struct Sleep {
-    output_arr: Arc<Mutex<Vec<usize>>>,
+    thread_pool: ThreadPool,
 }
 
 impl Sleep {
+    const NUM_THREADS: usize = 8;
+    /// The longest amount of time that any element may sleep for.
+    const MAX_SLEEP_TIME: f32 = 4.0;
+    /// The longest amount of time between two adjacent values waking up.
+    const MAX_TICK_TIME: f32 = 0.01;
+    /// The longest amount of time that a worker sleeps for before checking
+    /// whether the sort has been cancelled.
+    const CANCEL_CHECK_TIME: Duration = Duration::from_millis(10);
+
     pub fn new() -> Self {
-        Self { output_arr: Arc::new(Mutex::new(vec![])) }
+        Self {
+            thread_pool: ThreadPool::build(
+                Self::NUM_THREADS,
+                Some(thread_priority::ThreadPriority::Max),
+                Some(&[
+                    "sleep sort #0",
+                    "sleep sort #1",
+                    "sleep sort #2",
+                    "sleep sort #3",
+                    "sleep sort #4",
+                    "sleep sort #5",
+                    "sleep sort #6",
+                    "sleep sort #7",
+                ]),
+            )
+            .expect("failed to allocate sleep sort threads"),
+        }
+    }
+
+    /// Sleeps until `wake`, unless `cancel` is cancelled first. Returns
+    /// whether the sleep was cancelled.
+    fn sleep_until(wake: Instant, cancel: Option<&CancelToken>) -> bool {
+        loop {
+            if cancel.is_some_and(CancelToken::is_cancelled) {
+                return true;
+            }
+
+            let remaining = wake.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+
+            sleep(remaining.min(Self::CANCEL_CHECK_TIME));
+        }
     }
 }
 
 impl SortProcessor for Sleep {
     fn process(&mut self, arr: &mut SortArray) {
         let n = arr.len();
-        self.output_arr.lock().reserve_exact(n);
+        // the output is local to each sort, so that a cancelled sort can't
+        // leave any elements behind for the next one.
+        let output = Arc::new(Mutex::new(Vec::with_capacity(n)));
+        let cancel = arr.cancel_token().cloned();
+
+        let tick = Duration::from_secs_f32(
+            (Self::MAX_SLEEP_TIME / n as f32).min(Self::MAX_TICK_TIME),
+        );
 
-        let mut threads = vec![];
+        // each worker's timer wheel, where each slot holds the elements which
+        // wake up at that tick.
+        let mut wheels = vec![vec![]; Self::NUM_THREADS];
 
         for i in 0..n {
-            let out = Arc::clone(&self.output_arr);
             let element = arr.read(i);
+            let wheel: &mut Vec<Vec<usize>> = &mut wheels[i % Self::NUM_THREADS];
 
-            threads.push(spawn(move || {
-                _ = thread_priority::set_current_thread_priority(
-                    thread_priority::ThreadPriority::Max,
-                );
+            if wheel.len() <= element {
+                wheel.resize(element + 1, vec![]);
+            }
 
-                sleep(Duration::from_millis(element as u64 * 10));
-                out.lock().push(element);
-            }));
+            wheel[element].push(element);
         }
 
-        for th in threads {
-            th.join().unwrap();
+        let (tx, rx) = unbounded();
+        let start = Instant::now();
+
+        for wheel in wheels {
+            let out = Arc::clone(&output);
+            let cancel = cancel.clone();
+            let mut wheel = Some(wheel);
+            let tx = tx.clone();
+
+            self.thread_pool.execute(move || {
+                let Some(wheel) = wheel.take() else {
+                    return;
+                };
+
+                for (t, slot) in wheel.into_iter().enumerate() {
+                    if slot.is_empty() {
+                        continue;
+                    }
+
+                    let wake = start + tick * t as u32;
+                    if Self::sleep_until(wake, cancel.as_ref()) {
+                        break;
+                    }
+
+                    out.lock().extend(slot);
+                }
+
+                _ = tx.send(());
+            });
         }
 
-        let mut out = self.output_arr.lock();
-        for i in 0..n {
-            arr.write(i, out[i]);
+        drop(tx);
+
+        for _ in 0..Self::NUM_THREADS {
+            rx.recv().expect("sleep sort thread panicked");
+        }
+
+        // the workers stop early once the sort is cancelled, so the output
+        // is incomplete.
+        if cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            std::panic::resume_unwind(Box::new(Cancelled));
         }
 
-        out.clear();
+        let out = output.lock();
+        for (i, &element) in out.iter().enumerate() {
+            arr.write(i, element);
+        }
     }
 }
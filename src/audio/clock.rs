@@ -0,0 +1,142 @@
+use super::*;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+
+/// The time without a callback after which the clock is considered stalled,
+/// in seconds.
+const STALL_TIME: f64 = 0.1;
+
+/// The clock of the audio callback, which counts the samples processed so
+/// far.
+///
+/// Playback is driven by this clock rather than the frame rate, so that note
+/// events and the visuals stay phase-locked.
+#[derive(Clone, Debug)]
+pub struct AudioClock {
+    /// The samples processed since the sample rate last changed.
+    samples: Arc<AtomicU64>,
+    /// The time on the clock when the sample rate last changed, in seconds,
+    /// which the samples are counted on from.
+    offset: Arc<Atomic<f64>>,
+    /// When the last callback happened.
+    callback_timer: Arc<Atomic<InstantTime>>,
+    /// The sample rate of the audio callback, which is shared between clones
+    /// as it changes if the stream is rebuilt — see [`Self::set_config()`].
+    sample_rate: Arc<AtomicU32>,
+    /// The number of samples in each buffer of the audio callback.
+    buffer_size: Arc<AtomicUsize>,
+}
+
+impl AudioClock {
+    /// Creates a clock for an audio callback which runs with `config`.
+    pub fn new(config: AudioConfig) -> Self {
+        Self {
+            samples: Arc::new(AtomicU64::new(0)),
+            offset: Arc::new(Atomic::new(0.0)),
+            callback_timer: Arc::new(Atomic::new(InstantTime(Instant::now()))),
+            sample_rate: Arc::new(AtomicU32::new(config.sample_rate)),
+            buffer_size: Arc::new(AtomicUsize::new(config.buffer_size)),
+        }
+    }
+
+    /// Sets the sample rate and buffer size of the clock and all of its
+    /// clones, for when it is moved to a new audio callback. The clock counts
+    /// as having just been called back, so that it isn't stalled before the
+    /// new callback's first buffer.
+    ///
+    /// If the sample rate changes, the samples counted so far are converted
+    /// to seconds at the old rate, so that the time carries on from where it
+    /// was rather than jumping.
+    pub fn set_config(&self, config: AudioConfig) {
+        let old_rate = self.sample_rate();
+
+        if config.sample_rate != old_rate {
+            let samples = self.samples.swap(0, Relaxed) as f64;
+            let elapsed = samples / old_rate as f64;
+            self.offset.store(self.offset.load(Relaxed) + elapsed, Relaxed);
+        }
+
+        self.sample_rate.store(config.sample_rate, Relaxed);
+        self.buffer_size.store(config.buffer_size, Relaxed);
+        self.callback_timer.store(InstantTime(Instant::now()), Relaxed);
+    }
+
+    /// The sample rate and buffer size of the audio callback.
+    pub fn config(&self) -> AudioConfig {
+        AudioConfig {
+            sample_rate: self.sample_rate.load(Relaxed),
+            buffer_size: self.buffer_size.load(Relaxed),
+        }
+    }
+
+    /// The sample rate of the audio callback.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.load(Relaxed)
+    }
+
+    /// The number of samples in each buffer of the audio callback.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size.load(Relaxed)
+    }
+
+    /// The time between audio callbacks, in seconds.
+    pub fn buffer_time(&self) -> f64 {
+        self.config().buffer_time()
+    }
+
+    /// The time since the last callback, in seconds.
+    pub fn since_callback(&self) -> f64 {
+        self.callback_timer.load(Relaxed).elapsed().as_secs_f64()
+    }
+
+    /// Advances the clock by `frames` samples. This is called once per audio
+    /// callback.
+    pub fn advance(&self, frames: usize) {
+        self.callback_timer.store(InstantTime(Instant::now()), Relaxed);
+        self.samples.fetch_add(frames as u64, Relaxed);
+    }
+
+    /// The current time on the clock in seconds, or `None` if the audio
+    /// callback has stalled (e.g. if there is no output device).
+    ///
+    /// The time is interpolated between callbacks, so it is smooth enough to
+    /// drive playback each frame. It may occasionally step backwards by up to
+    /// a buffer, as the sample count and callback time are updated
+    /// separately.
+    pub fn now(&self) -> Option<f64> {
+        let since_callback = self.since_callback();
+
+        if since_callback > STALL_TIME {
+            return None;
+        }
+
+        let samples = self.samples.load(Relaxed) as f64;
+        let sample_rate = self.sample_rate() as f64;
+        let offset = self.offset.load(Relaxed);
+
+        Some(
+            offset
+                + samples / sample_rate
+                + since_callback.min(self.buffer_time()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_rate_changes_keep_the_time() {
+        let config = AudioConfig { sample_rate: 48000, buffer_size: 512 };
+        let clock = AudioClock::new(config);
+        clock.advance(48000);
+
+        clock.set_config(AudioConfig { sample_rate: 44100, ..config });
+        let now = clock.now().unwrap();
+        assert!((1.0..1.01).contains(&now), "{now}");
+
+        clock.advance(44100);
+        let now = clock.now().unwrap();
+        assert!((2.0..2.01).contains(&now), "{now}");
+    }
+}
@@ -12,6 +12,13 @@ pub struct BallisticsFilter {
     /// The "constant time envelope" release level.
     cte_release: f32,
 
+    /// The attack time in milliseconds, which the attack level is computed
+    /// from.
+    attack_ms: f32,
+    /// The release time in milliseconds, which the release level is
+    /// computed from.
+    release_ms: f32,
+
     /// The internal sample rate.
     sample_rate: f32x2,
 }
@@ -25,6 +32,9 @@ impl BallisticsFilter {
             cte_attack: 0.0,
             cte_release: 0.0,
 
+            attack_ms: 0.0,
+            release_ms: 0.0,
+
             sample_rate: f32x2::splat(sample_rate),
         }
     }
@@ -49,6 +59,14 @@ impl BallisticsFilter {
         self
     }
 
+    /// Sets the sample rate of the filter, and recomputes the attack and
+    /// release levels so that their times stay the same.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = f32x2::splat(sample_rate);
+        self.cte_attack = self.calculate_cte(self.attack_ms);
+        self.cte_release = self.calculate_cte(self.release_ms);
+    }
+
     /// Resets the internal buffer to `0.0`.
     pub fn reset(&mut self) {
         self.z1[CH_L] = 0.0;
@@ -65,6 +83,7 @@ impl BallisticsFilter {
     /// Panics if `time_ms` is negative.
     pub fn set_attack_time_ms(&mut self, time_ms: f32) {
         assert!(time_ms.is_sign_positive());
+        self.attack_ms = time_ms;
         self.cte_attack = self.calculate_cte(time_ms);
     }
 
@@ -78,6 +97,7 @@ impl BallisticsFilter {
     /// Panics if `time_ms` is negative.
     pub fn set_release_time_ms(&mut self, time_ms: f32) {
         assert!(time_ms.is_sign_positive());
+        self.release_ms = time_ms;
         self.cte_release = self.calculate_cte(time_ms);
     }
 
@@ -137,3 +157,35 @@ impl SimdAudioEffect for BallisticsFilter {
         self.sample_rate[CH_L]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The number of samples which `filter` takes to rise to most of a
+    /// full-scale input.
+    fn rise_time(mut filter: BallisticsFilter) -> usize {
+        let target = 1.0 - (-TAU).exp();
+
+        (1..).find(|_| filter.tick(f32x2::splat(1.0))[CH_L] >= target).unwrap()
+    }
+
+    #[test]
+    fn sample_rate_changes_keep_the_timing() {
+        let mut filter = BallisticsFilter::new(44100.0)
+            .with_attack_time(10.0)
+            .with_release_time(100.0);
+        filter.set_sample_rate(96000.0);
+
+        let fresh = BallisticsFilter::new(96000.0)
+            .with_attack_time(10.0)
+            .with_release_time(100.0);
+        assert_eq!(filter.cte_attack, fresh.cte_attack);
+        assert_eq!(filter.cte_release, fresh.cte_release);
+
+        // the attack takes 10 ms at either rate.
+        assert!(rise_time(filter).abs_diff(960) < 10);
+        let slow = BallisticsFilter::new(44100.0).with_attack_time(10.0);
+        assert!(rise_time(slow).abs_diff(441) < 5);
+    }
+}
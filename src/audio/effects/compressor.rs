@@ -3,6 +3,25 @@ use super::*;
 pub const DEFAULT_ATTACK_TIME: f32 = 30.0;
 pub const DEFAULT_RELEASE_TIME: f32 = 500.0;
 
+/// The makeup gain of a [`Compressor`], which is applied after compression
+/// to restore some of the level which it takes away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MakeupGain {
+    /// Makes up half of the gain reduction of a full-scale signal, which
+    /// follows the threshold, ratio and knee width.
+    Auto,
+    /// A fixed makeup gain in decibels.
+    Manual(f32),
+}
+
+impl Default for MakeupGain {
+    fn default() -> Self {
+        Self::Manual(0.0)
+    }
+}
+
+unsafe impl NoUninit for MakeupGain {}
+
 #[derive(Debug, Clone)]
 pub struct Compressor {
     sample_rate: f32,
@@ -13,6 +32,13 @@ pub struct Compressor {
 
     knee_width: f32,
 
+    makeup: MakeupGain,
+    /// The linear makeup gain, which is updated whenever it may change.
+    makeup_gain: f32,
+
+    /// The gain reduction of the last sample in decibels, for metering.
+    gain_reduction_db: f32,
+
     filter: BallisticsFilter,
 }
 
@@ -24,6 +50,9 @@ impl Compressor {
             threshold_db: 0.0,
             ratio: 1.0,
             knee_width: 0.0,
+            makeup: MakeupGain::default(),
+            makeup_gain: 1.0,
+            gain_reduction_db: 0.0,
             filter: BallisticsFilter::new(sample_rate)
                 .with_attack_time(DEFAULT_ATTACK_TIME)
                 .with_release_time(DEFAULT_RELEASE_TIME),
@@ -61,6 +90,12 @@ impl Compressor {
         self
     }
 
+    /// Provides a makeup gain to the `Compressor`.
+    pub fn with_makeup_gain(mut self, makeup: MakeupGain) -> Self {
+        self.set_makeup_gain(makeup);
+        self
+    }
+
     /// Sets the compressor's threshold in decibels.
     ///
     /// # Panics
@@ -69,6 +104,7 @@ impl Compressor {
     pub fn set_threshold_db(&mut self, level_db: f32) {
         assert!(level_db <= 0.0);
         self.threshold_db = level_db;
+        self.update_makeup();
     }
 
     /// Sets the ratio of the compressor. Any values over `100.0` are clamped to
@@ -80,6 +116,7 @@ impl Compressor {
     pub fn set_ratio(&mut self, ratio: f32) {
         assert!(ratio >= 1.0);
         self.ratio = ratio.clamp(1.0, 100.0);
+        self.update_makeup();
     }
 
     /// Sets the compressor's knee width.
@@ -89,6 +126,27 @@ impl Compressor {
     /// Panics if `width` is negative.
     pub fn set_knee_width(&mut self, width: f32) {
         assert!(width.is_sign_positive());
+        self.knee_width = width;
+        self.update_makeup();
+    }
+
+    /// Sets the compressor's makeup gain.
+    pub fn set_makeup_gain(&mut self, makeup: MakeupGain) {
+        self.makeup = makeup;
+        self.update_makeup();
+    }
+
+    /// The current gain reduction in decibels, as a positive value, of
+    /// whichever channel is reduced the most.
+    pub const fn gain_reduction_db(&self) -> f32 {
+        self.gain_reduction_db
+    }
+
+    /// Sets the compressor's sample rate, keeping its attack and release
+    /// times.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.filter.set_sample_rate(sample_rate);
     }
 
     /// Sets the compressor's attack time in milliseconds.
@@ -109,6 +167,15 @@ impl Compressor {
         self.filter.set_release_time_ms(time_ms);
     }
 
+    fn update_makeup(&mut self) {
+        let makeup_db = match self.makeup {
+            MakeupGain::Auto => -0.5 * self.gain_function(0.0),
+            MakeupGain::Manual(level_db) => level_db,
+        };
+
+        self.makeup_gain = Decibels(makeup_db).to_gain().0;
+    }
+
     fn gain_function(&self, input: f32) -> f32 {
         let Self { threshold_db: thresh, ratio, knee_width: width, .. } = self;
         let half_width = width * 0.5;
@@ -144,14 +211,48 @@ impl SimdAudioEffect for Compressor {
     #[inline]
     fn tick(&mut self, sample: f32x2) -> f32x2 {
         let env = self.filter.tick(sample);
-        let gain = Audio::db_to_level_simd(
-            self.gain_function_simd(Audio::level_to_db_simd(env)),
-        );
+        let env_db = LinearGain(env).to_db();
+        let reduction_db = self.gain_function_simd(env_db.0);
+        self.gain_reduction_db = -reduction_db[CH_L].min(reduction_db[CH_R]);
+
+        let gain = Decibels(reduction_db).to_gain();
 
-        gain * sample
+        gain.0 * f32x2::splat(self.makeup_gain) * sample
     }
 
     fn sample_rate(&self) -> f32 {
         self.sample_rate
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knee_and_makeup_gain() {
+        let hard = Compressor::new(48000.0)
+            .with_threshold_db(-18.0)
+            .with_ratio(4.0);
+        let soft = hard.clone().with_knee_width(12.0);
+
+        // at the threshold, only the soft knee compresses.
+        assert_eq!(hard.gain_function(-18.0), 0.0);
+        assert!(soft.gain_function(-18.0) < 0.0);
+        // and both agree above the knee.
+        let above = hard.gain_function(0.0) - soft.gain_function(0.0);
+        assert!(above.abs() < 1e-4);
+
+        let mut comp = soft.with_makeup_gain(MakeupGain::Auto);
+        assert_eq!(comp.gain_reduction_db(), 0.0);
+
+        let mut out = SIMD_ZERO;
+        for _ in 0..48000 {
+            out = comp.tick(f32x2::splat(1.0));
+        }
+
+        // a full-scale signal is reduced by 13.5 dB, half of which is made up.
+        assert!((comp.gain_reduction_db() - 13.5).abs() < 0.1);
+        assert!((LinearGain(out[CH_L]).to_db().0 + 6.75).abs() < 0.1);
+    }
+}
@@ -0,0 +1,201 @@
+use super::*;
+use clap::ValueEnum;
+
+/// The longest delay time, in milliseconds.
+pub const MAX_DELAY_TIME_MS: f32 = 2000.0;
+/// The highest feedback level, which stops the echoes from building up
+/// forever.
+const MAX_FEEDBACK: f32 = 0.95;
+
+/// How the echoes of a [`Delay`] move between the channels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[repr(u32)]
+pub enum DelayMode {
+    /// The delay is bypassed.
+    #[default]
+    Off,
+    /// Each channel echoes on its own side.
+    Stereo,
+    /// The echoes bounce between the left and right channels.
+    PingPong,
+}
+
+impl DelayMode {
+    const ALL: [Self; 3] = [Self::Off, Self::Stereo, Self::PingPong];
+
+    /// Cycles to the next mode.
+    pub fn cycle_next(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + 1) % Self::ALL.len()];
+    }
+}
+
+impl std::fmt::Display for DelayMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Stereo => write!(f, "stereo"),
+            Self::PingPong => write!(f, "ping-pong"),
+        }
+    }
+}
+
+/// The parameters of a [`Delay`], which can be changed while it runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DelayParams {
+    pub mode: DelayMode,
+    /// The time between each echo, in milliseconds.
+    pub time_ms: f32,
+    /// How much of each echo is fed back into the next, between `0.0` and
+    /// `1.0`.
+    pub feedback: f32,
+    /// The balance between the dry signal and the echoes, between `0.0` and
+    /// `1.0`.
+    pub mix: f32,
+}
+
+unsafe impl NoUninit for DelayParams {}
+
+impl DelayParams {
+    /// Whether the delay can be heard.
+    pub fn is_enabled(self) -> bool {
+        self.mode != DelayMode::Off && self.mix > 0.0
+    }
+}
+
+impl Default for DelayParams {
+    fn default() -> Self {
+        Self {
+            mode: DelayMode::default(),
+            time_ms: 250.0,
+            feedback: 0.4,
+            mix: 0.3,
+        }
+    }
+}
+
+impl std::fmt::Display for DelayParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.is_enabled() {
+            return write!(f, "off");
+        }
+
+        write!(
+            f,
+            "{} ({:.0} ms, feedback {:.0}%)",
+            self.mode,
+            self.time_ms,
+            self.feedback * 100.0
+        )
+    }
+}
+
+/// A stereo delay whose echoes either stay on their own side or bounce
+/// between the channels.
+#[derive(Debug, Clone)]
+pub struct Delay {
+    buffer: Vec<f32x2>,
+    write_pos: usize,
+    /// The delay time in samples.
+    delay: usize,
+    params: DelayParams,
+    sample_rate: f32,
+}
+
+impl Delay {
+    /// Creates a new `Delay`.
+    pub fn new(sample_rate: f32) -> Self {
+        let len = (MAX_DELAY_TIME_MS * 1e-3 * sample_rate).ceil() as usize + 1;
+
+        let mut delay = Self {
+            buffer: vec![SIMD_ZERO; len],
+            write_pos: 0,
+            delay: 1,
+            params: DelayParams::default(),
+            sample_rate,
+        };
+
+        delay.set_params(DelayParams::default());
+        delay
+    }
+
+    /// Provides the mode, time, feedback and mix to the delay.
+    pub fn with_params(mut self, params: DelayParams) -> Self {
+        self.set_params(params);
+        self
+    }
+
+    /// Sets the mode, time, feedback and mix of the delay. The time is
+    /// clamped to [`MAX_DELAY_TIME_MS`], and the mix and feedback are
+    /// clamped between `0.0` and `1.0` (the feedback slightly below).
+    pub fn set_params(&mut self, params: DelayParams) {
+        let time_ms = params.time_ms.clamp(0.0, MAX_DELAY_TIME_MS);
+
+        self.params = DelayParams {
+            mode: params.mode,
+            time_ms,
+            feedback: params.feedback.clamp(0.0, MAX_FEEDBACK),
+            mix: params.mix.clamp(0.0, 1.0),
+        };
+
+        let samples = (time_ms * 1e-3 * self.sample_rate).round() as usize;
+        self.delay = samples.clamp(1, self.buffer.len() - 1);
+    }
+}
+
+impl SimdAudioEffect for Delay {
+    #[inline]
+    fn tick(&mut self, sample: f32x2) -> f32x2 {
+        let DelayParams { mode, feedback, mix, .. } = self.params;
+
+        if mode == DelayMode::Off {
+            return sample;
+        }
+
+        let len = self.buffer.len();
+        let delayed = self.buffer[(self.write_pos + len - self.delay) % len];
+        let feedback = f32x2::splat(feedback);
+
+        self.buffer[self.write_pos] = match mode {
+            // the input is summed to mono and only enters on the left, and
+            // each echo is fed back into the opposite channel.
+            DelayMode::PingPong => {
+                let input =
+                    f32x2::from_array([sample.reduce_sum() * 0.5, 0.0]);
+                delayed.reverse().mul_add(feedback, input)
+            }
+            _ => delayed.mul_add(feedback, sample),
+        };
+        self.write_pos = (self.write_pos + 1) % len;
+
+        let mix = f32x2::splat(mix);
+        delayed.mul_add(mix, sample * (SIMD_ONE - mix))
+    }
+
+    fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ping_pong_alternates_channels() {
+        // an echo every sample at 1 kHz.
+        let mut delay = Delay::new(1000.0).with_params(DelayParams {
+            mode: DelayMode::PingPong,
+            time_ms: 1.0,
+            feedback: 0.5,
+            mix: 1.0,
+        });
+
+        let out: Vec<[f32; 2]> = [1.0, 0.0, 0.0, 0.0]
+            .into_iter()
+            .map(|x| delay.tick(f32x2::splat(x)).to_array())
+            .collect();
+
+        assert_eq!(out, [[0.0, 0.0], [1.0, 0.0], [0.0, 0.5], [0.25, 0.0]]);
+    }
+}
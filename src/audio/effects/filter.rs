@@ -46,17 +46,6 @@ impl Filter {
         }
     }
 
-    /// Provides a frequency to the filter.
-    ///
-    /// # Panics
-    ///
-    /// Panics in debug mode if `freq > Self::sample_rate() * 0.5` or if `freq`
-    /// is negative.
-    pub fn with_freq(mut self, freq: f32) -> Self {
-        self.set_freq(freq);
-        self
-    }
-
     /// Provides a `FilterType` to the filter.
     pub fn with_type(mut self, filter_type: FilterType) -> Self {
         self.set_type(filter_type);
@@ -5,11 +5,19 @@ use nannou_audio::Buffer;
 
 pub mod ballistics_filter;
 pub mod compressor;
+pub mod delay;
 pub mod filter;
+pub mod reverb;
+pub mod soft_clipper;
+pub mod tone;
 
 pub use ballistics_filter::BallisticsFilter;
 pub use filter::{Filter, FilterType};
-pub use compressor::Compressor;
+pub use compressor::{Compressor, MakeupGain};
+pub use delay::{Delay, DelayMode, DelayParams, MAX_DELAY_TIME_MS};
+pub use reverb::{Reverb, ReverbParams};
+pub use soft_clipper::SoftClipper;
+pub use tone::{ToneControl, ToneParams};
 
 /// Trait for SIMD audio processing effects, which *only* support two channels.
 pub trait SimdAudioEffect: Debug + Clone {
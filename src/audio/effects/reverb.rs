@@ -0,0 +1,252 @@
+use super::*;
+
+/// The lengths of the comb filters in samples at 44.1 kHz, from Freeverb.
+const COMB_LENGTHS: [usize; 8] =
+    [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// The lengths of the allpass filters in samples at 44.1 kHz, from Freeverb.
+const ALLPASS_LENGTHS: [usize; 4] = [556, 441, 341, 225];
+/// The number of samples which the right channel's delays are longer by at
+/// 44.1 kHz, which decorrelates the channels.
+const STEREO_SPREAD: usize = 23;
+/// The sample rate which the lengths above are tuned for.
+const TUNING_SAMPLE_RATE: f32 = 44100.0;
+
+/// The gain of the input to the comb filters.
+const INPUT_GAIN: f32 = 0.015;
+/// The gain of the reverberated signal, which makes up for `INPUT_GAIN`.
+const WET_GAIN: f32 = 3.0;
+/// How much high frequencies are damped each time they pass through a comb
+/// filter.
+const DAMPING: f32 = 0.2;
+const ALLPASS_FEEDBACK: f32 = 0.5;
+
+/// The parameters of a [`Reverb`], which can be changed while it runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReverbParams {
+    /// The balance between the dry and reverberated signal, between `0.0`
+    /// (dry) and `1.0` (wet).
+    pub mix: f32,
+    /// The size of the room between `0.0` and `1.0`, which sets how long the
+    /// reverb rings for.
+    pub size: f32,
+}
+
+unsafe impl NoUninit for ReverbParams {}
+
+impl ReverbParams {
+    /// The mix levels which can be cycled through, the first of which turns
+    /// the reverb off.
+    pub const MIX_STEPS: [f32; 4] = [0.0, 0.15, 0.3, 0.5];
+
+    /// Whether the reverb can be heard.
+    pub fn is_enabled(self) -> bool {
+        self.mix > 0.0
+    }
+
+    /// Cycles to the next mix level in [`Self::MIX_STEPS`], or to the first
+    /// if the mix isn't one of them.
+    pub fn cycle_mix(&mut self) {
+        let idx = Self::MIX_STEPS
+            .iter()
+            .position(|&mix| mix == self.mix)
+            .map_or(0, |i| (i + 1) % Self::MIX_STEPS.len());
+
+        self.mix = Self::MIX_STEPS[idx];
+    }
+}
+
+impl Default for ReverbParams {
+    fn default() -> Self {
+        Self { mix: 0.0, size: 0.5 }
+    }
+}
+
+impl std::fmt::Display for ReverbParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.is_enabled() {
+            return write!(f, "off");
+        }
+
+        write!(
+            f,
+            "{:.0}% (size {:.0}%)",
+            self.mix * 100.0,
+            self.size * 100.0
+        )
+    }
+}
+
+/// A delay line for each channel, where the right channel's may be longer.
+#[derive(Debug, Clone)]
+struct DelayLine {
+    buffers: [Vec<f32>; 2],
+    pos: [usize; 2],
+}
+
+impl DelayLine {
+    fn new(len: usize, spread: usize) -> Self {
+        Self {
+            buffers: [vec![0.0; len.max(1)], vec![0.0; (len + spread).max(1)]],
+            pos: [0, 0],
+        }
+    }
+
+    /// Returns the oldest sample of each channel.
+    #[inline]
+    fn read(&self) -> f32x2 {
+        f32x2::from_array([
+            self.buffers[CH_L][self.pos[CH_L]],
+            self.buffers[CH_R][self.pos[CH_R]],
+        ])
+    }
+
+    /// Replaces the oldest sample of each channel with `sample`.
+    #[inline]
+    fn write(&mut self, sample: f32x2) {
+        for ch in [CH_L, CH_R] {
+            let buf = &mut self.buffers[ch];
+            let pos = &mut self.pos[ch];
+
+            buf[*pos] = sample[ch];
+            *pos = (*pos + 1) % buf.len();
+        }
+    }
+}
+
+/// A feedback comb filter with a lowpass filter in its feedback path.
+#[derive(Debug, Clone)]
+struct Comb {
+    line: DelayLine,
+    z1: f32x2,
+}
+
+impl Comb {
+    #[inline]
+    fn tick(&mut self, input: f32x2, feedback: f32x2) -> f32x2 {
+        let damping = f32x2::splat(DAMPING);
+
+        let out = self.line.read();
+        self.z1 = out.mul_add(SIMD_ONE - damping, self.z1 * damping);
+        self.line.write(self.z1.mul_add(feedback, input));
+
+        out
+    }
+}
+
+/// A Freeverb-style stereo reverb, made up of parallel comb filters followed
+/// by allpass filters in series.
+#[derive(Debug, Clone)]
+pub struct Reverb {
+    combs: Vec<Comb>,
+    allpasses: Vec<DelayLine>,
+    params: ReverbParams,
+    feedback: f32x2,
+    sample_rate: f32,
+}
+
+impl Reverb {
+    /// Creates a new `Reverb`.
+    pub fn new(sample_rate: f32) -> Self {
+        let scale = |len: usize| {
+            (len as f32 * sample_rate / TUNING_SAMPLE_RATE).round() as usize
+        };
+        let spread = scale(STEREO_SPREAD);
+
+        let mut reverb = Self {
+            combs: COMB_LENGTHS
+                .iter()
+                .map(|&len| Comb {
+                    line: DelayLine::new(scale(len), spread),
+                    z1: SIMD_ZERO,
+                })
+                .collect(),
+            allpasses: ALLPASS_LENGTHS
+                .iter()
+                .map(|&len| DelayLine::new(scale(len), spread))
+                .collect(),
+            params: ReverbParams::default(),
+            feedback: SIMD_ZERO,
+            sample_rate,
+        };
+
+        reverb.set_params(ReverbParams::default());
+        reverb
+    }
+
+    /// Provides the mix and size to the reverb.
+    pub fn with_params(mut self, params: ReverbParams) -> Self {
+        self.set_params(params);
+        self
+    }
+
+    /// Sets the sample rate of the reverb, which resizes its delay lines. The
+    /// reverb's tail is cleared if the sample rate changes.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        if sample_rate != self.sample_rate {
+            *self = Self::new(sample_rate).with_params(self.params);
+        }
+    }
+
+    /// Sets the mix and size of the reverb, which are clamped between `0.0`
+    /// and `1.0`.
+    pub fn set_params(&mut self, params: ReverbParams) {
+        self.params = ReverbParams {
+            mix: params.mix.clamp(0.0, 1.0),
+            size: params.size.clamp(0.0, 1.0),
+        };
+        self.feedback = f32x2::splat(self.params.size.mul_add(0.28, 0.7));
+    }
+}
+
+impl SimdAudioEffect for Reverb {
+    #[inline]
+    fn tick(&mut self, sample: f32x2) -> f32x2 {
+        // both channels share a mono input, and the stereo image comes from
+        // the different delay lengths of each channel.
+        let input = f32x2::splat(sample.reduce_sum() * INPUT_GAIN);
+
+        let mut wet = SIMD_ZERO;
+        for comb in &mut self.combs {
+            wet += comb.tick(input, self.feedback);
+        }
+
+        let allpass_feedback = f32x2::splat(ALLPASS_FEEDBACK);
+        for allpass in &mut self.allpasses {
+            let delayed = allpass.read();
+            allpass.write(delayed.mul_add(allpass_feedback, wet));
+            wet = delayed - wet;
+        }
+
+        let mix = f32x2::splat(self.params.mix);
+        (wet * f32x2::splat(WET_GAIN)).mul_add(mix, sample * (SIMD_ONE - mix))
+    }
+
+    fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverb_tail() {
+        let mut dry = Reverb::new(48000.0);
+        let impulse = f32x2::splat(1.0);
+        assert_eq!(dry.tick(impulse), impulse);
+
+        let mut wet = Reverb::new(48000.0)
+            .with_params(ReverbParams { mix: 1.0, size: 0.5 });
+        wet.tick(impulse);
+
+        // the shortest comb filter is about 25 ms long at 48 kHz.
+        let tail = (0..4800).map(|_| wet.tick(SIMD_ZERO)[CH_L].abs());
+        assert!(tail.sum::<f32>() > 0.0);
+
+        // the parameters are kept when the sample rate changes.
+        wet.set_sample_rate(96000.0);
+        assert_eq!(wet.sample_rate(), 96000.0);
+        assert_eq!(wet.params, ReverbParams { mix: 1.0, size: 0.5 });
+    }
+}
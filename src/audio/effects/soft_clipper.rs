@@ -0,0 +1,84 @@
+use super::*;
+
+/// The fraction of the ceiling below which the clipper has no effect.
+const KNEE: f32 = 0.5;
+
+/// A stereo soft clipper, which leaves quiet samples untouched and smoothly
+/// saturates louder ones with a `tanh` curve so that they never exceed the
+/// ceiling.
+///
+/// The clipper has no state and doesn't depend on the sample rate, so unlike
+/// the other effects it isn't a [`SimdAudioEffect`].
+#[derive(Debug, Clone)]
+pub struct SoftClipper {
+    /// The linear gain which the output never exceeds.
+    ceiling: f32,
+}
+
+impl SoftClipper {
+    /// Creates a new `SoftClipper` with a ceiling of 0 dB.
+    pub const fn new() -> Self {
+        Self { ceiling: 1.0 }
+    }
+
+    /// Provides a ceiling (in decibels) to the clipper.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level_db` is greater than `0.0`.
+    pub fn with_ceiling_db(mut self, level_db: f32) -> Self {
+        self.set_ceiling_db(level_db);
+        self
+    }
+
+    /// Sets the clipper's ceiling in decibels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level_db` is greater than `0.0`.
+    pub fn set_ceiling_db(&mut self, level_db: f32) {
+        assert!(level_db <= 0.0);
+        self.ceiling = Decibels(level_db).to_gain().0;
+    }
+
+    #[inline]
+    fn clip(&self, sample: f32) -> f32 {
+        let threshold = self.ceiling * KNEE;
+        let x = sample.abs();
+
+        if x <= threshold {
+            return sample;
+        }
+
+        // the curve's slope matches the linear part at the threshold, and
+        // approaches the ceiling as the input grows.
+        let range = self.ceiling - threshold;
+        let y = range.mul_add(((x - threshold) / range).tanh(), threshold);
+
+        y.copysign(sample)
+    }
+
+    /// Clips both channels of `sample`.
+    #[inline]
+    pub fn tick(&self, sample: f32x2) -> f32x2 {
+        f32x2::from_array([self.clip(sample[CH_L]), self.clip(sample[CH_R])])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soft_clipping() {
+        let clipper = SoftClipper::new().with_ceiling_db(-6.0);
+        let ceiling = Decibels(-6.0).to_gain().0;
+
+        let quiet = f32x2::from_array([0.1, -0.2]);
+        assert_eq!(clipper.tick(quiet), quiet);
+
+        let loud = clipper.tick(f32x2::from_array([100.0, -100.0]));
+        assert!(loud[CH_L] > 0.0 && loud[CH_L] <= ceiling);
+        assert!(loud[CH_R] < 0.0 && loud[CH_R] >= -ceiling);
+    }
+}
@@ -0,0 +1,257 @@
+use super::*;
+
+/// The range which the crossover frequencies are clamped to, in Hz.
+const FREQ_RANGE: std::ops::RangeInclusive<f32> = 20.0..=20000.0;
+/// The range which the band gains are clamped to, in decibels.
+pub const TONE_GAIN_RANGE: std::ops::RangeInclusive<f32> = -24.0..=12.0;
+
+/// The parameters of a [`ToneControl`], which can be changed while it runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneParams {
+    /// The crossover between the low and mid bands, in Hz.
+    pub low_freq: f32,
+    /// The gain of the low band, in decibels.
+    pub low_db: f32,
+    /// The gain of the mid band, in decibels.
+    pub mid_db: f32,
+    /// The crossover between the mid and high bands, in Hz.
+    pub high_freq: f32,
+    /// The gain of the high band, in decibels.
+    pub high_db: f32,
+}
+
+unsafe impl NoUninit for ToneParams {}
+
+impl ToneParams {
+    /// The named tone settings which can be cycled through, the first of
+    /// which is the default.
+    pub const PRESETS: [(&'static str, Self); 3] = [
+        ("default", Self::new(300.0, -12.0, 0.0, 4000.0, 0.0)),
+        ("warm", Self::new(300.0, -3.0, 0.0, 2500.0, -9.0)),
+        ("bright", Self::new(400.0, -15.0, -2.0, 3000.0, 4.0)),
+    ];
+
+    pub const fn new(
+        low_freq: f32,
+        low_db: f32,
+        mid_db: f32,
+        high_freq: f32,
+        high_db: f32,
+    ) -> Self {
+        Self { low_freq, low_db, mid_db, high_freq, high_db }
+    }
+
+    /// Parses the tone from the name of a preset, or from the gains of the
+    /// low, mid and high bands in decibels, separated by commas — e.g.
+    /// `-12,0,3`. The crossovers of the default preset are used for the
+    /// latter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `s` isn't a preset and there aren't three gains,
+    /// or if any are outside [`TONE_GAIN_RANGE`].
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if let Some(&(_, preset)) = Self::PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+        {
+            return Ok(preset);
+        }
+
+        let values = parse_values(s)?;
+        let [low_db, mid_db, high_db] = values[..] else {
+            return Err(String::from(
+                "expected a preset, or the low, mid and high gains separated \
+                 by commas",
+            ));
+        };
+
+        if !values.iter().all(|g| TONE_GAIN_RANGE.contains(g)) {
+            return Err(format!(
+                "the gains must be between {} and {} dB",
+                TONE_GAIN_RANGE.start(),
+                TONE_GAIN_RANGE.end()
+            ));
+        }
+
+        Ok(Self { low_db, mid_db, high_db, ..Self::default() })
+    }
+
+    /// Parses the low and high crossover frequencies in Hz, separated by a
+    /// comma — e.g. `300,4000`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there aren't two frequencies, if either is
+    /// outside the audible range, or if the low crossover isn't below the
+    /// high one.
+    pub fn parse_crossovers(s: &str) -> Result<(f32, f32), String> {
+        let values = parse_values(s)?;
+        let [low, high] = values[..] else {
+            return Err(String::from(
+                "expected the low and high crossovers, separated by a comma",
+            ));
+        };
+
+        if !(FREQ_RANGE.contains(&low) && FREQ_RANGE.contains(&high)) {
+            return Err(format!(
+                "the crossovers must be between {} and {} Hz",
+                FREQ_RANGE.start(),
+                FREQ_RANGE.end()
+            ));
+        }
+
+        if low >= high {
+            return Err(String::from(
+                "the low crossover must be below the high crossover",
+            ));
+        }
+
+        Ok((low, high))
+    }
+
+    /// The name of the preset which matches these settings, if any.
+    pub fn preset_name(self) -> Option<&'static str> {
+        Self::PRESETS
+            .iter()
+            .find(|&&(_, preset)| preset == self)
+            .map(|&(name, _)| name)
+    }
+
+    /// The preset after these settings, or the first preset if these
+    /// settings aren't one.
+    pub fn next_preset(self) -> Self {
+        let idx = Self::PRESETS
+            .iter()
+            .position(|&(_, preset)| preset == self)
+            .map_or(0, |i| (i + 1) % Self::PRESETS.len());
+
+        Self::PRESETS[idx].1
+    }
+}
+
+impl Default for ToneParams {
+    fn default() -> Self {
+        Self::PRESETS[0].1
+    }
+}
+
+impl std::fmt::Display for ToneParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = self.preset_name() {
+            write!(f, "{name} ")?;
+        }
+
+        write!(
+            f,
+            "(low {:+.0} dB, mid {:+.0} dB, high {:+.0} dB)",
+            self.low_db, self.mid_db, self.high_db
+        )
+    }
+}
+
+/// Parses numbers separated by commas.
+fn parse_values(s: &str) -> Result<Vec<f32>, String> {
+    s.split(',')
+        .map(|v| v.trim().parse::<f32>().map_err(|e| format!("{e}")))
+        .collect()
+}
+
+/// A three-band tone control. The signal is split into low, mid and high
+/// bands with a pair of first-order filters, which sum back to the original
+/// signal when every band is at unity gain.
+#[derive(Debug, Clone)]
+pub struct ToneControl {
+    low: Filter,
+    high: Filter,
+    /// The linear gains of the low, mid and high bands.
+    gains: [f32x2; 3],
+    params: Option<ToneParams>,
+    sample_rate: f32,
+}
+
+impl ToneControl {
+    /// Creates a new `ToneControl` with the default settings.
+    pub fn new(sample_rate: f32) -> Self {
+        let mut tone = Self {
+            low: Filter::new(sample_rate).with_type(FilterType::Lowpass),
+            high: Filter::new(sample_rate).with_type(FilterType::Highpass),
+            gains: [SIMD_ONE; 3],
+            params: None,
+            sample_rate,
+        };
+
+        tone.set_params(ToneParams::default());
+        tone
+    }
+
+    /// Provides the crossovers and gains to the tone control.
+    pub fn with_params(mut self, params: ToneParams) -> Self {
+        self.set_params(params);
+        self
+    }
+
+    /// Sets the crossovers and gains of the tone control. The crossovers
+    /// are clamped below the Nyquist frequency, and the gains are clamped to
+    /// [`TONE_GAIN_RANGE`].
+    pub fn set_params(&mut self, params: ToneParams) {
+        if self.params == Some(params) {
+            return;
+        }
+
+        let max_freq = FREQ_RANGE.end().min(self.sample_rate * 0.49);
+        let freq = |hz: f32| hz.clamp(*FREQ_RANGE.start(), max_freq);
+        let gain = |db: f32| {
+            let (min, max) = TONE_GAIN_RANGE.into_inner();
+            f32x2::splat(Decibels(db.clamp(min, max)).to_gain().0)
+        };
+
+        self.low.set_freq(freq(params.low_freq));
+        self.high.set_freq(freq(params.high_freq));
+        self.gains =
+            [gain(params.low_db), gain(params.mid_db), gain(params.high_db)];
+        self.params = Some(params);
+    }
+}
+
+impl SimdAudioEffect for ToneControl {
+    #[inline]
+    fn tick(&mut self, sample: f32x2) -> f32x2 {
+        let [low_gain, mid_gain, high_gain] = self.gains;
+
+        let low = self.low.tick(sample);
+        let high = self.high.tick(sample);
+        let mid = sample - low - high;
+
+        low.mul_add(low_gain, mid.mul_add(mid_gain, high * high_gain))
+    }
+
+    fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_tone_is_transparent() {
+        let flat = ToneParams::new(300.0, 0.0, 0.0, 4000.0, 0.0);
+        let mut tone = ToneControl::new(48000.0).with_params(flat);
+
+        for i in 0..256 {
+            let x = f32x2::splat((i as f32 * 0.1).sin());
+            let y = tone.tick(x);
+            assert!((y - x).abs().reduce_max() < 1e-5);
+        }
+
+        assert_eq!(ToneParams::parse("warm"), Ok(ToneParams::PRESETS[1].1));
+        assert!(ToneParams::parse("0,0").is_err());
+        assert_eq!(
+            ToneParams::parse_crossovers("200, 5000"),
+            Ok((200.0, 5000.0))
+        );
+        assert!(ToneParams::parse_crossovers("5000,200").is_err());
+    }
+}
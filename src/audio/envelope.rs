@@ -1,41 +1,166 @@
 use super::*;
 
-/// A simple ramp amplitude envelope.
+/// The parameters of an ADSR amplitude envelope, where times are in seconds.
+///
+/// Notes have no "note off", so each note is released as soon as it has
+/// decayed to the sustain level — the sustain level is the level which the
+/// release starts from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Adsr {
+    pub attack: f32,
+    pub decay: f32,
+    /// The level which the decay falls to, between `0.0` and `1.0`.
+    pub sustain: f32,
+    pub release: f32,
+}
+
+unsafe impl NoUninit for Adsr {}
+
+impl Adsr {
+    /// The named envelopes which can be cycled through, the first of which
+    /// is the default.
+    pub const PRESETS: [(&'static str, Self); 4] = [
+        ("pluck", Self::new(0.01, 0.0, 1.0, 0.035)),
+        ("percussive", Self::new(0.001, 0.015, 0.3, 0.025)),
+        ("soft", Self::new(0.03, 0.0, 1.0, 0.12)),
+        ("swell", Self::new(0.08, 0.04, 0.7, 0.25)),
+    ];
+
+    pub const fn new(
+        attack: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+    ) -> Self {
+        Self { attack, decay, sustain, release }
+    }
+
+    /// Parses an envelope from the name of a preset, or from its attack,
+    /// decay and release times in milliseconds and its sustain level between
+    /// `0.0` and `1.0`, separated by commas — e.g. `10,0,1,35`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `s` isn't a preset and there aren't four values,
+    /// or if any are negative or the sustain level is above `1.0`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if let Some(&(_, preset)) = Self::PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+        {
+            return Ok(preset);
+        }
+
+        let values = s
+            .split(',')
+            .map(|v| v.trim().parse::<f32>().map_err(|e| format!("{e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let [attack, decay, sustain, release] = values[..] else {
+            return Err(String::from(
+                "expected the attack, decay, sustain and release, separated \
+                 by commas",
+            ));
+        };
+
+        if values.iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return Err(String::from("the values must be non-negative"));
+        }
+
+        if sustain > 1.0 {
+            return Err(String::from("the sustain level must be at most 1"));
+        }
+
+        Ok(Self::new(attack / 1e3, decay / 1e3, sustain, release / 1e3))
+    }
+
+    /// The name of the preset which matches this envelope, if any.
+    pub fn preset_name(self) -> Option<&'static str> {
+        Self::PRESETS
+            .iter()
+            .find(|&&(_, preset)| preset == self)
+            .map(|&(name, _)| name)
+    }
+
+    /// Renders the envelope at `sample_rate`, so that it can be shared by
+    /// every voice which uses it (see [`AdsrEnvelope`]).
+    pub fn render(self, sample_rate: f32) -> Arc<[f32]> {
+        let samples = |secs: f32| (secs * sample_rate).round() as usize;
+        let (attack, decay, release) =
+            (samples(self.attack), samples(self.decay), samples(self.release));
+        let sustain = self.sustain.clamp(0.0, 1.0);
+
+        let attack = (0..attack).map(|i| i as f32 / attack as f32);
+        let decay = (0..decay).map(|i| {
+            let x = i as f32 / decay as f32;
+            x.mul_add(sustain - 1.0, 1.0)
+        });
+        let release = (0..release).map(|i| {
+            let x = (release - i) as f32 / release as f32;
+            sustain * x.powf(1.5)
+        });
+
+        attack.chain(decay).chain(release).collect()
+    }
+
+    /// The preset after this envelope, or the first preset if this envelope
+    /// isn't one.
+    pub fn next_preset(self) -> Self {
+        let idx = Self::PRESETS
+            .iter()
+            .position(|&(_, preset)| preset == self)
+            .map_or(0, |i| (i + 1) % Self::PRESETS.len());
+
+        Self::PRESETS[idx].1
+    }
+}
+
+impl Default for Adsr {
+    fn default() -> Self {
+        Self::PRESETS[0].1
+    }
+}
+
+impl std::fmt::Display for Adsr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = self.preset_name() {
+            write!(f, "{name} ")?;
+        }
+
+        write!(
+            f,
+            "(A {:.0} ms, D {:.0} ms, S {:.0}%, R {:.0} ms)",
+            self.attack * 1e3,
+            self.decay * 1e3,
+            self.sustain * 100.0,
+            self.release * 1e3
+        )
+    }
+}
+
+/// The state of an envelope for a single note, which reads through an
+/// envelope rendered by [`Adsr::render()`].
 #[derive(Debug)]
-pub struct AmpEnvelope {
-    data: &'static [f32],
+pub struct AdsrEnvelope {
+    data: Arc<[f32]>,
     read_pos: usize,
     simd: f32x2,
 }
 
-impl AmpEnvelope {
-    /// Creates a new envelope.
-    pub const fn new(envelope_data: &[u8]) -> Self {
-        let f32_size = std::mem::size_of::<f32>();
-
-        Self {
-            data: unsafe {
-                std::slice::from_raw_parts(
-                    envelope_data.as_ptr().cast::<f32>(),
-                    envelope_data.len() / f32_size,
-                )
-            },
-            read_pos: 0,
-            simd: f32x2::from_array([0.0, 0.0]),
-        }
+impl AdsrEnvelope {
+    /// Creates an envelope which plays back `data`. The data is shared, so
+    /// later changes to the envelope only affect new notes.
+    pub fn new(data: Arc<[f32]>) -> Self {
+        Self { data, read_pos: 0, simd: f32x2::splat(0.0) }
     }
 
     /// Returns the next envelope sample. Returns `None` if the envelope has
     /// reached its end point.
     pub fn next(&mut self) -> Option<f32> {
-        if !self.is_active() {
-            return None;
-        }
-
-        let pos = self.read_pos;
+        let sample = self.data.get(self.read_pos).copied()?;
         self.read_pos += 1;
 
-        Some(self.data[pos])
+        Some(sample)
     }
 
     /// Returns the next envelope sample as a `f32x2` SIMD type. If the envelope
@@ -50,8 +175,43 @@ impl AmpEnvelope {
         self.simd
     }
 
+    /// Returns the next [`WIDE_FRAMES`] envelope samples, for both channels
+    /// — see [`to_wide()`].
+    #[inline]
+    pub fn next_wide(&mut self) -> f32x8 {
+        to_wide(std::array::from_fn(|_| self.next_simd()))
+    }
+
+    /// The last envelope sample, or `0.0` before the first.
+    pub fn level(&self) -> f32 {
+        self.simd[CH_L]
+    }
+
     /// Whether the envelope is active.
-    pub const fn is_active(&self) -> bool {
+    pub fn is_active(&self) -> bool {
         self.read_pos < self.data.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adsr_stages() {
+        // 2 samples each of attack, decay and release, at 1 kHz.
+        let adsr = Adsr::new(0.002, 0.002, 0.5, 0.002);
+        let mut env = AdsrEnvelope::new(adsr.render(1000.0));
+        let samples: Vec<f32> = std::iter::from_fn(|| env.next()).collect();
+
+        assert_eq!(samples.len(), 6);
+        assert_eq!(samples[..4], [0.0, 0.5, 1.0, 0.75]);
+        assert_eq!(samples[4], 0.5);
+        assert!(samples[5] < 0.5 && samples[5] > 0.0);
+
+        assert_eq!(Adsr::parse("10, 0, 1, 35"), Ok(Adsr::default()));
+        assert_eq!(Adsr::parse("Pluck"), Ok(Adsr::default()));
+        assert!(Adsr::parse("10,0,1.5,35").is_err());
+        assert!(Adsr::parse("10,0,1").is_err());
+    }
+}
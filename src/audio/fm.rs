@@ -0,0 +1,84 @@
+use super::*;
+
+/// The frequency of the modulator relative to the carrier. Whole numbers
+/// keep the sidebands harmonic.
+const RATIO: f32 = 2.0;
+/// The modulation index of a note at full amplitude.
+const MAX_INDEX: f32 = 4.0;
+
+/// A two-operator FM oscillator, where a sine wave modulator at [`RATIO`]
+/// times the frequency modulates the phase of a sine wave carrier.
+///
+/// The modulation index follows the amplitude of the note, so louder notes
+/// have more sidebands and a brighter, more metallic sound.
+#[derive(Debug)]
+pub struct FmOscSimd {
+    inc: f32x2,
+    carrier_phase: f32x2,
+    modulator_phase: f32x2,
+    index: f32x2,
+}
+
+impl FmOscSimd {
+    pub fn new(freq_hz: f32, sample_rate: f32, amp: f32) -> Self {
+        debug_assert!(0.0 < freq_hz && freq_hz <= sample_rate * 0.5);
+
+        let inc = freq_hz / sample_rate;
+        let index = amp.clamp(0.0, 1.0) * MAX_INDEX;
+
+        Self {
+            inc: f32x2::splat(inc),
+            carrier_phase: f32x2::splat(0.0),
+            modulator_phase: f32x2::splat(0.0),
+            index: f32x2::splat(index),
+        }
+    }
+}
+
+impl SimdOscillator for FmOscSimd {
+    fn set_frequency(&mut self, freq_hz: f32, sample_rate: f32) {
+        debug_assert!(0.0 < freq_hz && freq_hz <= sample_rate * 0.5);
+
+        let inc = freq_hz / sample_rate;
+
+        self.inc[CH_L] = inc;
+        self.inc[CH_R] = inc;
+    }
+
+    #[inline]
+    fn tick(&mut self) -> f32x2 {
+        let modulator = (self.modulator_phase * SIMD_TAU).sin();
+        let output = self
+            .index
+            .mul_add(modulator, self.carrier_phase * SIMD_TAU)
+            .sin();
+
+        self.carrier_phase = (self.carrier_phase + self.inc).fract();
+        self.modulator_phase = self
+            .inc
+            .mul_add(f32x2::splat(RATIO), self.modulator_phase)
+            .fract();
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silent_notes_are_unmodulated() {
+        let sr = SAMPLE_RATE as f32;
+        let mut fm = FmOscSimd::new(440.0, sr, 0.0);
+        let mut sine = sine::SineOscSimd::new(440.0, sr);
+
+        for _ in 0..1000 {
+            let (a, b) = (fm.tick()[CH_L], sine.tick()[CH_L]);
+            assert!((a - b).abs() < 1e-5, "{a} != {b}");
+        }
+
+        let mut fm = FmOscSimd::new(440.0, sr, 1.0);
+        assert!((0..1000).all(|_| fm.tick()[CH_L].abs() <= 1.0));
+    }
+}
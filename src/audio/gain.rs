@@ -0,0 +1,108 @@
+use super::*;
+
+/// The gain of each voice at full volume, while few voices are active.
+const VOICE_GAIN: f32 = 0.08;
+/// The number of active voices up to which the voice gain isn't compensated.
+const UNCOMPENSATED_VOICES: f32 = 16.0;
+/// The time which the voice gain takes to follow changes in the volume and
+/// voice count, in seconds.
+const SMOOTHING_TIME: f32 = 0.05;
+
+/// A gain which moves linearly from `start` to `end` over a buffer, so that
+/// it changes without clicks.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GainRamp {
+    pub start: f32,
+    pub end: f32,
+}
+
+// SAFETY: GainRamp is two f32s, so it contains no padding.
+unsafe impl NoUninit for GainRamp {}
+
+impl GainRamp {
+    /// The gain at sample `pos` of a buffer `len` samples long.
+    #[inline]
+    pub fn at(self, pos: usize, len: usize) -> f32 {
+        (self.end - self.start).mul_add(pos as f32 / len as f32, self.start)
+    }
+}
+
+impl Default for GainRamp {
+    fn default() -> Self {
+        Self { start: VOICE_GAIN, end: VOICE_GAIN }
+    }
+}
+
+/// The gain of the voices, which follows the master volume and is lowered as
+/// more voices play at once, so that dense passages don't overwhelm the
+/// compressor. It is shared with the voice threads as a [`GainRamp`] for each
+/// buffer.
+#[derive(Debug)]
+pub struct VoiceGain {
+    ramp: Arc<Atomic<GainRamp>>,
+    gain: f32,
+}
+
+impl VoiceGain {
+    pub fn new() -> Self {
+        Self {
+            ramp: Arc::new(Atomic::new(GainRamp::default())),
+            gain: VOICE_GAIN,
+        }
+    }
+
+    /// The gain ramp of the current buffer.
+    pub const fn ramp(&self) -> &Arc<Atomic<GainRamp>> {
+        &self.ramp
+    }
+
+    /// Moves the gain towards the gain for `volume_db` and `num_voices`
+    /// voices over the next buffer, which lasts for `buffer_time` seconds.
+    pub fn update(
+        &mut self,
+        volume_db: f32,
+        num_voices: u32,
+        buffer_time: f32,
+    ) {
+        let voices = (num_voices as f32).max(UNCOMPENSATED_VOICES);
+        let compensation = (UNCOMPENSATED_VOICES / voices).sqrt();
+        let volume = Decibels(volume_db).to_gain().0;
+        let target = VOICE_GAIN * volume * compensation;
+
+        let coeff = 1.0 - (-buffer_time / SMOOTHING_TIME).exp();
+        let start = self.gain;
+        self.gain = (target - start).mul_add(coeff, start);
+
+        self.ramp.store(GainRamp { start, end: self.gain }, Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gain_ramps_and_compensates() {
+        let mut gain = VoiceGain::new();
+        let buffer_time = 0.01;
+
+        gain.update(0.0, 4, buffer_time);
+        assert_eq!(gain.ramp().load(Relaxed), GainRamp::default());
+
+        // many voices lower the gain, but gradually.
+        gain.update(0.0, 1024, buffer_time);
+        let ramp = gain.ramp().load(Relaxed);
+        assert_eq!(ramp.start, VOICE_GAIN);
+        assert!(ramp.end < VOICE_GAIN && ramp.end > VOICE_GAIN / 8.0);
+        assert_eq!(ramp.at(0, 512), ramp.start);
+        let mid = ramp.at(256, 512);
+        assert!(mid < ramp.start && mid > ramp.end);
+
+        for _ in 0..100 {
+            gain.update(-6.0, 1024, buffer_time);
+        }
+        let target = VOICE_GAIN * Decibels(-6.0).to_gain().0 / 8.0;
+        assert!((gain.ramp().load(Relaxed).end - target).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,102 @@
+use super::*;
+
+/// The DSP load above which new notes start to be thinned out.
+const THIN_THRESHOLD: f32 = 0.7;
+/// The DSP load above which active voices are culled.
+const CULL_THRESHOLD: f32 = 0.9;
+/// The largest fraction of new notes which are dropped.
+const MAX_THINNING: f32 = 0.9;
+/// The fraction of active voices which are culled each buffer while the load
+/// is above [`CULL_THRESHOLD`].
+const CULL_FRACTION: f32 = 0.1;
+/// The amount which the thinning falls by each buffer once the load drops,
+/// so that notes return gradually rather than all at once.
+const RECOVERY_PER_BUFFER: f32 = 0.01;
+/// How quickly the smoothed load follows the load when it falls, per buffer.
+const LOAD_SMOOTHING: f32 = 0.1;
+
+/// The feedback from a [`LoadGovernor`] to the rest of the app.
+#[derive(Clone, Debug, Default)]
+pub struct LoadFeedback {
+    /// The fraction of new notes which the players drop, between `0.0` and
+    /// [`MAX_THINNING`].
+    pub thinning: Arc<Atomic<f32>>,
+    /// The fraction of active voices which the voice handlers cull in their
+    /// next buffer.
+    pub cull: Arc<Atomic<f32>>,
+}
+
+/// Watches the DSP load of the audio callback, and thins out new notes and
+/// culls the quietest voices as it approaches 100%, so that the audio
+/// degrades gracefully rather than glitching.
+#[derive(Debug)]
+pub struct LoadGovernor {
+    feedback: LoadFeedback,
+    /// The load, which rises immediately but falls smoothly.
+    load: f32,
+}
+
+impl LoadGovernor {
+    /// Creates a governor which reports to `feedback`.
+    pub fn new(feedback: LoadFeedback) -> Self {
+        Self { feedback, load: 0.0 }
+    }
+
+    /// Updates the thinning and culling with the load of the last buffer.
+    pub fn update(&mut self, load: f32) {
+        self.load = if load > self.load {
+            load
+        }
+        else {
+            (load - self.load).mul_add(LOAD_SMOOTHING, self.load)
+        };
+
+        let over = (self.load - THIN_THRESHOLD) / (1.0 - THIN_THRESHOLD);
+        let target = over.clamp(0.0, 1.0) * MAX_THINNING;
+        let thinning = self.feedback.thinning.load(Relaxed);
+
+        self.feedback.thinning.store(
+            if target > thinning {
+                target
+            }
+            else {
+                (thinning - RECOVERY_PER_BUFFER).max(target)
+            },
+            Relaxed,
+        );
+
+        let cull = if load > CULL_THRESHOLD { CULL_FRACTION } else { 0.0 };
+        self.feedback.cull.store(cull, Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn governor_thins_and_recovers() {
+        let feedback = LoadFeedback::default();
+        let mut governor = LoadGovernor::new(feedback.clone());
+
+        governor.update(0.5);
+        assert_eq!(feedback.thinning.load(Relaxed), 0.0);
+
+        governor.update(1.2);
+        assert_eq!(feedback.thinning.load(Relaxed), MAX_THINNING);
+        assert_eq!(feedback.cull.load(Relaxed), CULL_FRACTION);
+
+        // the notes come back gradually once the load falls.
+        for _ in 0..10 {
+            governor.update(0.1);
+        }
+        let thinning = feedback.thinning.load(Relaxed);
+        assert!(thinning > 0.0 && thinning < MAX_THINNING);
+        assert_eq!(feedback.cull.load(Relaxed), 0.0);
+
+        for _ in 0..200 {
+            governor.update(0.1);
+        }
+        assert_eq!(feedback.thinning.load(Relaxed), 0.0);
+    }
+}
@@ -0,0 +1,91 @@
+use super::*;
+
+/// The time which the displayed load takes to rise towards a higher load, in
+/// seconds.
+const RISE_TIME: f32 = 0.05;
+/// The time which the displayed load takes to fall towards a lower load, in
+/// seconds, which is longer so that brief dips don't hide a high load.
+const FALL_TIME: f32 = 0.5;
+
+/// Measures the DSP load of the audio callback: the time spent processing
+/// each buffer as a fraction of the time which the buffer lasts for. The load
+/// is smoothed before it is stored, so that the displayed value is stable.
+#[derive(Debug)]
+pub struct LoadMeter {
+    /// The smoothed load, which is shared with the UI.
+    output: Arc<Atomic<f32>>,
+    smoothed: f32,
+    /// When processing of the current buffer started.
+    start: Instant,
+}
+
+impl LoadMeter {
+    /// Creates a meter which stores its smoothed load in `output`.
+    pub fn new(output: Arc<Atomic<f32>>) -> Self {
+        Self { output, smoothed: 0.0, start: Instant::now() }
+    }
+
+    /// The smoothed load.
+    #[cfg(test)]
+    pub const fn output(&self) -> &Arc<Atomic<f32>> {
+        &self.output
+    }
+
+    /// Starts measuring a buffer.
+    pub fn begin(&mut self) {
+        self.start = Instant::now();
+    }
+
+    /// Finishes measuring a buffer of `frames` samples at `sample_rate`, and
+    /// returns its unsmoothed load.
+    pub fn end(&mut self, frames: usize, sample_rate: u32) -> f32 {
+        let buffer_time = frames as f32 / sample_rate as f32;
+        if buffer_time <= 0.0 {
+            return 0.0;
+        }
+
+        let load = self.start.elapsed().as_secs_f32() / buffer_time;
+        self.smooth(load, buffer_time);
+
+        load
+    }
+
+    /// Lets the load fall towards zero while no audio is processed (e.g.
+    /// while muted).
+    pub fn idle(&mut self, frames: usize, sample_rate: u32) {
+        self.smooth(0.0, frames as f32 / sample_rate as f32);
+    }
+
+    fn smooth(&mut self, load: f32, buffer_time: f32) {
+        let time = if load > self.smoothed { RISE_TIME } else { FALL_TIME };
+        let coeff = 1.0 - (-buffer_time / time).exp();
+
+        self.smoothed = (load - self.smoothed).mul_add(coeff, self.smoothed);
+        self.output.store(self.smoothed, Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_is_smoothed() {
+        let mut meter = LoadMeter::new(Arc::new(Atomic::new(0.0)));
+        let output = Arc::clone(meter.output());
+
+        // a sudden full load shows up quickly, but not all at once.
+        meter.smooth(1.0, 0.01);
+        let first = output.load(Relaxed);
+        assert!(first > 0.0 && first < 1.0);
+
+        for _ in 0..100 {
+            meter.smooth(1.0, 0.01);
+        }
+        assert!(output.load(Relaxed) > 0.99);
+
+        // and falls more slowly than it rose.
+        meter.idle(480, 48000);
+        assert!(1.0 - output.load(Relaxed) < first);
+    }
+}
@@ -5,35 +5,54 @@ use crossbeam_channel::Receiver;
 use nannou_audio::*;
 use std::sync::atomic::AtomicU32;
 use std::time::Instant;
-use thread_pool::{AudioThreadPool, AudioThreadPoolReferences, MAX_BLOCK_SIZE};
-
+use gain::{GainRamp, VoiceGain};
+use thread_pool::{AudioThreadPool, MAX_BLOCK_SIZE};
+
+pub use clock::AudioClock;
+pub use envelope::Adsr;
+pub use governor::{LoadFeedback, LoadGovernor};
+pub use load_meter::LoadMeter;
+pub use offline::OfflineAudio;
 pub use effects::AudioEffect;
 pub use effects::*;
-pub use voice::{VoiceHandler, NUM_VOICES};
+pub use units::{Decibels, LinearGain, MidiNote, Tuning};
+pub use voice::{
+    OverrideVoiceBehavior, Polyphony, VoiceHandler, MIN_VOICES, NUM_VOICES,
+};
 
+mod clock;
 pub mod effects;
 mod envelope;
+mod fm;
+mod gain;
+mod governor;
+mod load_meter;
+mod noise;
+mod offline;
 mod process;
+mod pulse;
+mod saw;
 mod sine;
 mod thread_pool;
 mod tri;
+pub mod units;
 mod voice;
 
 pub const CH_L: usize = 0;
 pub const CH_R: usize = 1;
 
-// Musical scales
-pub const MAJ_PENT_SCALE: [f32; 5] = [0.0, 2.0, 4.0, 7.0, 9.0];
-pub const MIN_PENT_SCALE: [f32; 5] = [0.0, 3.0, 5.0, 7.0, 10.0];
-pub const MAJOR_SCALE: [f32; 7] = [0.0, 2.0, 4.0, 5.0, 7.0, 9.0, 11.0];
-pub const MINOR_SCALE: [f32; 7] = [0.0, 2.0, 3.0, 5.0, 7.0, 8.0, 10.0];
-
-/// The app's audio sample rate.
+/// The app's preferred audio sample rate, which is used whenever the output
+/// device supports it — see [`device_sample_rate()`]. Exports are always
+/// rendered at this rate.
 pub const SAMPLE_RATE: u32 = 48000;
 /// The number of audio channels.
 pub const NUM_CHANNELS: usize = 2;
-/// The app's audio buffer size.
+/// The app's default audio buffer size.
 pub const BUFFER_SIZE: usize = 1 << 9; // 512
+/// The audio buffer sizes which can be chosen — smaller buffers have less
+/// latency, while larger buffers are less likely to glitch on slower
+/// machines.
+pub const BUFFER_SIZES: [usize; 3] = [256, 512, 1024];
 
 /// The number of threads used for concurrent audio generation.
 const NUM_AUDIO_THREADS: usize = 16;
@@ -46,6 +65,16 @@ pub enum OscillatorType {
     #[default]
     Sine,
     Tri,
+    Saw,
+    /// A pulse wave which is high for `width` of each cycle, between `0.0`
+    /// and `1.0`.
+    Pulse { width: f32 },
+    /// A short burst of noise, which is pink if `pink` is set and white
+    /// otherwise.
+    Noise { pink: bool },
+    /// Two-operator FM, whose modulation index follows the amplitude of the
+    /// note.
+    Fm,
 }
 
 /// Trait for oscillators.
@@ -54,10 +83,124 @@ pub trait Oscillator: std::fmt::Debug {
     fn tick(&mut self) -> f32;
 }
 
+/// The number of stereo frames in a wide (`f32x8`) SIMD value.
+pub const WIDE_FRAMES: usize = 4;
+/// The offset of each sample in a wide SIMD value from its first frame.
+pub const WIDE_FRAME_OFFSETS: f32x8 =
+    f32x8::from_array([0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0]);
+
 /// Trait for SIMD oscillators.
 pub trait SimdOscillator: std::fmt::Debug {
     fn set_frequency(&mut self, freq_hz: f32, sample_rate: f32);
     fn tick(&mut self) -> f32x2;
+
+    /// Returns the next [`WIDE_FRAMES`] stereo samples at once — see
+    /// [`to_wide()`]. Oscillators which can compute several samples in
+    /// parallel override this, while the others tick once per frame.
+    #[inline]
+    fn tick_wide(&mut self) -> f32x8 {
+        to_wide([self.tick(), self.tick(), self.tick(), self.tick()])
+    }
+}
+
+/// Packs [`WIDE_FRAMES`] stereo frames into a single SIMD value, with the
+/// channels of each frame interleaved.
+#[inline]
+pub fn to_wide(frames: [f32x2; WIDE_FRAMES]) -> f32x8 {
+    let [a, b, c, d] = frames;
+
+    f32x8::from_array([
+        a[CH_L], a[CH_R], b[CH_L], b[CH_R], c[CH_L], c[CH_R], d[CH_L], d[CH_R],
+    ])
+}
+
+/// Unpacks the stereo frames of a wide SIMD value — see [`to_wide()`].
+#[inline]
+pub fn from_wide(wide: f32x8) -> [f32x2; WIDE_FRAMES] {
+    let x = wide.to_array();
+
+    std::array::from_fn(|i| f32x2::from_array([x[i * 2], x[i * 2 + 1]]))
+}
+
+/// Repeats a stereo frame across a wide SIMD value.
+#[inline]
+pub fn splat_wide(frame: f32x2) -> f32x8 {
+    std::simd::simd_swizzle!(frame, [0, 1, 0, 1, 0, 1, 0, 1])
+}
+
+/// Returns the sample rate which the default output device should be opened
+/// at: [`SAMPLE_RATE`] if the device supports it, or the device's own default
+/// rate otherwise (e.g. for devices which are fixed at 44.1 kHz).
+pub fn device_sample_rate() -> u32 {
+    let Some(device) = Host::new().default_output_device() else {
+        return SAMPLE_RATE;
+    };
+
+    let supports_preferred =
+        device.supported_output_configs().is_ok_and(|mut configs| {
+            configs.any(|config| {
+                (config.min_sample_rate().0..=config.max_sample_rate().0)
+                    .contains(&SAMPLE_RATE)
+            })
+        });
+
+    if supports_preferred {
+        return SAMPLE_RATE;
+    }
+
+    device
+        .default_output_config()
+        .map_or(SAMPLE_RATE, |config| config.sample_rate().0)
+}
+
+/// The sample rate and buffer size which the audio engine runs at, which are
+/// fixed once it has been created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AudioConfig {
+    pub sample_rate: u32,
+    /// The number of samples in each buffer, which is a power of two.
+    pub buffer_size: usize,
+}
+
+impl AudioConfig {
+    /// The time taken to play each buffer, in seconds.
+    pub fn buffer_time(self) -> f64 {
+        self.buffer_size as f64 / self.sample_rate as f64
+    }
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self { sample_rate: SAMPLE_RATE, buffer_size: BUFFER_SIZE }
+    }
+}
+
+/// The parameters of the audio engine which can be changed while it runs,
+/// which are shared between the app and the audio threads.
+#[derive(Clone, Debug, Default)]
+pub struct AudioParams {
+    /// The amplitude envelope of new voices.
+    pub adsr: Arc<Atomic<Adsr>>,
+    /// The tone control on the output.
+    pub tone: Arc<Atomic<ToneParams>>,
+    /// The echoes on the output.
+    pub delay: Arc<Atomic<DelayParams>>,
+    /// The reverb on the output.
+    pub reverb: Arc<Atomic<ReverbParams>>,
+    /// The master volume of the voices, in decibels.
+    pub volume_db: Arc<Atomic<f32>>,
+    /// The makeup gain of the output compressor.
+    pub makeup: Arc<Atomic<MakeupGain>>,
+    /// The level which the output is soft-clipped to, in decibels.
+    pub ceiling_db: Arc<Atomic<f32>>,
+    /// The polyphony cap, and what happens to new notes beyond it.
+    pub polyphony: Arc<Atomic<Polyphony>>,
+    /// The feedback from the load governor, which thins out notes and
+    /// voices under high DSP load.
+    pub load: LoadFeedback,
+    /// The current gain reduction of the output compressor in decibels,
+    /// which is shown in the UI.
+    pub gain_reduction_db: Arc<Atomic<f32>>,
 }
 
 /// An atomic-compatible wrapper around an `Instant`.
@@ -78,160 +221,192 @@ impl std::ops::Deref for InstantTime {
 pub struct Audio {
     /// A receiver for income audio note events.
     note_receiver: Arc<Receiver<NoteEvent>>,
-    /// The sample rate.
-    sample_rate: u32,
-
-    /// The audio voice handlers.
-    // TODO: these don't need to be stored here, and can be moved into the
-    // audio threads.
-    voice_handlers: Vec<Arc<Mutex<VoiceHandler>>>,
-    /// The audio voice buffers.
-    voice_buffers: Vec<Arc<Mutex<Vec<f32x2>>>>,
+    /// The sample rate and buffer size.
+    config: AudioConfig,
+
     /// The counters for the number of active voices for each voice handler.
     voice_counters: Vec<Arc<AtomicU32>>,
-    /// The buffers which were modified (i.e. written to) for this block.
-    modified_buffers: Vec<Arc<AtomicBool>>,
-    /// The audio voice thread pool.
+    /// The audio voice thread pool, which holds the voice buffers.
     thread_pool: AudioThreadPool,
+    /// The gain of the voices, which the voice threads follow.
+    voice_gain: VoiceGain,
 
     /// The "main" audio buffer for the audio model, which uses SIMD values and
     /// is copied to the main buffer.
     main_buffer: Vec<f32x2>,
 
-    callback_timer: Arc<Atomic<InstantTime>>,
+    /// The clock of the audio callback, which drives playback.
+    clock: AudioClock,
 
     voice_counter: Arc<AtomicU32>,
 
     running: bool,
     compressor: Compressor,
-    lp: Filter,
-    hp: Filter,
-    dsp_load: Arc<Atomic<f32>>,
+    tone: ToneControl,
+    delay: Delay,
+    reverb: Reverb,
+    clipper: SoftClipper,
+    params: AudioParams,
+    load_meter: LoadMeter,
+    governor: LoadGovernor,
 }
 
 impl Audio {
-    /// Creates a new `AudioModel`.
+    /// Creates a new `AudioModel` which runs with `config`, and follows any
+    /// changes to `params`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer size isn't a power of two.
     pub fn new(
         note_receiver: Receiver<NoteEvent>,
         voice_counter: Arc<AtomicU32>,
+        params: &AudioParams,
+        config: AudioConfig,
     ) -> Self {
-        const { assert!(BUFFER_SIZE.is_power_of_two()) }
+        assert!(config.buffer_size.is_power_of_two());
         const { assert!(NUM_AUDIO_THREADS.is_power_of_two()) }
         const { assert!(NUM_VOICES.is_power_of_two()) }
 
-        let sr = SAMPLE_RATE as f32;
+        let sr = config.sample_rate as f32;
+        let buffer_size = config.buffer_size;
         let note_receiver = Arc::new(note_receiver);
 
-        let voice_handlers: Vec<Arc<Mutex<VoiceHandler>>> = (0
-            ..NUM_AUDIO_THREADS)
+        // each audio thread owns its voice handler, and they all share the
+        // same rendered envelope.
+        let envelope = params.adsr.load(Relaxed).render(sr);
+        let voice_handlers: Vec<VoiceHandler> = (0..NUM_AUDIO_THREADS)
             .map(|_| {
-                Arc::new(Mutex::new(VoiceHandler::new::<VOICES_PER_HANDLER>(
-                    sr,
-                )))
+                let envelope = Arc::clone(&envelope);
+                VoiceHandler::new::<VOICES_PER_HANDLER>(sr, envelope, params)
             })
             .collect();
 
-        // note that this program only supports two channels, so we use f32x2 as
-        // the sample type to represent both channels.
-        let voice_buffers: Vec<Arc<Mutex<Vec<f32x2>>>> = (0..NUM_AUDIO_THREADS)
-            .map(|_| Arc::new(Mutex::new(vec![f32x2::splat(0.0); BUFFER_SIZE])))
-            .collect();
-
-        let modified_buffers: Vec<Arc<AtomicBool>> = (0..NUM_AUDIO_THREADS)
-            .map(|_| Arc::new(AtomicBool::new(false)))
-            .collect();
-
         let voice_counters: Vec<Arc<AtomicU32>> = (0..NUM_AUDIO_THREADS)
             .map(|_| Arc::new(AtomicU32::new(0)))
             .collect();
 
+        let voice_gain = VoiceGain::new();
+
         Self {
-            sample_rate: SAMPLE_RATE,
+            config,
 
+            // note that this program only supports two channels, so we use
+            // f32x2 as the sample type to represent both channels.
             thread_pool: AudioThreadPool::build(
-                &AudioThreadPoolReferences {
-                    output_buffers: &voice_buffers,
-                    voice_handlers: &voice_handlers,
-                    voice_counters: &voice_counters,
-                    modified_flags: &modified_buffers,
-                },
+                voice_handlers,
+                &voice_counters,
+                voice_gain.ramp(),
                 &note_receiver,
+                buffer_size,
             )
             .expect("failed to create audio thread pool"),
+            voice_gain,
 
             note_receiver,
 
-            voice_handlers,
-            voice_buffers,
             voice_counters,
-            modified_buffers,
 
-            main_buffer: vec![f32x2::splat(0.0); BUFFER_SIZE],
+            main_buffer: vec![f32x2::splat(0.0); buffer_size],
 
-            callback_timer: Arc::new(Atomic::new(InstantTime(Instant::now()))),
+            clock: AudioClock::new(config),
             voice_counter,
             running: true,
             compressor: Compressor::new(sr)
                 .with_threshold_db(-18.0)
                 .with_ratio(100.0)
-                .with_knee_width(12.0),
-            lp: Filter::new(sr)
-                .with_type(FilterType::Lowpass)
-                .with_freq(4000.0),
-            hp: Filter::new(sr)
-                .with_type(FilterType::Highpass)
-                .with_freq(300.0),
-            dsp_load: Arc::new(Atomic::new(0.0)),
+                .with_knee_width(12.0)
+                .with_makeup_gain(params.makeup.load(Relaxed)),
+            tone: ToneControl::new(sr)
+                .with_params(params.tone.load(Relaxed)),
+            delay: Delay::new(sr).with_params(params.delay.load(Relaxed)),
+            reverb: Reverb::new(sr).with_params(params.reverb.load(Relaxed)),
+            clipper: SoftClipper::new()
+                .with_ceiling_db(params.ceiling_db.load(Relaxed)),
+            params: params.clone(),
+            load_meter: LoadMeter::new(Arc::new(Atomic::new(0.0))),
+            governor: LoadGovernor::new(params.load.clone()),
         }
     }
 
+    /// Creates an engine which isn't connected to a device or to any
+    /// players — its notes are passed to [`Self::render_block_offline()`]
+    /// instead.
+    pub fn new_offline(params: &AudioParams, config: AudioConfig) -> Self {
+        let (_, note_receiver) = crossbeam_channel::unbounded();
+
+        Self::new(note_receiver, Arc::new(AtomicU32::new(0)), params, config)
+    }
+
     /// Returns a reference to the audio note receiver.
     pub const fn note_receiver(&self) -> &Arc<Receiver<NoteEvent>> {
         &self.note_receiver
     }
 
-    /// Returns a reference to the callback timer.
-    pub const fn callback_timer(&self) -> &Arc<Atomic<InstantTime>> {
-        &self.callback_timer
+    /// Returns a reference to the audio clock.
+    pub const fn clock(&self) -> &AudioClock {
+        &self.clock
     }
 
-    /// Returns a reference to the DSP load level.
-    pub const fn dsp_load(&self) -> &Arc<Atomic<f32>> {
-        &self.dsp_load
+    /// Replaces the engine's clock with `clock`, which is moved to this
+    /// engine's sample rate and buffer size. This lets a rebuilt engine keep
+    /// driving the players of the engine it replaces.
+    pub fn with_clock(mut self, clock: AudioClock) -> Self {
+        clock.set_config(self.config);
+        self.clock = clock;
+        self
     }
 
-    pub fn update_callback_timer(&self) {
-        let timer = &self.callback_timer;
-
-        if timer.load(Relaxed).elapsed().as_secs_f32() >= 0.0001 {
-            timer.store(InstantTime(Instant::now()), Relaxed);
-        }
+    /// Replaces the engine's DSP load level with `dsp_load`.
+    pub fn with_dsp_load(mut self, dsp_load: Arc<Atomic<f32>>) -> Self {
+        self.load_meter = LoadMeter::new(dsp_load);
+        self
     }
 
-    /// Converts the `AudioModel` into a CPAL audio stream.
-    pub fn into_stream(self) -> Stream<Self> {
+    /// Converts the `AudioModel` into a CPAL audio stream on the default
+    /// output device, and starts it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no output device, or if the stream can't
+    /// be built or started on it.
+    pub fn into_stream(self) -> Result<Stream<Self>, String> {
         let audio_host = Host::new();
-        let sr = self.sample_rate;
+        let AudioConfig { sample_rate, buffer_size } = self.config;
 
         let stream = audio_host
             .new_output_stream(self)
             .render(process::process)
             .channels(NUM_CHANNELS)
-            .sample_rate(sr)
-            .frames_per_buffer(BUFFER_SIZE)
+            .sample_rate(sample_rate)
+            .frames_per_buffer(buffer_size)
             .build()
-            .unwrap();
-
-        stream.play().unwrap();
+            .map_err(|e| format!("failed to build audio stream: {e}"))?;
 
         stream
+            .play()
+            .map_err(|e| format!("failed to start audio stream: {e}"))?;
+
+        Ok(stream)
+    }
+
+    /// Moves the engine to `sample_rate`, if the stream runs at a different
+    /// rate to the one it was created for. The clock and the compressor
+    /// follow the new rate, so that playback and the dynamics keep their
+    /// timing.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        if sample_rate == self.config.sample_rate {
+            return;
+        }
+
+        self.config.sample_rate = sample_rate;
+        self.clock.set_config(self.config);
+        self.compressor.set_sample_rate(sample_rate as f32);
+        self.reverb.set_sample_rate(sample_rate as f32);
     }
 
     pub fn stop(&mut self) {
         self.running = false;
-        self.voice_buffers
-            .iter()
-            .for_each(|b| b.lock().fill(f32x2::splat(0.0)));
     }
 
     pub fn start(&mut self) {
@@ -254,74 +429,6 @@ impl Audio {
         }
     }
 
-    /// Calculates the frequency value of the provided MIDI note value, relative
-    /// to 440 Hz.
-    #[inline]
-    pub fn note_to_freq(note_value: f32) -> f32 {
-        const TUNING_FREQ_HZ: f32 = 440.0;
-
-        ((note_value - 69.0) / 12.0).exp2() * TUNING_FREQ_HZ
-    }
-
-    pub fn quantize_to_scale(scale: &[f32], note: f32, root: f32) -> f32 {
-        let mut lower = root;
-
-        while !(lower..=(lower + 12.0)).contains(&note) {
-            lower += if note > lower { 12.0 } else { -12.0 };
-        }
-
-        let mut min = f32::MAX;
-        let mut idx = 0;
-
-        for (i, &int) in scale.iter().enumerate() {
-            let cur = lower + int;
-            let val = (note - cur).abs();
-
-            if val < min {
-                min = val;
-                idx = i;
-            }
-        }
-
-        lower + scale[idx]
-    }
-
-    /// Calculates amplitude in decibels from a linear power level.
-    #[inline]
-    pub fn level_to_db(level: f32) -> f32 {
-        20.0 * level.abs().log10()
-    }
-
-    /// Calculates amplitude in decibels from a SIMD vector of linear power
-    /// levels.
-    #[inline]
-    pub fn level_to_db_simd(level: f32x2) -> f32x2 {
-        const SIMD_20: f32x2 = f32x2::from_array([20.0, 20.0]);
-        SIMD_20 * level.abs().log10()
-    }
-
-    /// Calculates the linear power level from a SIMD vector of amplitude (as
-    /// decibels).
-    #[inline]
-    pub fn db_to_level_simd(db_value: f32x2) -> f32x2 {
-        const SIMD_10: f32x2 = f32x2::from_array([10.0, 10.0]);
-        const SIMD_20: f32x2 = f32x2::from_array([20.0, 20.0]);
-
-        // there is no `powf()` implemented for SIMD types, but we can use
-        // exp2(log2(x) * power) to achieve the same result. the below is
-        // therefore equivalent to `SIMD_10.powf(db_value / SIMD_20)`.
-        f32x2::exp2(f32x2::log2(SIMD_10) * (db_value / SIMD_20))
-
-        // alternatively...
-        // (SIMD_10.log2() * (db_value / SIMD_20)).exp2()
-    }
-
-    /// Calculates the linear power level from amplitude as decibels.
-    #[inline]
-    pub fn db_to_level(db_value: f32) -> f32 {
-        10.0f32.powf(db_value / 20.0)
-    }
-
     fn update_voice_counter(&self) {
         self.voice_counter.store(
             self.voice_counters.iter().map(|c| c.load(Relaxed)).sum(),
@@ -338,35 +445,82 @@ impl Audio {
     /// Generates and processes new audio, and writes it to the provided
     /// `Buffer`.
     pub fn process(&mut self, buffer: &mut Buffer) {
-        // if any of these buffers are locked before we call the voice thread
-        // pool, then there's a scheduling error in the pool.
-        for (i, buf) in self.voice_buffers.iter().enumerate() {
-            debug_assert!(
-                !buf.is_locked(),
-                "voice buffer {i} was locked before dispatching voice threads"
-            );
-        }
+        self.generate();
+        self.copy_to_main_buffer(buffer);
+        self.update_voice_counter();
+    }
+
+    /// Generates and processes a buffer of new audio with `notes`, whose
+    /// timings are their offsets in samples from the start of the buffer,
+    /// and returns its interleaved stereo samples rather than writing them
+    /// to a device — see [`OfflineAudio`].
+    pub fn render_block_offline(
+        &mut self,
+        notes: impl IntoIterator<Item = NoteEvent>,
+    ) -> Vec<f32> {
+        self.thread_pool.dispatch(notes);
+        self.generate();
+        self.update_voice_counter();
+
+        let out = self.main_buffer.iter().flat_map(|s| s.to_array()).collect();
+        self.main_buffer.fill(f32x2::splat(0.0));
+
+        out
+    }
 
+    /// Generates and processes new audio in the main SIMD buffer.
+    fn generate(&mut self) {
+        self.update_voice_gain();
         let any_executed = self.thread_pool.execute();
 
         self.sum_to_main_buf();
 
-        if any_executed {
+        let delay = self.params.delay.load(Relaxed);
+        let reverb = self.params.reverb.load(Relaxed);
+        self.tone.set_params(self.params.tone.load(Relaxed));
+        self.delay.set_params(delay);
+        self.reverb.set_params(reverb);
+        self.compressor.set_makeup_gain(self.params.makeup.load(Relaxed));
+        self.clipper.set_ceiling_db(self.params.ceiling_db.load(Relaxed));
+
+        // the echoes and reverb keep ringing after the voices have finished.
+        let reduction_db = if any_executed
+            || delay.is_enabled()
+            || reverb.is_enabled()
+        {
             self.process_fx();
+            self.compressor.gain_reduction_db()
         }
+        else {
+            0.0
+        };
+        self.params.gain_reduction_db.store(reduction_db, Relaxed);
+    }
 
-        self.copy_to_main_buffer(buffer);
-        self.update_voice_counter();
+    /// Moves the voice gain towards the master volume, compensated for the
+    /// voices which will play in this buffer.
+    fn update_voice_gain(&mut self) {
+        let active: u32 =
+            self.voice_counters.iter().map(|c| c.load(Relaxed)).sum();
+        let incoming = (self.note_receiver.len()
+            + self.thread_pool.queued_notes()) as u32;
+
+        self.voice_gain.update(
+            self.params.volume_db.load(Relaxed),
+            active + incoming,
+            self.config.buffer_time() as f32,
+        );
     }
 
     /// Processes the internal FX on the main SIMD buffer.
     #[inline]
     fn process_fx(&mut self) {
         for sample in &mut self.main_buffer {
-            *sample = self.hp.tick(*sample);
+            *sample = self.tone.tick(*sample);
             *sample = self.compressor.tick(*sample);
-
-            *sample = sample.simd_clamp(-SIMD_ONE, SIMD_ONE);
+            *sample = self.delay.tick(*sample);
+            *sample = self.reverb.tick(*sample);
+            *sample = self.clipper.tick(*sample);
         }
     }
 
@@ -381,24 +535,15 @@ impl Audio {
         self.main_buffer.fill(f32x2::splat(0.0));
     }
 
-    /// Sums the contents of the modified voices buffers to the main SIMD
-    /// buffer.
+    /// Sums the voice buffers which were filled for this block to the main
+    /// SIMD buffer. The buffers are never locked, as the voice threads have
+    /// already moved on to their other buffers.
     #[inline]
     fn sum_to_main_buf(&mut self) {
-        for (buf, flag) in self
-            .modified_buffers
-            .iter()
-            .filter(|f| f.load(Relaxed))
-            .enumerate()
-        {
-            let buf = self.voice_buffers[buf].lock();
-
-            for (i, sample) in self.main_buffer.iter_mut().enumerate() {
-                *sample += buf[i];
+        for buf in self.thread_pool.outputs() {
+            for (sample, voice) in self.main_buffer.iter_mut().zip(buf) {
+                *sample += *voice;
             }
-
-            // reset the flag and the buffer for the next frame
-            flag.store(false, Relaxed);
         }
     }
 }
@@ -0,0 +1,111 @@
+use super::*;
+
+/// The time it takes a burst to decay by around 63%, in seconds.
+const DECAY_TIME: f32 = 0.008;
+
+/// The seed of the next oscillator, which is advanced by a large odd number
+/// each time so that every burst is different.
+static NEXT_SEED: AtomicU32 = AtomicU32::new(0x9e37_79b9);
+
+/// A noise oscillator, which produces a short burst of white or pink noise
+/// that decays exponentially, like a hit on a drum.
+///
+/// The noise is passed through a one-pole low-pass filter whose cutoff
+/// follows the frequency, so that higher notes are brighter.
+#[derive(Debug)]
+pub struct NoiseOscSimd {
+    /// The state of the xorshift generator.
+    state: u32,
+    /// The filter states of the pink noise, or `None` for white noise.
+    pink: Option<[f32; 3]>,
+    /// The coefficient of the low-pass filter.
+    coeff: f32,
+    lowpass: f32,
+    /// The level of the burst, which is multiplied by `decay` every sample.
+    level: f32,
+    decay: f32,
+}
+
+impl NoiseOscSimd {
+    pub fn new(freq_hz: f32, sample_rate: f32, pink: bool) -> Self {
+        debug_assert!(0.0 < freq_hz && freq_hz <= sample_rate * 0.5);
+
+        let state = NEXT_SEED.fetch_add(0x9e37_79b9, Relaxed) | 1;
+
+        Self {
+            state,
+            pink: pink.then_some([0.0; 3]),
+            coeff: Self::coeff(freq_hz, sample_rate),
+            lowpass: 0.0,
+            level: 1.0,
+            decay: (-1.0 / (DECAY_TIME * sample_rate)).exp(),
+        }
+    }
+
+    /// The low-pass coefficient for a note at `freq_hz`, whose cutoff is a
+    /// few octaves above the note.
+    fn coeff(freq_hz: f32, sample_rate: f32) -> f32 {
+        let cutoff = (freq_hz * 8.0).min(sample_rate * 0.45);
+
+        1.0 - (-TAU * cutoff / sample_rate).exp()
+    }
+
+    /// The next white noise sample, between `-1.0` and `1.0`.
+    #[inline]
+    fn white(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+
+        (self.state as f32 / u32::MAX as f32).mul_add(2.0, -1.0)
+    }
+}
+
+impl SimdOscillator for NoiseOscSimd {
+    fn set_frequency(&mut self, freq_hz: f32, sample_rate: f32) {
+        debug_assert!(0.0 < freq_hz && freq_hz <= sample_rate * 0.5);
+
+        self.coeff = Self::coeff(freq_hz, sample_rate);
+    }
+
+    #[inline]
+    fn tick(&mut self) -> f32x2 {
+        let white = self.white();
+
+        // Paul Kellet's economy pink noise filter.
+        let noise = match &mut self.pink {
+            Some(b) => {
+                b[0] = 0.99765f32.mul_add(b[0], white * 0.099_046);
+                b[1] = 0.963f32.mul_add(b[1], white * 0.296_516_4);
+                b[2] = 0.57f32.mul_add(b[2], white * 1.052_691_3);
+
+                white.mul_add(0.1848, b[0] + b[1] + b[2]) * 0.25
+            }
+            None => white,
+        };
+
+        self.lowpass += (noise - self.lowpass) * self.coeff;
+        let output = self.lowpass * self.level;
+        self.level *= self.decay;
+
+        f32x2::splat(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_decays() {
+        for pink in [false, true] {
+            let mut osc = NoiseOscSimd::new(4000.0, SAMPLE_RATE as f32, pink);
+            let samples: Vec<f32> =
+                (0..SAMPLE_RATE / 10).map(|_| osc.tick()[CH_L]).collect();
+
+            assert!(samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+            assert!(samples[..100].iter().any(|s| s.abs() > 0.05));
+            assert!(samples[4000..].iter().all(|s| s.abs() < 1e-3));
+        }
+    }
+}
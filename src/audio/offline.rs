@@ -0,0 +1,192 @@
+//! Rendering audio faster (or slower) than real time, in step with exported
+//! frames.
+
+use super::*;
+use std::io::{self, Write};
+
+/// The time which is rendered after the last note, so that it can decay, in
+/// seconds.
+const TAIL_TIME: f32 = 0.5;
+
+/// An audio engine which isn't connected to a device, and is instead driven
+/// by a sample clock which advances one video frame at a time. It always runs
+/// at [`SAMPLE_RATE`] with buffers of [`BUFFER_SIZE`], whatever the settings
+/// of the output device. Each frame's
+/// note events are scheduled at their exact sample positions, and the audio
+/// is kept in memory until it is written to a WAV file.
+#[derive(Debug)]
+pub struct OfflineAudio {
+    audio: Audio,
+    /// The number of samples in each frame.
+    samples_per_frame: u64,
+    /// The number of samples up to the end of the last frame.
+    clock: u64,
+    /// The note events which haven't been sent to the engine yet, along with
+    /// their positions in samples.
+    pending: Vec<(u64, NoteEvent)>,
+    /// The audio rendered so far, with the channels interleaved.
+    output: Vec<f32>,
+}
+
+impl OfflineAudio {
+    /// Creates an engine for frames which are `samples_per_frame` samples
+    /// long, which follows any changes to `params`.
+    pub fn new(samples_per_frame: u64, params: &AudioParams) -> Self {
+        Self {
+            audio: Audio::new_offline(params, AudioConfig::default()),
+            samples_per_frame,
+            clock: 0,
+            pending: vec![],
+            output: vec![],
+        }
+    }
+
+    /// Advances the clock by a frame, where the timing of each of `notes` is
+    /// its offset in samples from the start of the frame. Every buffer which
+    /// ends within the frame is rendered.
+    pub fn advance(&mut self, notes: impl IntoIterator<Item = NoteEvent>) {
+        let start = self.clock;
+
+        self.pending.extend(
+            notes
+                .into_iter()
+                .map(|note| (start + note.sample_offset() as u64, note)),
+        );
+        self.clock += self.samples_per_frame;
+
+        // a buffer can only be rendered once every note within it is known.
+        while self.rendered() + BUFFER_SIZE as u64 <= self.clock {
+            self.render_buffer();
+        }
+    }
+
+    /// Renders the rest of the last frame, along with a short tail so that
+    /// the last notes can decay, and writes the audio to `path` as a 16-bit
+    /// stereo WAV file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn finish(mut self, path: &str) -> io::Result<()> {
+        let end = self.clock + (TAIL_TIME * SAMPLE_RATE as f32) as u64;
+
+        while self.rendered() < end {
+            self.render_buffer();
+        }
+
+        // whole buffers are rendered, so the audio is trimmed to the tail.
+        self.output.truncate(end as usize * NUM_CHANNELS);
+
+        let file = std::fs::File::create(path)?;
+        let mut w = io::BufWriter::new(file);
+        write_wav(&mut w, &self.output)?;
+        w.flush()
+    }
+
+    /// The number of samples rendered so far.
+    fn rendered(&self) -> u64 {
+        (self.output.len() / NUM_CHANNELS) as u64
+    }
+
+    /// Renders the next buffer with the note events which fall within it.
+    fn render_buffer(&mut self) {
+        let start = self.rendered();
+        let end = start + BUFFER_SIZE as u64;
+        let mut notes = vec![];
+
+        self.pending.retain(|&(pos, note)| {
+            if pos >= end {
+                return true;
+            }
+
+            let timing = pos.saturating_sub(start) as u32;
+            notes.push(NoteEvent { timing, ..note });
+
+            false
+        });
+
+        let buffer = self.audio.render_block_offline(notes);
+        self.output.extend(buffer);
+    }
+}
+
+/// Writes the interleaved `samples` to `w` as a 16-bit stereo WAV file at
+/// [`SAMPLE_RATE`].
+fn write_wav(w: &mut impl Write, samples: &[f32]) -> io::Result<()> {
+    const BYTES_PER_FRAME: u32 = NUM_CHANNELS as u32 * 2;
+
+    let data_len = samples.len() as u32 * BYTES_PER_FRAME / 2;
+
+    w.write_all(b"RIFF")?;
+    w.write_all(&(36 + data_len).to_le_bytes())?;
+    w.write_all(b"WAVE")?;
+
+    w.write_all(b"fmt ")?;
+    w.write_all(&16_u32.to_le_bytes())?;
+    // uncompressed PCM.
+    w.write_all(&1_u16.to_le_bytes())?;
+    w.write_all(&(NUM_CHANNELS as u16).to_le_bytes())?;
+    w.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    w.write_all(&(SAMPLE_RATE * BYTES_PER_FRAME).to_le_bytes())?;
+    w.write_all(&(BYTES_PER_FRAME as u16).to_le_bytes())?;
+    w.write_all(&16_u16.to_le_bytes())?;
+
+    w.write_all(b"data")?;
+    w.write_all(&data_len.to_le_bytes())?;
+
+    for &sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        w.write_all(&sample.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wav_layout() {
+        let samples = [1.0, -1.0, 0.0, 0.0];
+        let mut bytes = vec![];
+        write_wav(&mut bytes, &samples).unwrap();
+
+        assert_eq!(bytes.len(), 44 + 8);
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(&bytes[4..8], &44_u32.to_le_bytes()[..]);
+        assert_eq!(&bytes[40..44], &8_u32.to_le_bytes()[..]);
+        assert_eq!(&bytes[44..48], &[0xff, 0x7f, 0x01, 0x80]);
+    }
+
+    /// Renders `notes` and a few buffers after them with a new engine.
+    fn render(notes: &[NoteEvent]) -> Vec<f32> {
+        let params = AudioParams::default();
+        let mut audio = Audio::new_offline(&params, AudioConfig::default());
+
+        let mut out = audio.render_block_offline(notes.iter().copied());
+        for _ in 0..3 {
+            out.extend(audio.render_block_offline(std::iter::empty()));
+        }
+
+        out
+    }
+
+    #[test]
+    fn offline_rendering_is_deterministic() {
+        let silence = render(&[]);
+        assert_eq!(silence.len(), BUFFER_SIZE * NUM_CHANNELS * 4);
+        assert!(silence.iter().all(|&s| s == 0.0));
+
+        let notes = [
+            NoteEvent::new(440.0, 0.5, 0, 0.0),
+            NoteEvent::new(660.0, 0.5, 100, 0.5),
+        ];
+        let out = render(&notes);
+
+        // the notes are heard, and are the same each time they're rendered.
+        assert!(out.iter().any(|s| s.abs() > 1e-3));
+        assert!(out.iter().all(|s| s.is_finite()));
+        assert_eq!(out, render(&notes));
+    }
+}
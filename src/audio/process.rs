@@ -1,29 +1,27 @@
 use super::*;
 use nannou_audio::Buffer;
 
-const BUFFER_TIME: f32 = BUFFER_SIZE as f32 / SAMPLE_RATE as f32;
-
 /// The audio processing callback.
 pub fn process(audio: &mut Audio, buffer: &mut Buffer) {
+    let frames = buffer.len_frames();
+    // the device may not run at the rate which the stream was opened with.
+    let sample_rate = buffer.sample_rate();
+    audio.set_sample_rate(sample_rate);
+
     if !audio.running {
-        audio.dsp_load.store(0.0, Relaxed);
+        audio.load_meter.idle(frames, sample_rate);
+        audio.governor.update(0.0);
+        // the clock keeps running while muted, as it drives playback.
+        audio.clock().advance(frames);
         return;
     }
 
-    let buf_start = Instant::now();
-
+    // the load is measured against the length of this buffer, which may
+    // differ from the requested buffer size on some devices.
+    audio.load_meter.begin();
     audio.process(buffer);
+    let load = audio.load_meter.end(frames, sample_rate);
 
-    update_callback_timer(audio);
-
-    let elapsed = buf_start.elapsed().as_secs_f32();
-    audio.dsp_load.store(elapsed / BUFFER_TIME, Relaxed);
-}
-
-fn update_callback_timer(audio: &Audio) {
-    let timer = audio.callback_timer();
-
-    if timer.load(Relaxed).elapsed().as_secs_f32() >= 0.0001 {
-        timer.store(InstantTime(Instant::now()), Relaxed);
-    }
+    audio.clock().advance(frames);
+    audio.governor.update(load);
 }
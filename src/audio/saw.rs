@@ -0,0 +1,82 @@
+use super::*;
+use std::simd::Select;
+
+/// A sawtooth wave oscillator, which ramps from `-1.0` up to `1.0` once per
+/// cycle.
+///
+/// The jump at the end of each cycle is smoothed with a polyBLEP (a
+/// polynomial approximation of a band-limited step), which removes most of
+/// the aliasing a naive sawtooth would have at high frequencies.
+#[derive(Debug)]
+pub struct SawOscSimd {
+    inc: f32x2,
+    phase: f32x2,
+}
+
+impl SawOscSimd {
+    pub fn new(freq_hz: f32, sample_rate: f32) -> Self {
+        debug_assert!(0.0 < freq_hz && freq_hz <= sample_rate * 0.5);
+
+        let inc = freq_hz / sample_rate;
+
+        Self { inc: f32x2::splat(inc), phase: f32x2::splat(0.0) }
+    }
+
+    /// The correction for the step at the end of each cycle, which is
+    /// nonzero within one sample of the step — `t` is the phase, and `dt` is
+    /// the phase increment per sample.
+    #[inline]
+    pub(super) fn poly_blep(t: f32x2, dt: f32x2) -> f32x2 {
+        // just after the step.
+        let x = t / dt;
+        let after = x.mul_add(SIMD_TWO, -x * x) - SIMD_ONE;
+
+        // just before the step.
+        let x = (t - SIMD_ONE) / dt;
+        let before = x.mul_add(x, x * SIMD_TWO) + SIMD_ONE;
+
+        t.simd_lt(dt)
+            .select(after, t.simd_gt(SIMD_ONE - dt).select(before, SIMD_ZERO))
+    }
+}
+
+impl SimdOscillator for SawOscSimd {
+    fn set_frequency(&mut self, freq_hz: f32, sample_rate: f32) {
+        debug_assert!(0.0 < freq_hz && freq_hz <= sample_rate * 0.5);
+
+        let inc = freq_hz / sample_rate;
+
+        self.inc[CH_L] = inc;
+        self.inc[CH_R] = inc;
+    }
+
+    #[inline]
+    fn tick(&mut self) -> f32x2 {
+        let naive = self.phase.mul_add(SIMD_TWO, -SIMD_ONE);
+        let output = naive - Self::poly_blep(self.phase, self.inc);
+        self.phase = (self.phase + self.inc).fract();
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saw_is_bounded_and_centered() {
+        let mut osc = SawOscSimd::new(1000.0, SAMPLE_RATE as f32);
+        // a whole number of cycles.
+        let samples: Vec<f32> =
+            (0..SAMPLE_RATE / 10).map(|_| osc.tick()[CH_L]).collect();
+
+        assert!(samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 1e-2, "mean is {mean}");
+
+        // the step is smoothed, so no two samples are a whole swing apart.
+        assert!(samples.windows(2).all(|w| (w[1] - w[0]).abs() < 1.5));
+    }
+}
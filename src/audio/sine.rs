@@ -34,6 +34,19 @@ impl SimdOscillator for SineOscSimd {
 
         output
     }
+
+    #[inline]
+    fn tick_wide(&mut self) -> f32x8 {
+        let phase = WIDE_FRAME_OFFSETS
+            .mul_add(splat_wide(self.inc), splat_wide(self.phase))
+            .fract();
+        self.phase = self
+            .inc
+            .mul_add(f32x2::splat(WIDE_FRAMES as f32), self.phase)
+            .fract();
+
+        (phase * f32x8::splat(TAU)).sin()
+    }
 }
 
 /// A simple sine wave oscillator.
@@ -1,12 +1,11 @@
 use super::*;
 use crate::thread_pool::PoolCreationError;
 use crossbeam_channel as cc;
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex};
 use std::{
     io::Result as IoResult,
-    panic,
     sync::{
-        atomic::{AtomicBool, Ordering::Relaxed},
+        atomic::{AtomicU32, Ordering::Relaxed},
         Arc,
     },
     thread::{self, JoinHandle},
@@ -15,6 +14,44 @@ use thread_priority as priority;
 
 /// The maximum block size for audio processing.
 pub const MAX_BLOCK_SIZE: usize = 64;
+/// The most note events which can be queued for each audio thread. Any more
+/// in a single buffer are dropped.
+const NOTE_QUEUE_SIZE: usize = 1024;
+
+/// A count of the threads which are still processing, which the audio
+/// callback can sleep on until every thread has finished.
+#[derive(Debug, Default)]
+struct Latch {
+    count: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl Latch {
+    /// Adds a thread to the count.
+    fn add(&self) {
+        *self.count.lock() += 1;
+    }
+
+    /// Removes a thread from the count, waking any waiting thread if it was
+    /// the last.
+    fn count_down(&self) {
+        let mut count = self.count.lock();
+        *count -= 1;
+
+        if *count == 0 {
+            self.finished.notify_all();
+        }
+    }
+
+    /// Blocks the calling thread until the count reaches zero.
+    fn wait(&self) {
+        let mut count = self.count.lock();
+
+        while *count > 0 {
+            self.finished.wait(&mut count);
+        }
+    }
+}
 
 #[derive(Debug)]
 struct Worker {
@@ -24,25 +61,28 @@ struct Worker {
 
 #[derive(Debug)]
 struct VoiceThreadData {
-    /// The audio output buffer for this thread.
-    output_buffer: Arc<Mutex<Vec<f32x2>>>,
+    /// The buffer which this thread writes its output to, while the audio
+    /// callback reads the output of its last execution from the other.
+    output_buffer: Vec<f32x2>,
+    /// The sender which passes each filled buffer back to the audio callback.
+    output_sender: Sender<Vec<f32x2>>,
 
-    /// The note event receiver.
-    note_receiver: Arc<Receiver<NoteEvent>>,
+    /// The receiver for the note events dispatched to this thread.
+    note_receiver: Receiver<NoteEvent>,
 
-    /// The voice handler for this thread.
-    voice_handler: Arc<Mutex<VoiceHandler>>,
+    /// The voice handler for this thread, which it owns for its lifetime.
+    voice_handler: VoiceHandler,
     /// The active voice counter for this thread.
     voice_counter: Arc<AtomicU32>,
-    /// Whether this thread's buffer has been modified.
-    modified_flag: Arc<AtomicBool>,
-
-    /// Whether this thread is currently busy.
-    busy_flag: Arc<AtomicBool>,
-    /// The number of tasks which are "queued".
-    queue_count: Arc<AtomicU32>,
-    /// The receiver to compute audio on this thread.
-    execute_receiver: Receiver<()>,
+    /// The gain of the voices over the current buffer.
+    gain: Arc<Atomic<GainRamp>>,
+
+    /// The count of busy threads, which this thread leaves once it has
+    /// processed its voices.
+    latch: Arc<Latch>,
+    /// The receiver to compute audio on this thread, which also hands back
+    /// the buffer which the audio callback has finished reading.
+    execute_receiver: Receiver<Vec<f32x2>>,
 }
 
 impl Worker {
@@ -55,47 +95,29 @@ impl Worker {
                     priority::ThreadPriority::Max,
                 );
 
-                let mut handler = data.voice_handler.lock();
-
-                // TODO: handle the voice gain in a better way.
-                let gain = [f32x2::splat(0.08); MAX_BLOCK_SIZE];
+                let mut handler = data.voice_handler;
+                let mut buf = data.output_buffer;
 
-                loop {
-                    data.busy_flag.store(false, Relaxed);
-
-                    if data.execute_receiver.recv().is_err() {
-                        break;
-                    }
-
-                    data.busy_flag.store(true, Relaxed);
-                    data.queue_count.fetch_sub(1, Relaxed);
+                let mut gain = [f32x2::splat(0.0); MAX_BLOCK_SIZE];
 
+                while let Ok(read_buf) = data.execute_receiver.recv() {
                     // this may be used in future
                     #[allow(unused_labels)]
                     'process: {
                         let mut next_event = data.note_receiver.try_recv().ok();
 
-                        // we panic here as it's a logic error for any of these
-                        // buffers to be locked by another thread before this
-                        // thread executes its audio processing.
-                        let mut buf = data
-                            .output_buffer
-                            .try_lock()
-                            .unwrap_or_else(|| {
-                                panic!(
-                                    "failed to lock voice buffer for voice thread #{id}"
-                                )
-                            });
-
                         let buffer_len = buf.len();
                         buf.fill(f32x2::splat(0.0));
 
+                        let ramp = data.gain.load(Relaxed);
+
                         // TODO: could this be implemented?
                         // if next_event.is_none() && !handler.any_active() {
                         //     break 'process;
                         // }
 
-                        data.modified_flag.store(true, Relaxed);
+                        // fade out some voices if the DSP load is too high
+                        handler.cull_quietest();
 
                         let mut block_start = 0;
                         let mut block_end = MAX_BLOCK_SIZE.min(buffer_len);
@@ -103,17 +125,17 @@ impl Worker {
                         // handle polyphonic voices
                         while block_start < buffer_len {
                             'events: loop {
-                                if handler.num_active() == VOICES_PER_HANDLER {
-                                    break 'events;
-                                }
-
                                 match next_event {
                                     // if we've snapped the block to an event
                                     Some(event)
                                         if (event.sample_offset() as usize)
                                             <= block_start =>
                                     {
-                                        handler.new_voice(event);
+                                        // notes which this handler can't
+                                        // play are dropped.
+                                        if handler.accepts_notes() {
+                                            handler.new_voice(event);
+                                        }
                                         next_event =
                                             data.note_receiver.try_recv().ok();
                                     }
@@ -132,6 +154,15 @@ impl Worker {
                                 }
                             }
 
+                            for (i, g) in gain[..block_end - block_start]
+                                .iter_mut()
+                                .enumerate()
+                            {
+                                *g = f32x2::splat(
+                                    ramp.at(block_start + i, buffer_len),
+                                );
+                            }
+
                             // process voices and clean any which are finished
                             handler.process_block(
                                 &mut buf, block_start, block_end, gain,
@@ -148,13 +179,16 @@ impl Worker {
                             .store(handler.num_active() as u32, Relaxed);
 
                         // println!("processed voice from thread {id}");
-
-                        drop(buf);
-                        // println!("thread {id} dropped buffer");
                     }
-                }
 
-                drop(handler);
+                    // the filled buffer is swapped for the one which the
+                    // callback has finished with. it's sent before the latch
+                    // is counted down, so that the callback always receives it.
+                    let filled = std::mem::replace(&mut buf, read_buf);
+                    _ = data.output_sender.send(filled);
+
+                    data.latch.count_down();
+                }
             },
         )?;
 
@@ -168,54 +202,71 @@ impl Worker {
     }
 }
 
-#[derive(Debug)]
-pub struct AudioThreadPoolReferences<'a> {
-    pub output_buffers: &'a [Arc<Mutex<Vec<f32x2>>>],
-    pub voice_handlers: &'a [Arc<Mutex<VoiceHandler>>],
-    pub voice_counters: &'a [Arc<AtomicU32>],
-    pub modified_flags: &'a [Arc<AtomicBool>],
-}
 
 #[derive(Debug)]
 pub struct AudioThreadPool {
     workers: Vec<Worker>,
-    num_queued: Arc<AtomicU32>,
-    busy_flags: Vec<Arc<AtomicBool>>,
-
-    execute_senders: Vec<Option<Sender<()>>>,
+    /// The count of threads which are still processing.
+    latch: Arc<Latch>,
+
+    execute_senders: Vec<Option<Sender<Vec<f32x2>>>>,
+    /// The receivers of the filled buffer of each thread.
+    output_receivers: Vec<Receiver<Vec<f32x2>>>,
+    /// The last filled buffer of each thread, which the audio callback reads
+    /// from while the thread fills its other buffer. These are only swapped
+    /// while the threads are idle, so the callback never has to lock them.
+    output_buffers: Vec<Vec<f32x2>>,
+    /// Whether each thread filled its buffer in the last execution.
+    executed: [bool; NUM_AUDIO_THREADS],
 
     voice_counters: Vec<Arc<AtomicU32>>,
+    /// The receiver for every incoming note event, which are dispatched to
+    /// the threads.
     note_receiver: Arc<Receiver<NoteEvent>>,
+    /// The senders of the note events for each thread.
+    note_senders: Vec<Sender<NoteEvent>>,
 }
 
 impl AudioThreadPool {
+    /// Creates a pool with a thread for each of `voice_handlers`, which
+    /// output buffers of `buffer_size` samples with their voices scaled by
+    /// `gain`.
     pub fn build(
-        refs: &AudioThreadPoolReferences<'_>,
+        voice_handlers: Vec<VoiceHandler>,
+        voice_counters: &[Arc<AtomicU32>],
+        gain: &Arc<Atomic<GainRamp>>,
         note_receiver: &Arc<Receiver<NoteEvent>>,
+        buffer_size: usize,
     ) -> Result<Self, PoolCreationError> {
+        assert_eq!(voice_handlers.len(), NUM_AUDIO_THREADS);
+
         let mut workers = Vec::with_capacity(NUM_AUDIO_THREADS);
         let mut execute_senders = Vec::with_capacity(NUM_AUDIO_THREADS);
-        let busy_flags: Vec<Arc<AtomicBool>> = (0..NUM_AUDIO_THREADS)
-            .map(|_| Arc::new(AtomicBool::new(false)))
-            .collect();
-        let num_queued = Arc::new(AtomicU32::new(0));
+        let mut note_senders = Vec::with_capacity(NUM_AUDIO_THREADS);
+        let mut output_receivers = Vec::with_capacity(NUM_AUDIO_THREADS);
+        let new_buffer = || vec![f32x2::splat(0.0); buffer_size];
+        let latch = Arc::new(Latch::default());
 
-        for id in 0..NUM_AUDIO_THREADS {
+        for (id, voice_handler) in voice_handlers.into_iter().enumerate() {
             let (execute_tx, execute_rx) = cc::bounded(0);
             execute_senders.push(Some(execute_tx));
+            let (note_tx, note_rx) = cc::bounded(NOTE_QUEUE_SIZE);
+            note_senders.push(note_tx);
+            let (output_tx, output_rx) = cc::bounded(1);
+            output_receivers.push(output_rx);
 
             match Worker::new(id, VoiceThreadData {
-                output_buffer: Arc::clone(&refs.output_buffers[id]),
+                output_buffer: new_buffer(),
+                output_sender: output_tx,
 
-                note_receiver: Arc::clone(note_receiver),
+                note_receiver: note_rx,
 
-                voice_handler: Arc::clone(&refs.voice_handlers[id]),
-                voice_counter: Arc::clone(&refs.voice_counters[id]),
-                modified_flag: Arc::clone(&refs.modified_flags[id]),
+                voice_handler,
+                voice_counter: Arc::clone(&voice_counters[id]),
+                gain: Arc::clone(gain),
 
-                busy_flag: Arc::clone(&busy_flags[id]),
+                latch: Arc::clone(&latch),
                 execute_receiver: execute_rx,
-                queue_count: Arc::clone(&num_queued),
             }) {
                 Ok(worker) => workers.push(worker),
                 Err(e) => return Err(PoolCreationError::FailedSpawn(e)),
@@ -224,17 +275,18 @@ impl AudioThreadPool {
 
         Ok(Self {
             workers,
-            num_queued,
-            busy_flags,
+            latch,
 
             execute_senders,
-
-            voice_counters: refs
-                .voice_counters
-                .iter()
-                .map(Arc::clone)
+            output_receivers,
+            output_buffers: (0..NUM_AUDIO_THREADS)
+                .map(|_| new_buffer())
                 .collect(),
+            executed: [false; NUM_AUDIO_THREADS],
+
+            voice_counters: voice_counters.iter().map(Arc::clone).collect(),
             note_receiver: Arc::clone(note_receiver),
+            note_senders,
         })
     }
 
@@ -242,57 +294,105 @@ impl AudioThreadPool {
     /// if at least one thread was signalled to compute, and false if none were
     /// signalled.
     ///
-    /// This also modifies its attached modified flags, which can be used to
-    /// identify which audio buffers have been modified.
-    pub fn execute(&self) -> bool {
-        // TODO: have channels for each thread, and divide the incoming events
-        // amongst all threads. this would allow free threads to focus on new
-        // voices, etc.
-
-        let num_active_voices = self
-            .voice_counters
-            .iter()
-            .map(|c| c.load(Relaxed))
-            .sum::<u32>();
-        let num_incoming = self.note_receiver.len() as u32;
-
-        // if there are no incoming events and no active voices, don't do
-        // anything.
-        if num_incoming == 0 && num_active_voices == 0 {
-            return false;
+    /// The buffers filled by the threads can then be read with
+    /// [`Self::outputs()`].
+    pub fn execute(&mut self) -> bool {
+        self.dispatch_notes();
+
+        // only the threads with active voices or incoming events are woken,
+        // as the others would only produce silence.
+        for i in 0..NUM_AUDIO_THREADS {
+            let has_voices = self.voice_counters[i].load(Relaxed) > 0;
+            let has_events = !self.note_senders[i].is_empty();
+
+            self.executed[i] = has_voices || has_events;
+
+            if self.executed[i] {
+                self.execute_thread(i);
+            }
         }
 
-        // otherwise, dispatch the minimum number of required threads to process
-        // all the voices.
-        for i in 0..(NUM_AUDIO_THREADS) {
-            self.execute_thread(i);
+        let any_executed = self.executed.contains(&true);
+
+        if any_executed {
+            self.block_until_free();
+            self.swap_buffers();
+        }
+
+        any_executed
+    }
+
+    /// The buffers which were filled in the last execution.
+    pub fn outputs(&self) -> impl Iterator<Item = &[f32x2]> {
+        self.output_buffers
+            .iter()
+            .zip(self.executed)
+            .filter_map(|(buf, executed)| executed.then_some(buf.as_slice()))
+    }
+
+    /// Takes the buffers which the threads filled in the last execution.
+    fn swap_buffers(&mut self) {
+        for i in 0..NUM_AUDIO_THREADS {
+            if !self.executed[i] {
+                continue;
+            }
+
+            // every executed thread sends its buffer before it finishes.
+            self.output_buffers[i] = self.output_receivers[i]
+                .try_recv()
+                .expect("audio thread finished without sending its buffer");
         }
+    }
+
+    /// The number of note events which are queued for the threads, and will
+    /// be played in their next execution.
+    pub fn queued_notes(&self) -> usize {
+        self.note_senders.iter().map(Sender::len).sum()
+    }
 
-        self.block_until_free();
+    /// Divides `events` amongst the threads, giving each one to the thread
+    /// with the fewest voices (counting those it has just been given). They
+    /// are played in the next execution, along with any incoming events.
+    pub fn dispatch(&self, events: impl IntoIterator<Item = NoteEvent>) {
+        let mut loads: [u32; NUM_AUDIO_THREADS] =
+            std::array::from_fn(|i| self.voice_counters[i].load(Relaxed));
+        for event in events {
+            let (id, load) = loads
+                .iter_mut()
+                .enumerate()
+                .min_by_key(|(_, load)| **load)
+                .expect("there is at least one audio thread");
+
+            if self.note_senders[id].try_send(event).is_ok() {
+                *load += 1;
+            }
+        }
+    }
 
-        true
+    /// Divides the incoming note events amongst the threads.
+    fn dispatch_notes(&self) {
+        self.dispatch(self.note_receiver.try_iter());
     }
 
     /// Blocks the calling thread until all audio threads are free (i.e. when
-    /// all audio processing is done).
+    /// all audio processing is done). The thread sleeps rather than spinning
+    /// while it waits.
     #[inline]
     pub fn block_until_free(&self) {
-        // TODO: could a spin lock or yield be used here to improve efficiency?
-        while self.any_busy() {}
+        self.latch.wait();
     }
 
-    /// Whether any of the audio threads are currently busy.
-    pub fn any_busy(&self) -> bool {
-        self.num_queued.load(Relaxed) > 0
-            || self.busy_flags.iter().any(|b| b.load(Relaxed))
-    }
-
-    fn execute_thread(&self, thread_id: usize) {
+    /// Signals a thread to compute its voices, handing it back the buffer
+    /// it filled last time to fill after this execution.
+    fn execute_thread(&mut self, thread_id: usize) {
         assert!(thread_id < NUM_AUDIO_THREADS);
 
+        let buf = std::mem::take(&mut self.output_buffers[thread_id]);
+
         if let Some(tx) = &self.execute_senders[thread_id] {
-            tx.send(()).unwrap();
-            self.num_queued.fetch_add(1, Relaxed);
+            // the thread is counted before it can possibly finish.
+            self.latch.add();
+            tx.send(buf).unwrap();
         }
     }
 }
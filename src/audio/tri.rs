@@ -33,6 +33,20 @@ impl SimdOscillator for TriOscSimd {
 
         (x.abs() - SIMD_HALF) * SIMD_TWO
     }
+
+    #[inline]
+    fn tick_wide(&mut self) -> f32x8 {
+        let phase = WIDE_FRAME_OFFSETS
+            .mul_add(splat_wide(self.inc), splat_wide(self.phase))
+            .fract();
+        self.phase = self
+            .inc
+            .mul_add(f32x2::splat(WIDE_FRAMES as f32), self.phase)
+            .fract();
+
+        let x = phase.mul_add(f32x8::splat(2.0), f32x8::splat(-1.0));
+        (x.abs() - f32x8::splat(0.5)) * f32x8::splat(2.0)
+    }
 }
 
 /// A simple triangle wave oscillator.
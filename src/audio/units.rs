@@ -0,0 +1,282 @@
+//! Audio units — decibels, linear gain and MIDI notes — along with the
+//! conversions between them, pan laws and musical scales.
+
+use crate::prelude::*;
+
+/// The frequency of A4 (MIDI note 69), which all notes are tuned relative to.
+pub const TUNING_FREQ_HZ: f32 = 440.0;
+
+// Musical scales, as semitones above the root note.
+pub const MAJ_PENT_SCALE: [f32; 5] = [0.0, 2.0, 4.0, 7.0, 9.0];
+pub const MIN_PENT_SCALE: [f32; 5] = [0.0, 3.0, 5.0, 7.0, 10.0];
+pub const MAJOR_SCALE: [f32; 7] = [0.0, 2.0, 4.0, 5.0, 7.0, 9.0, 11.0];
+pub const MINOR_SCALE: [f32; 7] = [0.0, 2.0, 3.0, 5.0, 7.0, 8.0, 10.0];
+
+/// The names of the notes in an octave, starting from C.
+const NOTE_NAMES: [&str; 12] =
+    ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// The musical scale which notes are quantized to, if any.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scale {
+    /// Notes aren't quantized.
+    #[default]
+    Off,
+    Major,
+    Minor,
+    MajorPentatonic,
+    MinorPentatonic,
+}
+
+/// A scale along with its root note, which notes can be quantized to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tuning {
+    pub scale: Scale,
+    /// The root note, in semitones above C.
+    pub root: u8,
+}
+
+/// An amplitude in decibels, where `0.0` is unity gain. `T` is either `f32`,
+/// or `f32x2` for a stereo pair.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Decibels<T = f32>(pub T);
+
+/// A linear amplitude, where `1.0` is unity gain. `T` is either `f32`, or
+/// `f32x2` for a stereo pair.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct LinearGain<T = f32>(pub T);
+
+/// A MIDI note number, which may be fractional (e.g. for detuned notes).
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct MidiNote(pub f32);
+
+impl Decibels {
+    /// Converts to a linear gain.
+    #[inline]
+    pub fn to_gain(self) -> LinearGain {
+        LinearGain(10.0_f32.powf(self.0 / 20.0))
+    }
+}
+
+impl Decibels<f32x2> {
+    /// Converts to a linear gain.
+    #[inline]
+    pub fn to_gain(self) -> LinearGain<f32x2> {
+        const SIMD_10: f32x2 = f32x2::from_array([10.0, 10.0]);
+        const SIMD_20: f32x2 = f32x2::from_array([20.0, 20.0]);
+
+        // there is no `powf()` implemented for SIMD types, but we can use
+        // exp2(log2(x) * power) to achieve the same result. the below is
+        // therefore equivalent to `SIMD_10.powf(self.0 / SIMD_20)`.
+        LinearGain(f32x2::exp2(f32x2::log2(SIMD_10) * (self.0 / SIMD_20)))
+    }
+}
+
+impl LinearGain {
+    /// Converts to decibels. The sign of the gain is ignored, and a gain of
+    /// `0.0` is negative infinity.
+    #[inline]
+    #[allow(dead_code)] // only the stereo conversion is used outside of tests
+    pub fn to_db(self) -> Decibels {
+        Decibels(20.0 * self.0.abs().log10())
+    }
+}
+
+impl LinearGain<f32x2> {
+    /// Converts to decibels — see [`LinearGain::to_db()`].
+    #[inline]
+    pub fn to_db(self) -> Decibels<f32x2> {
+        const SIMD_20: f32x2 = f32x2::from_array([20.0, 20.0]);
+
+        Decibels(SIMD_20 * self.0.abs().log10())
+    }
+}
+
+impl MidiNote {
+    /// Converts the note to a frequency in Hz, relative to
+    /// [`TUNING_FREQ_HZ`].
+    #[inline]
+    pub fn to_freq(self) -> f32 {
+        ((self.0 - 69.0) / 12.0).exp2() * TUNING_FREQ_HZ
+    }
+
+    /// Returns the note at `freq_hz`, relative to [`TUNING_FREQ_HZ`].
+    #[inline]
+    #[allow(dead_code)] // only used by tests so far
+    pub fn from_freq(freq_hz: f32) -> Self {
+        Self((freq_hz / TUNING_FREQ_HZ).log2().mul_add(12.0, 69.0))
+    }
+
+    /// Returns the nearest note in `scale` (see [`MAJOR_SCALE`] etc.) with
+    /// the root note `root`, in any octave.
+    pub fn quantize_to_scale(self, scale: &[f32], root: Self) -> Self {
+        let note = self.0;
+        let mut lower = root.0;
+
+        while !(lower..=(lower + 12.0)).contains(&note) {
+            lower += if note > lower { 12.0 } else { -12.0 };
+        }
+
+        let mut min = f32::MAX;
+        let mut idx = 0;
+
+        for (i, &int) in scale.iter().enumerate() {
+            let cur = lower + int;
+            let val = (note - cur).abs();
+
+            if val < min {
+                min = val;
+                idx = i;
+            }
+        }
+
+        Self(lower + scale[idx])
+    }
+}
+
+impl Scale {
+    const ALL: [Self; 5] = [
+        Self::Off,
+        Self::Major,
+        Self::Minor,
+        Self::MajorPentatonic,
+        Self::MinorPentatonic,
+    ];
+
+    /// Cycles to the next scale.
+    pub fn cycle_next(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + 1) % Self::ALL.len()];
+    }
+
+    /// The semitones above the root note of the notes in the scale, or
+    /// `None` if notes aren't quantized.
+    pub const fn intervals(self) -> Option<&'static [f32]> {
+        match self {
+            Self::Off => None,
+            Self::Major => Some(&MAJOR_SCALE),
+            Self::Minor => Some(&MINOR_SCALE),
+            Self::MajorPentatonic => Some(&MAJ_PENT_SCALE),
+            Self::MinorPentatonic => Some(&MIN_PENT_SCALE),
+        }
+    }
+}
+
+impl std::fmt::Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::MajorPentatonic => "major pentatonic",
+            Self::MinorPentatonic => "minor pentatonic",
+        })
+    }
+}
+
+impl Tuning {
+    /// Moves the root note up by a semitone, wrapping around the octave.
+    pub fn cycle_root(&mut self) {
+        self.root = (self.root + 1) % 12;
+    }
+
+    /// Returns the nearest note to `note` in the scale, or `note` itself if
+    /// notes aren't quantized.
+    pub fn quantize(self, note: MidiNote) -> MidiNote {
+        self.scale.intervals().map_or(note, |scale| {
+            note.quantize_to_scale(scale, MidiNote(self.root as f32))
+        })
+    }
+}
+
+impl std::fmt::Display for Tuning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.scale {
+            Scale::Off => f.write_str("off"),
+            scale => write!(f, "{} {scale}", NOTE_NAMES[self.root as usize]),
+        }
+    }
+}
+
+/// Returns the gains of each channel for `pan` (between `-1.0` and `1.0`),
+/// using a linear pan law. Higher values are panned towards the left channel.
+#[inline]
+pub fn linear_pan(pan: f32) -> f32x2 {
+    let pan = (pan.clamp(-1.0, 1.0) + 1.0) * 0.5;
+
+    f32x2::from_array([pan, 1.0 - pan])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() <= 1e-4 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    #[test]
+    fn db_gain_round_trip() {
+        for db in [-96.0, -18.0, -6.0, 0.0, 6.0, 12.0] {
+            let gain = Decibels(db).to_gain();
+            assert!(approx_eq(gain.to_db().0, db), "{db} dB -> {gain:?}");
+
+            let simd = Decibels(f32x2::splat(db)).to_gain();
+            assert!(approx_eq(simd.0[CH_L], gain.0));
+            assert!(approx_eq(simd.to_db().0[CH_R], db));
+        }
+
+        assert!(approx_eq(Decibels(-6.0).to_gain().0, 0.501_187));
+        assert!(approx_eq(
+            LinearGain(-0.5).to_db().0,
+            LinearGain(0.5).to_db().0
+        ));
+    }
+
+    #[test]
+    fn note_freq_round_trip() {
+        assert!(approx_eq(MidiNote(69.0).to_freq(), TUNING_FREQ_HZ));
+        assert!(approx_eq(MidiNote(81.0).to_freq(), TUNING_FREQ_HZ * 2.0));
+
+        for note in [21.0, 36.0, 60.5, 104.0] {
+            let freq = MidiNote(note).to_freq();
+            assert!(approx_eq(MidiNote::from_freq(freq).0, note));
+        }
+    }
+
+    #[test]
+    fn quantize_to_scale() {
+        let quantize = |note| {
+            MidiNote(note).quantize_to_scale(&MAJOR_SCALE, MidiNote(60.0)).0
+        };
+
+        assert_eq!(quantize(61.0), 60.0);
+        assert_eq!(quantize(66.2), 67.0);
+        assert_eq!(quantize(49.0), 48.0);
+        assert_eq!(quantize(75.6), 76.0);
+    }
+
+    #[test]
+    fn tuning_quantizes_only_with_a_scale() {
+        let mut tuning = Tuning::default();
+        assert_eq!(tuning.quantize(MidiNote(61.3)), MidiNote(61.3));
+
+        // A minor pentatonic: A, C, D, E, G.
+        tuning.scale = Scale::MinorPentatonic;
+        tuning.root = 9;
+        assert_eq!(tuning.quantize(MidiNote(61.3)), MidiNote(62.0));
+        assert_eq!(tuning.quantize(MidiNote(70.0)), MidiNote(69.0));
+        assert_eq!(tuning.to_string(), "A minor pentatonic");
+    }
+
+    #[test]
+    fn linear_pan_sums_to_unity() {
+        for pan in [-1.0, -0.3, 0.0, 0.5, 1.0] {
+            let gains = linear_pan(pan);
+            assert!(approx_eq(gains[CH_L] + gains[CH_R], 1.0));
+        }
+
+        assert_eq!(linear_pan(0.0), f32x2::splat(0.5));
+        assert_eq!(linear_pan(1.0), f32x2::from_array([1.0, 0.0]));
+    }
+}
@@ -1,10 +1,19 @@
 use super::*;
-use envelope::AmpEnvelope;
+use clap::ValueEnum;
+use envelope::{Adsr, AdsrEnvelope};
+use fm::FmOscSimd;
+use noise::NoiseOscSimd;
+use pulse::PulseOscSimd;
+use saw::SawOscSimd;
 use sine::SineOscSimd;
 use tri::TriOscSimd;
 
 /// The maximum number of polyphonic audio voices.
 pub const NUM_VOICES: usize = if cfg!(debug_assertions) { 512 } else { 2048 };
+/// The lowest polyphony cap, as each audio thread has at least one voice.
+pub const MIN_VOICES: usize = NUM_AUDIO_THREADS;
+/// The time which stolen and culled voices are faded out over, in seconds.
+const STEAL_FADE_TIME: f32 = 0.005;
 
 /// A single voice.
 #[derive(Debug)]
@@ -13,9 +22,24 @@ struct Voice {
     sample_rate: f32,
     osc: Box<dyn SimdOscillator + Send>,
     freq: f32,
-    envelope: AmpEnvelope,
+    envelope: AdsrEnvelope,
     amp: f32x2,
     pan: f32x2,
+    /// The voice which this voice replaced, while it fades out.
+    stolen: Option<StolenVoice>,
+    /// The gain of the voice while it is being culled, which falls to zero
+    /// over [`STEAL_FADE_TIME`].
+    cull_gain: Option<f32>,
+}
+
+/// A voice which was stolen by another voice, and is faded out over
+/// [`STEAL_FADE_TIME`] rather than cut off with a click.
+#[derive(Debug)]
+struct StolenVoice {
+    voice: Box<Voice>,
+    gain: f32,
+    /// The amount which the gain falls by each sample.
+    step: f32,
 }
 
 impl Voice {
@@ -23,7 +47,7 @@ impl Voice {
         event: NoteEvent,
         id: u64,
         sr: f32,
-        envelope_data: &[u8],
+        envelope: Arc<[f32]>,
     ) -> Self {
         Self {
             id,
@@ -37,22 +61,79 @@ impl Voice {
                     Box::new(TriOscSimd::new(event.freq(), sr))
                         as Box<dyn SimdOscillator + Send>
                 }
+                OscillatorType::Saw => {
+                    Box::new(SawOscSimd::new(event.freq(), sr))
+                        as Box<dyn SimdOscillator + Send>
+                }
+                OscillatorType::Pulse { width } => {
+                    Box::new(PulseOscSimd::new(event.freq(), sr, width))
+                        as Box<dyn SimdOscillator + Send>
+                }
+                OscillatorType::Noise { pink } => {
+                    Box::new(NoiseOscSimd::new(event.freq(), sr, pink))
+                        as Box<dyn SimdOscillator + Send>
+                }
+                OscillatorType::Fm => {
+                    Box::new(FmOscSimd::new(event.freq(), sr, event.amp()))
+                        as Box<dyn SimdOscillator + Send>
+                }
             },
             freq: event.freq(),
             amp: f32x2::splat(event.amp()),
-            envelope: AmpEnvelope::new(envelope_data),
-            pan: {
-                let pan = (event.pan() + 1.0) * 0.5;
-                let left = pan;
-                let right = 1.0 - pan;
-
-                f32x2::from_array([left, right])
-            },
+            envelope: AdsrEnvelope::new(envelope),
+            pan: units::linear_pan(event.pan()),
+            stolen: None,
+            cull_gain: None,
         }
     }
 
     pub fn next(&mut self) -> f32x2 {
-        self.osc.tick() * self.amp * self.pan * self.envelope.next_simd()
+        let mut out =
+            self.osc.tick() * self.amp * self.pan * self.envelope.next_simd();
+
+        if let Some(gain) = self.cull_gain.as_mut() {
+            out *= f32x2::splat(*gain);
+            *gain = (*gain - (STEAL_FADE_TIME * self.sample_rate).recip())
+                .max(0.0);
+        }
+
+        let Some(stolen) = self.stolen.as_mut() else {
+            return out;
+        };
+
+        let tail = stolen.voice.next() * f32x2::splat(stolen.gain);
+        stolen.gain -= stolen.step;
+        if stolen.gain <= 0.0 {
+            self.stolen = None;
+        }
+
+        out + tail
+    }
+
+    /// Returns the next [`WIDE_FRAMES`] frames of the voice at once — see
+    /// [`to_wide()`].
+    pub fn next_wide(&mut self) -> f32x8 {
+        // fading voices are rare, so they are simply rendered frame by frame.
+        if self.cull_gain.is_some() || self.stolen.is_some() {
+            return to_wide(std::array::from_fn(|_| self.next()));
+        }
+
+        self.osc.tick_wide()
+            * splat_wide(self.amp * self.pan)
+            * self.envelope.next_wide()
+    }
+
+    /// Fades out `voice` in place of this voice, which has stolen its slot.
+    fn fade_out(&mut self, mut voice: Self) {
+        // any voice which the stolen voice was itself fading out is dropped,
+        // so that at most one voice fades out behind another.
+        voice.stolen = None;
+
+        self.stolen = Some(StolenVoice {
+            voice: Box::new(voice),
+            gain: 1.0,
+            step: (STEAL_FADE_TIME * self.sample_rate).recip(),
+        });
     }
 
     /// Sets the frequency of the voice.
@@ -61,26 +142,130 @@ impl Voice {
         self.osc.set_frequency(self.freq, self.sample_rate);
     }
 
+    /// Fades the voice out over [`STEAL_FADE_TIME`].
+    pub fn cull(&mut self) {
+        if self.cull_gain.is_none() {
+            self.cull_gain = Some(1.0);
+        }
+    }
+
+    /// Whether the voice is being culled.
+    pub const fn is_culled(&self) -> bool {
+        self.cull_gain.is_some()
+    }
+
+    /// The current level of the voice.
+    pub fn level(&self) -> f32 {
+        self.amp[CH_L] * self.envelope.level() * self.cull_gain.unwrap_or(1.0)
+    }
+
     /// Returns `true` when the voice has finished producing audio.
-    pub const fn is_finished(&self) -> bool {
-        !self.envelope.is_active()
+    pub fn is_finished(&self) -> bool {
+        let silent = !self.envelope.is_active() || self.cull_gain == Some(0.0);
+        silent && self.stolen.is_none()
     }
 }
 
 /// The options available for when all voices are in use.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[repr(u32)]
 pub enum OverrideVoiceBehavior {
     /// Replace the oldest voice.
+    #[value(name = "oldest")]
     ReplaceOldest,
     /// Replace the voice with the lowest frequency.
+    #[value(name = "lowest")]
     ReplaceLowest,
     /// Replace the voice with the highest frequency.
+    #[value(name = "highest")]
     ReplaceHighest,
     /// Do not replace any active voices.
     #[default]
+    #[value(name = "none")]
     DoNotReplace,
 }
 
+impl OverrideVoiceBehavior {
+    const ALL: [Self; 4] = [
+        Self::ReplaceOldest,
+        Self::ReplaceLowest,
+        Self::ReplaceHighest,
+        Self::DoNotReplace,
+    ];
+
+    /// Cycles to the next behavior.
+    pub fn cycle_next(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + 1) % Self::ALL.len()];
+    }
+}
+
+impl std::fmt::Display for OverrideVoiceBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReplaceOldest => write!(f, "steal oldest"),
+            Self::ReplaceLowest => write!(f, "steal lowest"),
+            Self::ReplaceHighest => write!(f, "steal highest"),
+            Self::DoNotReplace => write!(f, "no stealing"),
+        }
+    }
+}
+
+/// The polyphony of the voice handlers, which can be changed while they
+/// run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Polyphony {
+    /// The most voices which can play at once, between [`MIN_VOICES`] and
+    /// [`NUM_VOICES`]. This is split evenly between the voice handlers, so
+    /// it is rounded up to a multiple of their number.
+    pub max_voices: u32,
+    /// What happens to new notes once every voice is in use.
+    pub steal: OverrideVoiceBehavior,
+}
+
+unsafe impl NoUninit for Polyphony {}
+
+impl Polyphony {
+    /// The polyphony caps which can be cycled through.
+    pub const CAPS: [u32; 6] = [64, 128, 256, 512, 1024, 2048];
+
+    /// Cycles to the next cap in [`Self::CAPS`] which is available, or back
+    /// to the lowest.
+    pub fn next_cap(&mut self) {
+        let available =
+            Self::CAPS.iter().copied().filter(|&c| c as usize <= NUM_VOICES);
+
+        self.max_voices = available
+            .clone()
+            .find(|&cap| cap > self.max_voices)
+            .or_else(|| available.min())
+            .unwrap_or(NUM_VOICES as u32);
+    }
+
+    /// The most voices which each of `num_handlers` handlers with
+    /// `handler_voices` voices each can play.
+    fn per_handler(self, num_handlers: usize, handler_voices: usize) -> usize {
+        (self.max_voices as usize)
+            .div_ceil(num_handlers)
+            .clamp(1, handler_voices)
+    }
+}
+
+impl Default for Polyphony {
+    fn default() -> Self {
+        Self {
+            max_voices: NUM_VOICES as u32,
+            steal: OverrideVoiceBehavior::default(),
+        }
+    }
+}
+
+impl std::fmt::Display for Polyphony {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} voices, {}", self.max_voices, self.steal)
+    }
+}
+
 /// The voice handler for managing polyphony.
 #[derive(Debug)]
 pub struct VoiceHandler {
@@ -90,34 +275,41 @@ pub struct VoiceHandler {
     sample_rate: f32,
     /// A counter for keeping track of old voices.
     id_counter: u64,
-    /// The behavior for overriding voices when all are in use.
-    override_behavior: OverrideVoiceBehavior,
-
-    envelope_data: Box<[u8]>,
+    /// The polyphony cap and the behavior for overriding voices when all
+    /// are in use, which are shared with the other handlers.
+    polyphony: Arc<Atomic<Polyphony>>,
+    /// The amplitude envelope of new voices, which is shared with the other
+    /// handlers.
+    adsr: Arc<Atomic<Adsr>>,
+    /// The rendered envelope of new voices, and the parameters which it was
+    /// rendered from. It is re-rendered when the shared envelope changes.
+    envelope: (Adsr, Arc<[f32]>),
+    /// The fraction of voices to cull each buffer, which is set by the load
+    /// governor — see [`LoadGovernor`].
+    cull: Arc<Atomic<f32>>,
 }
 
 impl VoiceHandler {
-    /// Creates a new `VoiceHandler`.
-    pub fn new<const N: usize>(sample_rate: f32) -> Self {
+    /// Creates a new `VoiceHandler`, which follows the envelope, polyphony
+    /// and load feedback in `params`. `envelope` is the envelope in `params`
+    /// rendered at `sample_rate` (see [`Adsr::render()`]), which is shared
+    /// by all of the handlers.
+    pub fn new<const N: usize>(
+        sample_rate: f32,
+        envelope: Arc<[f32]>,
+        params: &AudioParams,
+    ) -> Self {
         Self {
             voices: Vec::from([const { None }; N]),
             sample_rate,
             id_counter: 0,
-            override_behavior: OverrideVoiceBehavior::default(),
-            envelope_data: std::fs::read(ENVELOPE_DATA_PATH)
-                .expect("failed to read envelope data")
-                .into_boxed_slice(),
+            polyphony: Arc::clone(&params.polyphony),
+            adsr: Arc::clone(&params.adsr),
+            envelope: (params.adsr.load(Relaxed), envelope),
+            cull: Arc::clone(&params.load.cull),
         }
     }
 
-    /// Sets the voice override behavior for the `VoiceHandler`.
-    pub fn set_override_behavior(
-        &mut self,
-        new_behavior: OverrideVoiceBehavior,
-    ) {
-        self.override_behavior = new_behavior;
-    }
-
     /// Processes a block of audio.
     #[inline]
     pub fn process_block(
@@ -127,71 +319,104 @@ impl VoiceHandler {
         block_end: usize,
         gain: [f32x2; MAX_BLOCK_SIZE],
     ) {
+        let len = block_end - block_start;
+        // the frames which are rendered a few at a time.
+        let wide_len = len - len % WIDE_FRAMES;
+
         for voice in self.voices.iter_mut().flatten() {
-            for (i, sample) in (block_start..block_end).enumerate() {
-                buffer[sample] += voice.next() * gain[i];
+            for i in (0..wide_len).step_by(WIDE_FRAMES) {
+                let frames = from_wide(voice.next_wide());
+
+                for (k, frame) in frames.into_iter().enumerate() {
+                    buffer[block_start + i + k] += frame * gain[i + k];
+                }
+            }
+
+            for i in wide_len..len {
+                buffer[block_start + i] += voice.next() * gain[i];
             }
         }
     }
 
-    /// Starts a new voice.
+    /// Starts a new voice. If every voice under the polyphony cap is in use,
+    /// then one is stolen (and faded out) according to the override
+    /// behavior, or the note is dropped.
     pub fn new_voice(&mut self, event: NoteEvent) {
-        #[allow(clippy::enum_glob_use)]
-        use OverrideVoiceBehavior::*;
+        let polyphony = self.polyphony.load(Relaxed);
+        // voices above a lowered cap are left to finish on their own.
+        let limit = polyphony.per_handler(NUM_AUDIO_THREADS, self.voices.len());
 
-        if let Some(free_idx) = self.voices.iter().position(Option::is_none) {
-            // self.create_voice() is inlined here in case no voices are free
-            // (in which case no new voice is ever created)
+        if let Some(free_idx) =
+            self.voices[..limit].iter().position(Option::is_none)
+        {
             self.voices[free_idx] = Some(self.create_voice(event));
             return;
         }
 
-        if matches!(self.override_behavior, DoNotReplace) {
+        let Some(idx) = self.steal_candidate(polyphony.steal, limit) else {
             return;
+        };
+
+        let mut new_voice = self.create_voice(event);
+        if let Some(old_voice) = self.voices[idx].take() {
+            new_voice.fade_out(old_voice);
         }
+        self.voices[idx] = Some(new_voice);
+    }
 
-        let new_voice = self.create_voice(event);
+    /// The index of the voice among the first `limit` which `behavior`
+    /// replaces, if any.
+    fn steal_candidate(
+        &self,
+        behavior: OverrideVoiceBehavior,
+        limit: usize,
+    ) -> Option<usize> {
+        #[allow(clippy::enum_glob_use)]
+        use OverrideVoiceBehavior::*;
 
-        match self.override_behavior {
-            ReplaceOldest => {
-                let oldest = unsafe {
-                    self.voices
-                        .iter_mut()
-                        .min_by_key(|v| v.as_ref().unwrap_unchecked().id)
-                        .unwrap_unchecked()
-                };
+        let active = self.voices[..limit]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.as_ref().map(|v| (i, v)));
 
-                *oldest = Some(new_voice);
-            }
+        let candidate = match behavior {
+            ReplaceOldest => active.min_by_key(|(_, v)| v.id),
             ReplaceLowest => {
-                let lowest = unsafe {
-                    self.voices
-                        .iter_mut()
-                        .min_by(|v1, v2| {
-                            let v1_freq = v1.as_ref().unwrap_unchecked().freq;
-                            let v2_freq = v2.as_ref().unwrap_unchecked().freq;
-                            v1_freq.total_cmp(&v2_freq)
-                        })
-                        .unwrap_unchecked()
-                };
-
-                *lowest = Some(new_voice);
+                active.min_by(|(_, v1), (_, v2)| v1.freq.total_cmp(&v2.freq))
             }
             ReplaceHighest => {
-                let highest = unsafe {
-                    self.voices
-                        .iter_mut()
-                        .min_by(|v1, v2| {
-                            let v1_freq = v1.as_ref().unwrap_unchecked().freq;
-                            let v2_freq = v2.as_ref().unwrap_unchecked().freq;
-                            v2_freq.total_cmp(&v1_freq)
-                        })
-                        .unwrap_unchecked()
-                };
-
-                *highest = Some(new_voice);
+                active.max_by(|(_, v1), (_, v2)| v1.freq.total_cmp(&v2.freq))
+            }
+            DoNotReplace => None,
+        };
+
+        candidate.map(|(i, _)| i)
+    }
+
+    /// Fades out the quietest voices, if the load governor has asked for any
+    /// to be culled. Voices which are already fading out aren't counted.
+    pub fn cull_quietest(&mut self) {
+        let fraction = self.cull.load(Relaxed);
+        if fraction <= 0.0 {
+            return;
+        }
+
+        let num_active =
+            self.voices.iter().flatten().filter(|v| !v.is_culled()).count();
+        let num_cull = (num_active as f32 * fraction).ceil() as usize;
+
+        for _ in 0..num_cull {
+            let quietest = self
+                .voices
+                .iter_mut()
+                .flatten()
+                .filter(|v| !v.is_culled())
+                .min_by(|v1, v2| v1.level().total_cmp(&v2.level()));
+
+            match quietest {
+                Some(voice) => voice.cull(),
+                None => return,
             }
-            DoNotReplace => (),
         }
     }
 
@@ -215,6 +440,16 @@ impl VoiceHandler {
         self.voices.iter().any(Option::is_some)
     }
 
+    /// Returns `true` if a new note would start a voice, i.e. if a voice
+    /// under the polyphony cap is free, or one can be stolen.
+    pub fn accepts_notes(&self) -> bool {
+        let polyphony = self.polyphony.load(Relaxed);
+        let limit = polyphony.per_handler(NUM_AUDIO_THREADS, self.voices.len());
+
+        polyphony.steal != OverrideVoiceBehavior::DoNotReplace
+            || self.voices[..limit].iter().any(Option::is_none)
+    }
+
     /// Returns `true` if any voice is free.
     pub fn any_free(&self) -> bool {
         self.voices.iter().any(Option::is_none)
@@ -227,11 +462,16 @@ impl VoiceHandler {
 
     /// Returns a new voice.
     fn create_voice(&mut self, event: NoteEvent) -> Voice {
+        let adsr = self.adsr.load(Relaxed);
+        if adsr != self.envelope.0 {
+            self.envelope = (adsr, adsr.render(self.sample_rate));
+        }
+
         Voice::new(
             event,
             self.next_voice_id(),
             self.sample_rate,
-            &self.envelope_data,
+            Arc::clone(&self.envelope.1),
         )
     }
 
@@ -241,3 +481,120 @@ impl VoiceHandler {
         self.id_counter
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voice_stealing() {
+        let polyphony = Arc::new(Atomic::new(Polyphony {
+            max_voices: NUM_VOICES as u32,
+            steal: OverrideVoiceBehavior::ReplaceLowest,
+        }));
+        let params = AudioParams { polyphony, ..AudioParams::default() };
+        let polyphony = &params.polyphony;
+        let envelope = Adsr::default().render(48000.0);
+        let mut handler = VoiceHandler::new::<2>(48000.0, envelope, &params);
+        let freqs = |handler: &VoiceHandler| -> Vec<f32> {
+            handler.voices.iter().flatten().map(|v| v.freq).collect()
+        };
+
+        for freq in [200.0, 100.0, 300.0] {
+            handler.new_voice(NoteEvent::new(freq, 0.5, 0, 0.0));
+        }
+        assert_eq!(freqs(&handler), [200.0, 300.0]);
+
+        let steal = OverrideVoiceBehavior::DoNotReplace;
+        let max_voices = NUM_VOICES as u32;
+        polyphony.store(Polyphony { max_voices, steal }, Relaxed);
+        handler.new_voice(NoteEvent::new(400.0, 0.5, 0, 0.0));
+        assert_eq!(freqs(&handler), [200.0, 300.0]);
+
+        let mut polyphony =
+            Polyphony { max_voices: 2048, ..Polyphony::default() };
+        polyphony.next_cap();
+        assert_eq!(polyphony.max_voices, 64);
+    }
+
+    fn test_voices(num: usize, osc: OscillatorType) -> Vec<Voice> {
+        (0..num)
+            .map(|i| {
+                let freq = (i as f32).mul_add(7.0, 110.0);
+                let event = NoteEvent::new(freq, 0.5, 0, 0.3).with_type(osc);
+                let envelope = Adsr::default().render(48000.0);
+                Voice::new(event, i as u64, 48000.0, envelope)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wide_rendering() {
+        for osc in
+            [OscillatorType::Sine, OscillatorType::Tri, OscillatorType::Saw]
+        {
+            let mut voices = test_voices(2, osc);
+            voices[1].cull();
+
+            for voice in &mut voices {
+                let mut wide = Voice::new(
+                    NoteEvent::new(voice.freq, 0.5, 0, 0.3).with_type(osc),
+                    0,
+                    48000.0,
+                    Adsr::default().render(48000.0),
+                );
+                if voice.is_culled() {
+                    wide.cull();
+                }
+
+                for _ in 0..64 {
+                    let frames = from_wide(wide.next_wide());
+
+                    for frame in frames {
+                        let diff = (frame - voice.next()).abs();
+                        assert!(diff.reduce_max() < 1e-4, "{osc:?}: {diff:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    extern crate test;
+
+    /// Renders a buffer of 2048 voices frame by frame, to compare against
+    /// [`render_2048_voices_wide()`] with `cargo +nightly bench`.
+    #[bench]
+    fn render_2048_voices(b: &mut test::Bencher) {
+        let mut voices = test_voices(2048, OscillatorType::Sine);
+        let mut buffer = vec![SIMD_ZERO; BUFFER_SIZE];
+
+        b.iter(|| {
+            for voice in &mut voices {
+                for sample in &mut buffer {
+                    *sample += voice.next();
+                }
+            }
+            test::black_box(&mut buffer);
+        });
+    }
+
+    /// Renders a buffer of 2048 voices [`WIDE_FRAMES`] frames at a time.
+    #[bench]
+    fn render_2048_voices_wide(b: &mut test::Bencher) {
+        let mut voices = test_voices(2048, OscillatorType::Sine);
+        let mut buffer = vec![SIMD_ZERO; BUFFER_SIZE];
+
+        b.iter(|| {
+            for voice in &mut voices {
+                for chunk in buffer.chunks_exact_mut(WIDE_FRAMES) {
+                    let frames = from_wide(voice.next_wide());
+
+                    for (sample, frame) in chunk.iter_mut().zip(frames) {
+                        *sample += frame;
+                    }
+                }
+            }
+            test::black_box(&mut buffer);
+        });
+    }
+}
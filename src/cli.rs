@@ -0,0 +1,424 @@
+use super::*;
+use clap::{Parser, ValueEnum};
+use std::sync::OnceLock;
+
+/// The range of playback speeds which can be set.
+const SPEED_RANGE: std::ops::RangeInclusive<f32> = -5.0..=5.0;
+
+/// A visualizer for sorting algorithms.
+///
+/// Every option sets the initial state of a setting which can also be
+/// changed while the app is running.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Args {
+    /// The algorithm to start with.
+    #[arg(long, short)]
+    pub algorithm: Option<SortingAlgorithm>,
+    /// The number of elements in the array.
+    #[arg(long, short, value_parser = parse_size)]
+    pub size: Option<usize>,
+    /// The playback speed multiplier.
+    #[arg(long, value_parser = parse_speed, allow_negative_numbers = true)]
+    pub speed: Option<f32>,
+    /// The curve which playback follows over time.
+    #[arg(long)]
+    pub playback_curve: Option<PlaybackCurve>,
+    /// The distribution which the array is arranged into when shuffling.
+    #[arg(long, short)]
+    pub distribution: Option<Distribution>,
+    /// Starts in race mode, against this algorithm.
+    #[arg(long)]
+    pub race: Option<SortingAlgorithm>,
+    /// The maximum number of operations in a sort, after which it is stopped
+    /// early. 0 removes the limit.
+    #[arg(long, default_value_t = DEFAULT_OP_LIMIT)]
+    pub op_limit: usize,
+    /// Seeds the shuffles and algorithms, so that runs are reproducible.
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Shuffles the array and plays the algorithm once the app has started.
+    #[arg(long)]
+    pub autoplay: bool,
+    /// Starts in tour mode, which shuffles, sorts and plays every algorithm in
+    /// turn.
+    #[arg(long)]
+    pub tour: bool,
+    /// The time to wait between each algorithm in tour mode, in seconds.
+    #[arg(long, default_value_t = 3.0, value_parser = parse_dwell)]
+    pub tour_dwell: f32,
+    /// Plays the algorithms listed in this file in turn, one per line.
+    #[arg(long, value_name = "FILE", value_parser = Playlist::load)]
+    pub playlist: Option<Playlist>,
+    /// The number of samples in each audio buffer: 256, 512 or 1024. Smaller
+    /// buffers have less latency, while larger buffers are less likely to
+    /// glitch on slower machines.
+    #[arg(
+        long,
+        default_value_t = BUFFER_SIZE,
+        value_parser = parse_buffer_size
+    )]
+    pub buffer_size: usize,
+    /// The most voices which can play at once. This is rounded up to a
+    /// multiple of the number of audio threads.
+    #[arg(
+        long,
+        default_value_t = NUM_VOICES as u32,
+        value_parser = parse_max_voices
+    )]
+    pub max_voices: u32,
+    /// What happens to new notes once every voice is in use.
+    #[arg(long, default_value = "none")]
+    pub voice_steal: OverrideVoiceBehavior,
+    /// Starts with the audio muted.
+    #[arg(long)]
+    pub muted: bool,
+    /// Broadcasts the playback progress, operations and notes as OSC messages
+    /// over UDP to this address.
+    #[arg(long, value_name = "HOST:PORT")]
+    pub osc_out: Option<String>,
+    /// Listens for OSC commands over UDP on this port, so that the app can be
+    /// controlled remotely.
+    #[arg(long, value_name = "PORT")]
+    pub osc_in: Option<u16>,
+    /// The operations which aren't highlighted or heard during playback,
+    /// separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub hide_operations: Vec<OperationKind>,
+    /// The operations which aren't heard during playback, but are still
+    /// highlighted, separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub mute_operations: Vec<OperationKind>,
+    /// The operations which are sonified with a sawtooth wave rather than
+    /// their usual sound, separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub saw_operations: Vec<OperationKind>,
+    /// The operations which are sonified with a pulse wave rather than their
+    /// usual sound, separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub pulse_operations: Vec<OperationKind>,
+    /// The width of the pulse wave, as the fraction of each cycle which is
+    /// high (0.5 for a square wave).
+    #[arg(long, default_value_t = 0.5, value_parser = parse_pulse_width)]
+    pub pulse_width: f32,
+    /// The operations which are sonified with short bursts of noise rather
+    /// than pitched tones, separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub noise_operations: Vec<OperationKind>,
+    /// Uses pink noise rather than white noise for the noise bursts, which
+    /// has a deeper sound.
+    #[arg(long)]
+    pub pink_noise: bool,
+    /// The operations which are sonified with two-operator FM rather than
+    /// their usual sound, separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub fm_operations: Vec<OperationKind>,
+    /// How notes are placed in the stereo field: by their index in the
+    /// array, or by their slice's position around the wheel.
+    #[arg(long)]
+    pub panning: Option<Panning>,
+    /// The master volume of the voices, in decibels (between -48 and 12).
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        value_parser = parse_volume,
+        allow_negative_numbers = true
+    )]
+    pub volume: f32,
+    /// The level which the output is softly clipped to, in decibels (between
+    /// -24 and 0).
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        value_parser = parse_ceiling,
+        allow_negative_numbers = true
+    )]
+    pub ceiling: f32,
+    /// The makeup gain of the output compressor: `auto`, or a level in
+    /// decibels (between -12 and 24).
+    #[arg(
+        long,
+        default_value = "0",
+        value_parser = parse_makeup,
+        allow_negative_numbers = true
+    )]
+    pub makeup: MakeupGain,
+    /// Gives every operation its usual sound, rather than using the sound
+    /// preset of each algorithm while none of the options above are set.
+    #[arg(long)]
+    pub no_algorithm_sounds: bool,
+    /// The amplitude envelope of each note: `pluck`, `percussive`, `soft`,
+    /// `swell`, or the attack, decay and release times in milliseconds and
+    /// the sustain level, separated by commas (e.g. `10,0,1,35`).
+    #[arg(long, value_parser = Adsr::parse)]
+    pub envelope: Option<Adsr>,
+    /// The tone of the output: `default`, `warm`, `bright`, or the gains of
+    /// the low, mid and high bands in dB, separated by commas (e.g.
+    /// `-12,0,3`).
+    #[arg(long, value_parser = ToneParams::parse, allow_hyphen_values = true)]
+    pub tone: Option<ToneParams>,
+    /// The crossovers between the low, mid and high bands of the tone
+    /// control in Hz, separated by a comma (e.g. `300,4000`).
+    #[arg(long, value_parser = ToneParams::parse_crossovers)]
+    pub tone_crossovers: Option<(f32, f32)>,
+    /// Adds echoes to the sound, which either stay on their own side or
+    /// bounce between the left and right channels.
+    #[arg(long, default_value = "off")]
+    pub delay: DelayMode,
+    /// The time between each echo, in milliseconds.
+    #[arg(long, default_value_t = 250.0, value_parser = parse_delay_time)]
+    pub delay_time: f32,
+    /// How much of each echo is fed back into the next, between 0 and 0.95.
+    #[arg(long, default_value_t = 0.4, value_parser = parse_delay_feedback)]
+    pub delay_feedback: f32,
+    /// The balance between the dry sound and the echoes, between 0 and 1.
+    #[arg(long, default_value_t = 0.3, value_parser = parse_fraction)]
+    pub delay_mix: f32,
+    /// The balance between the dry sound and the reverb, between 0 (no
+    /// reverb) and 1 (only reverb).
+    #[arg(long, default_value_t = 0.0, value_parser = parse_fraction)]
+    pub reverb_mix: f32,
+    /// The size of the reverb's room between 0 and 1, which sets how long it
+    /// rings for.
+    #[arg(long, default_value_t = 0.5, value_parser = parse_fraction)]
+    pub reverb_size: f32,
+    /// How the array is drawn.
+    #[arg(long)]
+    pub view: Option<View>,
+    /// The colors which values are drawn with: `rainbow`, `viridis`,
+    /// `grayscale`, `gradient`, or hex colors separated by commas (e.g.
+    /// `#000000,#ff0000`) for a custom gradient.
+    #[arg(long, value_parser = Palette::parse)]
+    pub palette: Option<Palette>,
+    /// Starts in colorblind-safe mode, which colors values by lightness and
+    /// marks each kind of operation with a different shape.
+    #[arg(long)]
+    pub colorblind: bool,
+    /// Loads how each kind of operation is highlighted from this file, where
+    /// each line is a `key = value` pair (e.g. `swap = lighten 0.1`).
+    #[arg(long, value_name = "FILE", value_parser = OverlayStyle::load)]
+    pub overlay_style: Option<OverlayStyle>,
+    /// Loads the background and text colors from this file, where each line
+    /// is a `key = value` pair (e.g. `background = #202020`).
+    #[arg(long, value_name = "FILE", value_parser = Theme::load)]
+    pub theme: Option<Theme>,
+    /// The extra layers to draw on the wheel, separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub visualization: Vec<Layer>,
+    /// Checks that the number of operations each algorithm performs matches
+    /// the recorded fixtures, then exits.
+    #[arg(long, conflicts_with = "update_op_counts")]
+    pub check_op_counts: bool,
+    /// Records the number of operations each algorithm performs to the
+    /// fixtures, then exits.
+    #[arg(long)]
+    pub update_op_counts: bool,
+}
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+/// Returns the command line arguments, which are parsed on the first call.
+/// If they are invalid, then the process exits with an error message.
+pub fn args() -> &'static Args {
+    ARGS.get_or_init(Args::parse)
+}
+
+/// A layer which can be drawn on the wheel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Layer {
+    /// The ring showing where each element will end up.
+    Target,
+    /// The trail left by writes.
+    WriteTrail,
+    /// The ring showing how often each element has been accessed.
+    HeatMap,
+    /// Cross-fades between the colors of swapped elements.
+    SwapAnimation,
+    /// Highlights the elements at either end which are in their final
+    /// positions.
+    SortedRegion,
+    /// Markers pointing at the pivots, bounds and heap roots which the
+    /// algorithm is working with.
+    Pointers,
+    /// Thin outlines between the slices of the wheel.
+    Outlines,
+}
+
+/// How the array is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum View {
+    /// The color wheel.
+    #[default]
+    Wheel,
+    /// A scatter plot of each element's value against its index.
+    Scatter,
+    /// An Archimedean spiral of colored segments.
+    Spiral,
+    /// Concentric rings, where each element's value sets its ring.
+    Rings,
+}
+
+impl View {
+    const ALL: [Self; 4] =
+        [Self::Wheel, Self::Scatter, Self::Spiral, Self::Rings];
+
+    /// Cycles to the next view.
+    pub fn cycle_next(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + 1) % Self::ALL.len()];
+    }
+
+    /// Cycles to the previous view.
+    pub fn cycle_prev(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()];
+    }
+}
+
+fn parse_size(s: &str) -> Result<usize, String> {
+    let size: usize = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (MIN_RESOLUTION..=MAX_RESOLUTION).contains(&size) {
+        Ok(size)
+    }
+    else {
+        Err(format!(
+            "the size must be between {MIN_RESOLUTION} and {MAX_RESOLUTION}"
+        ))
+    }
+}
+
+fn parse_dwell(s: &str) -> Result<f32, String> {
+    let dwell: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if dwell.is_finite() && dwell >= 0.0 {
+        Ok(dwell)
+    }
+    else {
+        Err(String::from("the dwell time must be a non-negative number"))
+    }
+}
+
+fn parse_pulse_width(s: &str) -> Result<f32, String> {
+    let width: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (0.01..=0.99).contains(&width) {
+        Ok(width)
+    }
+    else {
+        Err(String::from("the pulse width must be between 0.01 and 0.99"))
+    }
+}
+
+fn parse_buffer_size(s: &str) -> Result<usize, String> {
+    let size: usize = s.parse().map_err(|e| format!("{e}"))?;
+
+    if BUFFER_SIZES.contains(&size) {
+        Ok(size)
+    }
+    else {
+        Err(format!("the buffer size must be one of {BUFFER_SIZES:?}"))
+    }
+}
+
+fn parse_max_voices(s: &str) -> Result<u32, String> {
+    let voices: u32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (MIN_VOICES..=NUM_VOICES).contains(&(voices as usize)) {
+        Ok(voices)
+    }
+    else {
+        Err(format!(
+            "the number of voices must be between {MIN_VOICES} and {NUM_VOICES}"
+        ))
+    }
+}
+
+fn parse_delay_time(s: &str) -> Result<f32, String> {
+    let time: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (1.0..=MAX_DELAY_TIME_MS).contains(&time) {
+        Ok(time)
+    }
+    else {
+        Err(format!(
+            "the delay time must be between 1 and {MAX_DELAY_TIME_MS}"
+        ))
+    }
+}
+
+fn parse_delay_feedback(s: &str) -> Result<f32, String> {
+    let feedback: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (0.0..=0.95).contains(&feedback) {
+        Ok(feedback)
+    }
+    else {
+        Err(String::from("the feedback must be between 0 and 0.95"))
+    }
+}
+
+fn parse_volume(s: &str) -> Result<f32, String> {
+    let volume: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (-48.0..=12.0).contains(&volume) {
+        Ok(volume)
+    }
+    else {
+        Err(String::from("the volume must be between -48 and 12 dB"))
+    }
+}
+
+fn parse_makeup(s: &str) -> Result<MakeupGain, String> {
+    if s == "auto" {
+        return Ok(MakeupGain::Auto);
+    }
+
+    let level: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (-12.0..=24.0).contains(&level) {
+        Ok(MakeupGain::Manual(level))
+    }
+    else {
+        Err(String::from(
+            "the makeup gain must be auto, or between -12 and 24 dB",
+        ))
+    }
+}
+
+fn parse_ceiling(s: &str) -> Result<f32, String> {
+    let ceiling: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (-24.0..=0.0).contains(&ceiling) {
+        Ok(ceiling)
+    }
+    else {
+        Err(String::from("the ceiling must be between -24 and 0 dB"))
+    }
+}
+
+fn parse_fraction(s: &str) -> Result<f32, String> {
+    let value: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    }
+    else {
+        Err(String::from("the value must be between 0 and 1"))
+    }
+}
+
+fn parse_speed(s: &str) -> Result<f32, String> {
+    let speed: f32 = s.parse().map_err(|e| format!("{e}"))?;
+
+    if SPEED_RANGE.contains(&speed) {
+        Ok(speed)
+    }
+    else {
+        Err(format!(
+            "the speed must be between {} and {}",
+            SPEED_RANGE.start(),
+            SPEED_RANGE.end()
+        ))
+    }
+}
@@ -2,14 +2,66 @@
 
 use super::*;
 use std::{
+    cell::{Cell, RefCell},
     f32::consts::{FRAC_PI_2, TAU},
     marker::PhantomData as PD,
-    ops::Rem,
+    ops::{Range, Rem},
 };
 
 pub const DEFAULT_RESOLUTION: usize = 256;
+pub const MIN_RESOLUTION: usize = 3;
 pub const MAX_RESOLUTION: usize = 1 << 14; // 16384
 pub const CIRCLE_RADIUS: f32 = 300.0;
+/// The smallest resolution at which the wheel is rendered with instancing on
+/// the GPU (see [`GpuWheel`]), rather than as a mesh.
+const GPU_MIN_RESOLUTION: usize = 4096;
+/// The distance between the edge of the wheel and the selected region marker.
+const REGION_MARKER_GAP: f32 = 8.0;
+/// The inner and outer radii of the target ring.
+const TARGET_RING_RADII: (f32, f32) =
+    (CIRCLE_RADIUS + 14.0, CIRCLE_RADIUS + 26.0);
+/// The inner and outer radii of the heat map ring.
+const HEAT_RING_RADII: (f32, f32) = (WHEEL_EXTENT - 10.0, WHEEL_EXTENT);
+/// The radius of the wheel including the rings drawn around it, at its
+/// natural scale.
+pub const WHEEL_EXTENT: f32 = CIRCLE_RADIUS + 40.0;
+/// The opacity of the target ring.
+const TARGET_RING_ALPHA: f32 = 0.45;
+/// The inner and outer radii of the auxiliary array ring.
+const AUX_RING_RADII: (f32, f32) = (CIRCLE_RADIUS * 0.12, CIRCLE_RADIUS * 0.35);
+/// The overlay used for slices marked by the write trail.
+const WRITE_TRAIL_OVERLAY: Overlay = Overlay::Lighten(0.3);
+/// The amount which slices outside of the sorted region are desaturated by,
+/// so that the sorted region stands out.
+const UNSORTED_DESATURATION: f32 = 0.35;
+/// The time it takes for an operation's overlay to fade out, in seconds.
+const OVERLAY_FADE_TIME: f32 = 0.2;
+/// The palette used in colorblind-safe mode, whatever the selected palette.
+const COLORBLIND_PALETTE: Palette = Palette::Cividis;
+/// The distance between the edge of the wheel and the operation markers drawn
+/// in colorblind-safe mode.
+const MARKER_INSET: f32 = 10.0;
+/// The smallest and largest half-size of the operation markers.
+const MARKER_SIZE_RANGE: (f32, f32) = (1.5, 6.0);
+/// The distance between the edge of the wheel and the tip of each pointer.
+const POINTER_GAP: f32 = 2.0;
+/// The length and half-width of each pointer.
+const POINTER_SIZE: (f32, f32) = (12.0, 6.0);
+/// The largest resolution at which slice outlines are drawn, beyond which
+/// they would cover the wheel.
+const OUTLINE_MAX_RESOLUTION: usize = 1024;
+/// The width of the outlines between slices, in points.
+const OUTLINE_WEIGHT: f32 = 1.0;
+/// The largest resolution at which the value of each slice is written on it.
+const LABEL_MAX_RESOLUTION: usize = 32;
+/// The distance between the center of the wheel and each value label, as a
+/// proportion of [`CIRCLE_RADIUS`].
+const LABEL_RADIUS: f32 = 0.8;
+/// The font size of the value labels, at the wheel's natural scale.
+const LABEL_FONT_SIZE: u32 = 16;
+/// The time it takes for swapped slices to cross-fade to their new colors,
+/// in seconds.
+const SWAP_ANIMATION_TIME: f32 = 0.1;
 
 pub const SWAP_COLOR: Rgb<f32> =
     Rgb { red: 0.9, green: 1.0, blue: 0.9, standard: PD };
@@ -18,7 +70,18 @@ pub const COMPARE_TRUE_COLOR: Rgb<f32> =
 pub const COMPARE_FALSE_COLOR: Rgb<f32> =
     Rgb { red: 0.0, green: 0.0, blue: 0.0, standard: PD };
 
-#[derive(Clone, Copy, Debug)]
+/// Errors which may occur when resizing the color wheel or its associated
+/// arrays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeError {
+    /// The requested resolution is outside of
+    /// [`MIN_RESOLUTION`]`..=`[`MAX_RESOLUTION`].
+    InvalidResolution(usize),
+    /// Two arrays which should be the same length are not.
+    MismatchedLengths { expected: usize, found: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Overlay {
     Override(Rgb<f32>),
     Invert,
@@ -26,6 +89,48 @@ pub enum Overlay {
     Lighten(f32),
 }
 
+/// An overlay which fades out over [`OVERLAY_FADE_TIME`], so that recent
+/// operations leave a trail behind them.
+#[derive(Clone, Copy, Debug)]
+struct FadingOverlay {
+    overlay: Overlay,
+    /// The kind of operation which applied the overlay, which sets the shape
+    /// of its marker in colorblind-safe mode.
+    kind: OperationKind,
+    /// The time since the overlay was applied, in seconds.
+    age: f32,
+}
+
+impl FadingOverlay {
+    const fn new(overlay: Overlay, kind: OperationKind) -> Self {
+        Self { overlay, kind, age: 0.0 }
+    }
+
+    /// How strongly the overlay is applied, from `1.0` when it is new to
+    /// `0.0` once it has faded out.
+    fn strength(self) -> f32 {
+        (1.0 - self.age / OVERLAY_FADE_TIME).clamp(0.0, 1.0)
+    }
+}
+
+/// A slice which is cross-fading from its value before a swap to its
+/// current value.
+#[derive(Clone, Copy, Debug)]
+struct SwapFade {
+    /// The value of the slice before the swap.
+    from: usize,
+    /// The time since the swap, in seconds.
+    age: f32,
+}
+
+impl SwapFade {
+    /// How far through the cross-fade the slice is, from `0.0` when it is
+    /// swapped to `1.0` once it shows its current value.
+    fn progress(self) -> f32 {
+        (self.age / SWAP_ANIMATION_TIME).clamp(0.0, 1.0)
+    }
+}
+
 /// The color wheel display.
 #[derive(Debug)]
 pub struct ColorWheel {
@@ -33,13 +138,71 @@ pub struct ColorWheel {
     vertices: Vec<Vec3>,
     /// The mesh indices for the color wheel.
     indices: Vec<usize>,
-    /// Any overlay colors for the sorting process.
-    overlay_colors: Vec<Option<Overlay>>,
-    /// The original array of colors.
+    /// The position and color of each point of the mesh, three per slice.
+    /// These are kept between frames, and only the colors of slices which
+    /// have changed are recomputed — see [`Self::refresh_mesh()`].
+    mesh_points: Vec<(Vec3, Rgb<f32>)>,
+    /// The value of each slice when its color in the mesh was last computed.
+    mesh_values: Vec<usize>,
+    /// Whether the color of each slice in the mesh needs to be recomputed,
+    /// even if its value hasn't changed.
+    mesh_dirty: Vec<bool>,
+    /// Any overlay colors for the sorting process, for each slice.
+    overlay_colors: Vec<Option<FadingOverlay>>,
+    /// The color for each value in the array.
     colors: Vec<Rgb<f32>>,
-    /// The indices for each slice's color — copied from the sorting array.
+    /// The palette which the colors are taken from.
+    palette: Palette,
+    /// How each kind of operation is highlighted.
+    overlay_style: OverlayStyle,
+    /// Whether the wheel is drawn in colorblind-safe mode, in which
+    /// [`COLORBLIND_PALETTE`] is used and operations are marked by shape as
+    /// well as by color.
+    colorblind: bool,
+    /// The value of each slice, which determines its color — copied from the
+    /// sorting array. These may contain duplicates.
     color_indices: Vec<usize>,
-    overlay_operations: Arc<[SortOperation]>,
+    overlay_operations: OperationSlice,
+    /// Whether the overlay operations are yet to be applied. While playback
+    /// is paused the same operations are provided every frame, so they are
+    /// only counted towards the heat map (and animated) once.
+    new_overlay_operations: bool,
+    /// The range of slices which is marked as selected, if any.
+    region: Option<Range<usize>>,
+    /// The values which each slice will end up with, drawn as a ring around
+    /// the wheel if set.
+    target: Option<Vec<usize>>,
+    /// The state of the auxiliary array of the current capture, drawn as a
+    /// ring inside the wheel if set.
+    aux: Option<Vec<Option<usize>>>,
+    /// Whether each slice has been written to since its element was last
+    /// moved, if the write trail is enabled.
+    write_trail: Option<Vec<bool>>,
+    /// The number of times each slice has been accessed during playback, if
+    /// the heat map is enabled.
+    heat: Option<Vec<u32>>,
+    /// The number of slices at the start and end of the wheel which are in
+    /// their final positions, if the sorted region is shown.
+    sorted_region: Option<(usize, usize)>,
+    /// The slices which are cross-fading after being swapped, if swaps are
+    /// animated.
+    swap_fades: Option<Vec<Option<SwapFade>>>,
+    /// The slices which the sorting algorithm is pointing at, drawn as
+    /// markers around the wheel.
+    pointers: Pointers,
+    /// Whether thin outlines are drawn between the slices, which separate
+    /// them at low resolutions.
+    outlines: bool,
+    /// The instanced renderer used at high resolutions, which is created
+    /// when it is first needed.
+    gpu: RefCell<Option<GpuWheel>>,
+    /// Whether the device can't render the wheel with a [`GpuWheel`], in
+    /// which case it is always drawn as a mesh.
+    gpu_unsupported: Cell<bool>,
+    /// The position of the center of the wheel in the window.
+    center: Vec2,
+    /// The scale which the wheel is drawn at, relative to [`CIRCLE_RADIUS`].
+    scale: f32,
 }
 
 impl ColorWheel {
@@ -48,21 +211,70 @@ impl ColorWheel {
         let mut s = Self {
             vertices: vec![Vec3::ZERO; DEFAULT_RESOLUTION + 1],
             indices: (0..DEFAULT_RESOLUTION * 3).collect(),
+            mesh_points: vec![],
+            mesh_values: vec![],
+            mesh_dirty: vec![],
             overlay_colors: vec![None; DEFAULT_RESOLUTION],
             colors: vec![Rgb::new(0.0, 0.0, 0.0); DEFAULT_RESOLUTION],
+            palette: Palette::default(),
+            overlay_style: OverlayStyle::default(),
+            colorblind: false,
             color_indices: (0..DEFAULT_RESOLUTION).collect(),
-            overlay_operations: [].into(),
+            overlay_operations: OperationSlice::default(),
+            new_overlay_operations: false,
+            region: None,
+            target: None,
+            aux: None,
+            write_trail: None,
+            heat: None,
+            sorted_region: None,
+            swap_fades: None,
+            pointers: Pointers::default(),
+            outlines: false,
+            gpu: RefCell::new(None),
+            gpu_unsupported: Cell::new(false),
+            center: Vec2::ZERO,
+            scale: 1.0,
         };
 
         s.set_mesh_vertices();
         s.set_color_array();
+        s.refresh_mesh();
 
         s
     }
 
     /// Resizes the color wheel.
-    pub fn resize(&mut self, new_resolution: usize) {
-        self.overlay_operations = [].into();
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ResizeError::InvalidResolution`] if `new_resolution` is
+    /// outside of [`MIN_RESOLUTION`]`..=`[`MAX_RESOLUTION`], in which case the
+    /// color wheel is left unchanged.
+    pub fn resize(&mut self, new_resolution: usize) -> Result<(), ResizeError> {
+        if !(MIN_RESOLUTION..=MAX_RESOLUTION).contains(&new_resolution) {
+            return Err(ResizeError::InvalidResolution(new_resolution));
+        }
+
+        self.overlay_operations = OperationSlice::default();
+        self.new_overlay_operations = false;
+        self.region = None;
+        self.target = None;
+        self.aux = None;
+        self.sorted_region = None;
+        self.pointers = Pointers::default();
+
+        if let Some(trail) = self.write_trail.as_mut() {
+            *trail = vec![false; new_resolution];
+        }
+
+        if let Some(heat) = self.heat.as_mut() {
+            *heat = vec![0; new_resolution];
+        }
+
+        if let Some(fades) = self.swap_fades.as_mut() {
+            *fades = vec![None; new_resolution];
+        }
 
         self.vertices = vec![Vec3::ZERO; new_resolution + 1];
         self.indices = (0..new_resolution * 3).collect();
@@ -72,14 +284,141 @@ impl ColorWheel {
 
         self.set_mesh_vertices();
         self.set_color_array();
+        self.refresh_mesh();
+
+        Ok(())
     }
 
     /// Provides a slice of operations which will be used to draw an overlay.
-    pub fn set_overlay_ops(&mut self, operations: Arc<[SortOperation]>) {
+    pub fn set_overlay_ops(&mut self, operations: OperationSlice) {
+        self.new_overlay_operations |=
+            !operations.ptr_eq(&self.overlay_operations);
         self.overlay_operations = operations;
     }
 
-    /// Returns a mutable reference to the color index array.
+    /// Marks a range of slices as selected, or clears the selection.
+    pub fn set_region(&mut self, region: Option<Range<usize>>) {
+        self.region = region;
+    }
+
+    /// The range of slices which is marked as selected, if any.
+    pub fn region(&self) -> Option<Range<usize>> {
+        self.region.clone()
+    }
+
+    /// Sets the values shown in the target ring, or hides the ring. Targets
+    /// which don't match the resolution of the wheel are ignored.
+    pub fn set_target(&mut self, target: Option<&[usize]>) {
+        match target.filter(|t| t.len() == self.resolution()) {
+            Some(t) => {
+                let arr = self.target.get_or_insert_with(Vec::new);
+                arr.clear();
+                arr.extend_from_slice(t);
+            }
+            None => self.target = None,
+        }
+    }
+
+    /// Sets the auxiliary array shown in the inner ring, or hides the ring.
+    /// Arrays which don't match the resolution of the wheel are ignored.
+    pub fn set_aux(&mut self, aux: Option<&[Option<usize>]>) {
+        match aux.filter(|a| a.len() == self.resolution()) {
+            Some(a) => {
+                let arr = self.aux.get_or_insert_with(Vec::new);
+                arr.clear();
+                arr.extend_from_slice(a);
+            }
+            None => self.aux = None,
+        }
+    }
+
+    /// Sets the number of slices at the start and end of the wheel which are
+    /// in their final positions (see [`Self::sorted_extent()`]), or hides the
+    /// sorted region. Slices outside of the region are drawn slightly
+    /// desaturated, so that the region stands out.
+    pub fn set_sorted_region(&mut self, region: Option<(usize, usize)>) {
+        if region != self.sorted_region {
+            self.sorted_region = region;
+            self.invalidate_mesh();
+        }
+    }
+
+    /// Sets the slices which the sorting algorithm is pointing at — see
+    /// [`SortArray::point()`]. Pointers outside of the wheel are ignored.
+    pub fn set_pointers(&mut self, pointers: Pointers) {
+        self.pointers = pointers;
+    }
+
+    /// Returns the number of slices at the start and at the end of the wheel
+    /// which already have their values in `target`. If every slice matches,
+    /// the whole wheel is counted as the start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` isn't the same length as the wheel.
+    pub fn sorted_extent(&self, target: &[usize]) -> (usize, usize) {
+        let arr = &self.color_indices;
+        assert_eq!(arr.len(), target.len(), "mismatched target length");
+
+        let matches = |&(a, b): &(&usize, &usize)| a == b;
+        let prefix = arr.iter().zip(target).take_while(matches).count();
+        let suffix = arr[prefix..]
+            .iter()
+            .rev()
+            .zip(target[prefix..].iter().rev())
+            .take_while(matches)
+            .count();
+
+        (prefix, suffix)
+    }
+
+    /// Enables or disables the write trail. While enabled, slices which are
+    /// written to stay marked until their element is next moved by a swap.
+    pub fn set_write_trail(&mut self, enabled: bool) {
+        self.write_trail = enabled.then(|| vec![false; self.resolution()]);
+        self.invalidate_mesh();
+    }
+
+    /// Unmarks every slice in the write trail.
+    pub fn clear_write_trail(&mut self) {
+        if let Some(trail) = self.write_trail.as_mut() {
+            trail.fill(false);
+            self.invalidate_mesh();
+        }
+    }
+
+    /// Enables or disables the heat map. While enabled, every access to a
+    /// slice is counted, and the counts are drawn as a ring around the wheel.
+    pub fn set_heat_map(&mut self, enabled: bool) {
+        self.heat = enabled.then(|| vec![0; self.resolution()]);
+    }
+
+    /// Resets the access count of every slice in the heat map.
+    pub fn clear_heat_map(&mut self) {
+        if let Some(heat) = self.heat.as_mut() {
+            heat.fill(0);
+        }
+    }
+
+    /// Enables or disables swap animation. While enabled, swapped slices
+    /// cross-fade to their new colors rather than changing instantly.
+    pub fn set_swap_animation(&mut self, enabled: bool) {
+        self.swap_fades = enabled.then(|| vec![None; self.resolution()]);
+        self.invalidate_mesh();
+    }
+
+    /// Enables or disables the outlines between slices. They are only drawn
+    /// at resolutions up to [`OUTLINE_MAX_RESOLUTION`].
+    pub fn set_outlines(&mut self, enabled: bool) {
+        self.outlines = enabled;
+    }
+
+    /// The value of each slice.
+    pub fn arr(&self) -> &[usize] {
+        &self.color_indices
+    }
+
+    /// Returns a mutable reference to the array of slice values.
     pub fn arr_mut(&mut self) -> &mut [usize] {
         &mut self.color_indices
     }
@@ -87,6 +426,83 @@ impl ColorWheel {
     /// Clears the overlay colors.
     pub fn clear_overlay(&mut self) {
         self.overlay_colors.fill(None);
+        self.invalidate_mesh();
+    }
+
+    /// Ages the overlay colors and swap animations by `delta_time` seconds,
+    /// removing any which have finished.
+    pub fn fade_overlays(&mut self, delta_time: f32) {
+        // slices which finish fading are drawn once more without their
+        // overlay or animation.
+        let slots = self.overlay_colors.iter_mut().zip(&mut self.mesh_dirty);
+
+        for (slot, dirty) in slots {
+            if let Some(fading) = slot.as_mut() {
+                fading.age += delta_time;
+
+                if fading.strength() == 0.0 {
+                    *slot = None;
+                    *dirty = true;
+                }
+            }
+        }
+
+        if let Some(fades) = self.swap_fades.as_mut() {
+            for (slot, dirty) in fades.iter_mut().zip(&mut self.mesh_dirty) {
+                if let Some(fade) = slot.as_mut() {
+                    fade.age += delta_time;
+
+                    if fade.progress() == 1.0 {
+                        *slot = None;
+                        *dirty = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks the color of every slice in the mesh to be recomputed.
+    fn invalidate_mesh(&mut self) {
+        self.mesh_dirty.fill(true);
+    }
+
+    /// Recomputes the colors of the slices in the mesh which have changed
+    /// since it was last refreshed, i.e. those whose value has changed, which
+    /// have been marked as dirty, or which have an overlay or animation. If
+    /// the resolution has changed, the whole mesh is rebuilt.
+    fn refresh_mesh(&mut self) {
+        let res = self.resolution();
+
+        if self.mesh_points.len() != res * 3 {
+            self.mesh_points = (0..res)
+                .flat_map(|slice| self.slice_vertices(slice))
+                .map(|p| (p, Rgb::new(0.0, 0.0, 0.0)))
+                .collect();
+            self.mesh_values = vec![0; res];
+            self.mesh_dirty = vec![true; res];
+        }
+
+        for slice in 0..res {
+            let value = self.color_indices[slice];
+            let animating = self.overlay_colors[slice].is_some()
+                || self.swap_fades.as_ref().is_some_and(|f| f[slice].is_some());
+
+            if !(self.mesh_dirty[slice]
+                || animating
+                || value != self.mesh_values[slice])
+            {
+                continue;
+            }
+
+            let color = self.slice_color(slice);
+
+            for point in &mut self.mesh_points[slice * 3..slice * 3 + 3] {
+                point.1 = color;
+            }
+
+            self.mesh_values[slice] = value;
+            self.mesh_dirty[slice] = false;
+        }
     }
 
     /// Precomputes the positions of all of the circle's vertices.
@@ -105,18 +521,425 @@ impl ColorWheel {
     /// Precomputes the color array — this is the ordered, constant array
     /// of color values.
     fn set_color_array(&mut self) {
-        for i in 0..self.resolution() {
-            let t = i as f32 / self.resolution() as f32;
-            let h = t * 360.0;
-            self.colors[i] = hsl_to_rgb(h, 1.0, 0.5);
+        if self.colorblind {
+            COLORBLIND_PALETTE.fill(&mut self.colors);
+        }
+        else {
+            self.palette.fill(&mut self.colors);
         }
+
+        self.invalidate_mesh();
+    }
+
+    /// Sets the palette which values are colored with. This has no effect in
+    /// colorblind-safe mode until it is disabled.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        self.set_color_array();
+    }
+
+    /// Sets how each kind of operation is highlighted.
+    pub fn set_overlay_style(&mut self, style: OverlayStyle) {
+        self.overlay_style = style;
+    }
+
+    /// Enables or disables colorblind-safe mode, in which values are colored
+    /// by lightness, and each kind of operation is marked with a different
+    /// shape near the edge of the wheel.
+    pub fn set_colorblind_mode(&mut self, enabled: bool) {
+        self.colorblind = enabled;
+        self.set_color_array();
     }
 
     /// Returns the resolution of the color wheel.
-    fn resolution(&self) -> usize {
+    pub fn resolution(&self) -> usize {
         self.colors.len()
     }
 
+    /// Returns the three vertices of the triangle for `slice`, relative to the
+    /// center of the wheel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slice >= `[`Self::resolution()`].
+    pub fn slice_vertices(&self, slice: usize) -> [Vec3; 3] {
+        let next = if slice + 2 > self.resolution() { 1 } else { slice + 2 };
+
+        [self.vertices[0], self.vertices[slice + 1], self.vertices[next]]
+    }
+
+    /// Sets the position and scale which the wheel is drawn at.
+    pub fn set_transform(&mut self, center: Vec2, scale: f32) {
+        self.center = center;
+        self.scale = scale;
+    }
+
+    /// Converts `pos` in the window to a position relative to the center of
+    /// the wheel, at its natural scale.
+    pub fn to_local(&self, pos: Vec2) -> Vec2 {
+        (pos - self.center) / self.scale
+    }
+
+    /// Returns the slice at `pos`, relative to the center of the wheel (see
+    /// [`Self::to_local()`]), or `None` if `pos` is outside of the wheel.
+    pub fn slice_at(&self, pos: Vec2) -> Option<usize> {
+        if pos.length() > CIRCLE_RADIUS {
+            return None;
+        }
+
+        // the slices are laid out from the top of the wheel, going clockwise.
+        let theta = pos.y.atan2(-pos.x);
+        let t = (theta - FRAC_PI_2).rem_euclid(TAU) / TAU;
+
+        Some((t * self.resolution() as f32) as usize % self.resolution())
+    }
+
+    /// Returns the color of `slice`, including any overlay (or write trail
+    /// marker). The color is derived from the slice's value, where any values
+    /// greater than the largest value of a sorted wheel use the last color.
+    ///
+    /// Overlays which are fading out are blended with the color the slice
+    /// would have without them, and slices which were recently swapped are
+    /// blended with the color of their previous value. Slices outside of the
+    /// sorted region (if it is shown) are desaturated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slice >= `[`Self::resolution()`].
+    pub fn slice_color(&self, slice: usize) -> Rgb<f32> {
+        let res = self.resolution();
+        let value = self.color_indices[slice].min(res - 1);
+        let mut color = self.colors[value];
+
+        if let Some(fade) = self.swap_fades.as_ref().and_then(|f| f[slice]) {
+            let from = self.value_color(fade.from);
+            color = Self::mix_colors(from, color, fade.progress());
+        }
+
+        if let Some((prefix, suffix)) = self.sorted_region {
+            if (prefix..res.saturating_sub(suffix)).contains(&slice) {
+                color = Self::desaturate_color(color, UNSORTED_DESATURATION);
+            }
+        }
+
+        let trail = self
+            .write_trail
+            .as_ref()
+            .and_then(|t| t[slice].then_some(WRITE_TRAIL_OVERLAY));
+        let base = trail.map_or(color, |o| Self::apply_overlay(color, o));
+
+        let Some(fading) = self.overlay_colors[slice] else {
+            return base;
+        };
+
+        let overlaid = Self::apply_overlay(color, fading.overlay);
+
+        Self::mix_colors(base, overlaid, fading.strength())
+    }
+
+    /// Linearly interpolates between `a` and `b` by `t`.
+    fn mix_colors(a: Rgb<f32>, b: Rgb<f32>, t: f32) -> Rgb<f32> {
+        Rgb::new(
+            (b.red - a.red).mul_add(t, a.red),
+            (b.green - a.green).mul_add(t, a.green),
+            (b.blue - a.blue).mul_add(t, a.blue),
+        )
+    }
+
+    /// Returns `color` with `overlay` applied.
+    fn apply_overlay(color: Rgb<f32>, overlay: Overlay) -> Rgb<f32> {
+        match overlay {
+            Overlay::Override(c) => c,
+            Overlay::Invert => Self::invert_color(color),
+            Overlay::Darken(amt) => Self::darken_color(color, amt),
+            Overlay::Lighten(amt) => Self::lighten_color(color, amt),
+        }
+    }
+
+    /// Computes the overlay colors from the operations provided via
+    /// [`Self::set_overlay_ops()`]. Slices which aren't touched by the
+    /// operations keep their overlays until they fade out (see
+    /// [`Self::fade_overlays()`]).
+    pub fn apply_overlay_ops(&mut self) {
+        let is_new = std::mem::take(&mut self.new_overlay_operations);
+
+        for op in self.overlay_operations.iter() {
+            if let Some(heat) = self.heat.as_mut().filter(|_| is_new) {
+                Self::count_accesses(heat, op.unpack());
+            }
+
+            let kind = OperationKind::of(*op);
+            let op = op.unpack();
+            let overlay = self
+                .overlay_style
+                .overlay_for(op)
+                .map(|overlay| FadingOverlay::new(overlay, kind));
+
+            match op {
+                SortOperation::Compare { a, b, .. } => {
+                    self.overlay_colors[a] = overlay;
+                    self.overlay_colors[b] = overlay;
+                }
+                SortOperation::Swap { a, b } => {
+                    // the array has already been swapped, so each slice
+                    // fades from the value which is now in the other.
+                    if let Some(fades) =
+                        self.swap_fades.as_mut().filter(|_| is_new)
+                    {
+                        let (va, vb) =
+                            (self.color_indices[a], self.color_indices[b]);
+                        fades[a] = Some(SwapFade { from: vb, age: 0.0 });
+                        fades[b] = Some(SwapFade { from: va, age: 0.0 });
+                    }
+
+                    self.overlay_colors[a] = overlay;
+                    self.overlay_colors[b] = overlay;
+
+                    if let Some(trail) = self.write_trail.as_mut() {
+                        trail[a] = false;
+                        trail[b] = false;
+                    }
+                }
+                SortOperation::Write { idx, .. } => {
+                    self.overlay_colors[idx] = overlay;
+
+                    if let Some(trail) = self.write_trail.as_mut() {
+                        trail[idx] = true;
+                    }
+                }
+                SortOperation::Read { idx } => {
+                    self.overlay_colors[idx] = overlay;
+                }
+                // auxiliary writes are shown in the auxiliary ring instead.
+                SortOperation::AuxWrite { .. } => {}
+            }
+        }
+    }
+
+    /// Increments the access count of each slice in `heat` which `op`
+    /// reads from or writes to. Auxiliary writes don't touch the array, so
+    /// they aren't counted.
+    fn count_accesses(heat: &mut [u32], op: SortOperation) {
+        match op {
+            SortOperation::Compare { a, b, .. }
+            | SortOperation::Swap { a, b } => {
+                heat[a] += 1;
+                heat[b] += 1;
+            }
+            SortOperation::Write { idx, .. } | SortOperation::Read { idx } => {
+                heat[idx] += 1;
+            }
+            SortOperation::AuxWrite { .. } => {}
+        }
+    }
+
+    /// Returns the color of the heat map for `count` accesses, where the
+    /// most-accessed slice has `max` accesses. The colors run from black,
+    /// through red, to yellow, on a logarithmic scale so that the less
+    /// frequently accessed slices are still visible.
+    fn heat_color(count: u32, max: u32) -> Rgb<f32> {
+        if max == 0 {
+            return Rgb::new(0.0, 0.0, 0.0);
+        }
+
+        let t = (count as f32).ln_1p() / (max as f32).ln_1p();
+
+        hsl_to_rgb(60.0 * t, 1.0, 0.5 * t)
+    }
+
+    /// Returns the color of `value`, where any values greater than the
+    /// largest value of a sorted wheel use the last color.
+    fn value_color(&self, value: usize) -> Rgb<f32> {
+        self.colors[value.min(self.resolution() - 1)]
+    }
+
+    /// Draws a ring between `radii` (inner and outer), with a segment of the
+    /// corresponding color for each slice. Segments without a color are not
+    /// drawn.
+    fn draw_ring(
+        &self,
+        draw: &Draw,
+        colors: impl Iterator<Item = Option<Rgb<f32>>>,
+        (inner, outer): (f32, f32),
+        alpha: f32,
+    ) {
+        let res = self.resolution();
+
+        let point = |i: usize, radius: f32| {
+            let theta = (i as f32 / res as f32) * TAU + FRAC_PI_2;
+            let (y, x) = theta.sin_cos();
+            vec3(-x * radius, y * radius, 0.0)
+        };
+
+        let points = colors.enumerate().filter_map(|(i, color)| {
+            let Rgb { red, green, blue, .. } = color?;
+            let color = srgba(red, green, blue, alpha);
+
+            let (a, b) = (point(i, inner), point(i, outer));
+            let (c, d) = (point(i + 1, inner), point(i + 1, outer));
+
+            Some([a, b, d, a, d, c].map(|p| (p, color)))
+        });
+
+        let points = points.flatten();
+
+        self.transform(draw)
+            .mesh()
+            .points_colored(points)
+            .xy(Vec2::ZERO);
+    }
+
+    /// Draws a marker near the edge of each slice with an overlay, whose
+    /// shape depends on the kind of operation: a diamond for comparisons, an
+    /// inward triangle for swaps, a square for writes, and an outward
+    /// triangle for reads. The markers fade out along with the overlays.
+    fn draw_operation_markers(&self, draw: &Draw) {
+        let res = self.resolution();
+        let radius = CIRCLE_RADIUS - MARKER_INSET;
+        let (min, max) = MARKER_SIZE_RANGE;
+        let h = (PI * radius / res as f32).clamp(min, max);
+
+        let points = self.overlay_colors.iter().enumerate().filter_map(
+            |(i, fading)| {
+                let fading = (*fading)?;
+                let theta = ((i as f32 + 0.5) / res as f32) * TAU + FRAC_PI_2;
+                let (y, x) = theta.sin_cos();
+                // the outward and clockwise directions at the slice.
+                let (out, side) = (vec2(-x, y), vec2(y, x));
+                let c = out * radius;
+                // a point on the marker, relative to its size.
+                let p = |o: f32, s: f32| (out * o + side * s) * h + c;
+                let color = srgba(1.0, 1.0, 1.0, fading.strength());
+                // single triangles are padded with an empty one.
+                let empty = [p(0.0, 0.0); 3];
+
+                let tris = match fading.kind {
+                    OperationKind::Comparisons => [
+                        [p(1.0, 0.0), p(0.0, 1.0), p(-1.0, 0.0)],
+                        [p(-1.0, 0.0), p(0.0, -1.0), p(1.0, 0.0)],
+                    ],
+                    OperationKind::Swaps => {
+                        [[p(-1.0, 0.0), p(1.0, 1.0), p(1.0, -1.0)], empty]
+                    }
+                    OperationKind::Writes => [
+                        [p(1.0, 1.0), p(1.0, -1.0), p(-1.0, -1.0)],
+                        [p(-1.0, -1.0), p(-1.0, 1.0), p(1.0, 1.0)],
+                    ],
+                    OperationKind::Reads => {
+                        [[p(1.0, 0.0), p(-1.0, 1.0), p(-1.0, -1.0)], empty]
+                    }
+                };
+
+                let points = tris.into_iter().flatten();
+                Some(points.map(move |p| (p.extend(0.0), color)))
+            },
+        );
+
+        self.transform(draw)
+            .mesh()
+            .points_colored(points.flatten())
+            .xy(Vec2::ZERO);
+    }
+
+    /// Draws a triangle just outside the wheel for each pointer, pointing at
+    /// the middle of its slice. Pivots and heap roots are white, and the
+    /// bounds of ranges are gray.
+    fn draw_pointers(&self, draw: &Draw) {
+        let res = self.resolution();
+        let (len, half_width) = POINTER_SIZE;
+        let radius = CIRCLE_RADIUS + POINTER_GAP;
+
+        let points = Pointer::ALL.into_iter().zip(self.pointers).filter_map(
+            |(pointer, idx)| {
+                let idx = idx.filter(|&i| i < res)?;
+                let theta = ((idx as f32 + 0.5) / res as f32) * TAU + FRAC_PI_2;
+                let (y, x) = theta.sin_cos();
+                let (out, side) = (vec2(-x, y), vec2(y, x));
+                let tip = out * radius;
+                let base = out * (radius + len);
+                let color = match pointer {
+                    Pointer::Pivot | Pointer::Root => Rgb::new(1.0, 1.0, 1.0),
+                    Pointer::Low | Pointer::Mid | Pointer::High => {
+                        Rgb::new(0.6, 0.6, 0.6)
+                    }
+                };
+
+                Some(
+                    [tip, base + side * half_width, base - side * half_width]
+                        .map(|p| (p.extend(0.0), color)),
+                )
+            },
+        );
+
+        self.transform(draw)
+            .mesh()
+            .points_colored(points.flatten())
+            .xy(Vec2::ZERO);
+    }
+
+    /// Draws a thin black line along each edge of every slice, including
+    /// around the edge of the wheel.
+    fn draw_outlines(&self, draw: &Draw) {
+        // the width is kept constant in the window, whatever the scale.
+        let half_width = OUTLINE_WEIGHT * 0.5 / self.scale;
+        let rim = &self.vertices[1..];
+
+        let spokes = rim.iter().flat_map(|&v| {
+            let side = vec3(-v.y, v.x, 0.0).normalize_or_zero() * half_width;
+
+            [side, v + side, v - side, side, v - side, -side]
+        });
+
+        let transformed = self.transform(draw);
+
+        transformed
+            .mesh()
+            .points_colored(spokes.map(|p| (p, BLACK)))
+            .xy(Vec2::ZERO);
+
+        transformed
+            .polyline()
+            .weight(half_width * 2.0)
+            .points_closed(rim.iter().map(|v| v.truncate()))
+            .color(BLACK);
+    }
+
+    /// Writes the value of each slice near the edge of the wheel, in black or
+    /// white depending on the slice's current color.
+    fn draw_value_labels(&self, draw: &Draw) {
+        let res = self.resolution();
+        let transformed = self.transform(draw);
+
+        for (i, &value) in self.color_indices.iter().enumerate() {
+            let theta = ((i as f32 + 0.5) / res as f32) * TAU + FRAC_PI_2;
+            let (y, x) = theta.sin_cos();
+            let pos = vec2(-x, y) * CIRCLE_RADIUS * LABEL_RADIUS;
+
+            let color = self.mesh_points[i * 3].1;
+            let luma = 0.2126 * color.red
+                + 0.7152 * color.green
+                + 0.0722 * color.blue;
+            let label_color = if luma > 0.5 { BLACK } else { WHITE };
+
+            transformed
+                .text(&value.to_string())
+                .font_size(LABEL_FONT_SIZE)
+                .xy(pos)
+                .color(label_color);
+        }
+    }
+
+    /// Whether the slices are rendered on the GPU rather than drawn as a
+    /// mesh — see [`Drawable::render()`].
+    fn uses_gpu(&self) -> bool {
+        self.resolution() >= GPU_MIN_RESOLUTION && !self.gpu_unsupported.get()
+    }
+
+    /// Returns `draw` transformed to the wheel's position and scale.
+    fn transform(&self, draw: &Draw) -> Draw {
+        draw.translate(self.center.extend(0.0)).scale(self.scale)
+    }
+
     fn invert_color(color: Rgb<f32>) -> Rgb<f32> {
         let mut hsl = rgb_to_hsl(color);
         hsl.0 = (hsl.0 + 180.0).rem(360.0);
@@ -129,6 +952,12 @@ impl ColorWheel {
         rgb_from_hsl(hsl)
     }
 
+    fn desaturate_color(color: Rgb<f32>, amount: f32) -> Rgb<f32> {
+        let mut hsl = rgb_to_hsl(color);
+        hsl.1 *= 1.0 - amount.clamp(0.0, 1.0);
+        rgb_from_hsl(hsl)
+    }
+
     fn lighten_color(color: Rgb<f32>, lighten_amount: f32) -> Rgb<f32> {
         let mut hsl = rgb_to_hsl(color);
         hsl.2 *= 1.0 + lighten_amount.clamp(0.0, 1.0);
@@ -137,77 +966,103 @@ impl ColorWheel {
 }
 
 impl Updatable for ColorWheel {
-    fn update(&mut self, _: &App, _: UpdateData) {
-        self.clear_overlay();
-
-        for &op in self.overlay_operations.iter() {
-            match op {
-                SortOperation::Compare { a, b, res } => {
-                    let overlay = if res {
-                        Overlay::Lighten(0.5)
-                    }
-                    else {
-                        Overlay::Darken(0.2)
-                    };
-
-                    self.overlay_colors[a] = Some(overlay);
-                    self.overlay_colors[b] = Some(overlay);
-                }
-                SortOperation::Swap { a, b } => {
-                    let overlay = Overlay::Lighten(0.1);
-                    self.overlay_colors[a] = Some(overlay);
-                    self.overlay_colors[b] = Some(overlay);
-                }
-                SortOperation::Write { idx, .. } => {
-                    self.overlay_colors[idx] = Some(Overlay::Darken(0.7));
-                }
-                SortOperation::Read { idx } => {
-                    self.overlay_colors[idx] = Some(Overlay::Lighten(0.3));
-                }
-            }
-        }
+    fn update(&mut self, _: &App, update: UpdateData) {
+        self.fade_overlays(update.delta_time);
+        self.apply_overlay_ops();
+        self.refresh_mesh();
     }
 }
 
 impl Drawable for ColorWheel {
     fn draw(&self, draw: &Draw, _: UpdateData) {
-        draw.translate(vec3(0.0, 50.0, 0.0))
-            .mesh()
-            .indexed_colored(
-                (0..self.resolution() * 3).map(|i| {
-                    let color_idx = self.color_indices[i / 3];
-
-                    let color = self.overlay_colors[color_idx].map_or(
-                        self.colors[color_idx],
-                        |o| match o {
-                            Overlay::Override(c) => c,
-                            Overlay::Invert => {
-                                Self::invert_color(self.colors[color_idx])
-                            }
-                            Overlay::Darken(amt) => {
-                                Self::darken_color(self.colors[color_idx], amt)
-                            }
-                            Overlay::Lighten(amt) => {
-                                Self::lighten_color(self.colors[color_idx], amt)
-                            }
-                        },
-                    );
-
-                    if i % 3 == 0 {
-                        (self.vertices[0], color)
-                    }
-                    else if i % 3 == 1 {
-                        (self.vertices[i / 3 + 1], color)
-                    }
-                    else {
-                        let off = i / 3 + 2;
-                        let idx = if off > self.resolution() { 1 } else { off };
-                        (self.vertices[idx], color)
-                    }
-                }),
-                self.indices.iter().copied(),
-            )
-            .xy(Vec2::ZERO);
+        if !self.uses_gpu() {
+            self.transform(draw)
+                .mesh()
+                .indexed_colored(
+                    self.mesh_points.iter().copied(),
+                    self.indices.iter().copied(),
+                )
+                .xy(Vec2::ZERO);
+        }
+
+        if self.outlines && self.resolution() <= OUTLINE_MAX_RESOLUTION {
+            self.draw_outlines(draw);
+        }
+
+        if self.resolution() <= LABEL_MAX_RESOLUTION {
+            self.draw_value_labels(draw);
+        }
+
+        if self.colorblind {
+            self.draw_operation_markers(draw);
+        }
+
+        if let Some(target) = &self.target {
+            let colors = target.iter().map(|&v| Some(self.value_color(v)));
+            self.draw_ring(draw, colors, TARGET_RING_RADII, TARGET_RING_ALPHA);
+        }
+
+        if let Some(heat) = &self.heat {
+            let max = heat.iter().copied().max().unwrap_or(0);
+            let colors = heat.iter().map(|&c| Some(Self::heat_color(c, max)));
+            self.draw_ring(draw, colors, HEAT_RING_RADII, 1.0);
+        }
+
+        if let Some(aux) = &self.aux {
+            // the ring is drawn over the wheel on a dark background, so that
+            // unwritten positions are left dark.
+            self.transform(draw)
+                .ellipse()
+                .radius(AUX_RING_RADII.1)
+                .color(BLACK);
+
+            let colors = aux.iter().map(|v| v.map(|v| self.value_color(v)));
+            self.draw_ring(draw, colors, AUX_RING_RADII, 1.0);
+        }
+
+        if self.pointers.iter().any(Option::is_some) {
+            self.draw_pointers(draw);
+        }
+
+        if let Some(region) = &self.region {
+            let res = self.resolution() as f32;
+            let radius = CIRCLE_RADIUS + REGION_MARKER_GAP;
+
+            let points = (region.start..=region.end).map(|i| {
+                let theta = (i as f32 / res) * TAU + FRAC_PI_2;
+                let (y, x) = theta.sin_cos();
+                vec2(-x * radius, y * radius)
+            });
+
+            self.transform(draw)
+                .polyline()
+                .weight(3.0)
+                .points(points)
+                .color(WHITE);
+        }
+    }
+
+    /// Renders the slices with instancing at high resolutions, where
+    /// submitting a mesh with three vertices per slice every frame would be
+    /// slow. The rings and markers around the wheel are still drawn by
+    /// [`Self::draw()`].
+    fn render(&self, frame: &Frame) {
+        if !self.uses_gpu() {
+            return;
+        }
+
+        let mut gpu = self.gpu.borrow_mut();
+
+        if gpu.is_none() {
+            let device = frame.device_queue_pair().device();
+            *gpu = GpuWheel::new(device, frame.texture_msaa_samples());
+            self.gpu_unsupported.set(gpu.is_none());
+        }
+
+        if let Some(gpu) = gpu.as_mut() {
+            let colors = self.mesh_points.iter().step_by(3).map(|&(_, c)| c);
+            gpu.render(frame, colors, self.center, self.scale);
+        }
     }
 }
 
@@ -221,7 +1076,7 @@ fn rgb_from_hsl(hsl: (f32, f32, f32)) -> Rgb<f32> {
 /// [Source](https://www.rapidtables.com/convert/color/hsl-to-rgb.html)
 #[rustfmt::skip]
 #[allow(clippy::many_single_char_names)]
-fn hsl_to_rgb(mut h: f32, s: f32, l: f32) -> Rgb<f32> {
+pub fn hsl_to_rgb(mut h: f32, s: f32, l: f32) -> Rgb<f32> {
     h = h.clamp(0.0, 360.0);
 
     let mut c = (1.0 - (2.0 * l - 1.0).abs()) * s;
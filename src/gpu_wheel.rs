@@ -0,0 +1,179 @@
+//! Instanced rendering of the color wheel on the GPU, which is used at high
+//! resolutions — see [`ColorWheel::render()`](Drawable::render).
+
+use super::*;
+
+/// The size of the uniform buffer, which holds eight `f32`s — see
+/// `gpu_wheel.wgsl`.
+const UNIFORMS_SIZE: u64 = 32;
+/// The size of each slice's color in the storage buffer.
+const COLOR_SIZE: u64 = 16;
+
+/// A render pipeline which draws each slice of the wheel as an instance of a
+/// single triangle, where the color of each slice is read from a storage
+/// buffer. Only the colors are uploaded each frame, rather than a mesh with
+/// three vertices per slice.
+#[derive(Debug)]
+pub struct GpuWheel {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    uniforms: wgpu::Buffer,
+    colors: wgpu::Buffer,
+    /// The number of slices which the color buffer has room for.
+    capacity: usize,
+    /// The color of each slice as it is uploaded, with a padding channel.
+    staging: Vec<[f32; 4]>,
+}
+
+impl GpuWheel {
+    /// Creates the pipeline for frames with `msaa_samples` samples, or
+    /// returns `None` if the device can't read storage buffers in vertex
+    /// shaders (e.g. on some OpenGL backends).
+    pub fn new(device: &wgpu::Device, msaa_samples: u32) -> Option<Self> {
+        if device.limits().max_storage_buffers_per_shader_stage == 0 {
+            return None;
+        }
+
+        let module =
+            device.create_shader_module(wgpu::include_wgsl!("gpu_wheel.wgsl"));
+
+        let bind_group_layout = wgpu::BindGroupLayoutBuilder::new()
+            .uniform_buffer(wgpu::ShaderStages::VERTEX, false)
+            .storage_buffer(wgpu::ShaderStages::VERTEX, false, true)
+            .build(device);
+        let layout = wgpu::create_pipeline_layout(
+            device,
+            Some("gpu_wheel"),
+            &[&bind_group_layout],
+            &[],
+        );
+
+        let pipeline =
+            wgpu::RenderPipelineBuilder::from_layout(&layout, &module)
+                .vertex_entry_point("vs_main")
+                .fragment_shader(&module)
+                .fragment_entry_point("fs_main")
+                .color_format(Frame::TEXTURE_FORMAT)
+                .cull_mode(None)
+                .sample_count(msaa_samples)
+                .build(device);
+
+        let uniforms =
+            Self::buffer(device, UNIFORMS_SIZE, wgpu::BufferUsages::UNIFORM);
+        let colors =
+            Self::buffer(device, COLOR_SIZE, wgpu::BufferUsages::STORAGE);
+        let bind_group = Self::bind_group(
+            device,
+            &bind_group_layout,
+            &uniforms,
+            &colors,
+        );
+
+        Some(Self {
+            pipeline,
+            bind_group_layout,
+            bind_group,
+            uniforms,
+            colors,
+            capacity: 1,
+            staging: vec![],
+        })
+    }
+
+    /// Draws a wheel with a slice for each of `colors` to `frame`, centered
+    /// at `center` (in points) and at `scale` relative to [`CIRCLE_RADIUS`].
+    /// The wheel is drawn over whatever is already in the frame.
+    pub fn render(
+        &mut self,
+        frame: &Frame,
+        colors: impl ExactSizeIterator<Item = Rgb<f32>>,
+        center: Vec2,
+        scale: f32,
+    ) {
+        let slices = colors.len();
+        let device_queue = frame.device_queue_pair();
+        let (device, queue) = (device_queue.device(), device_queue.queue());
+
+        if slices == 0 {
+            return;
+        }
+
+        // the color buffer only grows, so it is recreated at most a few times.
+        if slices > self.capacity {
+            self.colors = Self::buffer(
+                device,
+                slices as u64 * COLOR_SIZE,
+                wgpu::BufferUsages::STORAGE,
+            );
+            self.bind_group = Self::bind_group(
+                device,
+                &self.bind_group_layout,
+                &self.uniforms,
+                &self.colors,
+            );
+            self.capacity = slices;
+        }
+
+        self.staging.clear();
+        self.staging
+            .extend(colors.map(|c| [c.red, c.green, c.blue, 1.0]));
+
+        let half_size = frame.rect().wh() * 0.5;
+        let uniforms: [f32; 8] = [
+            center.x,
+            center.y,
+            half_size.x,
+            half_size.y,
+            scale,
+            CIRCLE_RADIUS,
+            slices as f32,
+            0.0,
+        ];
+
+        queue.write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&uniforms));
+        queue.write_buffer(
+            &self.colors,
+            0,
+            bytemuck::cast_slice(&self.staging),
+        );
+
+        let mut encoder = frame.command_encoder();
+        let mut pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(frame.texture_view(), |color| {
+                color.load_op(wgpu::LoadOp::Load)
+            })
+            .begin(&mut encoder);
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..slices as u32);
+    }
+
+    /// Creates a buffer of `size` bytes which can be written to.
+    fn buffer(
+        device: &wgpu::Device,
+        size: u64,
+        usage: wgpu::BufferUsages,
+    ) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gpu_wheel"),
+            size,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Binds the uniform and color buffers.
+    fn bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        uniforms: &wgpu::Buffer,
+        colors: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        wgpu::BindGroupBuilder::new()
+            .buffer_bytes(uniforms, 0, None)
+            .buffer_bytes(colors, 0, None)
+            .build(device, layout)
+    }
+}
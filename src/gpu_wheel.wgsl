@@ -0,0 +1,60 @@
+// Draws each slice of the color wheel as one instance of a triangle, whose
+// color is read from a storage buffer.
+
+struct Uniforms {
+    // the center of the wheel in the window, in points.
+    center: vec2<f32>,
+    // half of the size of the window, in points.
+    half_size: vec2<f32>,
+    scale: f32,
+    radius: f32,
+    slices: f32,
+    _padding: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+// the sRGB color of each slice.
+@group(0) @binding(1)
+var<storage, read> colors: array<vec4<f32>>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex: u32,
+    @builtin(instance_index) slice: u32,
+) -> VertexOutput {
+    var local = vec2<f32>(0.0, 0.0);
+
+    // the first vertex is the center, and the others are on the edge of the
+    // wheel, which is laid out clockwise from the top.
+    if vertex > 0u {
+        let t = f32(slice + vertex - 1u) / uniforms.slices;
+        let theta = t * 6.2831855 + 1.5707964;
+        local = vec2<f32>(-cos(theta), sin(theta)) * uniforms.radius;
+    }
+
+    let pos = (uniforms.center + local * uniforms.scale) / uniforms.half_size;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(pos, 0.0, 1.0);
+    out.color = vec4<f32>(srgb_to_linear(colors[slice].rgb), 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,156 @@
+use super::*;
+
+/// The space between the edges of the window and each panel.
+const MARGIN: f32 = 16.0;
+const MINIMAP_HEIGHT: f32 = 10.0;
+const PROGRESS_BAR_HEIGHT: f32 = 6.0;
+const STATS_GRAPH_HEIGHT: f32 = 36.0;
+/// The space between the progress bar and the statistics graph.
+const STATS_GRAPH_GAP: f32 = 8.0;
+/// The space needed by the UI text.
+const TEXT_SIZE: (f32, f32) = (500.0, 300.0);
+/// The smallest size of the window, which leaves room for the text, the
+/// minimap and a small wheel.
+pub const MIN_WINDOW_SIZE: (u32, u32) = (560, 480);
+/// The smallest scale which the wheel is drawn at, so that it stays visible
+/// in very small windows.
+const MIN_WHEEL_SCALE: f32 = 0.1;
+
+/// The positions of each panel in the window.
+///
+/// The minimap runs along the top of the window, and the text is placed
+/// either below or to the right of the wheel — whichever leaves more room for
+/// the wheel — so that nothing overlaps as the window is resized. The
+/// progress bar sits just beneath the wheel, with the statistics graph
+/// beneath it. In race mode, the space for the
+/// wheel is split between two wheels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowLayout {
+    pub wheel_center: Vec2,
+    /// The center of the second wheel, in race mode.
+    pub race_wheel_center: Option<Vec2>,
+    /// The scale of the wheels, relative to [`CIRCLE_RADIUS`].
+    pub wheel_scale: f32,
+    pub minimap: Rect,
+    pub progress_bar: Rect,
+    pub stats_graph: Rect,
+    pub text: Rect,
+}
+
+impl WindowLayout {
+    /// Computes the layout for a window with the bounds `window`, with two
+    /// wheels if `race`.
+    pub fn compute(window: Rect, race: bool) -> Self {
+        let (text_w, text_h) = TEXT_SIZE;
+
+        let minimap = Rect::from_x_y_w_h(
+            window.x(),
+            window.top() - MARGIN - MINIMAP_HEIGHT * 0.5,
+            (window.w() - MARGIN * 2.0).max(0.0),
+            MINIMAP_HEIGHT,
+        );
+
+        // the space left for the wheel and text.
+        let (left, right) = (window.left() + MARGIN, window.right() - MARGIN);
+        let (bottom, top) = (window.bottom() + MARGIN, minimap.bottom() - MARGIN);
+        // the space reserved beneath the wheel for the progress bar and the
+        // statistics graph.
+        let bar_space =
+            PROGRESS_BAR_HEIGHT + STATS_GRAPH_HEIGHT + STATS_GRAPH_GAP + MARGIN;
+
+        let below = Rect::from_corners(
+            pt2(left, (bottom + text_h + MARGIN + bar_space).min(top)),
+            pt2(right, top),
+        );
+        let beside = Rect::from_corners(
+            pt2(left, (bottom + bar_space).min(top)),
+            pt2((right - text_w - MARGIN).max(left), top),
+        );
+
+        // in race mode, the wheels are placed side by side or above one
+        // another, whichever leaves more room for them.
+        let wheel_rects = |r: Rect| {
+            if !race {
+                (r, None)
+            }
+            else if r.w() >= r.h() {
+                let (l, r) = split(r, true);
+                (l, Some(r))
+            }
+            else {
+                let (t, b) = split(r, false);
+                (t, Some(b))
+            }
+        };
+        let diameter = |r: Rect| {
+            let r = wheel_rects(r).0;
+            r.w().min(r.h())
+        };
+
+        let (wheel, text) = if diameter(beside) > diameter(below) {
+            let text = Rect::from_corners(
+                pt2(beside.right() + MARGIN, bottom),
+                pt2(right, top),
+            );
+
+            (beside, text)
+        }
+        else {
+            let text = Rect::from_corners(
+                pt2(left, bottom),
+                pt2(right, below.bottom() - bar_space - MARGIN),
+            );
+
+            (below, text)
+        };
+
+        let (first, second) = wheel_rects(wheel);
+        let radius = diameter(wheel) * 0.5;
+
+        // the progress bar spans the wheels, just beneath the lowest one.
+        let last = second.unwrap_or(first);
+        let progress_bar = Rect::from_corners(
+            pt2(
+                first.x() - radius,
+                last.y() - radius - MARGIN - PROGRESS_BAR_HEIGHT,
+            ),
+            pt2(last.x() + radius, last.y() - radius - MARGIN),
+        );
+        let stats_graph = Rect::from_corners(
+            pt2(
+                progress_bar.left(),
+                progress_bar.bottom() - STATS_GRAPH_GAP - STATS_GRAPH_HEIGHT,
+            ),
+            pt2(progress_bar.right(), progress_bar.bottom() - STATS_GRAPH_GAP),
+        );
+
+        Self {
+            wheel_center: first.xy(),
+            race_wheel_center: second.map(|r| r.xy()),
+            wheel_scale: (radius / WHEEL_EXTENT).max(MIN_WHEEL_SCALE),
+            minimap,
+            progress_bar,
+            stats_graph,
+            text,
+        }
+    }
+}
+
+/// Splits `rect` in half, into its left and right halves if `horizontal`,
+/// otherwise into its top and bottom halves.
+fn split(rect: Rect, horizontal: bool) -> (Rect, Rect) {
+    if horizontal {
+        let mid = rect.x();
+        (
+            Rect::from_corners(rect.bottom_left(), pt2(mid, rect.top())),
+            Rect::from_corners(pt2(mid, rect.bottom()), rect.top_right()),
+        )
+    }
+    else {
+        let mid = rect.y();
+        (
+            Rect::from_corners(pt2(rect.left(), mid), rect.top_right()),
+            Rect::from_corners(rect.bottom_left(), pt2(rect.right(), mid)),
+        )
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(clippy::wildcard_imports, clippy::needless_range_loop)]
 #![feature(portable_simd)]
+#![cfg_attr(test, feature(test))]
 
 use mimalloc::MiMalloc;
 
@@ -8,57 +9,62 @@ static GLOBAL: MiMalloc = MiMalloc;
 
 use nannou::prelude::*;
 
+mod action;
 mod algorithms;
 mod audio;
+mod cli;
 mod color_wheel;
+mod gpu_wheel;
+mod layout;
 mod message;
+mod minimap;
 mod model;
+mod op_counts;
+mod osc;
+mod overlay_style;
+mod palette;
+mod playlist;
 mod prelude;
 mod process;
+mod race;
+mod rings;
+mod rng;
+mod scatter;
+#[cfg(test)]
+mod snapshot;
 mod sorting;
+mod spiral;
+mod stats_graph;
+mod theme;
 mod thread_pool;
 mod ui;
 
+use action::Action;
 use audio::*;
 use color_wheel::*;
+use gpu_wheel::GpuWheel;
+use layout::{WindowLayout, MIN_WINDOW_SIZE};
 use message::NoteEvent;
+use minimap::Minimap;
 use model::Model;
+use osc::{OscReceiver, OscSender, RemoteCommand};
+use overlay_style::OverlayStyle;
+use palette::{parse_hex_color, Palette};
+use playlist::Playlist;
 use prelude::*;
 use process::*;
+use race::Race;
+use rings::Rings;
+use scatter::ScatterPlot;
+use spiral::Spiral;
+use stats_graph::StatsGraph;
+use theme::Theme;
 use ui::{Ui, UiData};
 
-const ENVELOPE_DATA_PATH: &str = "src/audio/envelope_data";
-
-// TODO: move this to the audio module
-fn generate_envelope_data() {
-    let sr = 48000.0;
-    let attack_len = 0.01;
-    let release_len = 0.035;
-
-    let attack = (attack_len * sr).round() as usize;
-    let release = (release_len * sr).round() as usize;
-
-    let mut start = vec![0.0; attack];
-    let mut end = vec![0.0; release];
-
-    for i in 0..attack {
-        let x = i as f32 / attack as f32;
-        start[i] = x.clamp(0.0, 1.0);
-    }
-    for i in 0..release {
-        let x = (release - i) as f32 / release as f32;
-        end[i] = (x.powf(1.5)).clamp(0.0, 1.0);
-    }
-
-    start.append(&mut end);
-
-    std::fs::write(ENVELOPE_DATA_PATH, unsafe {
-        std::slice::from_raw_parts(
-            start.as_ptr().cast::<u8>(),
-            start.len() * std::mem::size_of::<f32>(),
-        )
-    }).expect("failed to write envelope data");
-}
+/// The version of the app, which is stored in saved captures.
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The file which captures are saved to and loaded from.
+const CAPTURE_PATH: &str = "capture.sortcap";
 
 #[derive(Clone, Copy, Debug)]
 pub struct UpdateData {
@@ -72,6 +78,11 @@ pub trait Updatable {
 
 pub trait Drawable: Updatable {
     fn draw(&self, draw: &Draw, update: UpdateData);
+
+    /// Renders straight to `frame` on the GPU, beneath everything drawn with
+    /// [`Self::draw()`]. Most visuals are only drawn with `draw()`, so this
+    /// does nothing by default.
+    fn render(&self, _frame: &Frame) {}
 }
 
 fn update(app: &App, model: &mut Model, _: Update) {
@@ -79,15 +90,62 @@ fn update(app: &App, model: &mut Model, _: Update) {
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
+    // the background is drawn first, as some visuals are rendered straight
+    // to the frame before the rest are drawn over them.
     let draw = app.draw();
-    draw.background().color(BLACK);
+    model.draw_background(&draw, app.window_rect());
+    draw.to_frame(app, &frame).unwrap();
 
+    model.render(&frame);
+
+    let draw = app.draw();
     model.draw(&draw);
 
     draw.to_frame(app, &frame).unwrap();
 }
 
+/// Checks or updates the operation count fixtures — see [`op_counts`]. The
+/// process exits with an error if the check fails.
+fn run_op_counts(update: bool) {
+    if update {
+        match op_counts::update() {
+            Ok(()) => println!("Updated {}", op_counts::FIXTURES_PATH),
+            Err(e) => {
+                eprintln!("Failed to update the op count fixtures: {e}");
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    match op_counts::check() {
+        Ok(changes) if changes.is_empty() => {
+            println!("All operation counts match the fixtures");
+        }
+        Ok(changes) => {
+            eprintln!("Operation counts have changed:");
+            for change in changes {
+                eprintln!("  {change}");
+            }
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to check the op counts: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
-    generate_envelope_data();
+    // the arguments are parsed before the app starts, so that any errors are
+    // reported straight away.
+    let args = cli::args();
+
+    if args.check_op_counts || args.update_op_counts {
+        run_op_counts(args.update_op_counts);
+        return;
+    }
+
     nannou::app(Model::new).update(update).run();
 }
@@ -57,3 +57,13 @@ impl NoteEvent {
         self.pan
     }
 }
+
+/// A message sent to the app model from another thread.
+#[derive(Debug)]
+pub enum ModelMessage {
+    /// A sorting algorithm has finished computing. Its operations are streamed
+    /// to the player as they are computed, so this only marks the end of the
+    /// computation. The token is the one which the computation was started
+    /// with.
+    ComputeFinished(crate::sorting::CancelToken),
+}
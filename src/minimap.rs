@@ -0,0 +1,164 @@
+use super::*;
+
+/// The brightness of a slice without any array-changing operations.
+const MIN_BRIGHTNESS: f32 = 0.12;
+
+/// A thin strip which acts as a timeline for the current capture, where the
+/// brightness of each slice shows how busy that part of playback is, and its
+/// hue shows which kinds of operation it is made up of.
+#[derive(Debug)]
+pub struct Minimap {
+    /// The operation density of each slice of playback.
+    density: Arc<[f32]>,
+    /// The proportion of each kind of operation in each slice of playback.
+    mix: Arc<[[f32; 4]]>,
+    /// The playback progress, if there is a capture.
+    progress: Option<f32>,
+    /// The start and end of the A–B loop, if one is marked.
+    loop_points: Option<(f32, Option<f32>)>,
+    /// The markers of the capture, as progress.
+    markers: Vec<f32>,
+    /// The area of the window which the minimap covers.
+    rect: Rect,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self {
+            density: [].into(),
+            mix: [].into(),
+            progress: None,
+            loop_points: None,
+            markers: vec![],
+            rect: Rect::from_w_h(0.0, 0.0),
+        }
+    }
+
+    /// Sets the operation density to display — see
+    /// [`SortCapture::op_density()`].
+    pub fn set_density(&mut self, density: Arc<[f32]>) {
+        self.density = density;
+    }
+
+    /// Sets the proportion of each kind of operation to display — see
+    /// [`SortCapture::op_mix()`].
+    pub fn set_op_mix(&mut self, mix: Arc<[[f32; 4]]>) {
+        self.mix = mix;
+    }
+
+    /// Sets the position of the playhead, or hides it.
+    pub fn set_progress(&mut self, progress: Option<f32>) {
+        self.progress = progress;
+    }
+
+    /// Sets the start and end of the A–B loop to display — see
+    /// [`Player::loop_points()`].
+    pub fn set_loop_points(&mut self, loop_points: Option<(f32, Option<f32>)>) {
+        self.loop_points = loop_points;
+    }
+
+    /// Sets the markers to display — see [`Player::markers()`].
+    pub fn set_markers(&mut self, markers: Vec<f32>) {
+        self.markers = markers;
+    }
+
+    /// Returns the playback progress at `pos` in the window, or `None` if
+    /// `pos` is outside of the minimap or there is nothing to seek through.
+    pub fn progress_at(&self, pos: Vec2) -> Option<f32> {
+        let rect = self.rect;
+
+        (self.progress.is_some() && rect.contains(pos))
+            .then(|| (pos.x - rect.left()) / rect.w())
+    }
+
+    /// Sets the area of the window which the minimap covers.
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+}
+
+impl Updatable for Minimap {
+    fn update(&mut self, _: &App, _: UpdateData) {}
+}
+
+impl Drawable for Minimap {
+    fn draw(&self, draw: &Draw, _: UpdateData) {
+        let Some(progress) = self.progress else {
+            return;
+        };
+
+        let rect = self.rect;
+        let bin_width = rect.w() / self.density.len() as f32;
+
+        let points = self.density.iter().enumerate().flat_map(|(i, &d)| {
+            let l = (i as f32).mul_add(bin_width, rect.left());
+            let r = l + bin_width;
+            let (b, t) = (rect.bottom(), rect.top());
+
+            let v = d.mul_add(1.0 - MIN_BRIGHTNESS, MIN_BRIGHTNESS);
+            let (red, green, blue) =
+                self.mix.get(i).map_or((1.0, 1.0, 1.0), mix_color);
+            let color = rgb(red * v, green * v, blue * v);
+
+            [(l, b), (l, t), (r, t), (l, b), (r, t), (r, b)]
+                .map(|(x, y)| (vec3(x, y, 0.0), color))
+        });
+
+        draw.mesh().points_colored(points);
+
+        let x_at = |p: f32| p.clamp(0.0, 1.0).mul_add(rect.w(), rect.left());
+
+        if let Some((start, end)) = self.loop_points {
+            let start_x = x_at(start);
+
+            if let Some(end_x) = end.map(x_at) {
+                draw.rect()
+                    .x_y((start_x + end_x) * 0.5, rect.y())
+                    .w_h(end_x - start_x, rect.h())
+                    .color(rgba(0.2, 0.6, 1.0, 0.25));
+            }
+
+            for x in [Some(start_x), end.map(x_at)].into_iter().flatten() {
+                draw.line()
+                    .start(vec2(x, rect.bottom() - 2.0))
+                    .end(vec2(x, rect.top() + 2.0))
+                    .weight(1.0)
+                    .color(DODGERBLUE);
+            }
+        }
+
+        for &marker in &self.markers {
+            let x = x_at(marker);
+
+            draw.tri()
+                .points(
+                    vec2(x - 4.0, rect.bottom() - 6.0),
+                    vec2(x + 4.0, rect.bottom() - 6.0),
+                    vec2(x, rect.bottom() - 1.0),
+                )
+                .color(GOLD);
+        }
+
+        let x = x_at(progress);
+
+        draw.line()
+            .start(vec2(x, rect.bottom() - 3.0))
+            .end(vec2(x, rect.top() + 3.0))
+            .weight(2.0)
+            .color(ORANGERED);
+    }
+}
+
+/// Blends the colors of each kind of operation by their proportion in `mix`.
+fn mix_color(mix: &[f32; 4]) -> (f32, f32, f32) {
+    if mix.iter().all(|&m| m == 0.0) {
+        return (1.0, 1.0, 1.0);
+    }
+
+    OperationKind::ALL.into_iter().fold((0.0, 0.0, 0.0), |(r, g, b), kind| {
+        let c = kind.color().into_format::<f32>();
+        let m = mix[kind as usize];
+
+        (c.red.mul_add(m, r), c.green.mul_add(m, g), c.blue.mul_add(m, b))
+    })
+}
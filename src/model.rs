@@ -1,38 +1,230 @@
 #![allow(clippy::suboptimal_flops)]
 
 use super::*;
-use crate::{prelude::*, thread_pool::ThreadPool};
+use crate::{
+    cli::{self, Args, Layer, View},
+    message::ModelMessage,
+    prelude::*,
+    rng,
+    thread_pool::ThreadPool,
+};
 use nannou_audio::Stream;
+use std::{collections::VecDeque, io::Write, ops::Range};
+
+/// The state of the sorting array.
+///
+/// The sorting thread holds the array for as long as a sort is being
+/// computed, so the array can't be resized until it has finished. Resizing
+/// during playback is fine, as the player's capture is simply discarded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SortState {
+    /// Nothing is being computed.
+    #[default]
+    Idle,
+    /// A sort is being computed on the sorting thread.
+    Computing,
+    /// A sort is being computed, and the array will be resized to this
+    /// resolution once it has finished.
+    ResizePending(usize),
+}
+
+impl SortState {
+    /// Starts computing a sort. Returns `false`, leaving the state as it is,
+    /// if a sort is already being computed.
+    fn start_computing(&mut self) -> bool {
+        if *self != Self::Idle {
+            return false;
+        }
+
+        *self = Self::Computing;
+        true
+    }
+
+    /// Defers a resize to `resolution` until the sort being computed has
+    /// finished. Returns `false` if nothing is being computed, in which case
+    /// the resize should be applied straight away.
+    fn defer_resize(&mut self, resolution: usize) -> bool {
+        if *self == Self::Idle {
+            return false;
+        }
+
+        *self = Self::ResizePending(resolution);
+        true
+    }
+
+    /// Marks the sort as finished, returning the resolution of any resize
+    /// which was deferred until then.
+    fn finish_computing(&mut self) -> Option<usize> {
+        match std::mem::take(self) {
+            Self::ResizePending(res) => Some(res),
+            _ => None,
+        }
+    }
+}
+
+/// The frame rate which captures are exported at, which divides
+/// [`SAMPLE_RATE`] so that every frame is a whole number of samples.
+const EXPORT_FRAME_RATE: u32 = 60;
+
+/// The time without an audio callback after which the audio stream is
+/// considered dead, in seconds.
+const AUDIO_TIMEOUT: f64 = 1.0;
+/// The time between attempts to rebuild a dead audio stream, in seconds.
+const AUDIO_RETRY_INTERVAL: f32 = 2.0;
+
+/// A capture which is being played back and exported frame by frame, along
+/// with its audio — see [`Model::toggle_frame_export()`].
+#[derive(Debug)]
+struct FrameExport {
+    /// The directory which the frames and audio are saved to.
+    dir: String,
+    /// The number of frames saved so far.
+    frames: u32,
+    /// The audio engine which renders the notes of each frame.
+    audio: OfflineAudio,
+}
+
+/// The state of tour mode, in which every algorithm is shuffled, sorted and
+/// played in turn.
+#[derive(Clone, Copy, Debug)]
+struct Tour {
+    /// The time to wait between each run, in seconds.
+    dwell: f32,
+    /// When the model last became idle, or `None` while a run is in progress.
+    idle_since: Option<Instant>,
+}
 
 pub struct Model {
-    _window_id: WindowId,
+    window_id: WindowId,
 
     current_algorithm: Arc<Atomic<SortingAlgorithm>>,
-    previous_algorithm: Arc<Mutex<Option<SortingAlgorithm>>>,
+    previous_algorithm: Option<SortingAlgorithm>,
 
     process: Arc<Mutex<Process>>,
     color_wheel: ColorWheel,
+    /// The scatter plot, which is drawn in place of the wheel in the scatter
+    /// view.
+    scatter: ScatterPlot,
+    /// The spiral, which is drawn in place of the wheel in the spiral view.
+    spiral: Spiral,
+    /// The rings, which are drawn in place of the wheel in the rings view.
+    rings: Rings,
+    /// How the array is drawn.
+    view: View,
+    minimap: Minimap,
+    stats_graph: StatsGraph,
     ui: Ui,
+    /// The positions of the wheel, minimap and text in the window.
+    layout: WindowLayout,
     sort_arr: Arc<Mutex<SortArray>>,
-    player: Arc<Mutex<Player>>,
+    player: Player,
+    /// The race against another algorithm, if race mode is enabled.
+    race: Option<Race>,
 
     target_arr: Vec<usize>,
 
     thread_pool: ThreadPool,
 
-    audio_stream: Stream<Audio>,
+    /// The audio stream, or `None` if it couldn't be started.
+    audio_stream: Option<Stream<Audio>>,
+    /// The receiver of the players' note events, which is passed to each
+    /// rebuilt audio stream.
+    note_rx: Receiver<NoteEvent>,
+    /// The clock of the audio callback, which is carried over to rebuilt
+    /// audio streams so that the players keep following it.
+    audio_clock: AudioClock,
+    /// When the audio stream was last (unsuccessfully) rebuilt, if it has
+    /// died — see [`Model::update_audio_stream()`].
+    audio_lost: Option<Instant>,
     audio_voice_counter: Arc<AtomicU32>,
     dsp_load: Arc<Atomic<f32>>,
+    /// The envelope and effect parameters, which are shared with the audio
+    /// thread.
+    audio_params: AudioParams,
     audio_playing: bool,
+    /// The sender which playback is broadcast to, if OSC output is enabled.
+    osc: Option<Arc<OscSender>>,
+    /// The receiver of commands from an external controller, if OSC input is
+    /// enabled.
+    osc_in: Option<OscReceiver>,
+
+    /// Whether sorts are timed as they are computed, and played back at the
+    /// pace they were computed at.
+    real_time: bool,
 
     sorted: bool,
     resolution: usize,
 
+    /// The distribution which the wheel is arranged into when shuffling.
+    distribution: Distribution,
+    /// The palettes which can be cycled through — the built-in palettes,
+    /// followed by a custom palette if one was passed on the command line.
+    palettes: Vec<Palette>,
+    /// The index of the current palette in `palettes`.
+    palette: usize,
+    /// Whether the wheels are drawn in colorblind-safe mode, which overrides
+    /// the palette.
+    colorblind: bool,
+    /// How each kind of operation is highlighted on the wheels.
+    overlay_style: OverlayStyle,
+    /// The background and text colors of the window.
+    theme: Theme,
+    /// Whether shuffles use a uniform Fisher–Yates shuffle, rather than the
+    /// moving-window shuffle.
+    uniform_shuffle: bool,
+    /// How thoroughly the shuffles shuffle the wheel, between `0.0` and `1.0`.
+    shuffle_intensity: Arc<Atomic<f32>>,
     is_shuffling: bool,
-    computing: Arc<AtomicBool>,
-    auto_play_ch: (Arc<Sender<()>>, Receiver<()>),
-
-    sort_after_shuffle: bool,
+    state: SortState,
+    /// The token which cancels the sort being computed, if there is one.
+    cancel_token: Option<CancelToken>,
+    message_ch: (Sender<ModelMessage>, Receiver<ModelMessage>),
+
+    /// Actions which are performed in order whenever the model is idle.
+    action_queue: VecDeque<Action>,
+    /// The tour through every algorithm, if tour mode is enabled.
+    tour: Option<Tour>,
+    /// The algorithms which are played in turn when the playlist is played.
+    playlist: Playlist,
+    /// The actions of the recorded macro.
+    macro_actions: Vec<Action>,
+    recording_macro: bool,
+
+    /// Whether the wheel can be edited with the mouse.
+    editing: bool,
+    /// The slice and value which were last set while editing, if the mouse is
+    /// being dragged across the wheel.
+    last_edit: Option<(usize, usize)>,
+
+    /// Whether the target ring, showing the final state of the current
+    /// capture, is drawn.
+    show_target: bool,
+    write_trail: bool,
+    heat_map: bool,
+    swap_animation: bool,
+    /// Whether the elements at either end of the wheel which are in their
+    /// final positions are highlighted.
+    show_sorted_region: bool,
+    /// Whether the pointers of the current capture are drawn around the
+    /// wheel.
+    show_pointers: bool,
+    /// Whether outlines are drawn between the slices of the wheel.
+    outlines: bool,
+    /// The playback progress in the last frame, used to reset the write trail
+    /// and heat map when playback is rewound or a new capture is played.
+    last_progress: Option<f32>,
+
+    /// The range of elements which algorithms are run on, if only part of
+    /// the wheel is selected.
+    region: Option<Range<usize>>,
+    /// The slice where the region selection started, while the right mouse
+    /// button is held.
+    region_anchor: Option<usize>,
+
+    /// Whether the next frame is saved as a screenshot.
+    screenshot_requested: bool,
+    /// The capture being exported as a sequence of frames, if any.
+    frame_export: Option<FrameExport>,
 
     update_data: UpdateData,
 }
@@ -40,43 +232,70 @@ pub struct Model {
 impl Model {
     /// Creates a new app model.
     pub fn new(app: &App) -> Self {
-        let _window_id = app
+        let window_id = app
             .new_window()
             .view(super::view)
-            .title("Sorting Algorithms")
+            .title(format!("Sorting Algorithms v{APP_VERSION}"))
             .key_pressed(key_pressed)
-            .size(800, 800)
-            .resizable(false)
+            .size(800, 900)
+            .min_size(MIN_WINDOW_SIZE.0, MIN_WINDOW_SIZE.1)
+            .resizable(true)
             .build()
             .expect("failed to initialize main window");
 
-        let color_wheel = ColorWheel::new();
+        let layout = WindowLayout::compute(app.window_rect(), false);
         let (note_tx, note_rx) =
             bounded(if cfg!(debug_assertions) { 96 } else { 512 });
 
         let audio_voice_counter = Arc::new(AtomicU32::new(0));
 
-        let audio_model = Audio::new(note_rx, Arc::clone(&audio_voice_counter));
-        let audio_callback_timer = Arc::clone(audio_model.callback_timer());
-        let dsp_load = Arc::clone(audio_model.dsp_load());
-
-        let (ap_tx, ap_rx) = bounded(0);
+        let audio_params = AudioParams::default();
+        let audio_clock = AudioClock::new(AudioConfig {
+            sample_rate: SAMPLE_RATE,
+            buffer_size: cli::args().buffer_size,
+        });
+        let dsp_load = Arc::new(Atomic::new(0.0));
+
+        // the app carries on without sound if the stream can't be started,
+        // and keeps trying to start it in the background.
+        let audio_stream = start_audio(
+            &note_rx,
+            &audio_voice_counter,
+            &audio_params,
+            &audio_clock,
+            &dsp_load,
+        )
+        .map_err(|e| eprintln!("{e}"))
+        .ok();
+        let audio_lost = audio_stream.is_none().then(Instant::now);
+
+        let (msg_tx, msg_rx) = bounded(1);
 
         let algo = Arc::new(Atomic::new(SortingAlgorithm::default()));
+        let shuffle_intensity = Arc::new(Atomic::new(1.0));
 
-        Self {
-            _window_id,
+        let mut model = Self {
+            window_id,
 
-            process: Arc::new(Mutex::new(Process::new(Arc::clone(&algo)))),
+            process: Arc::new(Mutex::new(Process::new(
+                Arc::clone(&algo),
+                &shuffle_intensity,
+            ))),
             current_algorithm: algo,
-            previous_algorithm: Arc::new(Mutex::new(None)),
-
-            color_wheel,
+            previous_algorithm: None,
+
+            color_wheel: ColorWheel::new(),
+            scatter: ScatterPlot::new(),
+            spiral: Spiral::new(),
+            rings: Rings::new(),
+            view: View::default(),
+            minimap: Minimap::new(),
+            stats_graph: StatsGraph::new(),
             ui: Ui::new(),
+            layout,
             sort_arr: Arc::new(Mutex::new(SortArray::new(DEFAULT_RESOLUTION))),
-            player: Arc::new(Mutex::new(Player::new(
-                note_tx, audio_callback_timer,
-            ))),
+            player: Player::new(note_tx, audio_clock.clone()),
+            race: None,
 
             target_arr: (0..DEFAULT_RESOLUTION).collect(),
             resolution: DEFAULT_RESOLUTION,
@@ -86,10 +305,41 @@ impl Model {
 
             sorted: true,
 
-            computing: Arc::new(AtomicBool::new(false)),
-            auto_play_ch: (Arc::new(ap_tx), ap_rx),
-
-            sort_after_shuffle: false,
+            state: SortState::Idle,
+            cancel_token: None,
+            message_ch: (msg_tx, msg_rx),
+
+            action_queue: VecDeque::new(),
+            tour: None,
+            playlist: Playlist::default(),
+            macro_actions: Vec::new(),
+            recording_macro: false,
+
+            editing: false,
+            last_edit: None,
+
+            show_target: false,
+            write_trail: false,
+            heat_map: false,
+            swap_animation: false,
+            show_sorted_region: false,
+            show_pointers: false,
+            outlines: false,
+            last_progress: None,
+
+            region: None,
+            region_anchor: None,
+
+            screenshot_requested: false,
+            frame_export: None,
+            distribution: Distribution::default(),
+            palettes: Palette::BUILT_IN.to_vec(),
+            palette: 0,
+            colorblind: false,
+            overlay_style: OverlayStyle::default(),
+            theme: Theme::default(),
+            uniform_shuffle: false,
+            shuffle_intensity,
             is_shuffling: false,
 
             update_data: UpdateData {
@@ -97,43 +347,324 @@ impl Model {
                 delta_time: 0.0,
             },
 
-            audio_stream: audio_model.into_stream(),
+            audio_stream,
+            note_rx,
+            audio_clock,
+            audio_lost,
             audio_voice_counter,
             dsp_load,
+            audio_params,
             audio_playing: true,
+            osc: None,
+            osc_in: None,
+
+            real_time: false,
+        };
+
+        model.apply_layout();
+        model
+            .player
+            .set_note_thinning(Arc::clone(&model.audio_params.load.thinning));
+        model.apply_args(cli::args());
+        model
+    }
+
+    /// Applies the initial settings from the command line.
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(seed) = args.seed {
+            rng::seed(seed);
+        }
+
+        if let Some(algorithm) = args.algorithm {
+            self.current_algorithm.store(algorithm, Relaxed);
+        }
+
+        if let Some(size) = args.size {
+            // the size has already been validated.
+            _ = self.set_resolution(size);
+        }
+
+        self.sort_arr
+            .lock()
+            .set_op_limit((args.op_limit > 0).then_some(args.op_limit));
+
+        if let Some(speed) = args.speed {
+            self.player.set_speed(speed);
+        }
+
+        if let Some(distribution) = args.distribution {
+            self.distribution = distribution;
+        }
+
+        if let Some(view) = args.view {
+            self.view = view;
+        }
+
+        if let Some(palette) = &args.palette {
+            self.palette = self
+                .palettes
+                .iter()
+                .position(|p| p == palette)
+                .unwrap_or_else(|| {
+                    self.palettes.push(palette.clone());
+                    self.palettes.len() - 1
+                });
+            self.apply_palette();
+        }
+
+        if args.colorblind {
+            self.toggle_colorblind_mode();
+        }
+
+        if let Some(style) = args.overlay_style {
+            self.overlay_style = style;
+            self.apply_overlay_style();
+        }
+
+        if let Some(theme) = args.theme {
+            self.theme = theme;
+            self.ui.set_text_color(theme.text);
+        }
+
+        if let Some(curve) = args.playback_curve {
+            self.player.set_curve(curve);
+        }
+
+        if let Some(panning) = args.panning {
+            self.set_panning(panning);
+        }
+
+        // the opponent's player copies these from the main player.
+        self.player
+            .set_saw_kinds(OperationFilter::only(&args.saw_operations));
+        self.player.set_pulse_kinds(
+            OperationFilter::only(&args.pulse_operations),
+            args.pulse_width,
+        );
+        self.player.set_noise_kinds(
+            OperationFilter::only(&args.noise_operations),
+            args.pink_noise,
+        );
+        self.player
+            .set_fm_kinds(OperationFilter::only(&args.fm_operations));
+        self.player.set_algorithm_sounds(!args.no_algorithm_sounds);
+
+        if let Some(adsr) = args.envelope {
+            self.audio_params.adsr.store(adsr, Relaxed);
+        }
+
+        let mut tone = args.tone.unwrap_or_default();
+        if let Some((low, high)) = args.tone_crossovers {
+            tone.low_freq = low;
+            tone.high_freq = high;
+        }
+        self.set_tone(tone);
+
+        self.set_delay(DelayParams {
+            mode: args.delay,
+            time_ms: args.delay_time,
+            feedback: args.delay_feedback,
+            mix: args.delay_mix,
+        });
+        self.set_reverb(ReverbParams {
+            mix: args.reverb_mix,
+            size: args.reverb_size,
+        });
+        self.set_volume(args.volume);
+        self.set_output_ceiling(args.ceiling);
+        self.set_makeup_gain(args.makeup);
+        self.set_polyphony(Polyphony {
+            max_voices: args.max_voices,
+            steal: args.voice_steal,
+        });
+
+        // this comes before race mode is started, so that the opponent's
+        // notes are sent too.
+        if let Some(target) = &args.osc_out {
+            match OscSender::new(target) {
+                Ok(osc) => {
+                    println!("Sending OSC to {}", osc.target());
+                    self.osc = Some(Arc::new(osc));
+                    self.player.set_osc(self.osc.clone());
+                }
+                Err(e) => eprintln!("Failed to start OSC output: {e}"),
+            }
+        }
+
+        if let Some(port) = args.osc_in {
+            match OscReceiver::new(port) {
+                Ok(osc_in) => {
+                    println!("Listening for OSC on port {port}");
+                    self.osc_in = Some(osc_in);
+                }
+                Err(e) => eprintln!("Failed to start OSC input: {e}"),
+            }
+        }
+
+        if let Some(opponent) = args.race {
+            self.start_race(opponent);
+        }
+
+        if args.muted {
+            self.toggle_audio_processing();
+        }
+
+        for &kind in &args.hide_operations {
+            self.toggle_operation_kind(kind);
+        }
+
+        for &kind in &args.mute_operations {
+            self.toggle_operation_sound(kind);
+        }
+
+        for layer in &args.visualization {
+            match layer {
+                Layer::Target => self.show_target = true,
+                Layer::WriteTrail => {
+                    self.write_trail = true;
+                    self.color_wheel.set_write_trail(true);
+                }
+                Layer::HeatMap => {
+                    self.heat_map = true;
+                    self.color_wheel.set_heat_map(true);
+                }
+                Layer::SwapAnimation => {
+                    self.swap_animation = true;
+                    self.color_wheel.set_swap_animation(true);
+                }
+                Layer::SortedRegion => self.show_sorted_region = true,
+                Layer::Pointers => self.show_pointers = true,
+                Layer::Outlines => {
+                    self.outlines = true;
+                    self.color_wheel.set_outlines(true);
+                }
+            }
         }
+
+        if args.tour {
+            self.toggle_tour();
+        }
+
+        if let Some(playlist) = &args.playlist {
+            self.playlist = playlist.clone();
+            self.play_playlist();
+        }
+
+        if args.autoplay {
+            self.shuffle_and_sort();
+        }
+    }
+
+    /// Moves the wheel, minimap and text into their positions in the current
+    /// layout.
+    fn apply_layout(&mut self) {
+        let WindowLayout {
+            wheel_center,
+            race_wheel_center,
+            wheel_scale,
+            minimap,
+            progress_bar,
+            stats_graph,
+            text,
+        } = self.layout;
+
+        self.color_wheel.set_transform(wheel_center, wheel_scale);
+        self.scatter.set_transform(wheel_center, wheel_scale);
+        self.spiral.set_transform(wheel_center, wheel_scale);
+        self.rings.set_transform(wheel_center, wheel_scale);
+
+        if let (Some(race), Some(center)) =
+            (self.race.as_mut(), race_wheel_center)
+        {
+            race.set_transform(center, wheel_scale);
+        }
+
+        self.minimap.set_rect(minimap);
+        self.stats_graph.set_rect(stats_graph);
+        self.ui.set_rect(text);
+        self.ui.set_progress_bar_rect(progress_bar);
     }
 
-    pub fn set_resolution(&mut self, new_resolution: usize) {
-        // println!("setting resolution to {new_resolution}");
+    /// Sets the resolution of the color wheel, sorting array and player. If
+    /// a sort is being computed, the resolution is changed once it has
+    /// finished (see [`SortState`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `new_resolution` is invalid, in which case none of
+    /// the components are modified.
+    pub fn set_resolution(
+        &mut self,
+        new_resolution: usize,
+    ) -> Result<(), ResizeError> {
+        if self.is_computing() {
+            if !(MIN_RESOLUTION..=MAX_RESOLUTION).contains(&new_resolution) {
+                return Err(ResizeError::InvalidResolution(new_resolution));
+            }
+
+            self.state.defer_resize(new_resolution);
+            return Ok(());
+        }
+
+        // the sorting array is locked for the whole resize so that it can't
+        // change while a sort is being computed.
+        let mut sort_arr = self.sort_arr.lock();
+
+        // the color wheel validates the resolution before changing anything,
+        // so it is resized first.
+        self.color_wheel.resize(new_resolution)?;
 
-        let mut player = self.player.lock();
-        player.clear_capture();
-        player.clear_ops();
-        drop(player);
+        self.player.clear_capture();
+        self.player.clear_ops();
+        sort_arr.resize(new_resolution);
+
+        if let Some(race) = self.race.as_mut() {
+            // the resolution has already been validated by the main wheel.
+            _ = race.resize(new_resolution);
+        }
 
         self.target_arr = (0..new_resolution).collect();
-        self.sort_arr.lock().resize(new_resolution);
-        self.color_wheel.resize(new_resolution);
         self.resolution = new_resolution;
+        self.region = None;
+        self.region_anchor = None;
 
         self.sorted = true;
+
+        Ok(())
     }
 
     pub fn increase_resolution(&mut self) {
-        self.set_resolution((self.resolution * 8 / 6).min(MAX_RESOLUTION));
+        let res = self.requested_resolution();
+        _ = self.set_resolution((res * 8 / 6).min(MAX_RESOLUTION));
     }
 
     pub fn decrease_resolution(&mut self) {
-        self.set_resolution((self.resolution * 6 / 8).max(3));
+        let res = self.requested_resolution();
+        _ = self.set_resolution((res * 6 / 8).max(MIN_RESOLUTION));
     }
 
     pub fn double_resolution(&mut self) {
-        self.set_resolution((self.resolution * 2).min(MAX_RESOLUTION));
+        let res = self.requested_resolution();
+        _ = self.set_resolution((res * 2).min(MAX_RESOLUTION));
     }
 
     pub fn halve_resolution(&mut self) {
-        self.set_resolution((self.resolution / 2).max(3));
+        let res = self.requested_resolution();
+        _ = self.set_resolution((res / 2).max(MIN_RESOLUTION));
+    }
+
+    /// The resolution which the wheel will have once any pending resize has
+    /// been applied.
+    const fn requested_resolution(&self) -> usize {
+        match self.state {
+            SortState::ResizePending(res) => res,
+            _ => self.resolution,
+        }
+    }
+
+    /// Whether a sort is being computed.
+    fn is_computing(&self) -> bool {
+        self.state != SortState::Idle
     }
 
     pub fn next_algorithm(&self) {
@@ -148,233 +679,1845 @@ impl Model {
         self.current_algorithm.store(curr, Relaxed);
     }
 
-    // *** *** *** //
+    /// Toggles between the moving-window and Fisher–Yates shuffles.
+    pub fn toggle_uniform_shuffle(&mut self) {
+        self.uniform_shuffle = !self.uniform_shuffle;
+    }
 
-    /// Updates the app state.
-    pub fn update(&mut self, app: &App) {
-        self.update_data.delta_time =
-            self.update_data.last_frame.elapsed().as_secs_f32();
+    /// Increases the shuffle intensity by 10%.
+    pub fn increase_shuffle_intensity(&self) {
+        self.step_shuffle_intensity(1.0);
+    }
 
-        let mut player = self.player.lock();
-        let computing = self.computing.load(Relaxed);
+    /// Decreases the shuffle intensity by 10%.
+    pub fn decrease_shuffle_intensity(&self) {
+        self.step_shuffle_intensity(-1.0);
+    }
 
-        if self.auto_play_ch.1.try_recv().is_ok() {
-            player.play();
-        }
+    /// Moves the shuffle intensity by `steps` tenths, keeping it at an exact
+    /// multiple of 10%.
+    fn step_shuffle_intensity(&self, steps: f32) {
+        let tenths = (self.shuffle_intensity.load(Relaxed) * 10.0).round();
+        let intensity = (tenths + steps).clamp(0.0, 10.0) / 10.0;
 
-        if !computing && !player.is_playing() {
-            if self.sort_after_shuffle {
-                self.compute();
-                self.sort_after_shuffle = false;
-            }
+        self.shuffle_intensity.store(intensity, Relaxed);
+    }
 
-            if self.is_shuffling {
-                self.is_shuffling = false;
-            }
-        }
+    pub fn next_distribution(&mut self) {
+        self.distribution.cycle_next();
+    }
 
-        player.update(app, self.update_data);
+    pub fn previous_distribution(&mut self) {
+        self.distribution.cycle_prev();
+    }
 
-        self.color_wheel.set_overlay_ops(player.ops_last_frame());
-        self.color_wheel.update(app, self.update_data);
-        player.copy_arr_to(self.color_wheel.arr_mut());
+    pub fn next_palette(&mut self) {
+        self.palette = (self.palette + 1) % self.palettes.len();
+        self.apply_palette();
+    }
 
-        self.ui.update_text(UiData {
-            algorithm: self.current_algorithm.load(Relaxed),
-            data: player.sort_data(),
-            resolution: self.resolution,
-            player_time: player.playback_time(),
-            speed: player.speed(),
-            num_voices: self.audio_voice_counter.load(Relaxed),
-            dsp_load: self.dsp_load.load(Relaxed),
-            sorted: player.is_sorted(),
-            computing,
-            shuffling: self.is_shuffling,
-        });
+    pub fn increase_overlay_intensity(&mut self) {
+        self.set_overlay_intensity(self.overlay_style.intensity + 0.1);
+    }
 
-        drop(player);
+    pub fn decrease_overlay_intensity(&mut self) {
+        self.set_overlay_intensity(self.overlay_style.intensity - 0.1);
+    }
 
-        self.update_data.last_frame = Instant::now();
+    /// Sets the amount which lighten and darken overlays are scaled by,
+    /// clamped to [`overlay_style::INTENSITY_RANGE`].
+    fn set_overlay_intensity(&mut self, intensity: f32) {
+        let (min, max) = (
+            *overlay_style::INTENSITY_RANGE.start(),
+            *overlay_style::INTENSITY_RANGE.end(),
+        );
+        // rounded to avoid drifting away from multiples of 10%.
+        let intensity = (intensity * 10.0).round() / 10.0;
+
+        self.overlay_style.intensity = intensity.clamp(min, max);
+        self.apply_overlay_style();
     }
 
-    /// Draws the app visuals to the provided `Draw` instance.
-    pub fn draw(&self, draw: &Draw) {
-        self.color_wheel.draw(draw, self.update_data);
-        self.ui.draw(draw);
+    /// Highlights operations on the wheels with the current overlay style.
+    fn apply_overlay_style(&mut self) {
+        self.color_wheel.set_overlay_style(self.overlay_style);
+
+        if let Some(race) = self.race.as_mut() {
+            race.set_overlay_style(self.overlay_style);
+        }
     }
 
-    // *** *** *** //
+    /// Toggles colorblind-safe mode, which colors values by lightness and
+    /// marks each kind of operation with a different shape.
+    pub fn toggle_colorblind_mode(&mut self) {
+        self.colorblind = !self.colorblind;
+        self.apply_palette();
+    }
 
-    /// Forces the color wheel to be sorted via `std::sort_unstable`.
-    pub fn force_sort(&self) {
-        let mut player = self.player.lock();
-        let mut sort_arr = self.sort_arr.lock();
+    /// Colors the wheels with the current palette (or in colorblind-safe
+    /// mode, if it is enabled).
+    fn apply_palette(&mut self) {
+        let palette = &self.palettes[self.palette];
+        self.color_wheel.set_palette(palette.clone());
+        self.color_wheel.set_colorblind_mode(self.colorblind);
 
-        player.clear_capture();
-        sort_arr.prepare_for_sort(self.current_algorithm.load(Relaxed));
-        sort_arr.force_sort();
-        player.set_capture(sort_arr.dump_capture());
+        if let Some(race) = self.race.as_mut() {
+            race.set_palette(palette.clone());
+            race.set_colorblind_mode(self.colorblind);
+        }
     }
 
-    /// Returns `true` if the sorting array is correctly sorted.
-    pub fn is_sorted(&self) -> bool {
-        self.player.lock().is_sorted()
+    pub fn next_playback_curve(&mut self) {
+        let mut curve = self.player.curve();
+        curve.cycle_next();
+        self.player.set_curve(curve);
     }
 
-    /// Computes the sort.
-    pub fn compute(&self) {
-        self.computing.store(true, Relaxed);
+    pub fn previous_playback_curve(&mut self) {
+        let mut curve = self.player.curve();
+        curve.cycle_prev();
+        self.player.set_curve(curve);
+    }
 
-        // prepare the array
-        self.sort_arr
-            .lock()
-            .prepare_for_sort(self.current_algorithm.load(Relaxed));
+    /// Cycles to the next scale which notes are quantized to.
+    pub fn next_scale(&mut self) {
+        let mut tuning = self.player.tuning();
+        tuning.scale.cycle_next();
+        self.set_tuning(tuning);
+    }
 
-        let player = Arc::clone(&self.player);
-        let arr = Arc::clone(&self.sort_arr);
-        let process = Arc::clone(&self.process);
-        let computing = Arc::clone(&self.computing);
-        let ap_tx = Arc::clone(&self.auto_play_ch.0);
-        let curr_algo = Arc::clone(&self.current_algorithm);
-        let prev = Arc::clone(&self.previous_algorithm);
+    /// Moves the root note of the scale up by a semitone.
+    pub fn next_root_note(&mut self) {
+        let mut tuning = self.player.tuning();
+        tuning.cycle_root();
+        self.set_tuning(tuning);
+    }
 
-        self.thread_pool.execute(move || {
-            let mut arr = arr.lock();
-            process.lock().sort(&mut arr);
-            player.lock().set_capture(arr.dump_capture());
+    /// Cycles to the next way of placing notes in the stereo field.
+    pub fn next_panning(&mut self) {
+        let mut panning = self.player.panning();
+        panning.cycle_next();
+        self.set_panning(panning);
+    }
 
-            drop(arr);
+    /// Cycles to the next amplitude envelope preset. Notes which are already
+    /// playing keep the envelope they started with.
+    pub fn next_envelope(&mut self) {
+        let adsr = self.audio_params.adsr.load(Relaxed).next_preset();
+        self.audio_params.adsr.store(adsr, Relaxed);
+        println!("Envelope: {adsr}");
+    }
 
-            if let Some(prev) = prev.lock().take() {
-                curr_algo.store(prev, Relaxed);
-            }
+    /// Cycles to the next tone control preset.
+    pub fn next_tone_preset(&mut self) {
+        let tone = self.audio_params.tone.load(Relaxed).next_preset();
+        self.set_tone(tone);
+        println!("Tone: {tone}");
+    }
 
-            computing.store(false, Relaxed);
-            _ = ap_tx.send(());
-        });
+    /// Sets the crossovers and band gains of the tone control.
+    pub fn set_tone(&mut self, tone: ToneParams) {
+        self.audio_params.tone.store(tone, Relaxed);
     }
 
-    /// Starts a shuffle.
-    pub fn shuffle(&mut self) {
-        *self.previous_algorithm.lock() = Some(
-            self.current_algorithm
-                .swap(SortingAlgorithm::Shuffle, Relaxed),
-        );
+    /// Cycles to the next delay mode, the first of which turns the delay off.
+    pub fn next_delay_mode(&mut self) {
+        let mut delay = self.audio_params.delay.load(Relaxed);
+        delay.mode.cycle_next();
+        self.set_delay(delay);
+        println!("Delay: {delay}");
+    }
 
-        self.is_shuffling = true;
+    /// Sets the mode, time, feedback and mix of the delay.
+    pub fn set_delay(&mut self, delay: DelayParams) {
+        self.audio_params.delay.store(delay, Relaxed);
+    }
 
-        self.compute();
+    /// Sets the master volume of the voices, in decibels.
+    pub fn set_volume(&mut self, volume_db: f32) {
+        self.audio_params.volume_db.store(volume_db, Relaxed);
     }
 
-    pub fn increase_speed(&self) {
-        let mut player = self.player.lock();
+    /// Sets the makeup gain of the output compressor.
+    pub fn set_makeup_gain(&mut self, makeup: MakeupGain) {
+        self.audio_params.makeup.store(makeup, Relaxed);
+    }
 
-        let speed = player.speed();
-        player.set_speed((speed + 0.02).min(5.0));
+    /// Sets the level which the output is soft-clipped to, in decibels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level_db` is greater than `0.0`.
+    pub fn set_output_ceiling(&mut self, level_db: f32) {
+        assert!(level_db <= 0.0);
+        self.audio_params.ceiling_db.store(level_db, Relaxed);
     }
 
-    pub fn decrease_speed(&self) {
-        let mut player = self.player.lock();
+    /// Cycles to the next reverb mix level, the first of which turns the
+    /// reverb off.
+    pub fn next_reverb_mix(&mut self) {
+        let mut reverb = self.audio_params.reverb.load(Relaxed);
+        reverb.cycle_mix();
+        self.set_reverb(reverb);
+        println!("Reverb: {reverb}");
+    }
 
-        let speed = player.speed();
-        player.set_speed((speed - 0.02).max(-5.0));
+    /// Sets the wet/dry mix and room size of the reverb.
+    pub fn set_reverb(&mut self, reverb: ReverbParams) {
+        self.audio_params.reverb.store(reverb, Relaxed);
     }
 
-    pub fn play(&self) {
-        let mut player = self.player.lock();
-        if player.at_end() {
-            player.stop();
-        }
+    /// Cycles to the next polyphony cap in [`Polyphony::CAPS`].
+    pub fn next_polyphony_cap(&mut self) {
+        let mut polyphony = self.audio_params.polyphony.load(Relaxed);
+        polyphony.next_cap();
+        self.set_polyphony(polyphony);
+        println!("Polyphony: {polyphony}");
+    }
 
-        player.play();
+    /// Cycles to the next behavior for new notes once every voice is in use.
+    pub fn next_voice_steal(&mut self) {
+        let mut polyphony = self.audio_params.polyphony.load(Relaxed);
+        polyphony.steal.cycle_next();
+        self.set_polyphony(polyphony);
+        println!("Polyphony: {polyphony}");
     }
 
-    pub fn pause(&self) {
-        self.player.lock().pause();
+    /// Sets the polyphony cap, and what happens to new notes beyond it.
+    pub fn set_polyphony(&mut self, polyphony: Polyphony) {
+        self.audio_params.polyphony.store(polyphony, Relaxed);
     }
 
-    pub fn stop(&self) {
-        self.player.lock().stop();
+    /// Toggles whether operations of `kind` are highlighted and sonified.
+    pub fn toggle_operation_kind(&mut self, kind: OperationKind) {
+        let mut filter = self.player.op_filter();
+        filter.toggle(kind);
+        self.set_op_filter(filter);
     }
 
-    pub fn is_playing(&self) -> bool {
-        self.player.lock().is_playing()
+    /// Only highlights and sonifies operations of `kind`, or shows every
+    /// operation again if only `kind` was already shown.
+    pub fn solo_operation_kind(&mut self, kind: OperationKind) {
+        let solo = OperationFilter::only(&[kind]);
+
+        if self.player.op_filter() == solo {
+            self.set_op_filter(OperationFilter::default());
+        }
+        else {
+            self.set_op_filter(solo);
+        }
     }
 
-    pub fn current_algorithm(&self) -> String {
-        self.current_algorithm.load(Relaxed).to_string()
+    /// Toggles whether operations of `kind` are sonified, without hiding
+    /// them.
+    pub fn toggle_operation_sound(&mut self, kind: OperationKind) {
+        let mut filter = self.player.sound_filter();
+        filter.toggle(kind);
+        self.player.set_sound_filter(filter);
+
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().set_sound_filter(filter);
+        }
+
+        println!(
+            "{} the sound of {kind}",
+            if filter.shows(kind) { "Unmuted" } else { "Muted" }
+        );
     }
 
-    pub fn toggle_audio_processing(&mut self) {
-        // TODO: rather than stopping/starting audio processing, this should
-        // probably just control a volume level and/or prevent voices from being
-        // generated.
+    fn set_op_filter(&mut self, filter: OperationFilter) {
+        self.player.set_op_filter(filter);
 
-        self.audio_playing = !self.audio_playing;
-        if self.audio_playing {
-            println!("Unmuted audio");
-            _ = self.audio_stream.send(Audio::start);
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().set_op_filter(filter);
         }
-        else {
-            _ = self.audio_stream.send(Audio::stop);
-            self.audio_voice_counter.store(0, Relaxed);
-            self.dsp_load.store(0.0, Relaxed);
-            println!("Muted audio");
+    }
+
+    /// Sets the scale which notes are quantized to, for both players in race
+    /// mode.
+    fn set_tuning(&mut self, tuning: Tuning) {
+        self.player.set_tuning(tuning);
+
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().set_tuning(tuning);
         }
     }
 
-    pub fn shuffle_and_sort(&mut self) {
-        self.shuffle();
-        self.sort_after_shuffle = true;
+    /// Sets how notes are placed in the stereo field, for both players in
+    /// race mode.
+    fn set_panning(&mut self, panning: Panning) {
+        self.player.set_panning(panning);
+
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().set_panning(panning);
+        }
     }
-}
 
-/// The callback for key-down presses.
-pub fn key_pressed(app: &App, model: &mut Model, key: Key) {
-    match key {
-        // "play/pause"
-        Key::Space => {
-            if model.is_playing() {
-                model.pause();
-            }
-            else {
-                model.play();
-            }
+    // *** *** *** //
+
+    /// Updates the app state.
+    pub fn update(&mut self, app: &App) {
+        // while exporting, every frame plays back the same amount, however
+        // long it actually took.
+        self.update_data.delta_time = if self.frame_export.is_some() {
+            (EXPORT_FRAME_RATE as f32).recip()
         }
-        // "stop"
-        Key::Back | Key::Delete => model.stop(),
-        // "recompute"
-        Key::R => model.compute(),
-        // "shuffle"
-        Key::S => model.shuffle(),
-        Key::Return => {
-            if app.keys.mods.shift() {
-                model.previous_algorithm();
+        else {
+            self.update_data.last_frame.elapsed().as_secs_f32()
+        };
+
+        self.handle_messages();
+        self.handle_remote_commands();
+        self.update_audio_stream();
+
+        if !self.is_computing() && !self.is_playing() {
+            self.is_shuffling = false;
+
+            if let Some(action) = self.action_queue.pop_front() {
+                self.perform(action);
             }
-            else {
-                model.next_algorithm();
-            }
-        }
-        // increase res
-        Key::Plus | Key::Equals => model.increase_resolution(),
-        // decrease res
-        Key::Underline | Key::Minus => model.decrease_resolution(),
-        // increase speed
-        Key::Period => model.increase_speed(),
-        // decrease speed
-        Key::Comma => model.decrease_speed(),
-        // "force-sort"
-        Key::F => model.force_sort(),
-        Key::M => model.toggle_audio_processing(),
-        Key::N => {
-            if app.keys.mods.shift() {
-                model.previous_algorithm();
+        }
+
+        self.update_tour();
+
+        // the panels are reflowed whenever the window is resized, or race
+        // mode is toggled.
+        let layout =
+            WindowLayout::compute(app.window_rect(), self.race.is_some());
+        if layout != self.layout {
+            self.layout = layout;
+            self.apply_layout();
+        }
+
+        if self.editing {
+            self.update_editing(app);
+        }
+
+        self.update_region_selection(app);
+
+        if std::mem::take(&mut self.screenshot_requested) {
+            self.save_screenshot(app);
+        }
+
+        self.update_seeking(app);
+
+        let player = &mut self.player;
+        player.update(app, self.update_data);
+
+        // a capture which doesn't match the wheel is stale, so it is dropped
+        // rather than displayed.
+        if player.copy_arr_to(self.color_wheel.arr_mut()).is_err() {
+            player.clear_capture();
+            player.clear_ops();
+        }
+
+        let target = self.show_target.then(|| player.target_arr()).flatten();
+        self.color_wheel.set_target(target);
+        self.color_wheel.set_aux(player.aux_arr());
+
+        let sorted_region = self
+            .show_sorted_region
+            .then(|| player.target_arr())
+            .flatten()
+            .filter(|t| t.len() == self.color_wheel.resolution())
+            .map(|t| self.color_wheel.sorted_extent(t));
+        self.color_wheel.set_sorted_region(sorted_region);
+
+        let pointers = if self.show_pointers {
+            player.pointers()
+        }
+        else {
+            Pointers::default()
+        };
+        self.color_wheel.set_pointers(pointers);
+
+        // the write trail and heat map accumulate as playback goes forward,
+        // so they start again whenever playback goes backward (along with
+        // any overlays which are still fading out).
+        let progress = player.progress();
+        if progress.unwrap_or(0.0) < self.last_progress.unwrap_or(0.0) {
+            self.color_wheel.clear_write_trail();
+            self.color_wheel.clear_heat_map();
+            self.color_wheel.clear_overlay();
+        }
+        self.last_progress = progress;
+
+        self.color_wheel.set_overlay_ops(player.ops_last_frame());
+        self.color_wheel.update(app, self.update_data);
+
+        match self.view {
+            View::Wheel => {}
+            View::Scatter => self.scatter.sync(&self.color_wheel),
+            View::Spiral => self.spiral.sync(&self.color_wheel),
+            View::Rings => self.rings.sync(&self.color_wheel),
+        }
+
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().set_speed(player.speed());
+            race.update(
+                app,
+                self.update_data,
+                &self.color_wheel,
+                player.ops_last_frame(),
+                self.show_target,
+                self.view,
+            );
+        }
+
+        self.minimap.set_density(player.op_density());
+        self.minimap.set_op_mix(player.op_mix());
+        self.minimap.set_progress(player.progress());
+        self.minimap.set_loop_points(player.loop_points());
+        self.minimap.set_markers(player.markers());
+        self.minimap.update(app, self.update_data);
+
+        self.stats_graph.record(player.progress(), &player.ops_last_frame());
+
+        if let Some(osc) = &self.osc {
+            osc.send_frame(player.progress(), &player.ops_last_frame());
+        }
+
+        self.ui.update_text(UiData {
+            algorithm: self.current_algorithm.load(Relaxed),
+            distribution: self.distribution,
+            overlay_intensity: self.overlay_style.intensity,
+            palette: if self.colorblind {
+                "colorblind-safe"
             }
             else {
-                model.next_algorithm();
-            }
-            model.shuffle_and_sort();
-        }
-        _ => {}
+                self.palettes[self.palette].name()
+            },
+            uniform_shuffle: self.uniform_shuffle,
+            shuffle_intensity: self.shuffle_intensity.load(Relaxed),
+            data: player.sort_data(),
+            resolution: self.resolution,
+            player_time: player.playback_time(),
+            speed: player.speed(),
+            speed_preset: player.speed_preset(),
+            curve: player.curve(),
+            tuning: player.tuning(),
+            panning: player.panning(),
+            adsr: self.audio_params.adsr.load(Relaxed),
+            tone: self.audio_params.tone.load(Relaxed),
+            delay: self.audio_params.delay.load(Relaxed),
+            reverb: self.audio_params.reverb.load(Relaxed),
+            op_filter: player.op_filter(),
+            sound_filter: player.sound_filter(),
+            progress: player.progress(),
+            time_remaining: player.time_remaining(),
+            loop_points: player.loop_points(),
+            real_time: player.is_real_time(),
+            av_offset_ms: player.av_offset_ms(),
+            num_voices: self.audio_voice_counter.load(Relaxed),
+            polyphony: self.audio_params.polyphony.load(Relaxed),
+            dsp_load: self.dsp_load.load(Relaxed),
+            note_thinning: self.audio_params.load.thinning.load(Relaxed),
+            gain_reduction_db: self
+                .audio_params
+                .gain_reduction_db
+                .load(Relaxed),
+            audio_lost: self.audio_lost.is_some(),
+            sorted: player.is_sorted(),
+            computing: self.is_computing(),
+            pending_resolution: match self.state {
+                SortState::ResizePending(res) => Some(res),
+                _ => None,
+            },
+            shuffling: self.is_shuffling,
+            editing: self.editing,
+            region: self.region.as_ref().map(|r| (r.start, r.end)),
+            history_len: self.player.history().count(),
+            playlist_len: self.playlist.algorithms().len(),
+            tour: self.tour.map(|tour| {
+                tour.idle_since.map(|t| {
+                    (tour.dwell - t.elapsed().as_secs_f32()).max(0.0)
+                })
+            }),
+            race: self.race.as_ref().map(|race| {
+                (race.algorithm(), race.player().sort_data())
+            }),
+            recorded_actions: self
+                .recording_macro
+                .then_some(self.macro_actions.len()),
+        });
+
+        if self.frame_export.is_some() {
+            self.update_frame_export(app);
+        }
+
+        self.update_data.last_frame = Instant::now();
+    }
+
+    /// Rebuilds the audio stream on the default output device if it has died
+    /// (e.g. because its device was disconnected), retrying every
+    /// [`AUDIO_RETRY_INTERVAL`] seconds until it succeeds.
+    fn update_audio_stream(&mut self) {
+        // the clock keeps running while muted, so a stalled clock means that
+        // the callback has stopped.
+        if self.audio_stream.is_some()
+            && self.audio_clock.since_callback() < AUDIO_TIMEOUT
+        {
+            return;
+        }
+
+        if self.audio_lost.is_some_and(|t| {
+            t.elapsed().as_secs_f32() < AUDIO_RETRY_INTERVAL
+        }) {
+            return;
+        }
+
+        if self.audio_stream.is_some() {
+            eprintln!("Lost the audio stream, reconnecting...");
+            self.audio_voice_counter.store(0, Relaxed);
+            self.dsp_load.store(0.0, Relaxed);
+            self.audio_params.gain_reduction_db.store(0.0, Relaxed);
+        }
+
+        // the dead stream is dropped first, as it may still hold the device.
+        self.audio_stream = None;
+        // any notes sent while the stream was down are stale by now.
+        while self.note_rx.try_recv().is_ok() {}
+
+        match start_audio(
+            &self.note_rx,
+            &self.audio_voice_counter,
+            &self.audio_params,
+            &self.audio_clock,
+            &self.dsp_load,
+        ) {
+            Ok(stream) => {
+                if !self.audio_playing {
+                    _ = stream.send(Audio::stop);
+                }
+
+                self.audio_stream = Some(stream);
+                self.audio_lost = None;
+                println!("Reconnected the audio stream");
+            }
+            Err(e) => {
+                // the error is only reported on the first attempt.
+                if self.audio_lost.is_none() {
+                    eprintln!("{e}");
+                }
+
+                self.audio_lost = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Sets the values of the slices under the mouse while it is dragged
+    /// across the wheel. The distance from the center of the wheel sets the
+    /// value, and any slices skipped between frames are interpolated.
+    fn update_editing(&mut self, app: &App) {
+        let Some((slice, Some(height))) =
+            self.element_at(app.mouse.position()).filter(|_| {
+                app.mouse.buttons.left().is_down() && !self.is_computing()
+            })
+        else {
+            self.last_edit = None;
+            return;
+        };
+
+        let res = self.resolution;
+        let value = ((height * res as f32) as usize).min(res - 1);
+
+        if self.last_edit == Some((slice, value)) {
+            return;
+        }
+
+        let (prev_slice, prev_value) = self.last_edit.unwrap_or((slice, value));
+        let arr = self.color_wheel.arr_mut();
+
+        // fill in any slices between the last edit and this one, going the
+        // shorter way around the wheel (the scatter plot doesn't wrap around).
+        let forward = (slice + res - prev_slice) % res;
+        let (steps, dir) = if self.view == View::Scatter {
+            let dir = if slice > prev_slice { 1 } else { -1 };
+            (slice.abs_diff(prev_slice), dir)
+        }
+        else if forward <= res / 2 {
+            (forward, 1)
+        }
+        else {
+            (res - forward, -1)
+        };
+
+        for step in 1..steps {
+            let idx = (prev_slice as isize + step as isize * dir)
+                .rem_euclid(res as isize) as usize;
+            let t = step as f32 / steps as f32;
+            let delta = value as f32 - prev_value as f32;
+
+            arr[idx] = t.mul_add(delta, prev_value as f32).round() as usize;
+        }
+
+        arr[slice] = value;
+        self.last_edit = Some((slice, value));
+
+        // the edited wheel becomes the initial state of the next sort.
+        self.end_race();
+        let mut sort_arr = self.sort_arr.lock();
+        sort_arr.prepare_for_sort_with(
+            self.color_wheel.arr_mut(),
+            self.current_algorithm.load(Relaxed),
+        );
+
+        self.player.set_capture(sort_arr.dump_capture());
+        self.player.clear_ops();
+    }
+
+    /// Starts the next run of the tour once the model has been idle for the
+    /// tour's dwell time.
+    fn update_tour(&mut self) {
+        let busy = self.is_computing()
+            || self.is_playing()
+            || !self.action_queue.is_empty();
+
+        let Some(tour) = self.tour.as_mut() else {
+            return;
+        };
+
+        if busy {
+            tour.idle_since = None;
+            return;
+        }
+
+        let idle_since = *tour.idle_since.get_or_insert_with(Instant::now);
+
+        if idle_since.elapsed().as_secs_f32() >= tour.dwell {
+            tour.idle_since = None;
+            self.queue_action(Action::ShuffleAndSortNext);
+        }
+    }
+
+    /// Toggles tour mode, in which every algorithm is shuffled, sorted and
+    /// played in turn, waiting for [`Args::tour_dwell`] seconds between each.
+    /// The tour starts with the current algorithm.
+    pub fn toggle_tour(&mut self) {
+        if self.tour.take().is_some() {
+            println!("Disabled tour mode");
+            return;
+        }
+
+        self.tour = Some(Tour {
+            dwell: cli::args().tour_dwell,
+            idle_since: None,
+        });
+
+        self.queue_action(Action::Shuffle);
+        self.queue_action(Action::Compute);
+
+        println!("Enabled tour mode");
+    }
+
+    /// Adds the current algorithm to the end of the playlist.
+    pub fn add_to_playlist(&mut self) {
+        self.playlist.push(self.current_algorithm.load(Relaxed));
+        println!("Playlist: {}", self.playlist);
+    }
+
+    /// Removes the last algorithm from the playlist.
+    pub fn remove_from_playlist(&mut self) {
+        if self.playlist.pop().is_some() {
+            println!("Playlist: {}", self.playlist);
+        }
+    }
+
+    /// Queues a shuffle, sort and playback of each algorithm in the playlist,
+    /// in order. The playlist is kept, so it can be played again.
+    pub fn play_playlist(&mut self) {
+        if self.playlist.is_empty() {
+            println!("The playlist is empty");
+            return;
+        }
+
+        for &algorithm in self.playlist.algorithms() {
+            self.action_queue.extend([
+                Action::SelectAlgorithm(algorithm),
+                Action::Shuffle,
+                Action::Compute,
+            ]);
+        }
+    }
+
+    /// Seeks through the current capture while the minimap is clicked or
+    /// dragged across.
+    fn update_seeking(&mut self, app: &App) {
+        if !app.mouse.buttons.left().is_down() {
+            return;
+        }
+
+        if let Some(progress) = self.minimap.progress_at(app.mouse.position()) {
+            self.player.seek(progress);
+            self.sync_race();
+        }
+    }
+
+    /// Selects a region of the wheel while the mouse is dragged across it with
+    /// the right button held.
+    fn update_region_selection(&mut self, app: &App) {
+        if !app.mouse.buttons.right().is_down() {
+            self.region_anchor = None;
+            return;
+        }
+
+        let Some((slice, _)) = self.element_at(app.mouse.position()) else {
+            return;
+        };
+
+        let anchor = *self.region_anchor.get_or_insert(slice);
+        let region = anchor.min(slice)..anchor.max(slice) + 1;
+
+        // regions which are too small to sort are ignored.
+        self.region = (region.len() >= MIN_RESOLUTION).then_some(region);
+        self.color_wheel.set_region(self.region.clone());
+    }
+
+    /// Returns the element under `pos` in the window in the current view,
+    /// along with the value at that point as a proportion of the largest
+    /// value — the distance from the center of the wheel or rings, or the
+    /// height in the scatter plot. The spiral doesn't map positions to
+    /// values, so the value is `None` in the spiral view.
+    fn element_at(&self, pos: Vec2) -> Option<(usize, Option<f32>)> {
+        match self.view {
+            View::Wheel => {
+                let pos = self.color_wheel.to_local(pos);
+                let slice = self.color_wheel.slice_at(pos)?;
+
+                Some((slice, Some(pos.length() / CIRCLE_RADIUS)))
+            }
+            View::Scatter => self
+                .scatter
+                .element_at(pos)
+                .map(|(idx, height)| (idx, Some(height))),
+            View::Spiral => self.spiral.element_at(pos).map(|idx| (idx, None)),
+            View::Rings => self
+                .rings
+                .element_at(pos)
+                .map(|(idx, radius)| (idx, Some(radius))),
+        }
+    }
+
+    /// Cycles to the next way of drawing the array.
+    pub fn next_view(&mut self) {
+        self.view.cycle_next();
+    }
+
+    /// Cycles to the previous way of drawing the array.
+    pub fn previous_view(&mut self) {
+        self.view.cycle_prev();
+    }
+
+    /// Toggles the target ring, which shows where each element will end up.
+    pub fn toggle_target(&mut self) {
+        self.show_target = !self.show_target;
+    }
+
+    /// Toggles the write trail, which marks slices that have been written to
+    /// until their elements are next moved.
+    pub fn toggle_write_trail(&mut self) {
+        self.write_trail = !self.write_trail;
+        self.color_wheel.set_write_trail(self.write_trail);
+    }
+
+    /// Toggles the heat map, which shows how often each slice has been
+    /// accessed so far.
+    pub fn toggle_heat_map(&mut self) {
+        self.heat_map = !self.heat_map;
+        self.color_wheel.set_heat_map(self.heat_map);
+    }
+
+    /// Toggles the sorted region highlight, which makes the elements at
+    /// either end of the wheel which are in their final positions stand out.
+    pub fn toggle_sorted_region(&mut self) {
+        self.show_sorted_region = !self.show_sorted_region;
+    }
+
+    /// Toggles the pointers around the wheel, which show the pivots, bounds
+    /// and heap roots which the algorithm is working with.
+    pub fn toggle_pointers(&mut self) {
+        self.show_pointers = !self.show_pointers;
+    }
+
+    /// Toggles the outlines between the slices of the wheel.
+    pub fn toggle_outlines(&mut self) {
+        self.outlines = !self.outlines;
+        self.color_wheel.set_outlines(self.outlines);
+    }
+
+    /// Toggles swap animation, where swapped slices cross-fade to their new
+    /// colors.
+    pub fn toggle_swap_animation(&mut self) {
+        self.swap_animation = !self.swap_animation;
+        self.color_wheel.set_swap_animation(self.swap_animation);
+    }
+
+    /// Clears the selected region, so that algorithms are run on the whole
+    /// wheel.
+    pub fn clear_region(&mut self) {
+        self.region = None;
+        self.color_wheel.set_region(None);
+    }
+
+    /// Toggles mouse editing of the wheel.
+    pub fn toggle_editing(&mut self) {
+        self.editing = !self.editing;
+        self.last_edit = None;
+
+        println!("{} edit mode", if self.editing { "Entered" } else { "Exited" });
+    }
+
+    /// Performs any commands received from an external controller — see
+    /// [`RemoteCommand::parse()`].
+    fn handle_remote_commands(&mut self) {
+        let Some(osc_in) = self.osc_in.as_mut() else {
+            return;
+        };
+
+        for (address, args) in osc_in.receive() {
+            match RemoteCommand::parse(&address, &args) {
+                Ok(RemoteCommand::Action(action)) => self.handle_action(action),
+                Ok(RemoteCommand::Play) => self.play(),
+                Ok(RemoteCommand::Pause) => self.pause(),
+                Ok(RemoteCommand::SetSpeed(speed)) => {
+                    self.player.set_speed(speed);
+                }
+                Err(e) => eprintln!("Ignored OSC message: {e}"),
+            }
+        }
+    }
+
+    /// Drains any messages sent to the model from other threads.
+    fn handle_messages(&mut self) {
+        while let Ok(msg) = self.message_ch.1.try_recv() {
+            match msg {
+                ModelMessage::ComputeFinished(token) => {
+                    if let Some(prev) = self.previous_algorithm.take() {
+                        self.current_algorithm.store(prev, Relaxed);
+                    }
+
+                    // the token may already belong to a newer computation.
+                    if self
+                        .cancel_token
+                        .as_ref()
+                        .is_some_and(|current| current.is_same(&token))
+                    {
+                        self.cancel_token = None;
+                    }
+
+                    // both sides of a race start together once they have
+                    // both been computed.
+                    if let Some(race) =
+                        self.race.as_mut().filter(|r| r.is_computing())
+                    {
+                        race.finish_computing(&mut self.player);
+                        race.player_mut().play();
+                        self.player.play();
+                    }
+
+                    if let Some(res) = self.state.finish_computing() {
+                        // the resolution was validated when it was requested.
+                        _ = self.set_resolution(res);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws the background of the window, which covers `rect`.
+    pub fn draw_background(&self, draw: &Draw, rect: Rect) {
+        self.theme.draw_background(draw, rect);
+    }
+
+    /// Renders any visuals which are drawn straight to `frame`, beneath
+    /// everything drawn with [`Self::draw()`].
+    pub fn render(&self, frame: &Frame) {
+        if self.view == View::Wheel {
+            self.color_wheel.render(frame);
+        }
+
+        if let Some(race) = self.race.as_ref() {
+            race.render(frame, self.view);
+        }
+    }
+
+    /// Draws the app visuals to the provided `Draw` instance.
+    pub fn draw(&self, draw: &Draw) {
+        match self.view {
+            View::Wheel => self.color_wheel.draw(draw, self.update_data),
+            View::Scatter => self.scatter.draw(draw, self.update_data),
+            View::Spiral => self.spiral.draw(draw, self.update_data),
+            View::Rings => self.rings.draw(draw, self.update_data),
+        }
+
+        if let Some(race) = self.race.as_ref() {
+            race.draw(draw, self.update_data, self.view);
+        }
+
+        self.minimap.draw(draw, self.update_data);
+        self.stats_graph.draw(draw, self.update_data);
+        self.ui.draw(draw);
+    }
+
+    // *** *** *** //
+
+    /// Saves the current capture to [`CAPTURE_PATH`].
+    pub fn save_capture(&self) {
+        let Some(capture) = self.player.capture() else {
+            println!("No capture to save");
+            return;
+        };
+
+        if !capture.is_complete() {
+            println!("Cannot save a capture while computing");
+            return;
+        }
+
+        match std::fs::write(CAPTURE_PATH, capture.serialize()) {
+            Ok(()) => println!("Saved capture to {CAPTURE_PATH}"),
+            Err(e) => println!("Failed to save capture: {e}"),
+        }
+    }
+
+    /// Exports the current capture to `capture.json` or `capture.csv`,
+    /// depending on `format`.
+    pub fn export_capture(&self, format: ExportFormat) {
+        let Some(capture) = self.player.capture() else {
+            println!("No capture to export");
+            return;
+        };
+
+        if !capture.is_complete() {
+            println!("Cannot export a capture while computing");
+            return;
+        }
+
+        let path = format!("capture.{}", format.extension());
+        let result = std::fs::File::create(&path).and_then(|file| {
+            let mut w = std::io::BufWriter::new(file);
+            capture.export(format, &mut w)?;
+            w.flush()
+        });
+
+        match result {
+            Ok(()) => println!("Exported capture to {path}"),
+            Err(e) => println!("Failed to export capture: {e}"),
+        }
+    }
+
+    /// Saves the next frame to a PNG in the working directory, named after
+    /// the current time.
+    fn save_screenshot(&mut self, app: &App) {
+        let Some(window) = app.window(self.window_id) else {
+            return;
+        };
+
+        let path = format!("screenshot-{}.png", utc_timestamp());
+
+        // the frame is written to the file in the background once drawn.
+        window.capture_frame(&path);
+
+        println!("Saved screenshot to {path}");
+        self.ui.notify(format!("Saved screenshot to {path}"));
+    }
+
+    /// Starts exporting the current capture as a sequence of PNGs along with
+    /// its audio, or stops an export which is in progress.
+    ///
+    /// The capture is played from the start at [`EXPORT_FRAME_RATE`], where
+    /// every frame plays back exactly one frame's worth of the capture
+    /// (however long it takes to draw). Rather than being played, the notes
+    /// of each frame are rendered by an [`OfflineAudio`] engine on the same
+    /// clock, so the audio matches the frames exactly. The frames and audio
+    /// are saved to a new directory in the working directory, named after
+    /// the current time.
+    pub fn toggle_frame_export(&mut self) {
+        if self.frame_export.is_some() {
+            self.pause();
+            self.finish_frame_export();
+            return;
+        }
+
+        let Some(capture) = self.player.capture() else {
+            println!("No capture to export");
+            return;
+        };
+
+        if !capture.is_complete() || self.is_computing() {
+            println!("Cannot export frames while computing");
+            return;
+        }
+
+        let dir = format!("frames-{}", utc_timestamp());
+
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            println!("Failed to create {dir}: {e}");
+            return;
+        }
+
+        // a loop would never finish playing.
+        self.player.clear_loop();
+        self.stop();
+        self.set_offline(true);
+        self.play();
+
+        let samples_per_frame = (SAMPLE_RATE / EXPORT_FRAME_RATE) as u64;
+        self.frame_export = Some(FrameExport {
+            dir,
+            frames: 0,
+            audio: OfflineAudio::new(samples_per_frame, &self.audio_params),
+        });
+    }
+
+    /// Saves the next frame of the export and renders its audio, and
+    /// finishes the export once playback has stopped.
+    fn update_frame_export(&mut self, app: &App) {
+        let mut notes = self.player.take_notes();
+
+        if let Some(race) = self.race.as_mut() {
+            notes.extend(race.player_mut().take_notes());
+        }
+
+        let Some(export) = self.frame_export.as_mut() else {
+            return;
+        };
+
+        if let Some(window) = app.window(self.window_id) {
+            window.capture_frame(format!(
+                "{}/frame-{:05}.png",
+                export.dir, export.frames
+            ));
+            export.frames += 1;
+        }
+
+        export.audio.advance(notes);
+
+        if !self.is_playing() {
+            self.finish_frame_export();
+        }
+    }
+
+    /// Writes the audio of the export, and returns to normal playback.
+    fn finish_frame_export(&mut self) {
+        let Some(export) = self.frame_export.take() else {
+            return;
+        };
+
+        self.set_offline(false);
+
+        let path = format!("{}/audio.wav", export.dir);
+        let message = match export.audio.finish(&path) {
+            Ok(()) => {
+                format!("Exported {} frames to {}", export.frames, export.dir)
+            }
+            Err(e) => format!("Failed to write {path}: {e}"),
+        };
+
+        println!("{message}");
+        self.ui.notify(message);
+    }
+
+    /// Sets whether both players step playback at [`EXPORT_FRAME_RATE`] and
+    /// collect their notes for rendering offline, rather than following the
+    /// audio clock — see [`Player::set_fixed_delta()`].
+    fn set_offline(&mut self, enabled: bool) {
+        let delta_time =
+            enabled.then(|| (EXPORT_FRAME_RATE as f32).recip());
+
+        self.player.set_fixed_delta(delta_time);
+        self.player.set_offline_notes(enabled);
+
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().set_fixed_delta(delta_time);
+            race.player_mut().set_offline_notes(enabled);
+        }
+    }
+
+    /// Loads the capture saved to [`CAPTURE_PATH`], resizing the wheel to
+    /// match it if needed. The capture is loaded at the start of its playback.
+    pub fn load_capture(&mut self) {
+        if self.is_computing() {
+            println!("Cannot load a capture while computing");
+            return;
+        }
+
+        let bytes = match std::fs::read(CAPTURE_PATH) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                println!("Failed to read {CAPTURE_PATH}: {e}");
+                return;
+            }
+        };
+
+        let capture = match SortCapture::deserialize(&bytes) {
+            Ok((capture, warnings)) => {
+                for warning in warnings {
+                    println!("Warning: {warning}");
+                }
+
+                capture
+            }
+            Err(e) => {
+                println!("Failed to load capture: {e:?}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.use_capture(capture) {
+            println!("Failed to load capture: {e:?}");
+            return;
+        }
+
+        println!("Loaded capture from {CAPTURE_PATH}");
+    }
+
+    /// Replays a capture from the player's history — see
+    /// [`Player::take_from_history()`].
+    pub fn replay_from_history(&mut self, older: bool) {
+        if self.is_computing() {
+            println!("Cannot replay a capture while computing");
+            return;
+        }
+
+        let Some(capture) = self.player.take_from_history(older) else {
+            println!("There are no previous captures to replay");
+            return;
+        };
+
+        let summary = format!(
+            "{} ({} elements, {} operations)",
+            capture.algorithm(),
+            capture.len(),
+            capture.num_operations()
+        );
+
+        match self.use_capture(capture) {
+            Ok(()) => println!("Replaying {summary}"),
+            Err(e) => println!("Failed to replay {summary}: {e:?}"),
+        }
+    }
+
+    /// Sets `capture` as the player's capture at the start of its playback,
+    /// resizing the wheel to match it if needed.
+    fn use_capture(&mut self, capture: SortCapture) -> Result<(), ResizeError> {
+        if capture.len() != self.resolution {
+            self.set_resolution(capture.len())?;
+        }
+
+        let algorithm = capture.algorithm();
+        if !algorithm.is_shuffle() {
+            self.current_algorithm.store(algorithm, Relaxed);
+        }
+
+        self.end_race();
+
+        // the sorting array continues from the end of the capture, as it
+        // would if the capture had just been computed.
+        self.sort_arr
+            .lock()
+            .prepare_for_sort_with(capture.final_arr(), algorithm);
+
+        self.player.set_capture(capture);
+        self.player.clear_ops();
+
+        Ok(())
+    }
+
+    /// Forces the color wheel to be sorted via `std::sort_unstable`. If a
+    /// sort is being computed, this is queued until it has finished.
+    pub fn force_sort(&mut self) {
+        if self.is_computing() {
+            self.queue_action(Action::ForceSort);
+            return;
+        }
+
+        self.end_race();
+        let mut sort_arr = self.sort_arr.lock();
+
+        self.player.clear_capture();
+        sort_arr.prepare_for_sort(self.current_algorithm.load(Relaxed));
+        sort_arr.force_sort();
+        self.player.set_capture(sort_arr.dump_capture());
+    }
+
+    /// Returns `true` if the sorting array is correctly sorted.
+    pub fn is_sorted(&self) -> bool {
+        self.player.is_sorted()
+    }
+
+    /// Computes the sort, which is played back as it is computed. If a sort
+    /// is already being computed, this is queued until it has finished.
+    pub fn compute(&mut self) {
+        if !self.state.start_computing() {
+            self.queue_action(Action::Compute);
+            return;
+        }
+
+        let algorithm = self.current_algorithm.load(Relaxed);
+
+        // prepare the array
+        let mut sort_arr = self.sort_arr.lock();
+        sort_arr.set_record_timestamps(self.real_time);
+        sort_arr.prepare_for_sort(algorithm);
+        let (capture, stream) = sort_arr.start_stream();
+        let cancel_token = CancelToken::new();
+        sort_arr.set_cancel_token(Some(cancel_token.clone()));
+        drop(sort_arr);
+
+        // shuffles aren't raced, so the race wheel mirrors them instead.
+        let race = match self.race.as_mut() {
+            Some(race) if !algorithm.is_shuffle() => {
+                race.start_computing();
+                race.arr().lock().set_cancel_token(Some(cancel_token.clone()));
+                Some((race.arr(), race.algorithm()))
+            }
+            Some(race) => {
+                race.end();
+                None
+            }
+            None => None,
+        };
+
+        self.cancel_token = Some(cancel_token.clone());
+
+        // if the resolution changes while computing, the capture is cleared
+        // once the sort has finished. races are played once both sides have
+        // been computed.
+        self.player.reset_playback_time();
+        self.player.set_stream(capture, stream);
+        if race.is_none() {
+            self.player.play();
+        }
+
+        let arr = Arc::clone(&self.sort_arr);
+        let process = Arc::clone(&self.process);
+        let msg_tx = self.message_ch.0.clone();
+        let region = self.region.clone();
+
+        self.thread_pool.execute(move || {
+            let mut arr = arr.lock();
+
+            // the array may have been resized since the region was selected.
+            // a cancelled sort keeps the operations recorded so far, so that
+            // the wheel is left in the same state as the array.
+            let region = region.clone().filter(|r| r.end <= arr.len());
+            _ = match region.clone() {
+                Some(region) => process.lock().sort_region(&mut arr, region),
+                None => process.lock().sort(&mut arr),
+            };
+
+            // the opponent sorts the same input as the current algorithm.
+            if let Some((race_arr, opponent)) = race.as_ref() {
+                let (mut race_arr, opponent) = (race_arr.lock(), *opponent);
+                race_arr.prepare_for_sort_with(arr.initial_arr(), opponent);
+
+                let mut process = process.lock();
+                _ = match region {
+                    Some(region) => {
+                        process.sort_region_with(&mut race_arr, region, opponent)
+                    }
+                    None => process.sort_with(&mut race_arr, opponent),
+                };
+
+                race_arr.set_cancel_token(None);
+            }
+
+            if arr.is_truncated() {
+                println!(
+                    "The sort reached the operation limit, so its capture \
+                     was truncated"
+                );
+            }
+
+            // the array is also used on the main thread, which must never be
+            // interrupted.
+            arr.set_cancel_token(None);
+            arr.finish_stream();
+
+            drop(arr);
+
+            let finished = ModelMessage::ComputeFinished(cancel_token.clone());
+            _ = msg_tx.send(finished);
+        });
+    }
+
+    /// Cancels the sort being computed, if there is one. Playback continues
+    /// up to the point where the sort was cancelled.
+    pub fn cancel_compute(&mut self) {
+        if let Some(token) = self.cancel_token.as_ref() {
+            token.cancel();
+            println!("Cancelled computation");
+        }
+    }
+
+    /// Starts a shuffle, which arranges the wheel into the current
+    /// distribution. If a sort is being computed, this is queued until it has
+    /// finished.
+    pub fn shuffle(&mut self) {
+        if self.is_computing() {
+            self.queue_action(Action::Shuffle);
+            return;
+        }
+
+        if self.distribution != Distribution::Shuffled {
+            self.arrange(self.distribution);
+            return;
+        }
+
+        let shuffle = if self.uniform_shuffle {
+            SortingAlgorithm::FisherYates
+        }
+        else {
+            SortingAlgorithm::Shuffle
+        };
+
+        self.previous_algorithm =
+            Some(self.current_algorithm.swap(shuffle, Relaxed));
+
+        self.is_shuffling = true;
+
+        self.compute();
+    }
+
+    /// Arranges the wheel (or only the selected region, if there is one) into
+    /// `distribution`, writing each element in turn so that the arrangement
+    /// is played back like a shuffle.
+    ///
+    /// Within a region, the distribution is made from the values which are
+    /// already in it, so the rest of the wheel is left untouched.
+    fn arrange(&mut self, distribution: Distribution) {
+        self.end_race();
+        let mut sort_arr = self.sort_arr.lock();
+
+        self.player.clear_capture();
+        sort_arr.prepare_for_sort(SortingAlgorithm::Shuffle);
+
+        let range = self.region.clone().unwrap_or(0..self.resolution);
+        let mut values = sort_arr.initial_arr()[range.clone()].to_vec();
+        values.sort_unstable();
+
+        // the distribution's values are ranks amongst the region's values.
+        let ranks = distribution.generate(range.len());
+
+        for (i, rank) in ranks.into_iter().enumerate() {
+            sort_arr.write(range.start + i, values[rank]);
+        }
+
+        self.player.set_capture(sort_arr.dump_capture());
+        self.player.play();
+        self.is_shuffling = true;
+    }
+
+    pub fn increase_speed(&mut self) {
+        let speed = self.player.speed();
+        self.player.set_speed((speed + 0.02).min(5.0));
+    }
+
+    pub fn decrease_speed(&mut self) {
+        let speed = self.player.speed();
+        self.player.set_speed((speed - 0.02).max(-5.0));
+    }
+
+    /// Sets the playback speed to the preset at `idx` in
+    /// [`Player::SPEED_PRESETS`].
+    pub fn set_speed_preset(&mut self, idx: usize) {
+        if let Some(&speed) = Player::SPEED_PRESETS.get(idx) {
+            self.player.set_speed(speed);
+        }
+    }
+
+    pub fn reset_speed(&mut self) {
+        self.player.reset_speed();
+    }
+
+    pub fn increase_av_offset(&mut self) {
+        let offset = self.player.av_offset_ms();
+        self.player.set_av_offset_ms(offset + 5.0);
+    }
+
+    pub fn decrease_av_offset(&mut self) {
+        let offset = self.player.av_offset_ms();
+        self.player.set_av_offset_ms(offset - 5.0);
+    }
+
+    /// Estimates and applies the audio/visual offset — see
+    /// [`Player::estimate_av_offset()`].
+    pub fn estimate_av_offset(&mut self) {
+        let offset =
+            self.player.estimate_av_offset(self.update_data.delta_time);
+
+        println!("Estimated A/V offset as {offset:.1} ms");
+    }
+
+    /// Toggles real-time playback. Only sorts computed while it is enabled are
+    /// timed, so this takes effect from the next computed sort.
+    pub fn toggle_real_time(&mut self) {
+        self.real_time = !self.real_time;
+        self.player.set_real_time(self.real_time);
+
+        println!(
+            "{} real-time playback",
+            if self.real_time { "Enabled" } else { "Disabled" }
+        );
+    }
+
+    pub fn play(&mut self) {
+        match self.racing_player() {
+            // a race restarts once both sides have finished.
+            Some(race_player) => {
+                if self.player.at_end() && race_player.at_end() {
+                    self.stop();
+                }
+
+                self.player.play();
+                self.race.as_mut().unwrap().player_mut().play();
+            }
+            None => {
+                if self.player.at_end() {
+                    self.player.stop();
+                }
+
+                self.player.play();
+            }
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.player.pause();
+
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().pause();
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.player.stop();
+
+        if let Some(race) = self.race.as_mut() {
+            race.player_mut().stop();
+        }
+    }
+
+    /// Moves the playback position by `frames` frames of playback at the
+    /// current speed.
+    pub fn scrub(&mut self, frames: i32) {
+        self.player
+            .seek_relative(frames as f32, self.update_data.delta_time);
+        self.sync_race();
+    }
+
+    /// The player of the opponent in race mode, if a race has been computed.
+    fn racing_player(&self) -> Option<&Player> {
+        self.race.as_ref().map(Race::player).filter(|p| p.has_capture())
+    }
+
+    /// Moves the opponent's playback position to match the main player. Both
+    /// play the same number of operations per second, so they are kept at the
+    /// same operation.
+    fn sync_race(&mut self) {
+        let Some(position) = self.player.capture().map(SortCapture::position)
+        else {
+            return;
+        };
+
+        if let Some(race) = self.race.as_mut() {
+            let player = race.player_mut();
+            let len = player.capture().map_or(0, SortCapture::num_operations);
+
+            player.seek_to_position(position.min(len));
+        }
+    }
+
+    /// Toggles race mode, in which the current algorithm is raced against an
+    /// opponent on a second wheel. The current algorithm becomes the
+    /// opponent, and the algorithm to race against it can then be selected
+    /// as usual.
+    pub fn toggle_race(&mut self) {
+        if self.is_computing() {
+            println!("Cannot toggle race mode while computing");
+            return;
+        }
+
+        if self.race.take().is_some() {
+            self.player.reset_playback_time();
+            println!("Disabled race mode");
+            return;
+        }
+
+        self.start_race(self.current_algorithm.load(Relaxed));
+    }
+
+    /// Enables race mode against `opponent`.
+    fn start_race(&mut self, opponent: SortingAlgorithm) {
+        // the layout is reflowed for the second wheel in the next update.
+        self.race =
+            Some(Race::new(opponent, &self.player, &self.sort_arr.lock()));
+        self.apply_palette();
+        self.apply_overlay_style();
+
+        println!(
+            "Enabled race mode against {opponent} — select an algorithm and \
+             press R to race"
+        );
+    }
+
+    /// Ends the current race (if there is one) so that the race wheel mirrors
+    /// the main wheel, e.g. when the main wheel is shuffled.
+    fn end_race(&mut self) {
+        if let Some(race) = self.race.as_mut() {
+            race.end();
+            self.player.reset_playback_time();
+        }
+    }
+
+    /// Marks a point of the A–B loop at the current playback position — see
+    /// [`Player::mark_loop_point()`].
+    pub fn mark_loop_point(&mut self) {
+        if !self.player.has_capture() {
+            println!("Cannot loop without a capture");
+            return;
+        }
+
+        self.player.mark_loop_point();
+
+        match self.player.loop_points() {
+            Some((start, None)) => {
+                println!("Marked loop start at {:.1}%", start * 100.0);
+            }
+            Some((start, Some(end))) => println!(
+                "Looping between {:.1}% and {:.1}%",
+                start * 100.0,
+                end * 100.0
+            ),
+            None => println!("Cleared loop"),
+        }
+    }
+
+    pub fn clear_loop(&mut self) {
+        self.player.clear_loop();
+        println!("Cleared loop");
+    }
+
+    /// Adds a marker at the current playback position, or removes the marker
+    /// which is already there.
+    pub fn toggle_marker(&mut self) {
+        match self.player.toggle_marker() {
+            Some(true) => println!("Added marker"),
+            Some(false) => println!("Removed marker"),
+            None => println!("Cannot add a marker without a capture"),
+        }
+    }
+
+    /// Removes every marker from the current capture.
+    pub fn clear_markers(&mut self) {
+        self.player.clear_markers();
+        println!("Cleared markers");
+    }
+
+    /// Moves the playback position to the next marker, or the previous one if
+    /// `forward` is `false`.
+    pub fn jump_to_marker(&mut self, forward: bool) {
+        if self.player.jump_to_marker(forward) {
+            self.sync_race();
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.player.is_playing()
+            || self.racing_player().is_some_and(Player::is_playing)
+    }
+
+    pub fn current_algorithm(&self) -> String {
+        self.current_algorithm.load(Relaxed).to_string()
+    }
+
+    pub fn toggle_audio_processing(&mut self) {
+        // TODO: rather than stopping/starting audio processing, this should
+        // probably just control a volume level and/or prevent voices from being
+        // generated.
+
+        self.audio_playing = !self.audio_playing;
+        if self.audio_playing {
+            println!("Unmuted audio");
+            if let Some(stream) = &self.audio_stream {
+                _ = stream.send(Audio::start);
+            }
+        }
+        else {
+            if let Some(stream) = &self.audio_stream {
+                _ = stream.send(Audio::stop);
+            }
+            self.audio_voice_counter.store(0, Relaxed);
+            self.dsp_load.store(0.0, Relaxed);
+            self.audio_params.gain_reduction_db.store(0.0, Relaxed);
+            println!("Muted audio");
+        }
+    }
+
+    pub fn shuffle_and_sort(&mut self) {
+        self.shuffle();
+        self.queue_action(Action::Compute);
+    }
+
+    // *** *** *** //
+
+    /// Handles an action from user input. If a macro is being recorded, then
+    /// the action is also added to the macro.
+    pub fn handle_action(&mut self, action: Action) {
+        if self.recording_macro && !action.is_macro_control() {
+            self.macro_actions.push(action);
+        }
+
+        // stopping or cancelling also cancels anything which is queued.
+        if matches!(action, Action::Stop | Action::CancelCompute) {
+            self.action_queue.clear();
+        }
+
+        match action {
+            Action::ToggleMacroRecording => self.toggle_macro_recording(),
+            Action::PlayMacro => self.play_macro(),
+            _ => self.perform(action),
+        }
+    }
+
+    /// Adds an action to the queue, which will be performed once the model is
+    /// idle (i.e. not computing or playing) and all previously-queued actions
+    /// have been performed.
+    pub fn queue_action(&mut self, action: Action) {
+        self.action_queue.push_back(action);
+    }
+
+    /// Starts recording a new macro, or finishes recording the current one.
+    pub fn toggle_macro_recording(&mut self) {
+        self.recording_macro = !self.recording_macro;
+
+        if self.recording_macro {
+            self.macro_actions.clear();
+            println!("Recording macro");
+        }
+        else {
+            println!(
+                "Recorded macro with {} actions",
+                self.macro_actions.len()
+            );
+        }
+    }
+
+    /// Queues all of the actions in the recorded macro.
+    pub fn play_macro(&mut self) {
+        if self.recording_macro {
+            println!("Cannot play a macro while recording one");
+            return;
+        }
+
+        self.action_queue.extend(self.macro_actions.iter().copied());
+    }
+
+    /// Performs an action immediately.
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::TogglePlayback => {
+                if self.is_playing() {
+                    self.pause();
+                }
+                else {
+                    self.play();
+                }
+            }
+            Action::Stop => self.stop(),
+            Action::Scrub(frames) => self.scrub(frames),
+            Action::MarkLoopPoint => self.mark_loop_point(),
+            Action::ClearLoop => self.clear_loop(),
+            Action::ToggleMarker => self.toggle_marker(),
+            Action::ClearMarkers => self.clear_markers(),
+            Action::NextMarker => self.jump_to_marker(true),
+            Action::PreviousMarker => self.jump_to_marker(false),
+            Action::Compute => self.compute(),
+            Action::CancelCompute => self.cancel_compute(),
+            Action::Shuffle => self.shuffle(),
+            Action::ForceSort => self.force_sort(),
+            Action::NextAlgorithm => self.next_algorithm(),
+            Action::PreviousAlgorithm => self.previous_algorithm(),
+            Action::SelectAlgorithm(algorithm) => {
+                self.current_algorithm.store(algorithm, Relaxed);
+            }
+            Action::ShuffleAndSort => self.shuffle_and_sort(),
+            Action::ShuffleAndSortNext => {
+                self.next_algorithm();
+                self.shuffle_and_sort();
+            }
+            Action::ShuffleAndSortPrevious => {
+                self.previous_algorithm();
+                self.shuffle_and_sort();
+            }
+            Action::ToggleUniformShuffle => self.toggle_uniform_shuffle(),
+            Action::IncreaseShuffleIntensity => {
+                self.increase_shuffle_intensity();
+            }
+            Action::DecreaseShuffleIntensity => {
+                self.decrease_shuffle_intensity();
+            }
+            Action::NextDistribution => self.next_distribution(),
+            Action::PreviousDistribution => self.previous_distribution(),
+            Action::ToggleOperationKind(kind) => {
+                self.toggle_operation_kind(kind);
+            }
+            Action::SoloOperationKind(kind) => self.solo_operation_kind(kind),
+            Action::ToggleOperationSound(kind) => {
+                self.toggle_operation_sound(kind);
+            }
+            Action::NextPlaybackCurve => self.next_playback_curve(),
+            Action::NextPalette => self.next_palette(),
+            Action::ToggleColorblindMode => self.toggle_colorblind_mode(),
+            Action::IncreaseOverlayIntensity => {
+                self.increase_overlay_intensity();
+            }
+            Action::DecreaseOverlayIntensity => {
+                self.decrease_overlay_intensity();
+            }
+            Action::PreviousPlaybackCurve => self.previous_playback_curve(),
+            Action::IncreaseResolution => self.increase_resolution(),
+            Action::DecreaseResolution => self.decrease_resolution(),
+            Action::IncreaseSpeed => self.increase_speed(),
+            Action::DecreaseSpeed => self.decrease_speed(),
+            Action::SpeedPreset(idx) => self.set_speed_preset(idx),
+            Action::ResetSpeed => self.reset_speed(),
+            Action::IncreaseAvOffset => self.increase_av_offset(),
+            Action::DecreaseAvOffset => self.decrease_av_offset(),
+            Action::EstimateAvOffset => self.estimate_av_offset(),
+            Action::ToggleRealTime => self.toggle_real_time(),
+            Action::ToggleAudio => self.toggle_audio_processing(),
+            Action::NextScale => self.next_scale(),
+            Action::NextRootNote => self.next_root_note(),
+            Action::NextPanning => self.next_panning(),
+            Action::NextEnvelope => self.next_envelope(),
+            Action::NextTonePreset => self.next_tone_preset(),
+            Action::NextDelayMode => self.next_delay_mode(),
+            Action::NextReverbMix => self.next_reverb_mix(),
+            Action::NextPolyphonyCap => self.next_polyphony_cap(),
+            Action::NextVoiceSteal => self.next_voice_steal(),
+            Action::ToggleEditing => self.toggle_editing(),
+            Action::ClearRegion => self.clear_region(),
+            Action::ToggleTarget => self.toggle_target(),
+            Action::ToggleWriteTrail => self.toggle_write_trail(),
+            Action::ToggleHeatMap => self.toggle_heat_map(),
+            Action::ToggleSwapAnimation => self.toggle_swap_animation(),
+            Action::ToggleSortedRegion => self.toggle_sorted_region(),
+            Action::TogglePointers => self.toggle_pointers(),
+            Action::ToggleOutlines => self.toggle_outlines(),
+            Action::NextView => self.next_view(),
+            Action::PreviousView => self.previous_view(),
+            Action::SaveCapture => self.save_capture(),
+            Action::LoadCapture => self.load_capture(),
+            Action::ToggleRace => self.toggle_race(),
+            Action::ToggleTour => self.toggle_tour(),
+            Action::AddToPlaylist => self.add_to_playlist(),
+            Action::RemoveFromPlaylist => self.remove_from_playlist(),
+            Action::PlayPlaylist => self.play_playlist(),
+            Action::ReplayOlderCapture => self.replay_from_history(true),
+            Action::ReplayNewerCapture => self.replay_from_history(false),
+            Action::ExportCapture(format) => self.export_capture(format),
+            Action::Screenshot => self.screenshot_requested = true,
+            Action::ToggleFrameExport => self.toggle_frame_export(),
+            Action::ToggleMacroRecording | Action::PlayMacro => {}
+        }
+    }
+}
+
+/// Returns the current UTC time as `YYYY-MM-DD-HHMMSS`.
+fn utc_timestamp() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // converts days since 1970-01-01 to a date in the proleptic Gregorian
+    // calendar, where each era of 400 years starts on the 1st of March.
+    let z = days + 719_468;
+    let (era, day_of_era) = (z / 146_097, z % 146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = era * 400 + year_of_era + (month <= 2) as u64;
+
+    format!(
+        "{year:04}-{month:02}-{day:02}-{:02}{:02}{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// The callback for key-down presses.
+pub fn key_pressed(app: &App, model: &mut Model, key: Key) {
+    if let Some(action) = Action::from_key(key, app.keys.mods.shift()) {
+        model.handle_action(action);
+    }
+}
+
+/// Starts a new audio engine on the default output device, at the device's
+/// sample rate and the buffer size from the command line. The engine receives
+/// note events from `note_rx` and drives `clock`.
+///
+/// # Errors
+///
+/// Returns an error if the audio stream can't be started.
+fn start_audio(
+    note_rx: &Receiver<NoteEvent>,
+    voice_counter: &Arc<AtomicU32>,
+    params: &AudioParams,
+    clock: &AudioClock,
+    dsp_load: &Arc<Atomic<f32>>,
+) -> Result<Stream<Audio>, String> {
+    let config = AudioConfig {
+        sample_rate: audio::device_sample_rate(),
+        buffer_size: cli::args().buffer_size,
+    };
+    if config.sample_rate != SAMPLE_RATE {
+        println!(
+            "Running audio at the device's rate of {} Hz",
+            config.sample_rate
+        );
+    }
+
+    Audio::new(note_rx.clone(), Arc::clone(voice_counter), params, config)
+        .with_clock(clock.clone())
+        .with_dsp_load(Arc::clone(dsp_load))
+        .into_stream()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_state_transitions() {
+        let mut state = SortState::default();
+
+        // resizes are only deferred while computing.
+        assert!(!state.defer_resize(256));
+        assert_eq!(state, SortState::Idle);
+
+        assert!(state.start_computing());
+        assert_eq!(state, SortState::Computing);
+
+        // a second sort can't start until the first has finished, and it
+        // mustn't drop a pending resize.
+        assert!(!state.start_computing());
+        assert!(state.defer_resize(256));
+        assert!(!state.start_computing());
+        assert_eq!(state, SortState::ResizePending(256));
+
+        assert_eq!(state.finish_computing(), Some(256));
+        assert_eq!(state, SortState::Idle);
+
+        assert!(state.start_computing());
+        assert_eq!(state.finish_computing(), None);
+        assert_eq!(state, SortState::Idle);
     }
 }
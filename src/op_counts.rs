@@ -0,0 +1,203 @@
+//! Regression tracking for the number of operations each algorithm performs.
+//!
+//! The operation counts of every sort are recorded for a few fixed array
+//! sizes and seeds in [`FIXTURES_PATH`]. Changes to an algorithm which alter
+//! its behavior (whether intended or not) will almost always change its
+//! counts, so they are checked against the fixtures with `--check-op-counts`.
+//! Intended changes are recorded with `--update-op-counts`.
+
+use super::*;
+use algorithms::Algorithms;
+use std::fmt::{self, Display};
+
+/// The file which the expected operation counts are stored in.
+pub const FIXTURES_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/op_counts.txt");
+
+/// The array sizes and seeds which each algorithm is measured with.
+const CASES: [(usize, u64); 4] = [(8, 1), (64, 1), (64, 2), (256, 1)];
+
+/// The operation counts of an algorithm for one array size and seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpCounts {
+    pub algorithm: SortingAlgorithm,
+    pub size: usize,
+    pub seed: u64,
+    pub data: SortData,
+}
+
+/// An algorithm whose operation counts don't match the fixtures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpCountChange {
+    /// The expected counts, or `None` if there aren't any in the fixtures.
+    pub expected: Option<OpCounts>,
+    pub actual: OpCounts,
+}
+
+impl Display for OpCountChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let OpCounts { algorithm, size, seed, data } = self.actual;
+
+        write!(f, "{} ({size} elements, seed {seed}): ", algorithm.name())?;
+
+        match self.expected {
+            Some(expected) => write!(
+                f,
+                "expected {}, found {}",
+                fmt_data(expected.data),
+                fmt_data(data)
+            ),
+            None => write!(f, "no fixture, found {}", fmt_data(data)),
+        }
+    }
+}
+
+/// Measures the operation counts of every sort for each of the cases.
+///
+/// The input of each case is a full shuffle generated from its seed, which
+/// also seeds the algorithm — so this reseeds the global generator.
+pub fn measure_all() -> Vec<OpCounts> {
+    let mut algorithms = Algorithms::new(&Arc::new(Atomic::new(1.0)));
+    let mut counts = vec![];
+
+    for algorithm in SortingAlgorithm::sorts() {
+        for (size, seed) in CASES {
+            if size > max_size(algorithm) {
+                continue;
+            }
+
+            rng::seed(seed);
+            let input = Distribution::Shuffled.generate(size);
+
+            let mut arr = SortArray::new(size);
+            arr.prepare_for_sort_with(&input, algorithm);
+            algorithms.process(algorithm, &mut arr);
+
+            let data = arr.dump_capture().total_data();
+            counts.push(OpCounts { algorithm, size, seed, data });
+        }
+    }
+
+    counts
+}
+
+/// Compares the current operation counts against the fixtures, and returns
+/// every case which has changed. Cases in the fixtures which are no longer
+/// measured are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the fixtures couldn't be read or parsed.
+pub fn check() -> Result<Vec<OpCountChange>, String> {
+    let fixtures = std::fs::read_to_string(FIXTURES_PATH)
+        .map_err(|e| format!("failed to read {FIXTURES_PATH}: {e}"))?;
+    let expected = parse(&fixtures)?;
+
+    Ok(measure_all()
+        .into_iter()
+        .filter_map(|actual| {
+            let expected = expected.iter().copied().find(|e| {
+                (e.algorithm, e.size, e.seed)
+                    == (actual.algorithm, actual.size, actual.seed)
+            });
+
+            (expected != Some(actual))
+                .then_some(OpCountChange { expected, actual })
+        })
+        .collect())
+}
+
+/// Measures the current operation counts, and writes them to the fixtures.
+///
+/// # Errors
+///
+/// Returns an error if the fixtures couldn't be written.
+pub fn update() -> std::io::Result<()> {
+    let mut s = String::from(
+        "# The number of operations each algorithm performs on a shuffled \
+         array.\n# Update with `cargo run -- --update-op-counts`.\n\
+         # algorithm size seed reads comparisons writes swaps\n",
+    );
+
+    for OpCounts { algorithm, size, seed, data } in measure_all() {
+        let SortData { reads, comparisons, writes, swaps, .. } = data;
+
+        s.push_str(&format!(
+            "{} {size} {seed} {reads} {comparisons} {writes} {swaps}\n",
+            algorithm.name()
+        ));
+    }
+
+    std::fs::write(FIXTURES_PATH, s)
+}
+
+/// Parses the contents of the fixtures file.
+fn parse(fixtures: &str) -> Result<Vec<OpCounts>, String> {
+    fixtures
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            let err = || format!("invalid fixture on line {}: {line}", i + 1);
+            let mut parts = line.split_whitespace();
+
+            let algorithm = parts
+                .next()
+                .and_then(SortingAlgorithm::from_name)
+                .ok_or_else(err)?;
+            let mut num = || {
+                parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)
+            };
+
+            Ok(OpCounts {
+                algorithm,
+                size: num()?,
+                seed: num()? as u64,
+                data: SortData {
+                    reads: num()?,
+                    comparisons: num()?,
+                    writes: num()?,
+                    swaps: num()?,
+                    truncated: false,
+                },
+            })
+        })
+        .collect()
+}
+
+/// The largest size which `algorithm` is measured with, as some algorithms
+/// take far too long with larger arrays.
+const fn max_size(algorithm: SortingAlgorithm) -> usize {
+    match algorithm {
+        SortingAlgorithm::Bogo => 8,
+        // sleep sort takes time proportional to the array size.
+        SortingAlgorithm::Stooge | SortingAlgorithm::Sleep => 64,
+        _ => usize::MAX,
+    }
+}
+
+fn fmt_data(data: SortData) -> String {
+    let SortData { reads, comparisons, writes, swaps, .. } = data;
+
+    format!(
+        "{reads} reads, {comparisons} comparisons, {writes} writes, {swaps} \
+         swaps"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn op_counts_match_fixtures() {
+        let changes = check().unwrap();
+
+        assert!(
+            changes.is_empty(),
+            "operation counts have changed — if this is intended, run with \
+             `--update-op-counts`:\n{}",
+            changes.iter().map(|c| format!("  {c}\n")).collect::<String>()
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! A minimal [OSC](https://opensoundcontrol.stanford.edu/spec-1_0.html)
+//! sender and receiver, which broadcast playback over UDP so that external
+//! tools (e.g. SuperCollider or TouchDesigner) can follow a sort as it plays,
+//! and let an external controller drive the app.
+//!
+//! The following messages are sent:
+//!
+//! - `/sort/progress f`: the playback progress between `0.0` and `1.0`, once
+//!   per frame while a capture is loaded.
+//! - `/sort/op s i i`: each write, read and swap played back, as its kind
+//!   (`write`, `aux_write`, `read` or `swap`) and its two arguments — the
+//!   index and value of writes, the index (and `0`) of reads, and the
+//!   indices of swaps.
+//! - `/sort/op s i i i`: each comparison played back, as its kind
+//!   (`compare`), the indices which were compared, and the result (`1` if
+//!   the comparison was true, and `0` otherwise).
+//! - `/sort/note f f f`: each note sent to the audio engine, as its
+//!   frequency in Hz, amplitude and pan.
+//!
+//! The messages which are received are listed in [`RemoteCommand::parse()`].
+
+use super::*;
+use clap::ValueEnum;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+/// The most operations which are sent each frame, so that fast playback
+/// doesn't flood the network.
+const MAX_OPS_PER_FRAME: usize = 512;
+/// The largest packet which can be received.
+const MAX_PACKET_SIZE: usize = 1 << 12;
+/// The range which speeds received over OSC are clamped to.
+const SPEED_RANGE: std::ops::RangeInclusive<f32> = -5.0..=5.0;
+
+/// An argument of an OSC message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+}
+
+/// Encodes an OSC message to `address` with `args`.
+pub fn encode(address: &str, args: &[OscArg]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(64);
+    push_str(&mut buf, address);
+
+    let tags: String = std::iter::once(',')
+        .chain(args.iter().map(|arg| match arg {
+            OscArg::Int(_) => 'i',
+            OscArg::Float(_) => 'f',
+            OscArg::Str(_) => 's',
+        }))
+        .collect();
+    push_str(&mut buf, &tags);
+
+    for arg in args {
+        match arg {
+            OscArg::Int(i) => buf.extend_from_slice(&i.to_be_bytes()),
+            OscArg::Float(f) => buf.extend_from_slice(&f.to_be_bytes()),
+            OscArg::Str(s) => push_str(&mut buf, s),
+        }
+    }
+
+    buf
+}
+
+/// Appends `s` to `buf` as an OSC string, which is null-terminated and padded
+/// to a multiple of four bytes.
+fn push_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(s.as_bytes());
+    let padding = 4 - s.len() % 4;
+    buf.resize(buf.len() + padding, 0);
+}
+
+/// Decodes an OSC packet, which is either a single message or a bundle of
+/// them, into the address and arguments of each message. Arguments other
+/// than `i`, `f` and `s` aren't supported, so messages with them are
+/// skipped, as are any packets which are malformed.
+pub fn decode(packet: &[u8]) -> Vec<(String, Vec<OscArg>)> {
+    let mut messages = Vec::new();
+    decode_into(packet, &mut messages);
+    messages
+}
+
+fn decode_into(packet: &[u8], messages: &mut Vec<(String, Vec<OscArg>)>) {
+    let mut reader = Reader(packet);
+
+    let Some(address) = reader.string() else {
+        return;
+    };
+
+    if address == "#bundle" {
+        // the time tag is ignored, as everything is handled immediately.
+        if reader.bytes(8).is_none() {
+            return;
+        }
+
+        while let Some(len) = reader.int() {
+            let Some(element) = reader.bytes(len as usize) else {
+                return;
+            };
+            decode_into(element, messages);
+        }
+
+        return;
+    }
+
+    let Some(tags) = reader.string() else {
+        return;
+    };
+    let Some(tags) = tags.strip_prefix(',') else {
+        return;
+    };
+
+    let args: Option<Vec<_>> = tags
+        .chars()
+        .map(|tag| match tag {
+            'i' => reader.int().map(OscArg::Int),
+            'f' => {
+                reader.int().map(|i| OscArg::Float(f32::from_bits(i as u32)))
+            }
+            's' => reader.string().map(OscArg::Str),
+            _ => None,
+        })
+        .collect();
+
+    if let Some(args) = args {
+        messages.push((address, args));
+    }
+}
+
+/// Reads the parts of an OSC packet in order.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    /// Reads the next `len` bytes.
+    fn bytes(&mut self, len: usize) -> Option<&[u8]> {
+        if len > self.0.len() {
+            return None;
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    /// Reads a big-endian 32-bit integer.
+    fn int(&mut self) -> Option<i32> {
+        let bytes = self.bytes(4)?;
+        Some(i32::from_be_bytes(bytes.try_into().ok()?))
+    }
+
+    /// Reads a null-terminated string, along with its padding.
+    fn string(&mut self) -> Option<String> {
+        let len = self.0.iter().position(|&b| b == 0)?;
+        let s = std::str::from_utf8(&self.0[..len]).ok()?.to_owned();
+        self.bytes(len + 4 - len % 4)?;
+        Some(s)
+    }
+}
+
+/// Sends OSC messages over UDP to a single address. Sending never blocks,
+/// and any messages which can't be sent are dropped.
+#[derive(Debug)]
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl OscSender {
+    /// Creates a sender which sends to `target`, e.g. `127.0.0.1:9000`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `target` isn't a valid address, or if a socket
+    /// can't be opened.
+    pub fn new(target: &str) -> Result<Self, String> {
+        let target = target
+            .to_socket_addrs()
+            .map_err(|e| format!("{e}"))?
+            .next()
+            .ok_or_else(|| format!("no address found for \"{target}\""))?;
+
+        let local: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        }
+        else {
+            ([0; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local).map_err(|e| format!("{e}"))?;
+        socket.set_nonblocking(true).map_err(|e| format!("{e}"))?;
+
+        Ok(Self { socket, target })
+    }
+
+    /// The address which messages are sent to.
+    pub const fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Sends a message to `address` with `args`.
+    pub fn send(&self, address: &str, args: &[OscArg]) {
+        _ = self.socket.send_to(&encode(address, args), self.target);
+    }
+
+    /// Sends the playback progress, and up to [`MAX_OPS_PER_FRAME`] of the
+    /// operations played back this frame.
+    pub fn send_frame(&self, progress: Option<f32>, ops: &[PackedOperation]) {
+        if let Some(progress) = progress {
+            self.send("/sort/progress", &[OscArg::Float(progress)]);
+        }
+
+        for op in ops.iter().take(MAX_OPS_PER_FRAME) {
+            self.send("/sort/op", &op_args(op.unpack()));
+        }
+    }
+
+    /// Sends a note which was sent to the audio engine.
+    pub fn send_note(&self, event: &NoteEvent) {
+        self.send(
+            "/sort/note",
+            &[
+                OscArg::Float(event.freq()),
+                OscArg::Float(event.amp()),
+                OscArg::Float(event.pan()),
+            ],
+        );
+    }
+}
+
+/// Receives OSC messages over UDP. Receiving never blocks, so the receiver
+/// is polled once per frame.
+#[derive(Debug)]
+pub struct OscReceiver {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl OscReceiver {
+    /// Creates a receiver which listens on `port` on every interface.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port can't be bound.
+    pub fn new(port: u16) -> Result<Self, String> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))
+            .map_err(|e| format!("{e}"))?;
+        socket.set_nonblocking(true).map_err(|e| format!("{e}"))?;
+
+        Ok(Self { socket, buffer: vec![0; MAX_PACKET_SIZE] })
+    }
+
+    /// Returns the messages which have been received since the last call.
+    pub fn receive(&mut self) -> Vec<(String, Vec<OscArg>)> {
+        let mut messages = Vec::new();
+
+        while let Ok(len) = self.socket.recv(&mut self.buffer) {
+            messages.extend(decode(&self.buffer[..len]));
+        }
+
+        messages
+    }
+}
+
+/// A command received from an external controller.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RemoteCommand {
+    /// Performs an action, as if its key had been pressed.
+    Action(Action),
+    /// Starts playback, if it isn't playing already.
+    Play,
+    /// Pauses playback.
+    Pause,
+    /// Sets the playback speed.
+    SetSpeed(f32),
+}
+
+impl RemoteCommand {
+    /// Parses the command sent to `address` with `args`. The commands are:
+    ///
+    /// - `/sort/shuffle`: shuffles the wheel.
+    /// - `/sort/compute`: computes (and plays) the current algorithm.
+    /// - `/sort/run`: shuffles the wheel, then computes the current
+    ///   algorithm.
+    /// - `/sort/algorithm s`: selects an algorithm by name, using the same
+    ///   names as `--algorithm`.
+    /// - `/sort/algorithm/next` and `/sort/algorithm/previous`: cycles
+    ///   through the algorithms.
+    /// - `/sort/speed f`: sets the playback speed, between `-5.0` and `5.0`.
+    ///   Speeds which aren't finite are rejected.
+    /// - `/sort/play`, `/sort/pause`, `/sort/toggle` and `/sort/stop`:
+    ///   controls playback.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address isn't a command, or its arguments
+    /// are invalid.
+    pub fn parse(address: &str, args: &[OscArg]) -> Result<Self, String> {
+        let (min_speed, max_speed) = SPEED_RANGE.into_inner();
+        let speed = |s: f32| Self::SetSpeed(s.clamp(min_speed, max_speed));
+
+        let command = match (address, args) {
+            ("/sort/shuffle", []) => Self::Action(Action::Shuffle),
+            ("/sort/compute", []) => Self::Action(Action::Compute),
+            ("/sort/run", []) => Self::Action(Action::ShuffleAndSort),
+            ("/sort/algorithm", [OscArg::Str(name)]) => {
+                let algorithm = SortingAlgorithm::from_str(name, true)
+                    .map_err(|_| format!("unknown algorithm \"{name}\""))?;
+                Self::Action(Action::SelectAlgorithm(algorithm))
+            }
+            ("/sort/algorithm/next", []) => {
+                Self::Action(Action::NextAlgorithm)
+            }
+            ("/sort/algorithm/previous", []) => {
+                Self::Action(Action::PreviousAlgorithm)
+            }
+            ("/sort/speed", &[OscArg::Float(s)]) if !s.is_finite() => {
+                return Err(format!("invalid speed {s}"));
+            }
+            ("/sort/speed", &[OscArg::Float(s)]) => speed(s),
+            ("/sort/speed", &[OscArg::Int(s)]) => speed(s as f32),
+            ("/sort/play", []) => Self::Play,
+            ("/sort/pause", []) => Self::Pause,
+            ("/sort/toggle", []) => Self::Action(Action::TogglePlayback),
+            ("/sort/stop", []) => Self::Action(Action::Stop),
+            _ => {
+                return Err(format!("unknown OSC command {address} {args:?}"))
+            }
+        };
+
+        Ok(command)
+    }
+}
+
+/// The arguments of the `/sort/op` message for `op`.
+fn op_args(op: SortOperation) -> Vec<OscArg> {
+    let int = |n: usize| OscArg::Int(n as i32);
+    let kind = |s: &str| OscArg::Str(String::from(s));
+
+    match op {
+        SortOperation::Write { idx, value } => {
+            vec![kind("write"), int(idx), int(value)]
+        }
+        SortOperation::AuxWrite { idx, value } => {
+            vec![kind("aux_write"), int(idx), int(value)]
+        }
+        SortOperation::Read { idx } => vec![kind("read"), int(idx), int(0)],
+        SortOperation::Swap { a, b } => vec![kind("swap"), int(a), int(b)],
+        SortOperation::Compare { a, b, res } => {
+            vec![kind("compare"), int(a), int(b), OscArg::Int(res as i32)]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_encoding() {
+        // the address and type tags are padded to four bytes, and the
+        // arguments are big-endian.
+        let msg = encode("/a", &[OscArg::Int(1), OscArg::Float(1.0)]);
+        let expected: Vec<u8> = [
+            b"/a\0\0".as_slice(),
+            b",if\0",
+            &[0, 0, 0, 1],
+            &1.0f32.to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(msg, expected);
+
+        // strings which fill their four bytes still get a null terminator.
+        let msg = encode("/abc", &[OscArg::Str(String::from("swap"))]);
+        assert_eq!(msg, b"/abc\0\0\0\0,s\0\0swap\0\0\0\0");
+    }
+
+    #[test]
+    fn remote_commands() {
+        let args = [OscArg::Str(String::from("merge")), OscArg::Float(0.5)];
+        let decoded = decode(&encode("/test", &args));
+        assert_eq!(decoded, [(String::from("/test"), args.to_vec())]);
+
+        let parse = |address: &str, args: &[OscArg]| {
+            decode(&encode(address, args))
+                .into_iter()
+                .map(|(address, args)| RemoteCommand::parse(&address, &args))
+                .next()
+                .unwrap()
+        };
+
+        assert_eq!(
+            parse("/sort/stop", &[]),
+            Ok(RemoteCommand::Action(Action::Stop))
+        );
+        assert_eq!(
+            parse("/sort/speed", &[OscArg::Float(10.0)]),
+            Ok(RemoteCommand::SetSpeed(5.0))
+        );
+        assert!(parse("/sort/speed", &[OscArg::Float(f32::NAN)]).is_err());
+        assert!(parse("/sort/speed", &[OscArg::Float(f32::INFINITY)]).is_err());
+        let unknown = [OscArg::Str(String::from("nope"))];
+        assert!(parse("/sort/algorithm", &unknown).is_err());
+        assert!(parse("/sort/shuffle", &[OscArg::Int(1)]).is_err());
+    }
+}
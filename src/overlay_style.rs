@@ -0,0 +1,184 @@
+//! The overlays which operations are highlighted with on the wheel.
+
+use super::*;
+
+/// The range of overlay intensities which can be set.
+pub const INTENSITY_RANGE: std::ops::RangeInclusive<f32> = 0.0..=3.0;
+
+/// How each kind of operation is highlighted on the wheel.
+///
+/// Styles can be loaded from a file where each line sets the overlay for an
+/// operation, e.g. `swap = lighten 0.1` — see [`Self::parse()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OverlayStyle {
+    /// The overlay for comparisons which return `true`.
+    pub compare_true: Overlay,
+    /// The overlay for comparisons which return `false`.
+    pub compare_false: Overlay,
+    pub swap: Overlay,
+    pub write: Overlay,
+    pub read: Overlay,
+    /// The amount which lighten and darken overlays are scaled by.
+    pub intensity: f32,
+}
+
+impl OverlayStyle {
+    /// Parses a style, where each line is a `key = value` pair. The keys are
+    /// `compare-true`, `compare-false`, `swap`, `write` and `read`, whose
+    /// values are one of `lighten <amount>`, `darken <amount>`, `invert` or a
+    /// hex color (e.g. `#ffffff`), and `intensity`, whose value is a number.
+    /// Keys which aren't set keep their default overlays, and blank lines and
+    /// anything after a `#` followed by a space are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first line which is invalid.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut style = Self::default();
+
+        for (i, line) in text.lines().enumerate() {
+            // colors start with a `#`, so comments need a space after it.
+            let line = line.split("# ").next().unwrap_or_default().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            style
+                .set(line)
+                .map_err(|e| format!("line {}: {e}", i + 1))?;
+        }
+
+        Ok(style)
+    }
+
+    /// Reads and parses the style at `path` — see [`Self::parse()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {path}: {e}"))?;
+
+        Self::parse(&text)
+    }
+
+    /// Returns the overlay for `op`, scaled by the intensity, or `None` if
+    /// the operation isn't highlighted.
+    pub fn overlay_for(&self, op: SortOperation) -> Option<Overlay> {
+        let overlay = match op {
+            SortOperation::Compare { res: true, .. } => self.compare_true,
+            SortOperation::Compare { res: false, .. } => self.compare_false,
+            SortOperation::Swap { .. } => self.swap,
+            SortOperation::Write { .. } => self.write,
+            SortOperation::Read { .. } => self.read,
+            // auxiliary writes are shown in the auxiliary ring instead.
+            SortOperation::AuxWrite { .. } => return None,
+        };
+
+        Some(match overlay {
+            Overlay::Lighten(amt) => Overlay::Lighten(amt * self.intensity),
+            Overlay::Darken(amt) => Overlay::Darken(amt * self.intensity),
+            overlay => overlay,
+        })
+    }
+
+    /// Sets the value of a `key = value` pair.
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("expected `key = value`, found `{line}`"));
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        let overlay = match key {
+            "compare-true" => &mut self.compare_true,
+            "compare-false" => &mut self.compare_false,
+            "swap" => &mut self.swap,
+            "write" => &mut self.write,
+            "read" => &mut self.read,
+            "intensity" => {
+                self.intensity = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|i| INTENSITY_RANGE.contains(i))
+                    .ok_or_else(|| {
+                        format!(
+                            "expected an intensity between {} and {}, found \
+                             `{value}`",
+                            INTENSITY_RANGE.start(),
+                            INTENSITY_RANGE.end()
+                        )
+                    })?;
+
+                return Ok(());
+            }
+            _ => return Err(format!("unknown key `{key}`")),
+        };
+
+        *overlay = parse_overlay(value)?;
+
+        Ok(())
+    }
+}
+
+impl Default for OverlayStyle {
+    fn default() -> Self {
+        Self {
+            compare_true: Overlay::Lighten(0.5),
+            compare_false: Overlay::Darken(0.2),
+            swap: Overlay::Lighten(0.1),
+            write: Overlay::Darken(0.7),
+            read: Overlay::Lighten(0.3),
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Parses an overlay such as `lighten 0.5`, `invert` or `#ffffff`.
+fn parse_overlay(s: &str) -> Result<Overlay, String> {
+    if s.starts_with('#') {
+        return parse_hex_color(s).map(Overlay::Override);
+    }
+
+    let mut words = s.split_whitespace();
+    let kind = words.next().unwrap_or_default();
+    let amount = words.next().map(str::parse::<f32>);
+
+    match (kind, amount, words.next()) {
+        ("invert", None, None) => Ok(Overlay::Invert),
+        ("lighten", Some(Ok(amt)), None) => Ok(Overlay::Lighten(amt)),
+        ("darken", Some(Ok(amt)), None) => Ok(Overlay::Darken(amt)),
+        _ => Err(format!(
+            "expected `lighten <amount>`, `darken <amount>`, `invert` or a \
+             hex color, found `{s}`"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_overlay_style() {
+        let text = "# a comment\nswap = invert\nread = #ff0000  # red\n\n\
+                    write = darken 0.5\nintensity = 2";
+        let style = OverlayStyle::parse(text).unwrap();
+
+        assert_eq!(
+            style,
+            OverlayStyle {
+                swap: Overlay::Invert,
+                read: Overlay::Override(Rgb::new(1.0, 0.0, 0.0)),
+                write: Overlay::Darken(0.5),
+                intensity: 2.0,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            OverlayStyle::parse("swap = invert\nshuffle = invert").unwrap_err(),
+            "line 2: unknown key `shuffle`"
+        );
+    }
+}
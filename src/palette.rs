@@ -0,0 +1,197 @@
+//! The color palettes which values are drawn with.
+
+use super::*;
+use std::fmt::Display;
+
+/// The stops of the viridis palette, sampled evenly from the start to the
+/// end of the palette.
+const VIRIDIS_STOPS: [(f32, f32, f32); 9] = [
+    (0.267, 0.005, 0.329),
+    (0.279, 0.175, 0.483),
+    (0.230, 0.322, 0.546),
+    (0.173, 0.448, 0.557),
+    (0.128, 0.567, 0.551),
+    (0.158, 0.684, 0.502),
+    (0.369, 0.789, 0.383),
+    (0.678, 0.864, 0.190),
+    (0.993, 0.906, 0.144),
+];
+/// The stops of the cividis palette, which varies mostly in lightness so that
+/// it reads the same with most forms of color blindness.
+const CIVIDIS_STOPS: [(f32, f32, f32); 9] = [
+    (0.000, 0.133, 0.306),
+    (0.071, 0.208, 0.439),
+    (0.231, 0.286, 0.424),
+    (0.341, 0.365, 0.427),
+    (0.439, 0.443, 0.451),
+    (0.541, 0.525, 0.471),
+    (0.647, 0.612, 0.455),
+    (0.765, 0.702, 0.412),
+    (0.996, 0.910, 0.220),
+];
+/// The start and end of [`Palette::Gradient`].
+const GRADIENT_STOPS: [(f32, f32, f32); 2] =
+    [(0.1, 0.2, 0.6), (1.0, 0.55, 0.1)];
+/// The stops of [`Palette::Grayscale`].
+const GRAYSCALE_STOPS: [(f32, f32, f32); 2] =
+    [(0.05, 0.05, 0.05), (1.0, 1.0, 1.0)];
+
+/// The colors which the values of the array are mapped to, from the smallest
+/// value to the largest.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Palette {
+    /// Every hue, which wraps around so that the wheel is seamless once
+    /// sorted.
+    #[default]
+    Rainbow,
+    /// The perceptually uniform viridis palette, from purple to yellow.
+    Viridis,
+    /// The colorblind-friendly cividis palette, from dark blue to yellow.
+    Cividis,
+    /// From black to white.
+    Grayscale,
+    /// A gradient from blue to orange.
+    Gradient,
+    /// A gradient through any number of colors, spaced evenly.
+    Custom(Vec<Rgb<f32>>),
+}
+
+impl Palette {
+    /// The palettes which can be selected by name.
+    pub const BUILT_IN: [Self; 5] = [
+        Self::Rainbow,
+        Self::Viridis,
+        Self::Cividis,
+        Self::Grayscale,
+        Self::Gradient,
+    ];
+
+    /// Parses the name of a built-in palette (e.g. `viridis`), or the stops
+    /// of a custom palette as hex colors separated by commas (e.g.
+    /// `#000000,#ff0000,#ffffff`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `s` isn't a palette name, a color is invalid, or a
+    /// custom palette has fewer than two stops.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if let Some(palette) =
+            Self::BUILT_IN.into_iter().find(|p| p.name() == s)
+        {
+            return Ok(palette);
+        }
+
+        let stops = s
+            .split(',')
+            .map(|stop| parse_hex_color(stop.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if stops.len() < 2 {
+            return Err(format!(
+                "expected a palette name or at least two colors separated \
+                 by commas, found `{s}`"
+            ));
+        }
+
+        Ok(Self::Custom(stops))
+    }
+
+    /// The name of the palette, as accepted by `--palette`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Rainbow => "rainbow",
+            Self::Viridis => "viridis",
+            Self::Cividis => "cividis",
+            Self::Grayscale => "grayscale",
+            Self::Gradient => "gradient",
+            Self::Custom(_) => "custom",
+        }
+    }
+
+    /// Fills `colors` with the palette, where the first and last colors are
+    /// used for the smallest and largest values.
+    pub fn fill(&self, colors: &mut [Rgb<f32>]) {
+        let len = colors.len();
+        let to_rgb = |stops: &[(f32, f32, f32)]| -> Vec<Rgb<f32>> {
+            stops.iter().map(|&(r, g, b)| Rgb::new(r, g, b)).collect()
+        };
+
+        let stops = match self {
+            // the hue wraps around, so the last value stops short of the
+            // first color.
+            Self::Rainbow => {
+                for (i, color) in colors.iter_mut().enumerate() {
+                    let h = i as f32 / len as f32 * 360.0;
+                    *color = hsl_to_rgb(h, 1.0, 0.5);
+                }
+
+                return;
+            }
+            Self::Viridis => to_rgb(&VIRIDIS_STOPS),
+            Self::Cividis => to_rgb(&CIVIDIS_STOPS),
+            Self::Grayscale => to_rgb(&GRAYSCALE_STOPS),
+            Self::Gradient => to_rgb(&GRADIENT_STOPS),
+            Self::Custom(stops) => stops.clone(),
+        };
+
+        let last = len.saturating_sub(1).max(1) as f32;
+
+        for (i, color) in colors.iter_mut().enumerate() {
+            *color = interpolate(&stops, i as f32 / last);
+        }
+    }
+}
+
+impl Display for Palette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Returns the color at `t` (between `0.0` and `1.0`) along a gradient
+/// through `stops`, which are spaced evenly.
+fn interpolate(stops: &[Rgb<f32>], t: f32) -> Rgb<f32> {
+    let pos = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let idx = (pos as usize).min(stops.len() - 2);
+    let (a, b, t) = (stops[idx], stops[idx + 1], pos - idx as f32);
+
+    Rgb::new(
+        (b.red - a.red).mul_add(t, a.red),
+        (b.green - a.green).mul_add(t, a.green),
+        (b.blue - a.blue).mul_add(t, a.blue),
+    )
+}
+
+/// Parses a hex color such as `#ff8000` (the `#` is optional).
+pub fn parse_hex_color(s: &str) -> Result<Rgb<f32>, String> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    let invalid = || format!("invalid color `{s}`, expected e.g. `#ff8000`");
+
+    if hex.len() != 6 {
+        return Err(invalid());
+    }
+
+    let rgb = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    let channel = |shift: u32| ((rgb >> shift) & 0xff) as f32 / 255.0;
+
+    Ok(Rgb::new(channel(16), channel(8), channel(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_palette() {
+        assert_eq!(Palette::parse("viridis"), Ok(Palette::Viridis));
+        assert_eq!(
+            Palette::parse("#000000, ff0000"),
+            Ok(Palette::Custom(vec![
+                Rgb::new(0.0, 0.0, 0.0),
+                Rgb::new(1.0, 0.0, 0.0)
+            ]))
+        );
+        assert!(Palette::parse("#ffffff").is_err());
+        assert!(Palette::parse("#fff,#000").is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! Playlists of algorithms, which are shuffled, sorted and played in turn.
+
+use super::*;
+use clap::ValueEnum;
+use std::fmt::Display;
+
+/// A list of algorithms to play one after another, each with its own shuffle.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Playlist {
+    algorithms: Vec<SortingAlgorithm>,
+}
+
+impl Playlist {
+    /// Parses a playlist, where each line is the name of an algorithm (as
+    /// accepted by `--algorithm`, e.g. `quick-sort`). Blank lines and
+    /// anything after a `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first line which isn't an algorithm.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut algorithms = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let name = line.split('#').next().unwrap_or_default().trim();
+
+            if name.is_empty() {
+                continue;
+            }
+
+            let algorithm = SortingAlgorithm::from_str(name, true).map_err(
+                |_| format!("line {}: unknown algorithm `{name}`", i + 1),
+            )?;
+
+            algorithms.push(algorithm);
+        }
+
+        Ok(Self { algorithms })
+    }
+
+    /// Reads and parses the playlist at `path` — see [`Self::parse()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {path}: {e}"))?;
+
+        Self::parse(&text)
+    }
+
+    /// Adds `algorithm` to the end of the playlist.
+    pub fn push(&mut self, algorithm: SortingAlgorithm) {
+        self.algorithms.push(algorithm);
+    }
+
+    /// Removes the last algorithm from the playlist, if there is one.
+    pub fn pop(&mut self) -> Option<SortingAlgorithm> {
+        self.algorithms.pop()
+    }
+
+    /// The algorithms in the playlist, in order.
+    pub fn algorithms(&self) -> &[SortingAlgorithm] {
+        &self.algorithms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.algorithms.is_empty()
+    }
+}
+
+impl Display for Playlist {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, algorithm) in self.algorithms.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            write!(f, "{algorithm}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_playlist() {
+        let text = "merge\n\n# a comment\nquick-sort  # trailing comment\n";
+        let playlist = Playlist::parse(text).unwrap();
+
+        assert_eq!(
+            playlist.algorithms(),
+            [SortingAlgorithm::Merge, SortingAlgorithm::QuickSort]
+        );
+        assert_eq!(
+            Playlist::parse("merge\nnot-a-sort").unwrap_err(),
+            "line 2: unknown algorithm `not-a-sort`"
+        );
+    }
+}
@@ -1,5 +1,5 @@
 pub use super::*;
-pub use crate::audio::{Audio, BUFFER_SIZE, SAMPLE_RATE};
+pub use crate::audio::SAMPLE_RATE;
 pub use crate::sorting::*;
 pub use algorithms::SortingAlgorithm;
 pub use atomic::Atomic;
@@ -8,8 +8,8 @@ pub use nannou::prelude::*;
 pub use parking_lot::Mutex;
 pub use std::f32::consts::TAU;
 pub use std::simd::{
-    cmp::{SimdOrd, SimdPartialOrd},
-    f32x2,
+    cmp::SimdPartialOrd,
+    f32x2, f32x8,
     num::SimdFloat,
     StdFloat,
 };
@@ -1,23 +1,215 @@
 use super::algorithms::*;
 use super::*;
 use atomic::Atomic;
-use std::sync::Arc;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ops::Range, sync::Arc};
 
 /// The sorting algorithm process.
 #[derive(Debug)]
 pub struct Process {
     algorithms: Algorithms,
     current_algorithm: Arc<Atomic<SortingAlgorithm>>,
+    shuffle_intensity: Arc<Atomic<f32>>,
 }
 
 impl Process {
-    pub fn new(current_algorithm: Arc<Atomic<SortingAlgorithm>>) -> Self {
-        Self { algorithms: Algorithms::new(), current_algorithm }
+    pub fn new(
+        current_algorithm: Arc<Atomic<SortingAlgorithm>>,
+        shuffle_intensity: &Arc<Atomic<f32>>,
+    ) -> Self {
+        Self {
+            algorithms: Algorithms::new(shuffle_intensity),
+            current_algorithm,
+            shuffle_intensity: Arc::clone(shuffle_intensity),
+        }
     }
 
-    /// Processes the currently-selected algorithm if it can.
-    pub fn sort(&mut self, arr: &mut SortArray) {
-        self.algorithms
-            .process(self.current_algorithm.load(Relaxed), arr);
+    /// Processes the currently-selected algorithm if it can. A verification
+    /// sweep is recorded after any algorithm other than a shuffle.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if the array's cancel token was cancelled (see
+    /// [`SortArray::set_cancel_token()`]), in which case the array is left
+    /// as it was when the sort was interrupted.
+    pub fn sort(&mut self, arr: &mut SortArray) -> Result<(), Cancelled> {
+        self.sort_with(arr, self.current_algorithm.load(Relaxed))
+    }
+
+    /// Processes `algorithm`, rather than the currently-selected algorithm —
+    /// see [`Self::sort()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if the sort was cancelled.
+    pub fn sort_with(
+        &mut self,
+        arr: &mut SortArray,
+        algorithm: SortingAlgorithm,
+    ) -> Result<(), Cancelled> {
+        self.catch_cancel(|algorithms| {
+            algorithms.process(algorithm, arr);
+
+            if !algorithm.is_shuffle() {
+                arr.verify();
+            }
+        })
+    }
+
+    /// Processes the currently-selected algorithm on the elements in `range`
+    /// only, leaving the rest of the array untouched. The verification sweep
+    /// also only covers `range`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if the sort was cancelled — see [`Self::sort()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn sort_region(
+        &mut self,
+        arr: &mut SortArray,
+        range: Range<usize>,
+    ) -> Result<(), Cancelled> {
+        self.sort_region_with(arr, range, self.current_algorithm.load(Relaxed))
+    }
+
+    /// Processes `algorithm` on the elements in `range` only — see
+    /// [`Self::sort_region()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if the sort was cancelled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn sort_region_with(
+        &mut self,
+        arr: &mut SortArray,
+        range: Range<usize>,
+        algorithm: SortingAlgorithm,
+    ) -> Result<(), Cancelled> {
+        let mut sub = arr.sub_array(range.clone());
+
+        // the operations recorded before a cancellation are kept, so the
+        // sub-array is merged either way.
+        let result = self.catch_cancel(|algorithms| {
+            algorithms.process(algorithm, &mut sub);
+        });
+        arr.merge_sub_arrays(vec![(range.start, sub)]);
+        result?;
+
+        self.catch_cancel(|_| {
+            if !algorithm.is_shuffle() {
+                arr.verify_range(range);
+            }
+        })
+    }
+
+    /// Runs `f`, catching the unwind of a cancelled sort.
+    fn catch_cancel(
+        &mut self,
+        f: impl FnOnce(&mut Algorithms),
+    ) -> Result<(), Cancelled> {
+        match panic::catch_unwind(AssertUnwindSafe(|| f(&mut self.algorithms)))
+        {
+            Ok(()) => Ok(()),
+            Err(payload) if payload.is::<Cancelled>() => {
+                // the algorithms keep scratch buffers between sorts, which
+                // may have been left in an invalid state.
+                self.algorithms = Algorithms::new(&self.shuffle_intensity);
+                Err(Cancelled)
+            }
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sorts a reversed array of 256 elements with `algorithm`.
+    fn sort(
+        algorithm: SortingAlgorithm,
+        op_limit: Option<usize>,
+    ) -> (Result<(), Cancelled>, SortArray) {
+        let mut process = Process::new(
+            Arc::new(Atomic::new(algorithm)),
+            &Arc::new(Atomic::new(1.0)),
+        );
+        let input: Vec<usize> = (0..256).rev().collect();
+
+        let mut arr = SortArray::new(input.len());
+        arr.set_op_limit(op_limit);
+        arr.prepare_for_sort_with(&input, algorithm);
+
+        (process.sort(&mut arr), arr)
+    }
+
+    #[test]
+    fn op_limit_covers_parallel_sorts() {
+        let (result, mut arr) =
+            sort(SortingAlgorithm::ParallelQuickSort, Some(1000));
+        assert!(result.is_err() && arr.is_truncated());
+
+        let capture = arr.dump_capture();
+        assert!(capture.num_operations() <= 1000);
+
+        // the array still matches its operations.
+        let mut replayed = capture.initial_arr().to_vec();
+        for op in capture.operations() {
+            match op.unpack() {
+                SortOperation::Write { idx, value } => replayed[idx] = value,
+                SortOperation::Swap { a, b } => replayed.swap(a, b),
+                _ => {}
+            }
+        }
+        assert_eq!(replayed, capture.final_arr());
+    }
+
+    #[test]
+    fn verification_isnt_truncated() {
+        let (_, mut arr) = sort(SortingAlgorithm::Insertion, None);
+        let sort_ops = arr.dump_capture().verify_start().unwrap();
+
+        // a sort which only just fits under the limit finished, so it isn't
+        // truncated by its verification sweep.
+        let (result, arr) = sort(SortingAlgorithm::Insertion, Some(sort_ops));
+        assert!(result.is_ok() && !arr.is_truncated());
+    }
+
+    #[test]
+    fn cancelled_sleep_sort_leaves_nothing_behind() {
+        let algorithm = SortingAlgorithm::Sleep;
+        let mut process = Process::new(
+            Arc::new(Atomic::new(algorithm)),
+            &Arc::new(Atomic::new(1.0)),
+        );
+        let input: Vec<usize> = (0..64).rev().collect();
+        let mut arr = SortArray::new(input.len());
+
+        let token = CancelToken::new();
+        arr.set_cancel_token(Some(token.clone()));
+        arr.prepare_for_sort_with(&input, algorithm);
+
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            token.cancel();
+        });
+        let start = Instant::now();
+        assert_eq!(process.sort(&mut arr), Err(Cancelled));
+        assert!(start.elapsed().as_secs_f32() < 0.5);
+        canceller.join().unwrap();
+
+        // the next sort starts from an empty output.
+        arr.set_cancel_token(None);
+        arr.prepare_for_sort_with(&input, algorithm);
+        assert!(process.sort(&mut arr).is_ok());
+
+        let sorted: Vec<usize> = (0..64).collect();
+        assert_eq!(arr.dump_capture().final_arr(), sorted);
     }
 }
@@ -0,0 +1,211 @@
+use super::*;
+use crate::cli::View;
+
+/// A second wheel, which plays an "opponent" algorithm alongside the current
+/// one on the same input, so that the two can be raced against each other.
+///
+/// Both wheels play back the same number of operations per second, so the
+/// algorithm which performs fewer operations finishes first. Until a race has
+/// been computed, the second wheel mirrors the main wheel.
+#[derive(Debug)]
+pub struct Race {
+    /// The opponent algorithm.
+    algorithm: SortingAlgorithm,
+    wheel: ColorWheel,
+    /// The scatter plot drawn in place of the wheel in the scatter view.
+    scatter: ScatterPlot,
+    /// The spiral drawn in place of the wheel in the spiral view.
+    spiral: Spiral,
+    /// The rings drawn in place of the wheel in the rings view.
+    rings: Rings,
+    player: Player,
+    /// The array which the opponent is computed on.
+    arr: Arc<Mutex<SortArray>>,
+    /// Whether the opponent is being computed.
+    computing: bool,
+}
+
+impl Race {
+    /// Creates a race against `algorithm`, using `sort_arr`'s resolution and
+    /// operation limit. The race's player shares `player`'s audio engine.
+    pub fn new(
+        algorithm: SortingAlgorithm,
+        player: &Player,
+        sort_arr: &SortArray,
+    ) -> Self {
+        let mut wheel = ColorWheel::new();
+        // the sorting array always has a valid length.
+        _ = wheel.resize(sort_arr.len());
+
+        let mut arr = SortArray::new(sort_arr.len());
+        arr.set_op_limit(sort_arr.op_limit());
+
+        Self {
+            algorithm,
+            wheel,
+            scatter: ScatterPlot::new(),
+            spiral: Spiral::new(),
+            rings: Rings::new(),
+            player: player.new_sharing_audio(),
+            arr: Arc::new(Mutex::new(arr)),
+            computing: false,
+        }
+    }
+
+    /// The opponent algorithm.
+    pub const fn algorithm(&self) -> SortingAlgorithm {
+        self.algorithm
+    }
+
+    /// The array which the opponent is computed on.
+    pub fn arr(&self) -> Arc<Mutex<SortArray>> {
+        Arc::clone(&self.arr)
+    }
+
+    pub const fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn player_mut(&mut self) -> &mut Player {
+        &mut self.player
+    }
+
+    /// Resizes the race's wheel and array, which ends the current race.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `new_resolution` is invalid — see
+    /// [`ColorWheel::resize()`].
+    pub fn resize(&mut self, new_resolution: usize) -> Result<(), ResizeError> {
+        self.wheel.resize(new_resolution)?;
+        self.arr.lock().resize(new_resolution);
+        self.end();
+
+        Ok(())
+    }
+
+    /// Marks the opponent as being computed on the sorting thread.
+    pub fn start_computing(&mut self) {
+        self.end();
+        self.computing = true;
+    }
+
+    /// Whether the opponent is being computed.
+    pub const fn is_computing(&self) -> bool {
+        self.computing
+    }
+
+    /// Sets the opponent's capture once it has been computed, so that the
+    /// race can begin. The playback time of each player is set so that both
+    /// play the same number of operations per second, with the longer sort
+    /// taking [`Player::DEFAULT_PLAYBACK_TIME`] at 1x speed.
+    pub fn finish_computing(&mut self, main_player: &mut Player) {
+        self.computing = false;
+
+        let capture = self.arr.lock().dump_capture();
+        let main_ops = main_player.capture().map_or(0, |c| c.num_operations());
+        let ops = capture.num_operations();
+        let max_ops = main_ops.max(ops).max(1) as f32;
+
+        main_player.set_playback_time(
+            Player::DEFAULT_PLAYBACK_TIME * main_ops as f32 / max_ops,
+        );
+        self.player.set_playback_time(
+            Player::DEFAULT_PLAYBACK_TIME * ops as f32 / max_ops,
+        );
+        self.player.set_capture(capture);
+    }
+
+    /// Ends the current race, after which the wheel mirrors the main wheel
+    /// again.
+    pub fn end(&mut self) {
+        self.player.clear_capture();
+        self.player.clear_ops();
+        self.player.reset_playback_time();
+    }
+
+    /// Sets the palette which the wheel is colored with.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.wheel.set_palette(palette);
+    }
+
+    /// Sets how operations are highlighted on the wheel.
+    pub fn set_overlay_style(&mut self, style: OverlayStyle) {
+        self.wheel.set_overlay_style(style);
+    }
+
+    /// Enables or disables colorblind-safe mode on the wheel — see
+    /// [`ColorWheel::set_colorblind_mode()`].
+    pub fn set_colorblind_mode(&mut self, enabled: bool) {
+        self.wheel.set_colorblind_mode(enabled);
+    }
+
+    /// Moves the wheel (and the other views) to `center` in the window, at
+    /// `scale`.
+    pub fn set_transform(&mut self, center: Vec2, scale: f32) {
+        self.wheel.set_transform(center, scale);
+        self.scatter.set_transform(center, scale);
+        self.spiral.set_transform(center, scale);
+        self.rings.set_transform(center, scale);
+    }
+
+    /// Updates the race's player and wheel, along with whichever view is
+    /// drawn in place of the wheel, if any. If there is no race, the wheel
+    /// mirrors `main_wheel` instead.
+    pub fn update(
+        &mut self,
+        app: &App,
+        update: UpdateData,
+        main_wheel: &ColorWheel,
+        main_ops: OperationSlice,
+        show_target: bool,
+        view: View,
+    ) {
+        let player = &mut self.player;
+        player.update(app, update);
+
+        if player.has_capture()
+            && player.copy_arr_to(self.wheel.arr_mut()).is_ok()
+        {
+            let target = show_target.then(|| player.target_arr()).flatten();
+            self.wheel.set_target(target);
+            self.wheel.set_aux(player.aux_arr());
+            self.wheel.set_overlay_ops(player.ops_last_frame());
+        }
+        else {
+            if self.wheel.resolution() == main_wheel.resolution() {
+                self.wheel.arr_mut().copy_from_slice(main_wheel.arr());
+            }
+
+            self.wheel.set_target(None);
+            self.wheel.set_aux(None);
+            self.wheel.set_overlay_ops(main_ops);
+        }
+
+        self.wheel.update(app, update);
+
+        match view {
+            View::Wheel => {}
+            View::Scatter => self.scatter.sync(&self.wheel),
+            View::Spiral => self.spiral.sync(&self.wheel),
+            View::Rings => self.rings.sync(&self.wheel),
+        }
+    }
+
+    /// Renders the race's wheel straight to `frame` if it is being drawn —
+    /// see [`Drawable::render()`].
+    pub fn render(&self, frame: &Frame, view: View) {
+        if view == View::Wheel {
+            self.wheel.render(frame);
+        }
+    }
+
+    pub fn draw(&self, draw: &Draw, update: UpdateData, view: View) {
+        match view {
+            View::Wheel => self.wheel.draw(draw, update),
+            View::Scatter => self.scatter.draw(draw, update),
+            View::Spiral => self.spiral.draw(draw, update),
+            View::Rings => self.rings.draw(draw, update),
+        }
+    }
+}
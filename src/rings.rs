@@ -0,0 +1,171 @@
+use super::*;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::ops::Range;
+
+/// The smallest width of each ring, at its natural scale.
+const MIN_RING_WIDTH: f32 = 2.0;
+/// The minimum number of pieces the rings are drawn with, so that they stay
+/// smooth at low resolutions.
+const MIN_PIECES: usize = 720;
+/// The distance between the outermost ring and the selected region marker.
+const REGION_MARKER_GAP: f32 = 4.0;
+/// The color of the outline around the rings.
+const BORDER_COLOR: Rgb<f32> = Rgb {
+    red: 0.25,
+    green: 0.25,
+    blue: 0.25,
+    standard: std::marker::PhantomData,
+};
+
+/// The array drawn as concentric rings, where each element is an arc at the
+/// same angle as its slice of the [`ColorWheel`], at a distance from the
+/// center set by its value. This can be drawn in place of the wheel.
+///
+/// Elements with the same value lie on the same ring, so a sorted array
+/// forms a "pyramid" which winds outwards from the top of the wheel, while
+/// a shuffled array is scattered across every ring. Like the
+/// [`ScatterPlot`], the rings mirror the wheel which they are synced from
+/// (see [`Self::sync()`]).
+#[derive(Debug)]
+pub struct Rings {
+    /// The value of each element.
+    values: Vec<usize>,
+    /// The color of each element's arc.
+    colors: Vec<Rgb<f32>>,
+    /// The range of elements which is marked as selected, if any.
+    region: Option<Range<usize>>,
+    /// The position of the center of the rings in the window.
+    center: Vec2,
+    /// The scale which the rings are drawn at, relative to their natural
+    /// size.
+    scale: f32,
+}
+
+impl Rings {
+    pub fn new() -> Self {
+        Self {
+            values: vec![],
+            colors: vec![],
+            region: None,
+            center: Vec2::ZERO,
+            scale: 1.0,
+        }
+    }
+
+    /// Copies the values, colors and selected region of `wheel`.
+    pub fn sync(&mut self, wheel: &ColorWheel) {
+        let res = wheel.resolution();
+
+        self.values.clear();
+        self.values.extend_from_slice(wheel.arr());
+        self.colors.clear();
+        self.colors.extend((0..res).map(|slice| wheel.slice_color(slice)));
+        self.region = wheel.region();
+    }
+
+    /// Sets the position and scale which the rings are drawn at.
+    pub fn set_transform(&mut self, center: Vec2, scale: f32) {
+        self.center = center;
+        self.scale = scale;
+    }
+
+    /// Returns the element at `pos` in the window, along with the value of
+    /// the ring at that point as a proportion of the outermost ring, or
+    /// `None` if `pos` is outside of the rings.
+    pub fn element_at(&self, pos: Vec2) -> Option<(usize, f32)> {
+        let res = self.values.len();
+        let pos = (pos - self.center) / self.scale;
+        let radius = pos.length() / CIRCLE_RADIUS;
+
+        if res == 0 || radius > 1.0 {
+            return None;
+        }
+
+        // the elements are laid out from the top, going clockwise, as with
+        // the wheel.
+        let theta = pos.y.atan2(-pos.x);
+        let t = (theta - FRAC_PI_2).rem_euclid(TAU) / TAU;
+
+        Some(((t * res as f32) as usize % res, radius))
+    }
+
+    /// The point at `radius` from the center, `turns` of the way around
+    /// clockwise from the top.
+    fn point(turns: f32, radius: f32) -> Vec3 {
+        let theta = turns.mul_add(TAU, FRAC_PI_2);
+        let (y, x) = theta.sin_cos();
+
+        vec3(-x * radius, y * radius, 0.0)
+    }
+
+    /// Returns `draw` transformed to the rings' position and scale.
+    fn transform(&self, draw: &Draw) -> Draw {
+        draw.translate(self.center.extend(0.0)).scale(self.scale)
+    }
+}
+
+impl Updatable for Rings {
+    fn update(&mut self, _: &App, _: UpdateData) {}
+}
+
+impl Drawable for Rings {
+    fn draw(&self, draw: &Draw, _: UpdateData) {
+        let res = self.values.len();
+
+        if res == 0 {
+            return;
+        }
+
+        let draw = self.transform(draw);
+        let ring_width = CIRCLE_RADIUS / res as f32;
+        let half_width = ring_width.max(MIN_RING_WIDTH) * 0.5;
+        // each arc is split into pieces, so that long arcs still follow the
+        // curve of their ring.
+        let pieces = MIN_PIECES.div_ceil(res);
+        let turns_per_piece = 1.0 / (res * pieces) as f32;
+
+        draw.ellipse()
+            .radius(CIRCLE_RADIUS + half_width)
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke_color(BORDER_COLOR);
+
+        let points = self.values.iter().zip(&self.colors).enumerate().flat_map(
+            |(i, (&value, &color))| {
+                let radius = (value.min(res - 1) as f32 + 0.5) * ring_width;
+                let (inner, outer) = (radius - half_width, radius + half_width);
+
+                (0..pieces).flat_map(move |p| {
+                    let start = (i * pieces + p) as f32 * turns_per_piece;
+                    let end = start + turns_per_piece;
+
+                    let (a, b) =
+                        (Self::point(start, inner), Self::point(start, outer));
+                    let (c, d) =
+                        (Self::point(end, inner), Self::point(end, outer));
+
+                    [a, b, d, a, d, c].map(|p| (p, color))
+                })
+            },
+        );
+
+        draw.mesh().points_colored(points).xy(Vec2::ZERO);
+
+        if let Some(region) = &self.region {
+            let radius = CIRCLE_RADIUS + half_width + REGION_MARKER_GAP;
+            let turns_per_element = 1.0 / res as f32;
+            let (start, end) = (
+                region.start as f32 * turns_per_element,
+                region.end as f32 * turns_per_element,
+            );
+            let steps = (region.len() * pieces).max(1);
+
+            let points = (0..=steps).map(|s| {
+                let t = s as f32 / steps as f32;
+                Self::point((end - start).mul_add(t, start), radius).truncate()
+            });
+
+            draw.polyline().weight(3.0).points(points).color(WHITE);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! The random number generator used by the shuffles, distributions and
+//! algorithms, which may be seeded so that they are reproducible.
+//!
+//! These mirror the helpers in `nannou::rand`, but share a single generator
+//! rather than using a thread-local one.
+
+use nannou::rand::{
+    distributions::uniform::SampleUniform, rngs::StdRng, Rng, SeedableRng,
+};
+use parking_lot::{const_mutex, Mutex};
+
+static RNG: Mutex<Option<StdRng>> = const_mutex(None);
+
+/// Seeds the generator, so that the same sequence of values is produced
+/// every time.
+pub fn seed(seed: u64) {
+    *RNG.lock() = Some(StdRng::seed_from_u64(seed));
+}
+
+/// Calls `f` with the generator, which is seeded from system entropy if it
+/// hasn't been seeded yet.
+pub fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    f(RNG.lock().get_or_insert_with(StdRng::from_entropy))
+}
+
+/// Returns a random value in `min..max` (see `nannou::rand::random_range`).
+pub fn random_range<T>(min: T, max: T) -> T
+where
+    T: PartialOrd + SampleUniform,
+{
+    let (min, max) = if min <= max { (min, max) } else { (max, min) };
+    with_rng(|rng| rng.gen_range(min..max))
+}
+
+/// Returns a random value in `0.0..1.0`.
+pub fn random_f32() -> f32 {
+    with_rng(|rng| rng.gen())
+}
@@ -0,0 +1,136 @@
+use super::*;
+use std::ops::Range;
+
+/// The width and height of the plot at its natural scale, which matches the
+/// diameter of the wheel.
+const PLOT_SIZE: f32 = CIRCLE_RADIUS * 2.0;
+/// The smallest and largest size of each point, at the plot's natural scale.
+const POINT_SIZE_RANGE: (f32, f32) = (2.0, 8.0);
+/// The color of the plot's border.
+const BORDER_COLOR: Rgb<f32> = Rgb {
+    red: 0.25,
+    green: 0.25,
+    blue: 0.25,
+    standard: std::marker::PhantomData,
+};
+
+/// A scatter plot of each element's value against its index, which can be
+/// drawn in place of the [`ColorWheel`].
+///
+/// Each point uses the color of the corresponding slice of the wheel,
+/// including any overlays, so the plot mirrors the wheel which it is synced
+/// from (see [`Self::sync()`]). A sorted array forms a diagonal line from
+/// the bottom-left to the top-right.
+#[derive(Debug)]
+pub struct ScatterPlot {
+    /// The value of each element.
+    values: Vec<usize>,
+    /// The color of each element's point.
+    colors: Vec<Rgb<f32>>,
+    /// The range of elements which is marked as selected, if any.
+    region: Option<Range<usize>>,
+    /// The position of the center of the plot in the window.
+    center: Vec2,
+    /// The scale which the plot is drawn at, relative to its natural size.
+    scale: f32,
+}
+
+impl ScatterPlot {
+    pub fn new() -> Self {
+        Self {
+            values: vec![],
+            colors: vec![],
+            region: None,
+            center: Vec2::ZERO,
+            scale: 1.0,
+        }
+    }
+
+    /// Copies the values, colors and selected region of `wheel`.
+    pub fn sync(&mut self, wheel: &ColorWheel) {
+        let res = wheel.resolution();
+
+        self.values.clear();
+        self.values.extend_from_slice(wheel.arr());
+        self.colors.clear();
+        self.colors.extend((0..res).map(|slice| wheel.slice_color(slice)));
+        self.region = wheel.region();
+    }
+
+    /// Sets the position and scale which the plot is drawn at.
+    pub fn set_transform(&mut self, center: Vec2, scale: f32) {
+        self.center = center;
+        self.scale = scale;
+    }
+
+    /// Returns the element at `pos` in the window, along with the value at
+    /// that height as a proportion of the plot's height, or `None` if `pos`
+    /// is outside of the plot.
+    pub fn element_at(&self, pos: Vec2) -> Option<(usize, f32)> {
+        let res = self.values.len();
+        let local = (pos - self.center) / self.scale / PLOT_SIZE + 0.5;
+
+        if res == 0 || !(0.0..1.0).contains(&local.x) {
+            return None;
+        }
+
+        (0.0..1.0)
+            .contains(&local.y)
+            .then(|| ((local.x * res as f32) as usize, local.y))
+    }
+
+    /// Returns `draw` transformed to the plot's position and scale.
+    fn transform(&self, draw: &Draw) -> Draw {
+        draw.translate(self.center.extend(0.0)).scale(self.scale)
+    }
+}
+
+impl Updatable for ScatterPlot {
+    fn update(&mut self, _: &App, _: UpdateData) {}
+}
+
+impl Drawable for ScatterPlot {
+    fn draw(&self, draw: &Draw, _: UpdateData) {
+        let res = self.values.len();
+
+        if res == 0 {
+            return;
+        }
+
+        let draw = self.transform(draw);
+        let half = PLOT_SIZE * 0.5;
+        let step = PLOT_SIZE / res as f32;
+
+        draw.rect()
+            .w_h(PLOT_SIZE, PLOT_SIZE)
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke_color(BORDER_COLOR);
+
+        if let Some(region) = &self.region {
+            let width = region.len() as f32 * step;
+            let left = (region.start as f32).mul_add(step, -half);
+
+            draw.rect()
+                .x_y(left + width * 0.5, 0.0)
+                .w_h(width, PLOT_SIZE)
+                .color(srgba(1.0, 1.0, 1.0, 0.12));
+        }
+
+        let (min, max) = POINT_SIZE_RANGE;
+        let r = step.clamp(min, max) * 0.5;
+
+        let points = self.values.iter().zip(&self.colors).enumerate().flat_map(
+            |(i, (&value, &color))| {
+                let x = (i as f32 + 0.5).mul_add(step, -half);
+                let y = (value.min(res - 1) as f32 + 0.5).mul_add(step, -half);
+                let (l, rt, b, t) = (x - r, x + r, y - r, y + r);
+
+                [(l, b), (l, t), (rt, t), (l, b), (rt, t), (rt, b)]
+                    .map(|(x, y)| (vec3(x, y, 0.0), color))
+            },
+        );
+
+        draw.mesh().points_colored(points).xy(Vec2::ZERO);
+    }
+}
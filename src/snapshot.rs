@@ -0,0 +1,247 @@
+//! Offscreen "snapshots" of the color wheel, used to catch visual regressions.
+//!
+//! The wheel is rasterized on the CPU using the same slice geometry and colors
+//! as its mesh, so snapshots can be rendered without a window or GPU.
+
+use super::*;
+use nannou::image::{Rgb as Pixel, RgbImage};
+
+/// The amount of space around the wheel in a snapshot, relative to its radius.
+const MARGIN: f32 = 1.05;
+
+/// The differences between two snapshots.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// The largest difference in any single color channel.
+    pub max_channel_diff: u8,
+    /// The number of pixels which differ by more than the tolerance.
+    pub mismatched_pixels: usize,
+    /// The total number of pixels compared.
+    pub total_pixels: usize,
+}
+
+impl SnapshotDiff {
+    /// The proportion of pixels which differ by more than the tolerance.
+    pub fn mismatch_ratio(&self) -> f32 {
+        if self.total_pixels == 0 {
+            return 0.0;
+        }
+
+        self.mismatched_pixels as f32 / self.total_pixels as f32
+    }
+}
+
+/// Renders `wheel` to a square image of `size` pixels.
+pub fn render(wheel: &ColorWheel, size: u32) -> RgbImage {
+    let res = wheel.resolution();
+    let extent = CIRCLE_RADIUS * MARGIN;
+    let scale = extent * 2.0 / size as f32;
+
+    RgbImage::from_fn(size, size, |px, py| {
+        let pos = vec2(
+            (px as f32 + 0.5).mul_add(scale, -extent),
+            (py as f32 + 0.5).mul_add(-scale, extent),
+        );
+
+        // the angle gives us the slice (or one of its neighbors, as the edges
+        // of the slices are straight).
+        let Some(guess) = wheel.slice_at(pos) else {
+            return Pixel([0, 0, 0]);
+        };
+
+        [guess, (guess + res - 1) % res, (guess + 1) % res]
+            .into_iter()
+            .find(|&slice| in_triangle(pos, wheel.slice_vertices(slice)))
+            .map_or(Pixel([0, 0, 0]), |slice| to_pixel(wheel.slice_color(slice)))
+    })
+}
+
+/// Compares two snapshots, where channels which differ by no more than
+/// `tolerance` are considered equal.
+///
+/// # Panics
+///
+/// Panics if the images are not the same size.
+pub fn compare(a: &RgbImage, b: &RgbImage, tolerance: u8) -> SnapshotDiff {
+    assert_eq!(a.dimensions(), b.dimensions(), "mismatched snapshot sizes");
+
+    let mut diff = SnapshotDiff {
+        total_pixels: (a.width() * a.height()) as usize,
+        ..Default::default()
+    };
+
+    for (pa, pb) in a.pixels().zip(b.pixels()) {
+        let max = pa.0.iter().zip(pb.0).map(|(&ca, cb)| ca.abs_diff(cb)).max();
+        let max = max.unwrap_or(0);
+
+        diff.max_channel_diff = diff.max_channel_diff.max(max);
+
+        if max > tolerance {
+            diff.mismatched_pixels += 1;
+        }
+    }
+
+    diff
+}
+
+fn in_triangle(p: Vec2, [a, b, c]: [Vec3; 3]) -> bool {
+    let edge = |a: Vec3, b: Vec3| {
+        (b.x - a.x).mul_add(p.y - a.y, -(b.y - a.y) * (p.x - a.x))
+    };
+
+    let (e0, e1, e2) = (edge(a, b), edge(b, c), edge(c, a));
+
+    (e0 >= 0.0 && e1 >= 0.0 && e2 >= 0.0) || (e0 <= 0.0 && e1 <= 0.0 && e2 <= 0.0)
+}
+
+fn to_pixel(color: Rgb<f32>) -> Pixel<u8> {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    Pixel([channel(color.red), channel(color.green), channel(color.blue)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const SIZE: u32 = 256;
+    const TOLERANCE: u8 = 2;
+    const MAX_MISMATCH_RATIO: f32 = 0.001;
+
+    /// Compares `wheel` against the reference image `name`. The reference is
+    /// only (re)written if `UPDATE_SNAPSHOTS` is set, so a missing reference
+    /// fails rather than silently passing.
+    fn check(name: &str, wheel: &ColorWheel) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("snapshots")
+            .join(format!("{name}.png"));
+        let image = render(wheel, SIZE);
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            image.save(&path).expect("failed to write reference snapshot");
+            return;
+        }
+
+        assert!(
+            path.exists(),
+            "snapshot `{name}` has no reference at {} — if it is new, run \
+             with `UPDATE_SNAPSHOTS=1` to write it",
+            path.display()
+        );
+
+        let reference = nannou::image::open(&path)
+            .expect("failed to read reference snapshot")
+            .to_rgb8();
+        let diff = compare(&image, &reference, TOLERANCE);
+
+        assert!(
+            diff.mismatch_ratio() <= MAX_MISMATCH_RATIO,
+            "snapshot `{name}` differs from its reference: {diff:?}"
+        );
+    }
+
+    fn wheel_with_capture(
+        len: usize,
+        ops: impl FnOnce(&mut SortArray),
+    ) -> ColorWheel {
+        let mut arr = SortArray::new(len);
+        let reversed: Vec<usize> = (0..len).rev().collect();
+        arr.prepare_for_sort_with(&reversed, SortingAlgorithm::default());
+        ops(&mut arr);
+
+        let mut cap = arr.dump_capture();
+        let mut wheel = ColorWheel::new();
+        wheel.resize(len).unwrap();
+
+        wheel.set_overlay_ops(cap.set_progress(1.0));
+        wheel.apply_overlay_ops();
+        wheel.arr_mut().copy_from_slice(cap.arr());
+
+        wheel
+    }
+
+    #[test]
+    fn sorted_wheel() {
+        check("sorted_wheel", &ColorWheel::new());
+    }
+
+    #[test]
+    fn low_resolution_wheel() {
+        let mut wheel = ColorWheel::new();
+        wheel.resize(MIN_RESOLUTION).unwrap();
+
+        check("low_resolution_wheel", &wheel);
+    }
+
+    #[test]
+    fn reversed_wheel() {
+        check("reversed_wheel", &wheel_with_capture(64, |_| {}));
+    }
+
+    #[test]
+    fn overlay_wheel() {
+        let wheel = wheel_with_capture(64, |arr| {
+            arr.swap(0, 63);
+            _ = arr.cmp(10, 20, Ordering::Less);
+            _ = arr.cmp(30, 31, Ordering::Greater);
+            arr.write(40, 0);
+            _ = arr.read(50);
+        });
+
+        check("overlay_wheel", &wheel);
+    }
+
+    #[test]
+    fn write_trail_wheel() {
+        let mut arr = SortArray::new(64);
+        let reversed: Vec<usize> = (0..64).rev().collect();
+        arr.prepare_for_sort_with(&reversed, SortingAlgorithm::default());
+
+        for i in 0..16 {
+            arr.write(i, i);
+        }
+        arr.swap(4, 40);
+
+        let mut cap = arr.dump_capture();
+        let mut wheel = ColorWheel::new();
+        wheel.resize(64).unwrap();
+        wheel.set_write_trail(true);
+
+        // the trail persists after the frame's overlay has faded out.
+        wheel.set_overlay_ops(cap.set_progress(1.0));
+        wheel.apply_overlay_ops();
+        wheel.set_overlay_ops(OperationSlice::default());
+        wheel.fade_overlays(1.0);
+        wheel.apply_overlay_ops();
+        wheel.arr_mut().copy_from_slice(cap.arr());
+
+        check("write_trail_wheel", &wheel);
+    }
+
+    #[test]
+    fn duplicate_values_wheel() {
+        let mut wheel = ColorWheel::new();
+        wheel.resize(64).unwrap();
+
+        for (i, value) in wheel.arr_mut().iter_mut().enumerate() {
+            *value = (i / 8) * 8;
+        }
+
+        check("duplicate_values_wheel", &wheel);
+    }
+
+    #[test]
+    fn compare_tolerance() {
+        let a = RgbImage::from_pixel(4, 4, Pixel([100, 100, 100]));
+        let mut b = a.clone();
+        b.put_pixel(0, 0, Pixel([102, 100, 100]));
+        b.put_pixel(1, 0, Pixel([110, 100, 100]));
+
+        let diff = compare(&a, &b, TOLERANCE);
+
+        assert_eq!(diff.max_channel_diff, 10);
+        assert_eq!(diff.mismatched_pixels, 1);
+    }
+}
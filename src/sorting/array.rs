@@ -1,14 +1,157 @@
 use crate::prelude::*;
+use crossbeam_channel::unbounded;
+use std::{ops::Range, time::Instant};
+
+/// The number of operations which are buffered before being sent to a
+/// stream — see [`SortArray::start_stream()`].
+const STREAM_CHUNK_SIZE: usize = 4096;
+/// The default maximum number of operations in a computed sort — see
+/// [`SortArray::set_op_limit()`]. This is roughly 250 MB of operations.
+pub const DEFAULT_OP_LIMIT: usize = 20_000_000;
 
 /// Each kind of sorting operation.
 #[derive(Clone, Copy, Debug)]
 pub enum SortOperation {
     Write { idx: usize, value: usize },
+    /// A write to the auxiliary array — see [`SortArray::aux_write()`].
+    AuxWrite { idx: usize, value: usize },
     Read { idx: usize },
     Swap { a: usize, b: usize },
     Compare { a: usize, b: usize, res: bool },
 }
 
+impl SortOperation {
+    /// Returns the operation with all of its indices offset by `offset`.
+    #[must_use]
+    pub const fn offset(self, offset: usize) -> Self {
+        match self {
+            Self::Write { idx, value } => Self::Write { idx: idx + offset, value },
+            Self::AuxWrite { idx, value } => {
+                Self::AuxWrite { idx: idx + offset, value }
+            }
+            Self::Read { idx } => Self::Read { idx: idx + offset },
+            Self::Swap { a, b } => Self::Swap { a: a + offset, b: b + offset },
+            Self::Compare { a, b, res } => {
+                Self::Compare { a: a + offset, b: b + offset, res }
+            }
+        }
+    }
+}
+
+/// A [`SortOperation`] packed into 12 bytes rather than 24, by storing its
+/// indices and values as `u32`s. This is how operations are stored while
+/// they are recorded and played back.
+///
+/// Indices and values always fit, as arrays are at most [`MAX_RESOLUTION`]
+/// elements long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedOperation {
+    kind: u8,
+    a: u32,
+    b: u32,
+}
+
+impl PackedOperation {
+    // the kinds of each packed operation.
+    const WRITE: u8 = 0;
+    const AUX_WRITE: u8 = 1;
+    const READ: u8 = 2;
+    const SWAP: u8 = 3;
+    const COMPARE_FALSE: u8 = 4;
+    const COMPARE_TRUE: u8 = 5;
+
+    /// Unpacks the operation.
+    pub const fn unpack(self) -> SortOperation {
+        let (a, b) = (self.a as usize, self.b as usize);
+
+        match self.kind {
+            Self::WRITE => SortOperation::Write { idx: a, value: b },
+            Self::AUX_WRITE => SortOperation::AuxWrite { idx: a, value: b },
+            Self::READ => SortOperation::Read { idx: a },
+            Self::SWAP => SortOperation::Swap { a, b },
+            kind => SortOperation::Compare {
+                a,
+                b,
+                res: kind == Self::COMPARE_TRUE,
+            },
+        }
+    }
+}
+
+impl SortOperation {
+    /// Packs the operation — see [`PackedOperation`].
+    pub fn pack(self) -> PackedOperation {
+        let (kind, a, b) = match self {
+            Self::Write { idx, value } => (PackedOperation::WRITE, idx, value),
+            Self::AuxWrite { idx, value } => {
+                (PackedOperation::AUX_WRITE, idx, value)
+            }
+            Self::Read { idx } => (PackedOperation::READ, idx, 0),
+            Self::Swap { a, b } => (PackedOperation::SWAP, a, b),
+            Self::Compare { a, b, res } => (
+                if res {
+                    PackedOperation::COMPARE_TRUE
+                }
+                else {
+                    PackedOperation::COMPARE_FALSE
+                },
+                a,
+                b,
+            ),
+        };
+
+        debug_assert!(
+            a <= u32::MAX as usize && b <= u32::MAX as usize,
+            "operation too large to pack: {self:?}"
+        );
+
+        PackedOperation { kind, a: a as u32, b: b as u32 }
+    }
+}
+
+/// A position in the array which an algorithm points out while it sorts,
+/// such as the pivot of a partition — see [`SortArray::point()`]. Pointers
+/// are drawn as markers around the wheel during playback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pointer {
+    /// The pivot of a partition.
+    Pivot,
+    /// The start of the range being worked on.
+    Low,
+    /// The boundary between two parts of the range, such as two runs which
+    /// are being merged.
+    Mid,
+    /// The end of the range being worked on.
+    High,
+    /// The root of a heap.
+    Root,
+}
+
+impl Pointer {
+    pub const ALL: [Self; 5] =
+        [Self::Pivot, Self::Low, Self::Mid, Self::High, Self::Root];
+}
+
+/// The position of each [`Pointer`], in the order of [`Pointer::ALL`], where
+/// `None` is a pointer which isn't shown.
+pub type Pointers = [Option<usize>; Pointer::ALL.len()];
+
+/// A message sent from a [`SortArray`] which is streaming its operations.
+#[derive(Debug)]
+pub enum StreamMessage {
+    /// The operations recorded since the last message, along with the time
+    /// at which each was recorded if timestamps are being recorded.
+    Operations(Vec<PackedOperation>, Option<Vec<f32>>),
+    /// The verification sweep begins at this position in the stream.
+    VerifyStart(usize),
+    /// The pointers which were moved since the last message, along with the
+    /// position in the stream from which they apply.
+    Pointers(Vec<(usize, Pointers)>),
+    /// The sort has finished, and no more operations will be sent. The value
+    /// is whether the sort was stopped early by the operation limit.
+    Finished(bool),
+}
+
 #[derive(Debug)]
 pub struct SortArray {
     /// The current sorting algorithm.
@@ -21,11 +164,41 @@ pub struct SortArray {
 
     /// The buffer of operations, i.e. where the sorting operations are
     /// recorded to.
-    op_buffer: Vec<SortOperation>,
+    op_buffer: Vec<PackedOperation>,
 
     /// A counter which is passed to created `SortCapture`s to prevent
     /// unnecessary computation later.
     num_writes: usize,
+
+    /// The position in the operation buffer where the verification sweep
+    /// begins, if one has been recorded.
+    verify_start: Option<usize>,
+
+    /// The time at which each operation in the buffer was recorded, in
+    /// seconds since the sort began, if timestamps are being recorded.
+    timestamps: Option<Vec<f32>>,
+    /// The time at which the current sort began.
+    start_time: Instant,
+
+    /// Where operations are sent while they are being streamed.
+    stream: Option<Sender<StreamMessage>>,
+    /// The number of operations which have been sent to the stream, and are
+    /// no longer in the operation buffer.
+    num_streamed: usize,
+
+    /// The current position of each pointer.
+    pointers: Pointers,
+    /// The state of the pointers whenever they are moved, along with the
+    /// position in the operation buffer from which they apply. Like the
+    /// operations, these are removed once they are sent to the stream.
+    pointer_events: Vec<(usize, Pointers)>,
+
+    /// The token which cancels the current sort, if it can be cancelled.
+    cancel_token: Option<CancelToken>,
+    /// The maximum number of operations in a sort, if there is a limit.
+    op_limit: Option<usize>,
+    /// Whether the current sort was stopped by reaching the operation limit.
+    truncated: bool,
 }
 
 impl SortArray {
@@ -36,6 +209,16 @@ impl SortArray {
             initial_arr: (0..len).collect(),
             op_buffer: vec![],
             num_writes: 0,
+            verify_start: None,
+            timestamps: None,
+            start_time: Instant::now(),
+            stream: None,
+            num_streamed: 0,
+            pointers: Pointers::default(),
+            pointer_events: vec![],
+            cancel_token: None,
+            op_limit: None,
+            truncated: false,
         }
     }
 
@@ -61,6 +244,21 @@ impl SortArray {
         self.num_writes += 1;
     }
 
+    /// Records a write of `value` to position `idx` in the auxiliary array,
+    /// which has the same length as the main array. This doesn't affect the
+    /// main array — algorithms keep their own scratch buffers, and use this
+    /// so that writes to them can be visualized.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `idx >= `[`SortArray::len()`].
+    pub fn aux_write(&mut self, idx: usize, value: usize) {
+        assert!(idx < self.len(), "auxiliary index out of bounds");
+
+        self.push(SortOperation::AuxWrite { idx, value });
+        self.num_writes += 1;
+    }
+
     /// Returns the value at position `idx` in the array.
     ///
     /// # Panics
@@ -130,6 +328,11 @@ impl SortArray {
         self.arr.len()
     }
 
+    /// The state of the array before the current sort.
+    pub fn initial_arr(&self) -> &[usize] {
+        &self.initial_arr
+    }
+
     /// Copies the internal array to `dest`.
     ///
     /// # Panics
@@ -145,8 +348,96 @@ impl SortArray {
         dest.copy_from_slice(&self.arr);
     }
 
+    /// Records a linear "verification" sweep across the array, which compares
+    /// each pair of adjacent elements. The result of each comparison is `true`
+    /// if the pair is in order.
+    ///
+    /// This is not intended to be used by sorting algorithms — it is appended
+    /// after an algorithm has finished.
+    pub fn verify(&mut self) {
+        self.verify_range(0..self.len());
+    }
+
+    /// Records a verification sweep (see [`Self::verify()`]) across the
+    /// elements in `range` only.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn verify_range(&mut self, range: Range<usize>) {
+        assert!(range.end <= self.len(), "Range out of bounds");
+
+        let verify_start = self.num_streamed + self.op_buffer.len();
+        self.verify_start = Some(verify_start);
+
+        if let Some(stream) = self.stream.as_ref() {
+            _ = stream.send(StreamMessage::VerifyStart(verify_start));
+        }
+
+        for i in range.start..range.end.saturating_sub(1) {
+            let res = self.arr[i] <= self.arr[i + 1];
+            self.push(SortOperation::Compare { a: i, b: i + 1, res });
+        }
+    }
+
     // MANAGEMENT METHODS
 
+    /// Sets whether the time of each operation is recorded, which allows
+    /// captures to be played back at the pace they were computed at. This
+    /// takes effect from the next call to [`Self::prepare_for_sort()`].
+    pub fn set_record_timestamps(&mut self, record: bool) {
+        if record != self.timestamps.is_some() {
+            self.timestamps = record.then(Vec::new);
+        }
+    }
+
+    /// Sets the token which cancels sorts on this array. Once it has been
+    /// cancelled, the next operation unwinds with a [`Cancelled`] payload.
+    ///
+    /// Sub-arrays created with [`Self::sub_array()`] share the token.
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) {
+        self.cancel_token = token;
+    }
+
+    /// The token which cancels sorts on this array, if there is one.
+    pub const fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel_token.as_ref()
+    }
+
+    /// Sets the maximum number of operations which may be recorded in a sort,
+    /// so that long-running algorithms can't use up all of the memory. Once
+    /// the limit is reached, the sort is stopped in the same way as if it had
+    /// been cancelled (see [`Self::set_cancel_token()`]), and is marked as
+    /// truncated.
+    ///
+    /// The verification sweep (see [`Self::verify()`]) isn't held to the
+    /// limit, as it is only as long as the array.
+    ///
+    /// Sub-arrays created with [`Self::sub_array()`] are limited to the
+    /// operations which remain under the limit.
+    pub fn set_op_limit(&mut self, limit: Option<usize>) {
+        self.op_limit = limit;
+    }
+
+    /// The maximum number of operations in a sort, if there is a limit — see
+    /// [`Self::set_op_limit()`].
+    pub const fn op_limit(&self) -> Option<usize> {
+        self.op_limit
+    }
+
+    /// The number of operations which can still be recorded before the
+    /// operation limit is reached, if there is a limit.
+    pub fn remaining_ops(&self) -> Option<usize> {
+        let num_ops = self.num_streamed + self.op_buffer.len();
+
+        self.op_limit.map(|limit| limit.saturating_sub(num_ops))
+    }
+
+    /// Whether the current sort was stopped by reaching the operation limit.
+    pub const fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
     /// Prepares the array for sorting, using its current state as the initial
     /// array.
     pub fn prepare_for_sort(&mut self, algorithm: SortingAlgorithm) {
@@ -154,6 +445,17 @@ impl SortArray {
         self.initial_arr = self.arr.clone();
         self.op_buffer.clear();
         self.num_writes = 0;
+        self.verify_start = None;
+        self.start_time = Instant::now();
+        self.stream = None;
+        self.num_streamed = 0;
+        self.pointers = Pointers::default();
+        self.pointer_events.clear();
+        self.truncated = false;
+
+        if let Some(timestamps) = self.timestamps.as_mut() {
+            timestamps.clear();
+        }
     }
 
     /// Prepares the array for sorting, using the provided slice as the initial
@@ -178,12 +480,169 @@ impl SortArray {
     pub fn dump_capture(&mut self) -> SortCapture {
         use std::mem::take;
 
-        SortCapture::create(
+        // the operations of sub-arrays are interleaved when they are merged,
+        // so their timestamps may be out of order.
+        let timestamps = self.timestamps.as_mut().map(|timestamps| {
+            let mut timestamps = take(timestamps);
+            let mut latest = 0.0_f32;
+
+            for time in &mut timestamps {
+                latest = latest.max(*time);
+                *time = latest;
+            }
+
+            timestamps
+        });
+
+        let mut capture = SortCapture::create(
             take(&mut self.initial_arr),
-            Arc::new(take(&mut self.op_buffer).into_boxed_slice()),
+            self.arr.clone(),
+            take(&mut self.op_buffer),
             self.curr_algorithm,
             self.num_writes,
-        )
+            self.verify_start.take(),
+            timestamps,
+        );
+        capture.append_pointers(&take(&mut self.pointer_events));
+
+        capture
+    }
+
+    /// Starts streaming the operations of the current sort, so that they can
+    /// be played back while the sort is still being computed. This should be
+    /// called after [`Self::prepare_for_sort()`].
+    ///
+    /// Returns an empty capture of the sort, which is completed by the
+    /// messages received from the returned channel (see
+    /// [`SortCapture::append_operations()`]). Operations are sent in chunks
+    /// as they are recorded, and the stream ends with
+    /// [`StreamMessage::Finished`] once [`Self::finish_stream()`] is called.
+    pub fn start_stream(&mut self) -> (SortCapture, Receiver<StreamMessage>) {
+        // the stream is unbounded so that the sort never waits on the
+        // receiver, which may itself be waiting for the sort to finish.
+        let (tx, rx) = unbounded();
+
+        self.flush_stream();
+        self.stream = Some(tx);
+
+        let capture = SortCapture::streaming(
+            self.initial_arr.clone(),
+            self.curr_algorithm,
+            self.timestamps.is_some(),
+        );
+
+        (capture, rx)
+    }
+
+    /// Sends any remaining operations to the stream and ends it. Does nothing
+    /// if the operations are not being streamed.
+    pub fn finish_stream(&mut self) {
+        self.flush_stream();
+
+        if let Some(stream) = self.stream.take() {
+            _ = stream.send(StreamMessage::Finished(self.truncated));
+        }
+    }
+
+    /// Creates a new, separate `SortArray` from the elements in `range`. This
+    /// is used to sort parts of the array on other threads — see
+    /// [`Self::merge_sub_arrays()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn sub_array(&self, range: Range<usize>) -> Self {
+        let mut sub = Self::new(range.len());
+        sub.set_record_timestamps(self.timestamps.is_some());
+        sub.set_cancel_token(self.cancel_token.clone());
+        sub.set_op_limit(self.remaining_ops());
+        sub.prepare_for_sort_with(&self.arr[range], self.curr_algorithm);
+        sub
+    }
+
+    /// Merges the results of sub-arrays created via [`Self::sub_array()`],
+    /// where each sub-array is paired with its start position in this array.
+    ///
+    /// The operations of each sub-array are interleaved one at a time in the
+    /// order they are provided, so the sub-arrays appear to be processed in
+    /// parallel while the merged operations remain deterministic. They are
+    /// recorded as any other operation, so the merge stops if the sort is
+    /// cancelled or reaches the operation limit, with the array left as it
+    /// was at that point. If any sub-array was stopped by the limit, this
+    /// array is marked as truncated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any sub-array does not fit in this array at its position.
+    ///
+    /// Pointers moved by the sub-arrays are moved at the matching point in
+    /// the merged operations, so the pointers shown are those of whichever
+    /// sub-array moved them last.
+    pub fn merge_sub_arrays(&mut self, sub_arrays: Vec<(usize, Self)>) {
+        let max_ops =
+            sub_arrays.iter().map(|(_, sub)| sub.op_buffer.len()).max();
+        // the next pointer event of each sub-array.
+        let mut next_events = vec![0; sub_arrays.len()];
+
+        for (_, sub) in &sub_arrays {
+            self.truncated |= sub.truncated;
+        }
+
+        for i in 0..=max_ops.unwrap_or(0) {
+            for ((start, sub), next) in sub_arrays.iter().zip(&mut next_events)
+            {
+                for &(_, pointers) in sub.pointer_events[*next..]
+                    .iter()
+                    .take_while(|(position, _)| *position <= i)
+                {
+                    self.set_pointers(pointers.map(|p| p.map(|p| p + start)));
+                    *next += 1;
+                }
+
+                let Some(&op) = sub.op_buffer.get(i) else {
+                    continue;
+                };
+
+                // sub-array timestamps are relative to when each sub-array
+                // was created.
+                let offset = sub
+                    .start_time
+                    .saturating_duration_since(self.start_time)
+                    .as_secs_f32();
+                let time = sub.timestamps.as_ref().map_or(0.0, |t| t[i]);
+
+                // the operations are replayed on this array, so that it
+                // matches them even if the merge is stopped part-way.
+                let op = op.unpack().offset(*start);
+                self.push_timed(op, offset + time);
+
+                match op {
+                    SortOperation::Write { idx, value } => {
+                        self.arr[idx] = value;
+                        self.num_writes += 1;
+                    }
+                    SortOperation::AuxWrite { .. } => self.num_writes += 1,
+                    SortOperation::Swap { a, b } => self.arr.swap(a, b),
+                    SortOperation::Read { .. }
+                    | SortOperation::Compare { .. } => {}
+                }
+            }
+        }
+
+        for (start, sub) in &sub_arrays {
+            let range = *start..start + sub.len();
+            debug_assert_eq!(self.arr[range], sub.arr[..]);
+        }
+
+        self.flush_stream();
+    }
+
+    /// Points `pointer` at `idx`, or hides it if `idx` is `None`. This
+    /// isn't an operation, so it is free and doesn't affect the array.
+    pub fn point(&mut self, pointer: Pointer, idx: Option<usize>) {
+        let mut pointers = self.pointers;
+        pointers[pointer as usize] = idx;
+        self.set_pointers(pointers);
     }
 
     /// Resizes the sorting array.
@@ -191,17 +650,23 @@ impl SortArray {
         self.arr = (0..new_size).collect();
         self.initial_arr = (0..new_size).collect();
         self.op_buffer.clear();
+        self.pointer_events.clear();
+
+        if let Some(timestamps) = self.timestamps.as_mut() {
+            timestamps.clear();
+        }
     }
 
     /// Force-sorts the array.
     pub fn force_sort(&mut self) {
-        self.arr.iter_mut().enumerate().for_each(|(i, x)| *x = i);
+        self.arr.sort_unstable();
         self.initial_arr.copy_from_slice(&self.arr);
     }
 
-    /// Whether the array is currently sorted.
+    /// Whether the array is currently sorted. The array does not need to be a
+    /// permutation of `0..len`, and may contain duplicate values.
     pub fn is_sorted(&self) -> bool {
-        self.arr.iter().enumerate().all(|(i, &v)| i == v)
+        self.arr.is_sorted()
     }
 
     /// Returns the array as a slice.
@@ -215,6 +680,80 @@ impl SortArray {
     }
 
     fn push(&mut self, op: SortOperation) {
-        self.op_buffer.push(op);
+        self.push_timed(op, self.start_time.elapsed().as_secs_f32());
+    }
+
+    /// Records `op` as if it happened `time` seconds into the sort.
+    fn push_timed(&mut self, op: SortOperation, time: f32) {
+        if self.cancel_token.as_ref().is_some_and(CancelToken::is_cancelled) {
+            std::panic::resume_unwind(Box::new(Cancelled));
+        }
+
+        // the verification sweep comes after the sort has finished.
+        let verifying = self.verify_start.is_some();
+        if !verifying && self.remaining_ops() == Some(0) {
+            self.truncated = true;
+            std::panic::resume_unwind(Box::new(Cancelled));
+        }
+
+        self.op_buffer.push(op.pack());
+
+        if let Some(timestamps) = self.timestamps.as_mut() {
+            timestamps.push(time);
+        }
+
+        if self.stream.is_some() && self.op_buffer.len() >= STREAM_CHUNK_SIZE {
+            self.flush_stream();
+        }
+    }
+
+    /// Records the position of every pointer from the next operation onwards.
+    fn set_pointers(&mut self, pointers: Pointers) {
+        if pointers == self.pointers {
+            return;
+        }
+
+        let position = self.num_streamed + self.op_buffer.len();
+        self.pointers = pointers;
+
+        // pointers which are moved between the same two operations are never
+        // seen, so only the last move is kept.
+        match self.pointer_events.last_mut() {
+            Some(event) if event.0 == position => event.1 = pointers,
+            _ => self.pointer_events.push((position, pointers)),
+        }
+    }
+
+    /// Sends the buffered operations and pointers to the stream, if there is
+    /// one.
+    fn flush_stream(&mut self) {
+        use std::mem::{replace, take};
+
+        let Some(stream) = self.stream.as_ref() else {
+            return;
+        };
+
+        if !self.pointer_events.is_empty() {
+            let events = take(&mut self.pointer_events);
+            _ = stream.send(StreamMessage::Pointers(events));
+        }
+
+        if self.op_buffer.is_empty() {
+            return;
+        }
+
+        let ops = replace(
+            &mut self.op_buffer,
+            Vec::with_capacity(STREAM_CHUNK_SIZE),
+        );
+        let timestamps = self.timestamps.as_mut().map(take);
+
+        self.num_streamed += ops.len();
+
+        // if the receiver has gone (e.g. because the capture was cleared),
+        // then there's nothing left to stream to.
+        if stream.send(StreamMessage::Operations(ops, timestamps)).is_err() {
+            self.stream = None;
+        }
     }
 }
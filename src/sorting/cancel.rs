@@ -0,0 +1,38 @@
+use crate::prelude::*;
+
+/// A token which is used to cancel a computation from another thread.
+///
+/// Once the token is cancelled, the next operation on a [`SortArray`] holding
+/// it (see [`SortArray::set_cancel_token()`]) unwinds out of the algorithm
+/// with a [`Cancelled`] payload, which is caught by the
+/// [`Process`](crate::process::Process).
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the computation.
+    pub fn cancel(&self) {
+        self.0.store(true, Relaxed);
+    }
+
+    /// Whether the computation has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Relaxed)
+    }
+
+    /// Whether `other` is a clone of this token, i.e. whether it cancels the
+    /// same computation.
+    pub fn is_same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The payload which a cancelled computation unwinds with, and the error
+/// returned once it has been caught. Computations which reach their operation
+/// limit (see [`SortArray::set_op_limit()`]) are stopped in the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
@@ -1,17 +1,83 @@
 use crate::prelude::*;
+use std::ops::{Deref, Range};
+use SortingAlgorithm as SA;
 
-#[derive(Clone, Copy, Debug, Default)]
+/// The first bytes of every serialized capture.
+const MAGIC: &[u8; 4] = b"SCAP";
+/// The version of the serialization format. This must be incremented whenever
+/// the format changes, and older versions should still be readable — see
+/// [`SortCapture::deserialize()`].
+///
+/// - Version 1 stored the algorithm as its position in [`V1_ALGORITHMS`].
+/// - Version 2 added the version of the app which saved the capture, and
+///   stores the algorithm by name.
+/// - Version 3 added markers.
+/// - Version 4 added pointers.
+const FORMAT_VERSION: u8 = 4;
+
+/// The algorithms in the order they were stored in version 1 of the format.
+/// This must never change.
+const V1_ALGORITHMS: [SortingAlgorithm; 35] = [
+    SA::Bogo, SA::Stooge, SA::Gnome, SA::Bubble, SA::Selection, SA::Insertion,
+    SA::Pancake, SA::Shell, SA::Comb, SA::Cocktail, SA::Bingo, SA::Cycle,
+    SA::Counting, SA::Pigeonhole, SA::Merge, SA::Heap, SA::Timsort,
+    SA::QuickSort, SA::ParallelQuickSort, SA::RadixLSD2, SA::RadixLSD5,
+    SA::RadixLSD10, SA::RadixLSD32, SA::RadixLSD1000, SA::InPlaceRadixLSD2,
+    SA::InPlaceRadixLSD10, SA::InPlaceRadixLSD32, SA::InPlaceRadixLSD1000,
+    SA::RadixMSD2, SA::RadixMSD10, SA::RadixMSD32, SA::RadixMSD1000, SA::Sleep,
+    SA::FisherYates, SA::Shuffle,
+];
+
+// the tags of each kind of operation in the serialization format.
+const OP_WRITE: u8 = 0;
+const OP_AUX_WRITE: u8 = 1;
+const OP_READ: u8 = 2;
+const OP_SWAP: u8 = 3;
+const OP_COMPARE_FALSE: u8 = 4;
+const OP_COMPARE_TRUE: u8 = 5;
+
+/// Errors which may occur when deserializing a [`SortCapture`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The data does not start with the capture header.
+    InvalidHeader,
+    /// The data was serialized with a newer version of the format than this
+    /// version of the app supports.
+    UnsupportedVersion(u8),
+    /// The data ended unexpectedly.
+    UnexpectedEnd,
+    /// An integer is too large to be represented.
+    InvalidInteger,
+    /// A string is not valid UTF-8.
+    InvalidString,
+    /// The algorithm is not recognized (in version 1 of the format).
+    InvalidAlgorithm(u8),
+    /// The algorithm is not recognized.
+    UnknownAlgorithm(String),
+    /// An operation has an unrecognized tag.
+    InvalidOperation(u8),
+    /// An operation or position refers to an element outside of the array,
+    /// a value is too large to be stored, or the array's length isn't a
+    /// valid resolution.
+    OutOfBounds,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct SortData {
     pub reads: usize,
     pub comparisons: usize,
     pub writes: usize,
     pub swaps: usize,
+    /// Whether the sort was stopped early because it reached the operation
+    /// limit — see [`SortArray::set_op_limit()`]. This isn't affected by
+    /// playback.
+    pub truncated: bool,
 }
 
 impl SortData {
     pub fn update(&mut self, op: SortOperation, rewind: bool) {
         match op {
-            SortOperation::Write { .. } => {
+            SortOperation::Write { .. } | SortOperation::AuxWrite { .. } => {
                 if rewind {
                     self.writes -= 1;
                 }
@@ -54,26 +120,90 @@ impl SortData {
     }
 }
 
+/// A range of the operations in a [`SortCapture`], which shares the capture's
+/// buffer rather than copying it. This lets the operations played back each
+/// frame be passed to the audio thread and the wheel without allocating.
+///
+/// Dereferences to the operations in the range.
+#[derive(Clone, Debug, Default)]
+pub struct OperationSlice {
+    buffer: Arc<Vec<PackedOperation>>,
+    range: Range<usize>,
+}
+
+impl OperationSlice {
+    /// Whether `self` and `other` are the same range of the same buffer, such
+    /// as clones of one slice. This doesn't compare the operations.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.buffer, &other.buffer) && self.range == other.range
+    }
+}
+
+impl Deref for OperationSlice {
+    type Target = [PackedOperation];
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer[self.range.clone()]
+    }
+}
+
+impl FromIterator<PackedOperation> for OperationSlice {
+    fn from_iter<I: IntoIterator<Item = PackedOperation>>(iter: I) -> Self {
+        let buffer: Vec<_> = iter.into_iter().collect();
+        let range = 0..buffer.len();
+
+        Self { buffer: Arc::new(buffer), range }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SortCapture {
-    ///  The initial state of the array.
-    // initial_array: Vec<usize>,
-    /// The list of operations.
-    operations: Arc<Box<[SortOperation]>>,
+    /// The initial state of the array.
+    initial_arr: Vec<usize>,
+    /// The list of operations, which is shared with any
+    /// [`OperationSlice`]s of the capture.
+    operations: Arc<Vec<PackedOperation>>,
+    /// The time at which each operation was recorded, in seconds since the
+    /// sort began, if timestamps were recorded. These never decrease.
+    timestamps: Option<Vec<f32>>,
     /// A stack of written values, used to undo any previous write operations.
     write_stack: Vec<usize>,
 
     /// The scratch buffer, used to perform the operations.
     scratch: Vec<usize>,
+    /// The state of the array once all of the operations have been performed.
+    final_arr: Vec<usize>,
+    /// Whether all of the operations have been recorded. This is only `false`
+    /// while the operations are being streamed in — see
+    /// [`Self::streaming()`].
+    complete: bool,
+    /// The auxiliary array, where `None` is a position which hasn't been
+    /// written to.
+    aux: Vec<Option<usize>>,
+    /// A stack of written auxiliary values, used to undo auxiliary writes.
+    aux_write_stack: Vec<Option<usize>>,
+    /// Whether any of the operations write to the auxiliary array.
+    uses_aux: bool,
 
     /// The algorithm used for this sort.
     algorithm: SortingAlgorithm,
+    /// The position in the operation buffer where the verification sweep
+    /// begins, if there is one.
+    verify_start: Option<usize>,
 
     /// The current position in the operation buffer.
     cursor: usize,
     /// The previous position in the operation buffer.
     cursor_last: usize,
 
+    /// Positions in the operation buffer which have been marked, in
+    /// ascending order — see [`Self::toggle_marker()`].
+    markers: Vec<usize>,
+    /// The state of the pointers whenever they were moved, along with the
+    /// position in the operation buffer from which they apply, in ascending
+    /// order of position — see [`SortArray::point()`].
+    pointers: Vec<(usize, Pointers)>,
+
     pub data: SortData,
 }
 
@@ -81,34 +211,212 @@ impl SortCapture {
     /// Creates a new `SortCapture`.
     pub fn create(
         init_arr: Vec<usize>,
-        operations: Arc<Box<[SortOperation]>>,
+        final_arr: Vec<usize>,
+        operations: Vec<PackedOperation>,
         algorithm: SortingAlgorithm,
         num_writes: usize,
+        verify_start: Option<usize>,
+        timestamps: Option<Vec<f32>>,
     ) -> Self {
+        let uses_aux = operations
+            .iter()
+            .any(|op| matches!(op.unpack(), SortOperation::AuxWrite { .. }));
+
         Self {
-            // initial_array: init_arr.clone(),
-            operations,
+            initial_arr: init_arr.clone(),
+            operations: Arc::new(operations),
+            timestamps,
             write_stack: Vec::with_capacity(num_writes),
 
             scratch: init_arr,
+            aux: vec![None; final_arr.len()],
+            aux_write_stack: vec![],
+            uses_aux,
+            final_arr,
+            complete: true,
 
             algorithm,
+            verify_start,
 
             cursor: 0,
             cursor_last: 0,
 
+            markers: vec![],
+            pointers: vec![],
+
             data: SortData::default(),
         }
     }
 
+    /// Creates an empty `SortCapture` for a sort which is still being
+    /// computed, starting from `init_arr`. Its operations are added with
+    /// [`Self::append_operations()`] as they are streamed in, until
+    /// [`Self::finish()`] is called.
+    pub fn streaming(
+        init_arr: Vec<usize>,
+        algorithm: SortingAlgorithm,
+        timestamps: bool,
+    ) -> Self {
+        let mut capture = Self::create(
+            init_arr.clone(),
+            init_arr,
+            vec![],
+            algorithm,
+            0,
+            None,
+            timestamps.then(Vec::new),
+        );
+
+        capture.complete = false;
+        capture
+    }
+
+    /// Appends streamed operations to the capture, along with the time at
+    /// which each was recorded (if timestamps are being recorded).
+    pub fn append_operations(
+        &mut self,
+        ops: &[PackedOperation],
+        timestamps: Option<&[f32]>,
+    ) {
+        for op in ops {
+            match op.unpack() {
+                SortOperation::Write { idx, value } => {
+                    self.final_arr[idx] = value;
+                }
+                SortOperation::AuxWrite { .. } => self.uses_aux = true,
+                SortOperation::Swap { a, b } => self.final_arr.swap(a, b),
+                _ => {}
+            }
+        }
+
+        // slices aren't shared while the capture is incomplete (see
+        // `Self::slice()`), so the buffer is never copied here.
+        Arc::make_mut(&mut self.operations).extend_from_slice(ops);
+
+        // the operations of sub-arrays are interleaved when they are merged,
+        // so their timestamps may be out of order.
+        if let (Some(all), Some(new)) = (self.timestamps.as_mut(), timestamps) {
+            let mut latest = all.last().copied().unwrap_or(0.0);
+
+            all.extend(new.iter().map(|&time| {
+                latest = latest.max(time);
+                latest
+            }));
+        }
+    }
+
+    /// Appends moved pointers to the capture, each with the position in the
+    /// operation buffer from which it applies. These must not come before
+    /// any pointers which were already added.
+    pub fn append_pointers(&mut self, pointers: &[(usize, Pointers)]) {
+        for &(position, state) in pointers {
+            match self.pointers.last_mut() {
+                Some(last) if last.0 == position => last.1 = state,
+                _ => self.pointers.push((position, state)),
+            }
+        }
+    }
+
+    /// Returns the position of each pointer for the most recently performed
+    /// operation. No pointers are shown before the first operation, once
+    /// playback has finished, or during the verification sweep.
+    pub fn pointers(&self) -> Pointers {
+        let sorting = self.verify_start.unwrap_or(self.operations.len());
+
+        if self.cursor == 0 || self.cursor > sorting || self.is_done() {
+            return Pointers::default();
+        }
+
+        let idx = self.pointers.partition_point(|&(p, _)| p < self.cursor);
+        idx.checked_sub(1)
+            .map_or_else(Pointers::default, |i| self.pointers[i].1)
+    }
+
+    /// Sets the position in the operation buffer where the verification sweep
+    /// begins.
+    pub fn set_verify_start(&mut self, verify_start: Option<usize>) {
+        self.verify_start = verify_start;
+    }
+
+    /// Marks the capture as complete, once all of its operations have been
+    /// streamed in. `truncated` is whether the sort reached the operation
+    /// limit.
+    pub fn finish(&mut self, truncated: bool) {
+        self.complete = true;
+        self.data.truncated = truncated;
+    }
+
+    /// Whether all of the operations have been recorded, i.e. the capture is
+    /// not still being streamed in.
+    pub const fn is_complete(&self) -> bool {
+        self.complete
+    }
+
     /// The algorithm used for this sort.
     pub const fn algorithm(&self) -> SortingAlgorithm {
         self.algorithm
     }
 
+    /// The auxiliary array, if any of the operations write to it.
+    pub fn aux_arr(&self) -> Option<&[Option<usize>]> {
+        self.uses_aux.then_some(self.aux.as_slice())
+    }
+
+    /// The state of the array before any of the operations.
+    pub fn initial_arr(&self) -> &[usize] {
+        &self.initial_arr
+    }
+
+    /// The state of the array once playback has finished. For incomplete
+    /// captures, this is the state after the operations recorded so far.
+    pub fn final_arr(&self) -> &[usize] {
+        &self.final_arr
+    }
+
+    /// The position in the operation buffer where the verification sweep
+    /// begins, if there is one.
+    pub const fn verify_start(&self) -> Option<usize> {
+        self.verify_start
+    }
+
+    /// Adds a marker at the current playback position, or removes the marker
+    /// which is already there. Returns whether a marker was added.
+    pub fn toggle_marker(&mut self) -> bool {
+        match self.markers.binary_search(&self.cursor) {
+            Ok(idx) => {
+                self.markers.remove(idx);
+                false
+            }
+            Err(idx) => {
+                self.markers.insert(idx, self.cursor);
+                true
+            }
+        }
+    }
+
+    /// Removes every marker.
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+    }
+
+    /// The marked positions in the operation buffer, in ascending order.
+    pub fn markers(&self) -> &[usize] {
+        &self.markers
+    }
+
+    /// The position of the first marker after the current playback position.
+    pub fn next_marker(&self) -> Option<usize> {
+        self.markers.iter().copied().find(|&m| m > self.cursor)
+    }
+
+    /// The position of the last marker before the current playback position.
+    pub fn previous_marker(&self) -> Option<usize> {
+        self.markers.iter().copied().rev().find(|&m| m < self.cursor)
+    }
+
     /// The operation at the current playback position.
     pub fn current_operation(&self) -> SortOperation {
-        self.operations[self.cursor]
+        self.operations[self.cursor].unpack()
     }
 
     /// The internal array.
@@ -119,24 +427,295 @@ impl SortCapture {
     /// The number of elements in the array.
     pub fn len(&self) -> usize {
         self.scratch.len()
-        // self.initial_array.len()
     }
 
-    /// Whether the array is currently sorted.
+    /// Whether the array is currently sorted. The array does not need to be a
+    /// permutation of `0..len`, and may contain duplicate values.
     pub fn is_sorted(&self) -> bool {
-        self.scratch.iter().enumerate().all(|(i, &val)| i == val)
+        self.scratch.is_sorted()
     }
 
-    /// (unimplemented)
-    pub fn serialize(&self) {
-        unimplemented!();
+    /// Serializes the capture into a compact binary format, which can be
+    /// loaded with [`Self::deserialize()`].
+    ///
+    /// Only the initial array, algorithm, operations, any timestamps, the
+    /// markers and the pointers are stored — everything else is derived from
+    /// them when loading. Indices and values are stored as variable-length
+    /// integers. The version of the
+    /// app is stored too, so that captures from newer versions can be
+    /// flagged.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            MAGIC.len() + self.len() * 2 + self.operations.len() * 3,
+        );
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        write_string(&mut bytes, APP_VERSION);
+        write_string(&mut bytes, &self.algorithm.name());
+
+        write_varint(&mut bytes, self.len());
+        for &value in &self.initial_arr {
+            write_varint(&mut bytes, value);
+        }
+
+        // the start of the verification sweep is offset by one, so that zero
+        // can mean that there isn't one.
+        write_varint(&mut bytes, self.verify_start.map_or(0, |i| i + 1));
+
+        write_varint(&mut bytes, self.operations.len());
+        for op in self.operations.iter() {
+            let (tag, a, b) = match op.unpack() {
+                SortOperation::Write { idx, value } => (OP_WRITE, idx, value),
+                SortOperation::AuxWrite { idx, value } => {
+                    (OP_AUX_WRITE, idx, value)
+                }
+                SortOperation::Read { idx } => (OP_READ, idx, 0),
+                SortOperation::Swap { a, b } => (OP_SWAP, a, b),
+                SortOperation::Compare { a, b, res } => {
+                    (if res { OP_COMPARE_TRUE } else { OP_COMPARE_FALSE }, a, b)
+                }
+            };
+
+            bytes.push(tag);
+            write_varint(&mut bytes, a);
+
+            if tag != OP_READ {
+                write_varint(&mut bytes, b);
+            }
+        }
+
+        match &self.timestamps {
+            Some(timestamps) => {
+                bytes.push(1);
+                for time in timestamps.iter() {
+                    bytes.extend_from_slice(&time.to_le_bytes());
+                }
+            }
+            None => bytes.push(0),
+        }
+
+        write_varint(&mut bytes, self.markers.len());
+        for &marker in &self.markers {
+            write_varint(&mut bytes, marker);
+        }
+
+        // hidden pointers are stored as zero, and the rest are offset by one.
+        write_varint(&mut bytes, self.pointers.len());
+        for (position, pointers) in &self.pointers {
+            write_varint(&mut bytes, *position);
+
+            for pointer in pointers {
+                write_varint(&mut bytes, pointer.map_or(0, |i| i + 1));
+            }
+        }
+
+        bytes
     }
 
-    /// Whether the capture has finished playback or not.
+    /// Deserializes a capture which was serialized with
+    /// [`Self::serialize()`]. The capture starts at the beginning of playback.
+    ///
+    /// Captures saved with older versions of the format are migrated, and
+    /// are returned along with warnings describing what happened. Captures
+    /// saved by newer versions of the app are loaded if their format is
+    /// supported, also with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` is not a valid capture — see
+    /// [`DeserializeError`].
+    pub fn deserialize(
+        bytes: &[u8],
+    ) -> Result<(Self, Vec<String>), DeserializeError> {
+        let mut reader = Reader { bytes };
+        let mut warnings = vec![];
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DeserializeError::InvalidHeader);
+        }
+
+        let version = reader.byte()?;
+
+        let algorithm = match version {
+            1 => {
+                warnings.push(String::from(
+                    "the capture was saved in an old format (version 1), and \
+                     was migrated — save it again to upgrade it",
+                ));
+
+                let algorithm = reader.byte()?;
+                *V1_ALGORITHMS
+                    .get(algorithm as usize)
+                    .ok_or(DeserializeError::InvalidAlgorithm(algorithm))?
+            }
+            2..=FORMAT_VERSION => {
+                let app_version = reader.string()?;
+
+                if is_newer_version(&app_version, APP_VERSION) {
+                    warnings.push(format!(
+                        "the capture was saved by a newer version of the app \
+                         ({app_version}, this is {APP_VERSION})"
+                    ));
+                }
+
+                let name = reader.string()?;
+                SortingAlgorithm::from_name(&name)
+                    .ok_or(DeserializeError::UnknownAlgorithm(name))?
+            }
+            _ => return Err(DeserializeError::UnsupportedVersion(version)),
+        };
+
+        let len = reader.len()?;
+        if !(MIN_RESOLUTION..=MAX_RESOLUTION).contains(&len) {
+            return Err(DeserializeError::OutOfBounds);
+        }
+
+        // operations are packed into 32 bits (see `PackedOperation`), so
+        // larger values can't be stored.
+        let value = |reader: &mut Reader| {
+            let value = reader.varint()?;
+            u32::try_from(value)
+                .map(|_| value)
+                .map_err(|_| DeserializeError::OutOfBounds)
+        };
+
+        let init_arr = (0..len)
+            .map(|_| value(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let verify_start = reader.varint()?.checked_sub(1);
+
+        let num_ops = reader.len()?;
+        let index = |reader: &mut Reader| {
+            let idx = reader.varint()?;
+            (idx < len).then_some(idx).ok_or(DeserializeError::OutOfBounds)
+        };
+
+        // the final state of the array isn't stored, so the operations are
+        // performed as they are read.
+        let mut final_arr = init_arr.clone();
+        let mut num_writes = 0;
+        let mut operations = Vec::with_capacity(num_ops);
+
+        for _ in 0..num_ops {
+            let op = match reader.byte()? {
+                OP_WRITE => {
+                    let idx = index(&mut reader)?;
+                    let value = value(&mut reader)?;
+                    final_arr[idx] = value;
+                    num_writes += 1;
+
+                    SortOperation::Write { idx, value }
+                }
+                OP_AUX_WRITE => {
+                    let idx = index(&mut reader)?;
+                    num_writes += 1;
+
+                    SortOperation::AuxWrite { idx, value: value(&mut reader)? }
+                }
+                OP_READ => SortOperation::Read { idx: index(&mut reader)? },
+                OP_SWAP => {
+                    let (a, b) = (index(&mut reader)?, index(&mut reader)?);
+                    final_arr.swap(a, b);
+
+                    SortOperation::Swap { a, b }
+                }
+                tag @ (OP_COMPARE_FALSE | OP_COMPARE_TRUE) => {
+                    SortOperation::Compare {
+                        a: index(&mut reader)?,
+                        b: index(&mut reader)?,
+                        res: tag == OP_COMPARE_TRUE,
+                    }
+                }
+                tag => return Err(DeserializeError::InvalidOperation(tag)),
+            };
+
+            operations.push(op.pack());
+        }
+
+        if verify_start.is_some_and(|i| i > num_ops) {
+            return Err(DeserializeError::OutOfBounds);
+        }
+
+        let timestamps = match reader.byte()? {
+            0 => None,
+            _ => Some(
+                (0..num_ops)
+                    .map(|_| reader.f32())
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        };
+
+        // markers were added in version 3.
+        let mut markers = if version >= 3 {
+            (0..reader.len()?)
+                .map(|_| reader.varint())
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        else {
+            vec![]
+        };
+
+        if markers.iter().any(|&m| m > num_ops) {
+            return Err(DeserializeError::OutOfBounds);
+        }
+
+        markers.sort_unstable();
+        markers.dedup();
+
+        // pointers were added in version 4.
+        let mut pointers = vec![];
+
+        if version >= 4 {
+            for _ in 0..reader.len()? {
+                let position = reader.varint()?;
+                let mut state = Pointers::default();
+
+                for pointer in &mut state {
+                    *pointer = match reader.varint()? {
+                        0 => None,
+                        i if i <= len => Some(i - 1),
+                        _ => return Err(DeserializeError::OutOfBounds),
+                    };
+                }
+
+                if position > num_ops
+                    || pointers.last().is_some_and(|&(p, _)| p >= position)
+                {
+                    return Err(DeserializeError::OutOfBounds);
+                }
+
+                pointers.push((position, state));
+            }
+        }
+
+        let mut capture = Self::create(
+            init_arr,
+            final_arr,
+            operations,
+            algorithm,
+            num_writes,
+            verify_start,
+            timestamps,
+        );
+        capture.markers = markers;
+        capture.pointers = pointers;
+
+        Ok((capture, warnings))
+    }
+
+    /// Whether the capture has finished playback or not. Incomplete captures
+    /// may still receive more operations — see [`Self::is_complete()`].
     pub fn is_done(&self) -> bool {
         self.cursor == self.operations.len()
     }
 
+    /// The index of the next operation to be performed.
+    pub const fn position(&self) -> usize {
+        self.cursor
+    }
+
     /// Returns the current progress of the sorting process as a value between
     /// `0.0` and `1.0`.
     pub fn playback_progress(&self) -> f32 {
@@ -151,26 +730,91 @@ impl SortCapture {
     /// the buffer — i.e., if the progress is rewound, then the operations in
     /// the slice are still ordered going forward.
     #[must_use]
-    pub fn set_progress(&mut self, progress: f32) -> Arc<[SortOperation]> {
-        if self.operations.is_empty() {
-            return [].into();
-        }
-
-        self.cursor_last = self.cursor;
-
+    pub fn set_progress(&mut self, progress: f32) -> OperationSlice {
         let n = self.operations.len() as f32;
 
-        self.cursor = if progress >= 1.0 - f32::EPSILON {
+        let cursor = if progress >= 1.0 - f32::EPSILON {
             self.operations.len()
         }
         else {
             (progress.clamp(0.0, 1.0) * n).ceil().min(n) as usize
         };
 
-        self.set_arr();
+        self.set_cursor(cursor)
+    }
+
+    /// Whether the time of each operation was recorded.
+    pub const fn has_timestamps(&self) -> bool {
+        self.timestamps.is_some()
+    }
+
+    /// Returns the current playback position as a proportion of the time
+    /// which the sort took to compute, or `None` if there are no timestamps.
+    pub fn time_progress(&self) -> Option<f32> {
+        let timestamps = self.timestamps.as_ref()?;
+        let total = timestamps.last().copied().unwrap_or(0.0);
+
+        if self.cursor == 0 || total <= 0.0 {
+            return Some(0.0);
+        }
 
+        Some(timestamps[self.cursor - 1] / total)
+    }
+
+    /// Sets the playback position such that every operation which was
+    /// recorded in the first `time_progress` (between `0.0` and `1.0`) of the
+    /// sort's compute time has been performed. Returns the operations
+    /// performed in the process — see [`Self::set_progress()`].
+    ///
+    /// Returns `None` if there are no timestamps.
+    #[must_use]
+    pub fn set_time_progress(
+        &mut self,
+        time_progress: f32,
+    ) -> Option<OperationSlice> {
+        let timestamps = self.timestamps.as_ref()?;
+        let total = timestamps.last().copied().unwrap_or(0.0);
+
+        // if the sort was too fast to measure, then there is no pace to
+        // follow.
+        if total <= 0.0 {
+            return Some(self.set_progress(time_progress));
+        }
+
+        let time = time_progress * total;
+        let cursor = timestamps.partition_point(|&t| t <= time);
+
+        Some(self.set_cursor(cursor))
+    }
+
+    /// Returns the operations in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn operations_in(&self, range: Range<usize>) -> OperationSlice {
+        self.slice(range)
+    }
+
+    /// Returns `range` offset by `shift` operations, clamped to the bounds of
+    /// the operation buffer.
+    pub fn shifted_range(
+        &self,
+        range: Range<usize>,
+        shift: isize,
+    ) -> Range<usize> {
+        let n = self.operations.len() as isize;
+        let start = (range.start as isize + shift).clamp(0, n) as usize;
+        let end = (range.end as isize + shift).clamp(0, n) as usize;
+
+        start..end
+    }
+
+    /// The range of operations covered by the last call to
+    /// [`Self::set_progress()`], always ordered going forward.
+    pub fn last_range(&self) -> Range<usize> {
         // FIXME: please fix this nonsense
-        self.operations[match self.cursor.cmp(&self.cursor_last) {
+        match self.cursor.cmp(&self.cursor_last) {
             Ordering::Less => self.cursor..self.cursor_last,
             Ordering::Equal => {
                 if self.cursor == 0 {
@@ -181,28 +825,151 @@ impl SortCapture {
                 }
             }
             Ordering::Greater => self.cursor_last..self.cursor,
-        }]
-        .into()
+        }
+    }
+
+    /// The total number of operations in the capture.
+    pub fn num_operations(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Every operation in the capture.
+    pub fn operations(&self) -> &[PackedOperation] {
+        &self.operations
+    }
+
+    /// The time at which each operation was recorded, in seconds since the
+    /// sort began, if timestamps were recorded.
+    pub fn timestamps(&self) -> Option<&[f32]> {
+        self.timestamps.as_deref()
+    }
+
+    /// The data for the whole capture, i.e. the data once playback has
+    /// finished. The verification sweep isn't included.
+    pub fn total_data(&self) -> SortData {
+        let end = self.verify_start.unwrap_or(self.operations.len());
+        let mut data =
+            SortData { truncated: self.data.truncated, ..Default::default() };
+
+        for op in &self.operations[..end] {
+            data.update(op.unpack(), false);
+        }
+
+        data
+    }
+
+    /// Computes how busy each of `num_bins` equal slices of playback is, as
+    /// values between `0.0` and `1.0` relative to the busiest slice.
+    ///
+    /// As operations are played back at a constant rate, the busiest slices
+    /// are those with the most operations which change the array (i.e. writes
+    /// and swaps). There are fewer bins than `num_bins` if there are fewer
+    /// operations than bins.
+    pub fn op_density(&self, num_bins: usize) -> Vec<f32> {
+        let n = self.operations.len();
+        let num_bins = num_bins.min(n);
+        let mut bins = vec![0.0_f32; num_bins];
+
+        for (i, op) in self.operations.iter().enumerate() {
+            if matches!(
+                op.unpack(),
+                SortOperation::Write { .. }
+                    | SortOperation::AuxWrite { .. }
+                    | SortOperation::Swap { .. }
+            ) {
+                bins[i * num_bins / n] += 1.0;
+            }
+        }
+
+        let max = bins.iter().copied().fold(0.0, f32::max);
+
+        if max > 0.0 {
+            bins.iter_mut().for_each(|b| *b /= max);
+        }
+
+        bins
+    }
+
+    /// Computes the proportion of each kind of operation in each of
+    /// `num_bins` equal slices of playback, indexed by [`OperationKind`]. The
+    /// bins line up with those of [`Self::op_density()`].
+    pub fn op_mix(&self, num_bins: usize) -> Vec<[f32; 4]> {
+        let n = self.operations.len();
+        let num_bins = num_bins.min(n);
+        let mut bins = vec![[0.0_f32; 4]; num_bins];
+
+        for (i, &op) in self.operations.iter().enumerate() {
+            bins[i * num_bins / n][OperationKind::of(op) as usize] += 1.0;
+        }
+
+        for bin in &mut bins {
+            let total: f32 = bin.iter().sum();
+
+            if total > 0.0 {
+                bin.iter_mut().for_each(|b| *b /= total);
+            }
+        }
+
+        bins
     }
 
     pub fn reset_progress(&mut self) {
         _ = self.set_progress(0.0);
         self.write_stack.clear();
+        self.aux_write_stack.clear();
         self.cursor = 0;
         self.cursor_last = 0;
         self.data.reset();
     }
 
+    /// Moves the playback position to `cursor`, and returns the operations
+    /// performed in the process.
+    fn set_cursor(&mut self, cursor: usize) -> OperationSlice {
+        if self.operations.is_empty() {
+            return OperationSlice::default();
+        }
+
+        self.cursor_last = self.cursor;
+        self.cursor = cursor.min(self.operations.len());
+
+        self.set_arr();
+
+        self.slice(self.last_range())
+    }
+
+    /// Returns the operations in `range`. While the capture is still being
+    /// streamed in, the operations are copied instead of shared, so that the
+    /// buffer can keep growing without being copied itself.
+    fn slice(&self, range: Range<usize>) -> OperationSlice {
+        if self.complete {
+            // the bounds are checked now, rather than when the slice is used.
+            assert!(
+                range.start <= range.end && range.end <= self.operations.len(),
+                "operation range {range:?} out of bounds"
+            );
+
+            OperationSlice { buffer: Arc::clone(&self.operations), range }
+        }
+        else {
+            self.operations[range].iter().copied().collect()
+        }
+    }
+
     fn set_arr(&mut self) {
         if self.cursor_last == self.cursor {
             return;
         }
 
         let rewind = self.cursor < self.cursor_last;
+        let verify_start = self.verify_start.unwrap_or(usize::MAX);
 
         let mut update_arr = |i: usize| {
-            if let Some(op) = self.operations.get(i).copied() {
-                self.data.update(op, rewind);
+            if let Some(op) = self.operations.get(i).map(|op| op.unpack()) {
+                // the verification sweep isn't part of the algorithm, so it
+                // doesn't contribute to its data.
+                if i < verify_start {
+                    self.data.update(op, rewind);
+                }
 
                 match op {
                     SortOperation::Write { idx, value } => {
@@ -220,6 +987,15 @@ impl SortCapture {
                             self.scratch[idx] = value;
                         }
                     }
+                    SortOperation::AuxWrite { idx, value } => {
+                        if rewind {
+                            self.aux[idx] = self.aux_write_stack.pop().unwrap();
+                        }
+                        else {
+                            self.aux_write_stack.push(self.aux[idx]);
+                            self.aux[idx] = Some(value);
+                        }
+                    }
                     SortOperation::Swap { a, b } => {
                         // swap operations are always reversible.
                         self.scratch.swap(a, b);
@@ -241,3 +1017,210 @@ impl SortCapture {
         }
     }
 }
+
+/// Writes `value` to `bytes` as an unsigned LEB128 integer.
+fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+/// Writes `s` to `bytes`, prefixed with its length.
+fn write_string(bytes: &mut Vec<u8>, s: &str) {
+    write_varint(bytes, s.len());
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+/// Whether `version` is newer than `current`, where both are
+/// `major.minor.patch` versions. Versions which can't be parsed are never
+/// considered newer.
+fn is_newer_version(version: &str, current: &str) -> bool {
+    let parse = |v: &str| {
+        let mut parts = v.split('.').map(|p| p.parse::<u32>().ok());
+        Some([parts.next()??, parts.next()??, parts.next()??])
+    };
+
+    matches!((parse(version), parse(current)), (Some(v), Some(c)) if v > c)
+}
+
+/// Reads the parts of a serialized capture.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DeserializeError> {
+        if n > self.bytes.len() {
+            return Err(DeserializeError::UnexpectedEnd);
+        }
+
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, DeserializeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<usize, DeserializeError> {
+        let mut value = 0_usize;
+
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7F) as usize;
+
+            if bits << shift >> shift != bits {
+                return Err(DeserializeError::InvalidInteger);
+            }
+
+            value |= bits << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(DeserializeError::InvalidInteger)
+    }
+
+    /// Reads a length, which is checked against the remaining data so that
+    /// corrupt lengths can't cause huge allocations.
+    fn len(&mut self) -> Result<usize, DeserializeError> {
+        let len = self.varint()?;
+
+        // every element takes at least one byte.
+        if len > self.bytes.len() {
+            return Err(DeserializeError::UnexpectedEnd);
+        }
+
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, DeserializeError> {
+        let len = self.len()?;
+
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| DeserializeError::InvalidString)
+    }
+
+    fn f32(&mut self) -> Result<f32, DeserializeError> {
+        let bytes = self.take(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small capture which uses every kind of operation, along with
+    /// timestamps, markers and pointers.
+    fn test_capture() -> SortCapture {
+        let operations = [
+            SortOperation::Read { idx: 0 },
+            SortOperation::Compare { a: 0, b: 2, res: true },
+            SortOperation::Swap { a: 0, b: 2 },
+            SortOperation::AuxWrite { idx: 1, value: 0 },
+            SortOperation::Write { idx: 1, value: 1 },
+            SortOperation::Write { idx: 2, value: 3 },
+        ];
+        let timestamps = (0..operations.len()).map(|i| i as f32 * 0.5);
+
+        let mut capture = SortCapture::create(
+            vec![3, 0, 1],
+            vec![1, 1, 3],
+            operations.iter().map(|op| op.pack()).collect(),
+            SortingAlgorithm::Insertion,
+            3,
+            Some(5),
+            Some(timestamps.collect()),
+        );
+
+        let mut pointers = Pointers::default();
+        pointers[0] = Some(2);
+        capture.markers = vec![2, 4];
+        capture.pointers = vec![(1, pointers)];
+
+        capture
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let capture = test_capture();
+        let (loaded, warnings) =
+            SortCapture::deserialize(&capture.serialize()).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(loaded.algorithm(), capture.algorithm());
+        assert_eq!(loaded.initial_arr(), capture.initial_arr());
+        assert_eq!(loaded.final_arr(), capture.final_arr());
+        assert_eq!(loaded.operations(), capture.operations());
+        assert_eq!(loaded.timestamps(), capture.timestamps());
+        assert_eq!(loaded.verify_start(), capture.verify_start());
+        assert_eq!(loaded.markers(), capture.markers());
+        assert_eq!(loaded.pointers, capture.pointers);
+    }
+
+    #[test]
+    fn version_1_is_migrated() {
+        let mut bytes = MAGIC.to_vec();
+        let algorithm = V1_ALGORITHMS
+            .iter()
+            .position(|&a| a == SortingAlgorithm::Insertion)
+            .unwrap();
+        bytes.extend([1, algorithm as u8]);
+
+        // the array, no verification sweep, a swap and no timestamps.
+        bytes.extend([3, 2, 0, 1, 0, 1, OP_SWAP, 0, 2, 0]);
+
+        let (loaded, warnings) = SortCapture::deserialize(&bytes).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(loaded.algorithm(), SortingAlgorithm::Insertion);
+        assert_eq!(loaded.final_arr(), [1, 0, 2]);
+        assert_eq!(loaded.verify_start(), None);
+        assert!(loaded.markers().is_empty() && loaded.pointers.is_empty());
+    }
+
+    #[test]
+    fn out_of_bounds_values_are_rejected() {
+        let valid = test_capture().serialize();
+        assert!(SortCapture::deserialize(&valid).is_ok());
+
+        // a version 1 capture which writes a value that doesn't fit in a
+        // packed operation.
+        let capture = |values: &[usize], write: usize| {
+            let mut bytes = MAGIC.to_vec();
+            bytes.extend([1, 0]);
+
+            write_varint(&mut bytes, values.len());
+            for &value in values {
+                write_varint(&mut bytes, value);
+            }
+
+            bytes.extend([0, 1, OP_WRITE, 0]);
+            write_varint(&mut bytes, write);
+            bytes.push(0);
+
+            SortCapture::deserialize(&bytes).err()
+        };
+
+        let too_large = u32::MAX as usize + 1;
+        assert_eq!(capture(&[0, 1, 2], 1), None);
+        assert_eq!(
+            capture(&[0, 1, 2], too_large),
+            Some(DeserializeError::OutOfBounds)
+        );
+        assert_eq!(
+            capture(&[too_large, 1, 2], 1),
+            Some(DeserializeError::OutOfBounds)
+        );
+
+        // an array which is shorter than the smallest resolution.
+        assert_eq!(capture(&[0, 1], 1), Some(DeserializeError::OutOfBounds));
+    }
+}
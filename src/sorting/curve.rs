@@ -0,0 +1,98 @@
+use clap::ValueEnum;
+use std::fmt::Display;
+
+/// How the playback position moves through a capture over time.
+///
+/// Each curve maps the playback time (between `0.0` and `1.0`) to the
+/// playback progress, so that some parts of a sort can be played back more
+/// slowly than others without changing the overall playback time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum PlaybackCurve {
+    /// Every operation takes the same amount of time.
+    #[default]
+    Linear,
+    /// Starts slowly, and speeds up towards the end.
+    EaseIn,
+    /// Starts quickly, and slows down towards the end.
+    EaseOut,
+    /// Starts very slowly, with the time spent on each operation falling off
+    /// logarithmically, so long tails are heavily compressed.
+    Logarithmic,
+}
+
+impl PlaybackCurve {
+    const ALL: [Self; 4] =
+        [Self::Linear, Self::EaseIn, Self::EaseOut, Self::Logarithmic];
+
+    /// The base of [`PlaybackCurve::Logarithmic`] — higher values spend more
+    /// of the playback time at the start.
+    const LOG_BASE: f32 = 64.0;
+
+    /// Cycles to the next curve.
+    pub fn cycle_next(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + 1) % Self::ALL.len()];
+    }
+
+    /// Cycles to the previous curve.
+    pub fn cycle_prev(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()];
+    }
+
+    /// Returns the playback progress at playback time `t`.
+    pub fn progress_at(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::Logarithmic => {
+                (Self::LOG_BASE.powf(t) - 1.0) / (Self::LOG_BASE - 1.0)
+            }
+        }
+    }
+
+    /// Returns the playback time at which `progress` is reached, i.e. the
+    /// inverse of [`Self::progress_at()`].
+    pub fn time_at(self, progress: f32) -> f32 {
+        let p = progress.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => p,
+            Self::EaseIn => p.sqrt(),
+            Self::EaseOut => 1.0 - (1.0 - p).sqrt(),
+            Self::Logarithmic => {
+                p.mul_add(Self::LOG_BASE - 1.0, 1.0).ln() / Self::LOG_BASE.ln()
+            }
+        }
+    }
+
+    /// Returns the rate of playback progress at playback time `t`, relative
+    /// to [`PlaybackCurve::Linear`].
+    pub fn rate_at(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => 1.0,
+            Self::EaseIn => 2.0 * t,
+            Self::EaseOut => 2.0 * (1.0 - t),
+            Self::Logarithmic => {
+                Self::LOG_BASE.ln() * Self::LOG_BASE.powf(t)
+                    / (Self::LOG_BASE - 1.0)
+            }
+        }
+    }
+}
+
+impl Display for PlaybackCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Linear => "linear",
+            Self::EaseIn => "ease-in",
+            Self::EaseOut => "ease-out",
+            Self::Logarithmic => "logarithmic",
+        })
+    }
+}
@@ -0,0 +1,145 @@
+use crate::prelude::*;
+use crate::rng::{random_f32, random_range, with_rng};
+use clap::ValueEnum;
+use nannou::rand::seq::SliceRandom;
+use std::fmt::Display;
+
+/// The arrangement of values used to initialize the array before sorting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Distribution {
+    /// A full shuffle, via [`SortingAlgorithm::Shuffle`].
+    #[default]
+    #[value(help = "A full shuffle")]
+    Shuffled,
+    /// Every value, in descending order.
+    Reversed,
+    /// Sorted, apart from a small number of swaps between nearby elements.
+    NearlySorted,
+    /// Several ascending "teeth", each covering the full range of values.
+    Sawtooth,
+    /// Ascending to the middle of the array, then descending.
+    OrganPipe,
+    /// Shuffled values which are normally distributed around the middle of
+    /// the range.
+    Gaussian,
+    /// Shuffled values drawn from only a few unique values.
+    FewUnique,
+}
+
+impl Distribution {
+    const ALL: [Self; 7] = [
+        Self::Shuffled,
+        Self::Reversed,
+        Self::NearlySorted,
+        Self::Sawtooth,
+        Self::OrganPipe,
+        Self::Gaussian,
+        Self::FewUnique,
+    ];
+
+    /// The number of teeth in [`Distribution::Sawtooth`].
+    const NUM_TEETH: usize = 4;
+    /// The number of unique values in [`Distribution::FewUnique`].
+    const NUM_UNIQUE: usize = 8;
+    /// The proportion of elements which are swapped in
+    /// [`Distribution::NearlySorted`].
+    const NEARLY_SORTED_SWAPS: f32 = 0.05;
+
+    /// Cycles to the next distribution.
+    pub fn cycle_next(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + 1) % Self::ALL.len()];
+    }
+
+    /// Cycles to the previous distribution.
+    pub fn cycle_prev(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()];
+    }
+
+    /// Generates an array of `len` values, where every value is in
+    /// `0..len`.
+    ///
+    /// [`Distribution::Shuffled`] generates a random permutation, though the
+    /// app uses [`SortingAlgorithm::Shuffle`] to visualize it instead.
+    pub fn generate(self, len: usize) -> Vec<usize> {
+        let max = len.saturating_sub(1);
+
+        match self {
+            Self::Shuffled => {
+                let mut arr: Vec<usize> = (0..len).collect();
+                with_rng(|rng| arr.shuffle(rng));
+                arr
+            }
+            Self::Reversed => (0..len).rev().collect(),
+            Self::NearlySorted => {
+                let mut arr: Vec<usize> = (0..len).collect();
+                let num_swaps =
+                    (len as f32 * Self::NEARLY_SORTED_SWAPS) as usize;
+                let dist = (len / 20).max(1);
+
+                for _ in 0..num_swaps {
+                    let a = random_range(0, max);
+                    let b = (a + random_range(1, dist + 1)).min(max);
+                    arr.swap(a, b);
+                }
+
+                arr
+            }
+            Self::Sawtooth => {
+                let tooth = len.div_ceil(Self::NUM_TEETH).max(1);
+
+                (0..len).map(|i| (i % tooth) * len / tooth).collect()
+            }
+            Self::OrganPipe => {
+                let half = len.div_ceil(2);
+
+                (0..len)
+                    .map(|i| {
+                        if i < half { i * 2 } else { (len - 1 - i) * 2 + 1 }
+                    })
+                    .collect()
+            }
+            Self::Gaussian => {
+                let mean = len as f32 * 0.5;
+                let std_dev = len as f32 / 6.0;
+
+                (0..len)
+                    .map(|_| {
+                        // Box-Muller transform
+                        let u1 = random_f32().max(f32::EPSILON);
+                        let u2 = random_f32();
+                        let z = (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos();
+
+                        (z.mul_add(std_dev, mean).round().max(0.0) as usize)
+                            .min(max)
+                    })
+                    .collect()
+            }
+            Self::FewUnique => {
+                let mut arr: Vec<usize> = (0..len)
+                    .map(|i| {
+                        let level = i * Self::NUM_UNIQUE / len.max(1);
+                        level * len / Self::NUM_UNIQUE
+                    })
+                    .collect();
+                with_rng(|rng| arr.shuffle(rng));
+                arr
+            }
+        }
+    }
+}
+
+impl Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Shuffled => "Shuffled",
+            Self::Reversed => "Reversed",
+            Self::NearlySorted => "Nearly sorted",
+            Self::Sawtooth => "Sawtooth",
+            Self::OrganPipe => "Organ pipe",
+            Self::Gaussian => "Gaussian",
+            Self::FewUnique => "Few unique",
+        })
+    }
+}
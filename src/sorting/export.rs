@@ -0,0 +1,231 @@
+//! Exports captures to common text formats, so that they can be analyzed
+//! outside of the app.
+
+use crate::prelude::*;
+use std::io::{self, Write};
+
+/// A text format which captures can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A single object holding the summary, the initial and final arrays, and
+    /// every operation.
+    Json,
+    /// A row for each operation, after a header of `#`-prefixed summary
+    /// lines. The indices of each operation are in the `a` and `b` columns.
+    Csv,
+}
+
+impl ExportFormat {
+    /// The file extension for the format.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+impl SortCapture {
+    /// Writes the capture to `w` in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any errors from writing to `w`.
+    pub fn export(
+        &self,
+        format: ExportFormat,
+        w: &mut impl Write,
+    ) -> io::Result<()> {
+        match format {
+            ExportFormat::Json => self.export_json(w),
+            ExportFormat::Csv => self.export_csv(w),
+        }
+    }
+
+    fn export_json(&self, w: &mut impl Write) -> io::Result<()> {
+        let SortData { reads, comparisons, writes, swaps, .. } =
+            self.total_data();
+        let list = |arr: &[usize]| {
+            arr.iter().map(usize::to_string).collect::<Vec<_>>().join(",")
+        };
+
+        writeln!(w, "{{")?;
+        writeln!(w, "  \"algorithm\": {},", json_string(&self.algorithm()))?;
+        writeln!(w, "  \"length\": {},", self.len())?;
+        writeln!(
+            w,
+            "  \"summary\": {{ \"reads\": {reads}, \"writes\": {writes}, \
+             \"swaps\": {swaps}, \"comparisons\": {comparisons} }},"
+        )?;
+        writeln!(w, "  \"initial\": [{}],", list(self.initial_arr()))?;
+        writeln!(w, "  \"final\": [{}],", list(self.final_arr()))?;
+
+        match self.verify_start() {
+            Some(i) => writeln!(w, "  \"verify_start\": {i},")?,
+            None => writeln!(w, "  \"verify_start\": null,")?,
+        }
+
+        writeln!(w, "  \"operations\": [")?;
+
+        let timestamps = self.timestamps();
+        let n = self.num_operations();
+
+        for (i, op) in self.operations().iter().enumerate() {
+            let fields = match op.unpack() {
+                SortOperation::Write { idx, value } => format!(
+                    "\"kind\": \"write\", \"idx\": {idx}, \"value\": {value}"
+                ),
+                SortOperation::AuxWrite { idx, value } => format!(
+                    "\"kind\": \"aux_write\", \"idx\": {idx}, \
+                     \"value\": {value}"
+                ),
+                SortOperation::Read { idx } => {
+                    format!("\"kind\": \"read\", \"idx\": {idx}")
+                }
+                SortOperation::Swap { a, b } => {
+                    format!("\"kind\": \"swap\", \"a\": {a}, \"b\": {b}")
+                }
+                SortOperation::Compare { a, b, res } => format!(
+                    "\"kind\": \"compare\", \"a\": {a}, \"b\": {b}, \
+                     \"res\": {res}"
+                ),
+            };
+            let time = timestamps
+                .map(|t| format!(", \"time\": {}", t[i]))
+                .unwrap_or_default();
+            let comma = if i + 1 < n { "," } else { "" };
+
+            writeln!(w, "    {{ {fields}{time} }}{comma}")?;
+        }
+
+        writeln!(w, "  ]")?;
+        writeln!(w, "}}")
+    }
+
+    fn export_csv(&self, w: &mut impl Write) -> io::Result<()> {
+        let SortData { reads, comparisons, writes, swaps, .. } =
+            self.total_data();
+
+        writeln!(w, "# algorithm: {}", self.algorithm())?;
+        writeln!(w, "# length: {}", self.len())?;
+        writeln!(
+            w,
+            "# reads: {reads}, writes: {writes}, swaps: {swaps}, \
+             comparisons: {comparisons}"
+        )?;
+        writeln!(w, "step,kind,a,b,value,res,verify,time")?;
+
+        let timestamps = self.timestamps();
+        let verify_start = self.verify_start().unwrap_or(usize::MAX);
+        let cell =
+            |x: Option<usize>| x.map(|x| x.to_string()).unwrap_or_default();
+
+        for (i, op) in self.operations().iter().enumerate() {
+            // columns which don't apply to an operation are left empty.
+            let (kind, a, b, value, res) = match op.unpack() {
+                SortOperation::Write { idx, value } => {
+                    ("write", Some(idx), None, Some(value), None)
+                }
+                SortOperation::AuxWrite { idx, value } => {
+                    ("aux_write", Some(idx), None, Some(value), None)
+                }
+                SortOperation::Read { idx } => {
+                    ("read", Some(idx), None, None, None)
+                }
+                SortOperation::Swap { a, b } => {
+                    ("swap", Some(a), Some(b), None, None)
+                }
+                SortOperation::Compare { a, b, res } => {
+                    ("compare", Some(a), Some(b), None, Some(res))
+                }
+            };
+
+            writeln!(
+                w,
+                "{i},{kind},{},{},{},{},{},{}",
+                cell(a),
+                cell(b),
+                cell(value),
+                res.map(|r| r.to_string()).unwrap_or_default(),
+                i >= verify_start,
+                timestamps.map(|t| t[i].to_string()).unwrap_or_default(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats `value` as a JSON string.
+fn json_string(value: &impl std::fmt::Display) -> String {
+    let escaped = value.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(format: ExportFormat) -> String {
+        let operations = [
+            SortOperation::Compare { a: 0, b: 2, res: true },
+            SortOperation::Swap { a: 0, b: 2 },
+            SortOperation::Write { idx: 1, value: 1 },
+            SortOperation::Read { idx: 1 },
+        ];
+        let capture = SortCapture::create(
+            vec![2, 0, 1],
+            vec![1, 1, 2],
+            operations.iter().map(|op| op.pack()).collect(),
+            SortingAlgorithm::Insertion,
+            1,
+            Some(3),
+            Some(vec![0.0, 0.25, 0.5, 0.75]),
+        );
+
+        let mut out = vec![];
+        capture.export(format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn json_export() {
+        let json = export(ExportFormat::Json);
+        let lines: Vec<&str> = json.lines().collect();
+
+        assert_eq!(lines[0], "{");
+        assert_eq!(lines[1], "  \"algorithm\": \"Insertion sort\",");
+        assert_eq!(lines[2], "  \"length\": 3,");
+        assert_eq!(lines[4], "  \"initial\": [2,0,1],");
+        assert_eq!(lines[6], "  \"verify_start\": 3,");
+
+        // an object per operation, the last of which has no trailing comma.
+        let ops: Vec<&str> =
+            lines.iter().copied().filter(|l| l.contains("\"kind\"")).collect();
+        assert_eq!(ops.len(), 4);
+        assert_eq!(
+            ops[0],
+            "    { \"kind\": \"compare\", \"a\": 0, \"b\": 2, \
+             \"res\": true, \"time\": 0 },"
+        );
+        assert!(ops[2].contains("\"idx\": 1, \"value\": 1"));
+        assert!(ops[3].ends_with(" }"));
+        assert_eq!(lines[lines.len() - 2..], ["  ]", "}"]);
+    }
+
+    #[test]
+    fn csv_export() {
+        let csv = export(ExportFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "# algorithm: Insertion sort");
+        assert_eq!(lines[1], "# length: 3");
+        assert_eq!(lines[3], "step,kind,a,b,value,res,verify,time");
+
+        let rows = &lines[4..];
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], "0,compare,0,2,,true,false,0");
+        assert_eq!(rows[2], "2,write,1,,1,,false,0.5");
+        assert_eq!(rows[3], "3,read,1,,,,true,0.75");
+    }
+}
@@ -0,0 +1,121 @@
+use crate::prelude::*;
+use clap::ValueEnum;
+use std::fmt::Display;
+
+/// The categories of operation which can be hidden during playback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OperationKind {
+    /// Writes to the array or the auxiliary array.
+    Writes,
+    Reads,
+    Swaps,
+    Comparisons,
+}
+
+impl OperationKind {
+    /// Every category, in order.
+    pub const ALL: [Self; 4] =
+        [Self::Writes, Self::Reads, Self::Swaps, Self::Comparisons];
+
+    /// Returns the category of `op`.
+    pub const fn of(op: PackedOperation) -> Self {
+        match op.unpack() {
+            SortOperation::Write { .. } | SortOperation::AuxWrite { .. } => {
+                Self::Writes
+            }
+            SortOperation::Read { .. } => Self::Reads,
+            SortOperation::Swap { .. } => Self::Swaps,
+            SortOperation::Compare { .. } => Self::Comparisons,
+        }
+    }
+
+    /// The color which the category is shown with in the minimap and the
+    /// stats graph.
+    pub const fn color(self) -> Srgb<u8> {
+        match self {
+            Self::Writes => TOMATO,
+            Self::Reads => PLUM,
+            Self::Swaps => MEDIUMSEAGREEN,
+            Self::Comparisons => SKYBLUE,
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl Display for OperationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Writes => "writes",
+            Self::Reads => "reads",
+            Self::Swaps => "swaps",
+            Self::Comparisons => "comparisons",
+        })
+    }
+}
+
+/// Which categories of operation are shown and heard during playback.
+///
+/// Hidden operations are still applied to the array — they just aren't
+/// highlighted on the wheel or sonified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperationFilter {
+    shown: u8,
+}
+
+impl OperationFilter {
+    /// A filter which only shows `kinds`.
+    pub fn only(kinds: &[OperationKind]) -> Self {
+        Self { shown: kinds.iter().fold(0, |shown, k| shown | k.bit()) }
+    }
+
+    /// Whether operations of `kind` are shown.
+    pub const fn shows(self, kind: OperationKind) -> bool {
+        self.shown & kind.bit() != 0
+    }
+
+    /// Whether every operation is shown.
+    pub fn shows_all(self) -> bool {
+        OperationKind::ALL.iter().all(|&kind| self.shows(kind))
+    }
+
+    /// Toggles whether operations of `kind` are shown.
+    pub fn toggle(&mut self, kind: OperationKind) {
+        self.shown ^= kind.bit();
+    }
+
+    /// A filter which only shows the operations shown by both `self` and
+    /// `other`.
+    #[must_use]
+    pub const fn intersect(self, other: Self) -> Self {
+        Self { shown: self.shown & other.shown }
+    }
+
+    /// Whether `op` is shown.
+    pub const fn allows(self, op: PackedOperation) -> bool {
+        self.shows(OperationKind::of(op))
+    }
+
+    /// Returns the operations in `ops` which are shown.
+    pub fn apply(self, ops: &OperationSlice) -> OperationSlice {
+        if self.shows_all() {
+            ops.clone()
+        }
+        else {
+            ops.iter().copied().filter(|&op| self.allows(op)).collect()
+        }
+    }
+
+    /// The categories which are hidden.
+    pub fn hidden(self) -> impl Iterator<Item = OperationKind> {
+        OperationKind::ALL.into_iter().filter(move |&kind| !self.shows(kind))
+    }
+}
+
+impl Default for OperationFilter {
+    fn default() -> Self {
+        Self::only(&OperationKind::ALL)
+    }
+}
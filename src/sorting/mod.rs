@@ -1,7 +1,22 @@
 pub mod array;
+pub mod cancel;
 pub mod capture;
+pub mod curve;
+pub mod distribution;
+pub mod export;
+pub mod filter;
+pub mod panning;
 pub mod player;
 
-pub use array::{SortArray, SortOperation};
-pub use capture::{SortCapture, SortData};
+pub use array::{
+    PackedOperation, Pointer, Pointers, SortArray, SortOperation,
+    StreamMessage, DEFAULT_OP_LIMIT,
+};
+pub use cancel::{CancelToken, Cancelled};
+pub use capture::{OperationSlice, SortCapture, SortData};
+pub use curve::PlaybackCurve;
+pub use distribution::Distribution;
+pub use export::ExportFormat;
+pub use filter::{OperationFilter, OperationKind};
+pub use panning::Panning;
 pub use player::Player;
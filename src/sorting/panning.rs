@@ -0,0 +1,96 @@
+use crate::prelude::*;
+use clap::ValueEnum;
+use std::f32::consts::FRAC_PI_2;
+use std::fmt::Display;
+
+/// How notes are placed in the stereo field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Panning {
+    /// Notes are panned by their index in the array, with some randomness.
+    #[default]
+    Index,
+    /// Notes are panned to match their slice's position around the wheel.
+    Wheel,
+    /// Notes are panned as with [`Panning::Wheel`], and slices towards the
+    /// top of the wheel are quieter, as if they were further away.
+    WheelDepth,
+}
+
+impl Panning {
+    const ALL: [Self; 3] = [Self::Index, Self::Wheel, Self::WheelDepth];
+
+    /// How far notes at the sides of the wheel are panned.
+    const WHEEL_WIDTH: f32 = 0.75;
+    /// The gain which is lost at the top of the wheel with
+    /// [`Panning::WheelDepth`].
+    const DEPTH: f32 = 0.5;
+
+    /// Cycles to the next panning mode.
+    pub fn cycle_next(&mut self) {
+        let idx = *self as usize;
+        *self = Self::ALL[(idx + 1) % Self::ALL.len()];
+    }
+
+    /// Returns the pan (see [`NoteEvent::pan()`]) and gain of a note for the
+    /// element at `pos` (between `0.0` and `1.0`) in an array of `len_f`
+    /// elements.
+    pub fn place(self, pos: f32, len_f: f32) -> (f32, f32) {
+        if self == Self::Index {
+            let pan = (pos + random_range(-0.5, 0.5)).mul_add(2.0, -1.0);
+            return (pan.clamp(-1.0, 1.0) * 0.5, 1.0);
+        }
+
+        // the center of the slice, which the wheel starts drawing from the
+        // top and counter-clockwise.
+        let theta = (pos + 0.5 / len_f).mul_add(TAU, FRAC_PI_2);
+        let (y, x) = theta.sin_cos();
+
+        let gain = if self == Self::WheelDepth {
+            (y + 1.0).mul_add(-0.5 * Self::DEPTH, 1.0)
+        }
+        else {
+            1.0
+        };
+
+        // positive pans are towards the left channel.
+        (-x * Self::WHEEL_WIDTH, gain)
+    }
+}
+
+impl Display for Panning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Index => "by index",
+            Self::Wheel => "by wheel position",
+            Self::WheelDepth => "by wheel position, with depth",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wheel_panning() {
+        let len_f = 100.0;
+        let place = |panning: Panning, pos: f32| {
+            // offsets to the start of the slice, so that `pos` is its center.
+            panning.place(pos - 0.5 / len_f, len_f)
+        };
+
+        // the left of the wheel is panned left, and the right to the right.
+        let (left, _) = place(Panning::Wheel, 0.25);
+        let (right, _) = place(Panning::Wheel, 0.75);
+        assert!((left - Panning::WHEEL_WIDTH).abs() < 1e-4);
+        assert!((right + Panning::WHEEL_WIDTH).abs() < 1e-4);
+        assert!(place(Panning::Wheel, 0.0).0.abs() < 1e-4);
+
+        // the top of the wheel is further away.
+        assert_eq!(place(Panning::Wheel, 0.0).1, 1.0);
+        let (_, top) = place(Panning::WheelDepth, 0.0);
+        let (_, bottom) = place(Panning::WheelDepth, 0.5);
+        assert!((top - (1.0 - Panning::DEPTH)).abs() < 1e-4);
+        assert!((bottom - 1.0).abs() < 1e-4);
+    }
+}
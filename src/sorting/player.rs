@@ -1,72 +1,340 @@
 use crate::prelude::*;
-use crate::thread_pool::ThreadPool;
-use std::{thread, time::Duration};
+use crate::osc::OscSender;
+use crossbeam_channel::TryRecvError;
+use std::collections::VecDeque;
 
 const MAX_AUDIO_NOTES_PER_SECOND: usize = 40000;
+/// The number of slices which the operation density is computed for.
+const OP_DENSITY_BINS: usize = 512;
+/// The number of previous captures which are kept in the history.
+const MAX_HISTORY: usize = 8;
+
+/// The oscillators which some categories of operation are sonified with,
+/// in place of their usual sound.
+#[derive(Clone, Copy, Debug)]
+struct Timbres {
+    saw: OperationFilter,
+    pulse: OperationFilter,
+    /// The width of the pulse wave, between `0.0` and `1.0`.
+    pulse_width: f32,
+    noise: OperationFilter,
+    /// Whether the noise bursts are pink rather than white.
+    pink_noise: bool,
+    fm: OperationFilter,
+}
+
+impl Timbres {
+    /// Timbres which sonify the operations of `kinds` with `osc`, and every
+    /// other operation with its usual sound.
+    fn preset(osc: OscillatorType, kinds: &[OperationKind]) -> Self {
+        let kinds = OperationFilter::only(kinds);
+        let mut timbres = Self::default();
+
+        match osc {
+            OscillatorType::Saw => timbres.saw = kinds,
+            OscillatorType::Pulse { width } => {
+                timbres.pulse = kinds;
+                timbres.pulse_width = width;
+            }
+            OscillatorType::Noise { pink } => {
+                timbres.noise = kinds;
+                timbres.pink_noise = pink;
+            }
+            OscillatorType::Fm => timbres.fm = kinds,
+            OscillatorType::Sine | OscillatorType::Tri => {}
+        }
+
+        timbres
+    }
+
+    /// Whether every operation has its usual sound.
+    fn is_empty(self) -> bool {
+        let none = OperationFilter::only(&[]);
+
+        [self.saw, self.pulse, self.noise, self.fm].iter().all(|&f| f == none)
+    }
+
+    /// The oscillator which `op` is sonified with, if it isn't its usual one.
+    /// The sawtooth wave takes priority over the pulse wave, then noise, then
+    /// FM.
+    fn osc(self, op: PackedOperation) -> Option<OscillatorType> {
+        if self.saw.allows(op) {
+            Some(OscillatorType::Saw)
+        }
+        else if self.pulse.allows(op) {
+            Some(OscillatorType::Pulse { width: self.pulse_width })
+        }
+        else if self.noise.allows(op) {
+            Some(OscillatorType::Noise { pink: self.pink_noise })
+        }
+        else if self.fm.allows(op) {
+            Some(OscillatorType::Fm)
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl Default for Timbres {
+    fn default() -> Self {
+        Self {
+            saw: OperationFilter::only(&[]),
+            pulse: OperationFilter::only(&[]),
+            pulse_width: 0.5,
+            noise: OperationFilter::only(&[]),
+            pink_noise: false,
+            fm: OperationFilter::only(&[]),
+        }
+    }
+}
+
+/// How the notes of operations sound, aside from their pitch and level.
+#[derive(Clone, Copy, Debug)]
+struct NoteStyle {
+    timbres: Timbres,
+    tuning: Tuning,
+    panning: Panning,
+}
 
 #[derive(Debug)]
 struct AudioState {
-    callback_timer: Arc<Atomic<InstantTime>>,
+    clock: AudioClock,
     note_event_sender: Arc<Sender<NoteEvent>>,
 }
 
 #[derive(Debug)]
 pub struct Player {
     capture: Option<SortCapture>,
+    /// The stream which the operations of the capture are received from,
+    /// while it is still being computed.
+    stream: Option<Receiver<StreamMessage>>,
+    /// Previously-played sorts, most recent first, so that they can be
+    /// replayed without recomputing them.
+    history: VecDeque<SortCapture>,
+    /// Whether previous captures are kept in the history.
+    keep_history: bool,
 
     playback_time: f32,
     speed_mult: f32,
 
     is_playing: bool,
+    /// Whether captures with timestamps are played back at the pace they were
+    /// computed at, rather than at a constant number of operations per second.
+    real_time: bool,
+    /// The playback position in terms of compute time, while playing back in
+    /// real time. This is tracked separately from the capture, as a frame may
+    /// pass without any operations being performed.
+    time_progress: Option<f32>,
+    /// The curve which the playback position follows over time.
+    curve: PlaybackCurve,
+    /// The playback time along the curve, while playing back with a
+    /// nonlinear curve (or in real time, in which case this is tracked by
+    /// `time_progress` instead). This is tracked separately from the capture
+    /// for the same reason as `time_progress`.
+    curve_time: Option<f32>,
+    /// The (fractional) playback position while the capture is still being
+    /// streamed, which is tracked separately for the same reason as
+    /// `time_progress`.
+    stream_position: Option<f32>,
+    /// The start and end of the A–B loop, as positions in the capture (see
+    /// [`SortCapture::position()`]). The end is `None` while only the start
+    /// has been marked.
+    loop_points: Option<(usize, Option<usize>)>,
+    /// The categories of operation which are highlighted and sonified.
+    op_filter: OperationFilter,
+    /// The categories of operation which are sonified, out of those which
+    /// pass `op_filter`. Muted operations are still highlighted.
+    sound_filter: OperationFilter,
+    /// The categories of operation which are sonified with a sawtooth or
+    /// pulse wave, noise or FM, rather than their usual oscillator.
+    timbres: Timbres,
+    /// The scale which the pitches of notes are quantized to.
+    tuning: Tuning,
+    /// How notes are placed in the stereo field.
+    panning: Panning,
+    /// Whether the capture's algorithm's sound preset is used while no
+    /// timbres have been set — see [`SortingAlgorithm::sound_preset()`].
+    algorithm_sounds: bool,
 
     audio: AudioState,
+    /// The time on the audio clock at the last update, while playing.
+    last_clock: Option<f64>,
+    /// The time which each update plays back, if playback is stepped at a
+    /// fixed rate rather than following the audio clock.
+    fixed_delta: Option<f32>,
+    /// The note events of the last update, whose timings are their offsets
+    /// in samples from the start of the update, if notes are collected for
+    /// rendering offline rather than sent to the audio engine.
+    offline_notes: Option<Vec<NoteEvent>>,
 
-    ops_last_frame: Arc<[SortOperation]>,
+    ops_last_frame: OperationSlice,
+    /// The operation density of the current capture, computed when the
+    /// capture is set.
+    op_density: Arc<[f32]>,
+    /// The proportion of each kind of operation in each slice of the current
+    /// capture, computed along with the operation density.
+    op_mix: Arc<[[f32; 4]]>,
 
-    audio_msg_thread: ThreadPool,
+    /// The audio/visual offset in milliseconds. Positive values delay the
+    /// audio relative to the visuals, negative values make it lead.
+    av_offset_ms: f32,
+
+    /// The OSC sender which notes are also sent to, if OSC output is enabled.
+    osc: Option<Arc<OscSender>>,
+    /// The fraction of notes which are dropped to relieve the audio engine,
+    /// as fed back by its [`LoadGovernor`](crate::audio::LoadGovernor).
+    note_thinning: Arc<Atomic<f32>>,
 }
 
 impl Player {
     pub const DEFAULT_PLAYBACK_TIME: f32 = 8.0;
+    /// The playback speeds which can be jumped to directly.
+    pub const SPEED_PRESETS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+    /// The maximum audio/visual offset in either direction, in milliseconds.
+    pub const MAX_AV_OFFSET_MS: f32 = 500.0;
 
     pub fn new(
         note_event_sender: Sender<NoteEvent>,
-        callback_timer: Arc<Atomic<InstantTime>>,
+        clock: AudioClock,
     ) -> Self {
         Self {
             capture: None,
+            stream: None,
+            history: VecDeque::with_capacity(MAX_HISTORY),
+            keep_history: true,
 
             playback_time: Self::DEFAULT_PLAYBACK_TIME,
             speed_mult: 1.0,
 
             is_playing: false,
+            real_time: false,
+            time_progress: None,
+            curve: PlaybackCurve::default(),
+            curve_time: None,
+            stream_position: None,
+            loop_points: None,
+            op_filter: OperationFilter::default(),
+            sound_filter: OperationFilter::default(),
+            timbres: Timbres::default(),
+            tuning: Tuning::default(),
+            panning: Panning::default(),
+            algorithm_sounds: true,
 
             audio: AudioState {
-                callback_timer,
+                clock,
                 note_event_sender: Arc::new(note_event_sender),
             },
 
-            ops_last_frame: [].into(),
+            last_clock: None,
+            fixed_delta: None,
+            offline_notes: None,
+
+            ops_last_frame: OperationSlice::default(),
+            op_density: [].into(),
+            op_mix: [].into(),
+
+            av_offset_ms: 0.0,
 
-            audio_msg_thread: ThreadPool::build(
-                2,
-                None,
-                Some(&["audio messaging #0", "audio messaging #1"]),
-            )
-            .expect("failed to allocate audio msg thread"),
+            osc: None,
+            note_thinning: Arc::new(Atomic::new(0.0)),
         }
     }
 
-    /// Sets the `SortCapture` for the player.
+    /// Creates a player which sends its note events to the same audio engine
+    /// as `self`, with the same speed and curve. The new player keeps no
+    /// history of its captures.
+    pub fn new_sharing_audio(&self) -> Self {
+        let mut player = Self::new(
+            (*self.audio.note_event_sender).clone(),
+            self.audio.clock.clone(),
+        );
+
+        player.speed_mult = self.speed_mult;
+        player.curve = self.curve;
+        player.op_filter = self.op_filter;
+        player.sound_filter = self.sound_filter;
+        player.timbres = self.timbres;
+        player.tuning = self.tuning;
+        player.panning = self.panning;
+        player.algorithm_sounds = self.algorithm_sounds;
+        player.osc.clone_from(&self.osc);
+        player.note_thinning = Arc::clone(&self.note_thinning);
+        player.keep_history = false;
+
+        player
+    }
+
+    /// Sets the `SortCapture` for the player. The previous capture is kept in
+    /// the history — see [`Self::take_from_history()`].
     pub fn set_capture(&mut self, capture: SortCapture) {
+        self.archive_capture(false);
         self.is_playing = false;
+        self.reset_pacing();
+        self.op_density = capture.op_density(OP_DENSITY_BINS).into();
+        self.op_mix = capture.op_mix(OP_DENSITY_BINS).into();
+        self.loop_points = None;
         self.capture = Some(capture);
+        self.stream = None;
+    }
+
+    /// Sets a `SortCapture` which is still being computed, whose operations
+    /// are received from `stream` — see [`SortArray::start_stream()`]. The
+    /// capture can be played back while it is streamed in, in which case
+    /// playback waits whenever it catches up with the stream.
+    pub fn set_stream(
+        &mut self,
+        capture: SortCapture,
+        stream: Receiver<StreamMessage>,
+    ) {
+        self.set_capture(capture);
+        self.stream = Some(stream);
     }
 
-    /// Removes the player's current `SortCapture`.
+    /// Removes the player's current `SortCapture`, which is kept in the
+    /// history — see [`Self::take_from_history()`].
     pub fn clear_capture(&mut self) {
+        self.archive_capture(false);
         self.is_playing = false;
+        self.reset_pacing();
+        self.op_density = [].into();
+        self.op_mix = [].into();
+        self.loop_points = None;
         self.capture = None;
+        self.stream = None;
+    }
+
+    /// Takes a capture from the history so that it can be replayed, and
+    /// archives the current capture in its place.
+    ///
+    /// If `older`, the most recent capture is taken and the current capture
+    /// is archived as the oldest, so that repeated calls cycle back through
+    /// the history. Otherwise, the oldest capture is taken and the current
+    /// capture is archived as the most recent, cycling forwards.
+    ///
+    /// The player is left without a capture.
+    pub fn take_from_history(&mut self, older: bool) -> Option<SortCapture> {
+        let capture = if older {
+            self.history.pop_front()
+        }
+        else {
+            self.history.pop_back()
+        }?;
+
+        self.archive_capture(older);
+        self.clear_capture();
+
+        Some(capture)
+    }
+
+    /// The captures in the history, most recent first.
+    pub fn history(&self) -> impl Iterator<Item = &SortCapture> {
+        self.history.iter()
+    }
+
+    /// The player's current `SortCapture`, if it has one.
+    pub const fn capture(&self) -> Option<&SortCapture> {
+        self.capture.as_ref()
     }
 
     /// Whether the player currently has a capture loaded.
@@ -76,7 +344,7 @@ impl Player {
 
     /// Clears the operations captured in the last frame.
     pub fn clear_ops(&mut self) {
-        self.ops_last_frame = [].into();
+        self.ops_last_frame = OperationSlice::default();
     }
 
     /// The time it takes for the player to complete the array playback from
@@ -108,16 +376,203 @@ impl Player {
         self.speed_mult = speed;
     }
 
+    /// The index of the preset in [`Self::SPEED_PRESETS`] which matches the
+    /// current speed, if there is one.
+    pub fn speed_preset(&self) -> Option<usize> {
+        Self::SPEED_PRESETS
+            .iter()
+            .position(|&preset| (preset - self.speed_mult).abs() < 1e-3)
+    }
+
     /// Resets the speed multiplier, honoring [`Self::playback_time`].
     pub fn reset_speed(&mut self) {
         self.speed_mult = 1.0;
     }
 
+    /// Whether captures with timestamps are played back at the pace they were
+    /// computed at.
+    pub const fn is_real_time(&self) -> bool {
+        self.real_time
+    }
+
+    /// Sets whether captures with timestamps are played back at the pace they
+    /// were computed at. The overall playback time is unaffected.
+    pub fn set_real_time(&mut self, real_time: bool) {
+        self.real_time = real_time;
+        self.reset_pacing();
+    }
+
+    /// Sets whether the sound preset of the capture's algorithm is used
+    /// while no timbres have been set.
+    pub fn set_algorithm_sounds(&mut self, enabled: bool) {
+        self.algorithm_sounds = enabled;
+    }
+
+    /// Sets the OSC sender which each note is also sent to, if any.
+    pub fn set_osc(&mut self, osc: Option<Arc<OscSender>>) {
+        self.osc = osc;
+    }
+
+    /// Sets the fraction of notes which are dropped to relieve the audio
+    /// engine, which is shared with its load governor.
+    pub fn set_note_thinning(&mut self, thinning: Arc<Atomic<f32>>) {
+        self.note_thinning = thinning;
+    }
+
+    /// The scale which the pitches of notes are quantized to.
+    pub const fn tuning(&self) -> Tuning {
+        self.tuning
+    }
+
+    /// Sets the scale which the pitches of notes are quantized to — see
+    /// [`Tuning`].
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = tuning;
+    }
+
+    /// How notes are placed in the stereo field.
+    pub const fn panning(&self) -> Panning {
+        self.panning
+    }
+
+    /// Sets how notes are placed in the stereo field — see [`Panning`].
+    pub fn set_panning(&mut self, panning: Panning) {
+        self.panning = panning;
+    }
+
+    /// The curve which the playback position follows over time.
+    pub const fn curve(&self) -> PlaybackCurve {
+        self.curve
+    }
+
+    /// Sets the curve which the playback position follows over time — see
+    /// [`PlaybackCurve`]. The overall playback time is unaffected, and
+    /// shuffles are always played back linearly.
+    pub fn set_curve(&mut self, curve: PlaybackCurve) {
+        self.curve = curve;
+        self.reset_pacing();
+    }
+
+    /// The categories of operation which are highlighted and sonified.
+    pub const fn op_filter(&self) -> OperationFilter {
+        self.op_filter
+    }
+
+    /// Sets the categories of operation which are highlighted and sonified.
+    /// Hidden operations are still played back, just without being seen or
+    /// heard.
+    pub fn set_op_filter(&mut self, op_filter: OperationFilter) {
+        self.op_filter = op_filter;
+    }
+
+    /// The categories of operation which are sonified, out of those which
+    /// are shown.
+    pub const fn sound_filter(&self) -> OperationFilter {
+        self.sound_filter
+    }
+
+    /// Sets the categories of operation which are sonified. Unlike
+    /// [`Self::set_op_filter()`], muted operations are still highlighted.
+    pub fn set_sound_filter(&mut self, sound_filter: OperationFilter) {
+        self.sound_filter = sound_filter;
+    }
+
+    /// Sets the categories of operation which are sonified with a sawtooth
+    /// wave, whose brighter sound makes them stand out from the rest.
+    pub fn set_saw_kinds(&mut self, saw_kinds: OperationFilter) {
+        self.timbres.saw = saw_kinds;
+    }
+
+    /// Sets the categories of operation which are sonified with a pulse wave
+    /// of `width` (between `0.0` and `1.0`), whose hard edges give them a
+    /// harsher sound.
+    pub fn set_pulse_kinds(
+        &mut self,
+        pulse_kinds: OperationFilter,
+        width: f32,
+    ) {
+        self.timbres.pulse = pulse_kinds;
+        self.timbres.pulse_width = width;
+    }
+
+    /// Sets the categories of operation which are sonified with short bursts
+    /// of noise, which are pink if `pink` is set and white otherwise — with
+    /// swaps, this makes swap-heavy algorithms sound like drums.
+    pub fn set_noise_kinds(
+        &mut self,
+        noise_kinds: OperationFilter,
+        pink: bool,
+    ) {
+        self.timbres.noise = noise_kinds;
+        self.timbres.pink_noise = pink;
+    }
+
+    /// Sets the categories of operation which are sonified with FM, whose
+    /// brightness follows the loudness of each note.
+    pub fn set_fm_kinds(&mut self, fm_kinds: OperationFilter) {
+        self.timbres.fm = fm_kinds;
+    }
+
+    /// The audio/visual offset in milliseconds.
+    pub const fn av_offset_ms(&self) -> f32 {
+        self.av_offset_ms
+    }
+
+    /// Sets the audio/visual offset in milliseconds. Positive values delay the
+    /// audio relative to the visuals, and negative values make the audio
+    /// lead. The value is clamped to [`Self::MAX_AV_OFFSET_MS`] in either
+    /// direction.
+    pub fn set_av_offset_ms(&mut self, offset_ms: f32) {
+        self.av_offset_ms =
+            offset_ms.clamp(-Self::MAX_AV_OFFSET_MS, Self::MAX_AV_OFFSET_MS);
+    }
+
+    /// Estimates the audio/visual offset for this system, and applies it.
+    ///
+    /// Note events are scheduled into the next audio buffer, which the device
+    /// then holds for roughly another buffer before it is heard. The visuals
+    /// are presented a frame after they are drawn, so the difference between
+    /// the two is used as the offset. Nothing is measured, so this is only a
+    /// starting point for adjusting the offset by ear. Returns the new offset
+    /// in milliseconds.
+    pub fn estimate_av_offset(&mut self, frame_time: f32) -> f32 {
+        let buffer_time = self.audio.clock.buffer_time() as f32;
+        let audio_latency = buffer_time * 2.0;
+
+        self.set_av_offset_ms((frame_time - audio_latency) * 1000.0);
+        self.av_offset_ms
+    }
+
     /// Begins playback.
     pub fn play(&mut self) {
         self.is_playing = true;
     }
 
+    /// Steps playback by `delta_time` seconds every update rather than
+    /// following the audio clock, or returns to following it. Nothing is
+    /// sent to the audio engine while stepping, so that playback is
+    /// deterministic however long each update actually takes (e.g. when
+    /// exporting frames).
+    pub fn set_fixed_delta(&mut self, delta_time: Option<f32>) {
+        self.fixed_delta = delta_time;
+        self.last_clock = None;
+    }
+
+    /// Collects the note events of each update rather than sending them to
+    /// the audio engine, or stops collecting them, so that they can be
+    /// rendered offline in step with the visuals — see
+    /// [`Self::take_notes()`]. Notes are only collected while stepping at a
+    /// fixed rate (see [`Self::set_fixed_delta()`]).
+    pub fn set_offline_notes(&mut self, enabled: bool) {
+        self.offline_notes = enabled.then(Vec::new);
+    }
+
+    /// Takes the note events collected since the last call — see
+    /// [`Self::set_offline_notes()`].
+    pub fn take_notes(&mut self) -> Vec<NoteEvent> {
+        self.offline_notes.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
     /// Pauses playback at the current position.
     pub fn pause(&mut self) {
         self.is_playing = false;
@@ -126,15 +581,193 @@ impl Player {
     /// Pauses playback, and resets the playback position to the beginning.
     pub fn stop(&mut self) {
         self.is_playing = false;
+        self.reset_pacing();
 
         if let Some(cap) = self.capture.as_mut() {
             cap.reset_progress();
         }
     }
 
+    /// Moves the playback position to `progress` (between `0.0` and `1.0`)
+    /// without sonifying the operations in between. Does nothing if the
+    /// player has no capture.
+    pub fn seek(&mut self, progress: f32) {
+        if let Some(cap) = self.capture.as_mut() {
+            _ = cap.set_progress(progress);
+            self.ops_last_frame = OperationSlice::default();
+            self.reset_pacing();
+        }
+    }
+
+    /// Moves the playback position by `frames` frames of playback at the
+    /// current speed (backwards if `frames` is negative), where a frame lasts
+    /// `frame_time` seconds. The position always moves by at least one
+    /// operation, so that every operation can be stepped through. Like
+    /// [`Self::seek()`], the operations in between are not sonified.
+    pub fn seek_relative(&mut self, frames: f32, frame_time: f32) {
+        let progress_per_second = self.progress_per_second();
+        let Some(cap) = self.capture.as_ref() else {
+            return;
+        };
+
+        let n = cap.num_operations() as f32;
+        if n == 0.0 || frames == 0.0 {
+            return;
+        }
+
+        let ops = frames * frame_time * progress_per_second * n;
+        let ops = ops.abs().round().max(1.0).copysign(ops);
+        let target = (cap.position() as f32 + ops).clamp(0.0, n);
+
+        self.seek_to_position(target as usize);
+    }
+
+    /// Marks the current playback position as a point of the A–B loop. The
+    /// first call marks the start of the loop, and the second marks its end,
+    /// after which playback loops between the two. Once both are marked, the
+    /// loop is cleared instead.
+    ///
+    /// Does nothing if the player has no capture, or if the end would be the
+    /// same as the start.
+    pub fn mark_loop_point(&mut self) {
+        let Some(pos) = self.capture.as_ref().map(SortCapture::position)
+        else {
+            return;
+        };
+
+        self.loop_points = match self.loop_points {
+            None => Some((pos, None)),
+            Some((start, None)) if start == pos => Some((start, None)),
+            Some((start, None)) => {
+                Some((start.min(pos), Some(start.max(pos))))
+            }
+            Some((_, Some(_))) => None,
+        };
+    }
+
+    /// Clears the A–B loop.
+    pub fn clear_loop(&mut self) {
+        self.loop_points = None;
+    }
+
+    /// The start and end of the A–B loop as playback progress (between `0.0`
+    /// and `1.0`), if one has been marked — see [`Self::mark_loop_point()`].
+    /// The end is `None` while only the start has been marked.
+    pub fn loop_points(&self) -> Option<(f32, Option<f32>)> {
+        let cap = self.capture.as_ref()?;
+        let (start, end) = self.loop_points?;
+        let n = cap.num_operations().max(1) as f32;
+
+        Some((start as f32 / n, end.map(|end| end as f32 / n)))
+    }
+
+    /// Adds a marker at the current playback position, or removes the marker
+    /// which is already there — see [`SortCapture::toggle_marker()`]. Returns
+    /// whether a marker was added, or `None` if there is no capture.
+    pub fn toggle_marker(&mut self) -> Option<bool> {
+        self.capture.as_mut().map(SortCapture::toggle_marker)
+    }
+
+    /// Removes every marker from the current capture.
+    pub fn clear_markers(&mut self) {
+        if let Some(cap) = self.capture.as_mut() {
+            cap.clear_markers();
+        }
+    }
+
+    /// Moves the playback position to the next marker, or the previous one if
+    /// `forward` is `false`. Returns whether there was a marker to move to.
+    pub fn jump_to_marker(&mut self, forward: bool) -> bool {
+        let marker = self.capture.as_ref().and_then(|cap| {
+            if forward {
+                cap.next_marker()
+            }
+            else {
+                cap.previous_marker()
+            }
+        });
+
+        if let Some(position) = marker {
+            self.seek_to_position(position);
+        }
+
+        marker.is_some()
+    }
+
+    /// The markers of the current capture as playback progress (between
+    /// `0.0` and `1.0`).
+    pub fn markers(&self) -> Vec<f32> {
+        self.capture.as_ref().map_or_else(Vec::new, |cap| {
+            let n = cap.num_operations().max(1) as f32;
+            cap.markers().iter().map(|&m| m as f32 / n).collect()
+        })
+    }
+
+    /// The current playback progress between `0.0` and `1.0`, if the player
+    /// has a capture.
+    pub fn progress(&self) -> Option<f32> {
+        self.capture.as_ref().map(|cap| {
+            if cap.num_operations() > 1 {
+                cap.playback_progress().min(1.0)
+            }
+            else if cap.is_done() {
+                1.0
+            }
+            else {
+                0.0
+            }
+        })
+    }
+
+    /// The estimated time until playback reaches the end of the capture (or
+    /// the start, if the speed is negative) at the current speed, in seconds.
+    /// This is `None` if there is no capture, the speed is zero, or the
+    /// capture is still being computed — as its length isn't known yet.
+    pub fn time_remaining(&self) -> Option<f32> {
+        let cap = self.capture.as_ref().filter(|cap| cap.is_complete())?;
+        let progress_per_second = self.progress_per_second();
+
+        if progress_per_second.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let progress = if self.real_time && cap.has_timestamps() {
+            cap.time_progress().unwrap_or(0.0)
+        }
+        else {
+            cap.playback_progress()
+        };
+        let time = self
+            .time_progress
+            .or(self.curve_time)
+            .unwrap_or_else(|| self.effective_curve().time_at(progress))
+            .clamp(0.0, 1.0);
+
+        Some(if progress_per_second > 0.0 {
+            (1.0 - time) / progress_per_second
+        }
+        else {
+            time / -progress_per_second
+        })
+    }
+
+    /// The operation density of the current capture — see
+    /// [`SortCapture::op_density()`].
+    pub fn op_density(&self) -> Arc<[f32]> {
+        Arc::clone(&self.op_density)
+    }
+
+    /// The proportion of each kind of operation in each slice of the current
+    /// capture — see [`SortCapture::op_mix()`].
+    pub fn op_mix(&self) -> Arc<[[f32; 4]]> {
+        Arc::clone(&self.op_mix)
+    }
+
     /// Whether the player is at the end of the capture.
     pub fn at_end(&self) -> bool {
-        self.capture.as_ref().map_or(false, |c| c.is_done())
+        self.capture
+            .as_ref()
+            .map_or(false, |c| c.is_done() && c.is_complete())
     }
 
     /// Whether the player is playing.
@@ -154,148 +787,411 @@ impl Player {
         self.capture.as_ref().map(|c| c.algorithm())
     }
 
-    /// Copies the internal array state to the provided array.
+    /// Copies the internal array state to the provided array. Does nothing if
+    /// the player has no capture.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if `arr.len()` is not equal to the capture's array length.
-    pub fn copy_arr_to(&mut self, arr: &mut [usize]) {
-        if self.capture.is_none() {
-            return;
-        }
+    /// Returns [`ResizeError::MismatchedLengths`] if `arr.len()` is not equal
+    /// to the capture's array length.
+    pub fn copy_arr_to(&self, arr: &mut [usize]) -> Result<(), ResizeError> {
+        let Some(cap) = self.capture.as_ref() else {
+            return Ok(());
+        };
 
-        let cap = self.capture.as_ref().unwrap();
-        assert_eq!(cap.len(), arr.len(), "mismatched lengths");
+        if cap.len() != arr.len() {
+            return Err(ResizeError::MismatchedLengths {
+                expected: arr.len(),
+                found: cap.len(),
+            });
+        }
 
         arr.copy_from_slice(cap.arr());
+
+        Ok(())
+    }
+
+    /// The state of the array once the capture has finished playing, if the
+    /// player has a capture.
+    pub fn target_arr(&self) -> Option<&[usize]> {
+        self.capture.as_ref().map(SortCapture::final_arr)
+    }
+
+    /// The current state of the auxiliary array, if the player has a capture
+    /// which uses one.
+    pub fn aux_arr(&self) -> Option<&[Option<usize>]> {
+        self.capture.as_ref().and_then(SortCapture::aux_arr)
     }
 
-    pub fn ops_last_frame(&self) -> Arc<[SortOperation]> {
-        Arc::clone(&self.ops_last_frame)
+    /// The position of each pointer at the current playback position — see
+    /// [`SortCapture::pointers()`].
+    pub fn pointers(&self) -> Pointers {
+        self.capture
+            .as_ref()
+            .map_or_else(Pointers::default, SortCapture::pointers)
     }
 
-    #[allow(clippy::too_many_lines)]
-    fn send_note_events(&self, delta_time: f32) {
+    pub fn ops_last_frame(&self) -> OperationSlice {
+        self.ops_last_frame.clone()
+    }
+
+    /// Sends the note events for `ops` to the audio engine all at once, with
+    /// their timings spread evenly across the next audio buffer.
+    fn send_note_events(
+        &self,
+        ops: &OperationSlice,
+        first_op: usize,
+        delta_time: f32,
+    ) {
+        let event_sender = &self.audio.note_event_sender;
+        if event_sender.is_full() {
+            return;
+        }
+
+        // fewer notes are sent while the audio engine is under high load.
+        let thinning = 1.0 - self.note_thinning.load(Relaxed);
         let audio_ops_this_frame =
-            (MAX_AUDIO_NOTES_PER_SECOND as f32 * delta_time) as usize;
-        let time_between = delta_time
-            / (self.ops_last_frame.len().min(audio_ops_this_frame) as f32)
-            * 0.1;
+            (MAX_AUDIO_NOTES_PER_SECOND as f32 * delta_time * thinning)
+                as usize;
 
         // This will not panic, as we know capture is Some
         let cap = self.capture.as_ref().unwrap();
         let len_f = cap.len() as f32;
-        let curr = cap.algorithm();
+        let shuffle = cap.algorithm().is_shuffle();
+        let verify_start = cap.verify_start().unwrap_or(usize::MAX);
 
         assert!(len_f > f32::EPSILON, "invalid length");
 
-        let ops_last_frame = Arc::clone(&self.ops_last_frame);
-        let event_sender = Arc::clone(&self.audio.note_event_sender);
-        let callback_timer = Arc::clone(&self.audio.callback_timer);
+        let buffer_size = self.audio.clock.buffer_size() as f32;
+        let op_filter = self.op_filter.intersect(self.sound_filter);
+        let style = self.note_style();
 
-        if event_sender.is_full() {
+        // hidden and muted operations are skipped, but still count towards
+        // the position of the verification sweep.
+        let audible: Vec<_> = ops
+            .iter()
+            .enumerate()
+            .filter(|(_, &op)| op_filter.allows(op))
+            .take(audio_ops_this_frame)
+            .collect();
+
+        for (k, &(i, op)) in audible.iter().enumerate() {
+            let timing = (k as f32 / audible.len() as f32 * buffer_size) as u32;
+            let (event, second_event) = Self::op_notes(
+                *op,
+                first_op + i >= verify_start,
+                len_f,
+                shuffle,
+                style,
+                timing,
+            );
+
+            for event in std::iter::once(event).chain(second_event) {
+                if event_sender.try_send(event).is_err() {
+                    return;
+                }
+                if let Some(osc) = &self.osc {
+                    osc.send_note(&event);
+                }
+            }
+        }
+    }
+
+    /// Adds the note events for `ops` to the offline notes, spread evenly
+    /// across the `delta_time` seconds of the update — see
+    /// [`Self::set_offline_notes()`].
+    fn collect_notes(
+        &mut self,
+        ops: &OperationSlice,
+        first_op: usize,
+        delta_time: f32,
+    ) {
+        let style = self.note_style();
+        let (Some(cap), Some(notes)) =
+            (self.capture.as_ref(), self.offline_notes.as_mut())
+        else {
             return;
+        };
+
+        let audio_ops_this_frame =
+            (MAX_AUDIO_NOTES_PER_SECOND as f32 * delta_time) as usize;
+        let len_f = cap.len() as f32;
+        let shuffle = cap.algorithm().is_shuffle();
+        let verify_start = cap.verify_start().unwrap_or(usize::MAX);
+        let op_filter = self.op_filter.intersect(self.sound_filter);
+
+        let audible: Vec<_> = ops
+            .iter()
+            .enumerate()
+            .filter(|(_, &op)| op_filter.allows(op))
+            .take(audio_ops_this_frame)
+            .collect();
+        // the offline engine always runs at the preferred sample rate.
+        let samples = delta_time * SAMPLE_RATE as f32;
+
+        for (k, &(i, op)) in audible.iter().enumerate() {
+            let timing = (k as f32 / audible.len() as f32 * samples) as u32;
+            let (event, second_event) = Self::op_notes(
+                *op,
+                first_op + i >= verify_start,
+                len_f,
+                shuffle,
+                style,
+                timing,
+            );
+
+            notes.push(event);
+            notes.extend(second_event);
         }
+    }
 
-        self.audio_msg_thread.execute(move || {
-            let map = |x: f32| (x * 2.0 - 1.0).clamp(-1.0, 1.0) * 0.5;
-            let timing = || {
-                let samples_exact =
-                    callback_timer.load(Relaxed).elapsed().as_secs_f32()
-                        * SAMPLE_RATE as f32;
-
-                samples_exact.round() as u32 % BUFFER_SIZE as u32
-            };
-
-            for &op in ops_last_frame.iter().take(audio_ops_this_frame) {
-                let (freq, amp, pan);
-                let mut osc = OscillatorType::default();
-                let mut second_event = None;
-
-                match op {
-                    SortOperation::Write { idx, .. } => {
-                        let i = idx as f32 / len_f;
-                        freq = i * 0.5;
-                        amp = 0.6;
-                        pan = i;
-                    }
-                    SortOperation::Read { idx } => {
-                        let i = idx as f32 / len_f;
-                        freq = idx as f32 / len_f;
-                        amp = 0.5;
-                        pan = i;
-                        osc = OscillatorType::Tri;
-                    }
-                    SortOperation::Swap { a, b } => {
-                        let a_f = a as f32 / len_f;
-                        let b_f = b as f32 / len_f;
-
-                        let freq_mult =
-                            if matches!(curr, SortingAlgorithm::Shuffle) {
-                                0.5
-                            }
-                            else {
-                                1.0
-                            };
-
-                        freq = a_f * freq_mult;
-                        let freq_2 = b_f * freq_mult;
-                        amp = 0.7;
-                        pan = a_f;
-                        let pan_2 = b_f;
-
-                        second_event = Some(NoteEvent {
-                            osc,
-                            freq: Self::map_freq(freq_2),
-                            amp,
-                            timing: timing(),
-                            pan: map(pan_2 + random_range(-0.5, 0.5)),
-                        });
-                    }
-                    SortOperation::Compare { a, b, .. } => {
-                        let a_f = a as f32 / len_f;
-                        let b_f = b as f32 / len_f;
-                        freq = a_f * 0.5;
-                        let freq_2 = b_f * 0.5;
-                        amp = 0.4;
-                        pan = a_f;
-                        let pan_2 = b_f;
-                        osc = OscillatorType::Tri;
-
-                        second_event = Some(NoteEvent {
-                            osc,
-                            freq: Self::map_freq(freq_2),
-                            amp,
-                            timing: timing(),
-                            pan: map(pan_2 + random_range(-0.5, 0.5)),
-                        });
-                    }
-                }
+    /// The note event which sonifies `op` in a capture of `len_f` elements,
+    /// along with a second note for operations which involve two elements,
+    /// both at `timing`. Operations in the verification sweep have their own,
+    /// brighter sound, and the timbres, tuning and panning of `style` are
+    /// applied.
+    fn op_notes(
+        op: PackedOperation,
+        verifying: bool,
+        len_f: f32,
+        shuffle: bool,
+        style: NoteStyle,
+        timing: u32,
+    ) -> (NoteEvent, Option<NoteEvent>) {
+        let note = |osc, freq, amp: f32, pos| {
+            let (pan, gain) = style.panning.place(pos, len_f);
+
+            NoteEvent {
+                osc,
+                freq: Self::map_freq(freq, style.tuning),
+                amp: amp * gain,
+                timing,
+                pan,
+            }
+        };
 
-                thread::sleep(Duration::from_secs_f32(time_between));
-
-                if event_sender
-                    .try_send(NoteEvent {
-                        osc,
-                        freq: Self::map_freq(freq),
-                        amp,
-                        timing: timing(),
-                        pan: map(pan + random_range(-0.5, 0.5)),
-                    })
-                    .is_err()
-                {
-                    break;
+        let (freq, amp, pan);
+        let mut osc = OscillatorType::default();
+        let mut second_event = None;
+
+        match op.unpack() {
+            SortOperation::Write { idx, .. } => {
+                let i = idx as f32 / len_f;
+                freq = i * 0.5;
+                amp = 0.6;
+                pan = i;
+            }
+            SortOperation::AuxWrite { idx, .. } => {
+                let i = idx as f32 / len_f;
+                freq = i * 0.25;
+                amp = 0.35;
+                pan = i;
+            }
+            SortOperation::Read { idx } => {
+                let i = idx as f32 / len_f;
+                freq = idx as f32 / len_f;
+                amp = 0.5;
+                pan = i;
+                osc = OscillatorType::Tri;
+            }
+            SortOperation::Swap { a, b } => {
+                let a_f = a as f32 / len_f;
+                let b_f = b as f32 / len_f;
+
+                let freq_mult = if shuffle { 0.5 } else { 1.0 };
+
+                freq = a_f * freq_mult;
+                let freq_2 = b_f * freq_mult;
+                amp = 0.7;
+                pan = a_f;
+                let pan_2 = b_f;
+
+                second_event = Some(note(osc, freq_2, amp, pan_2));
+            }
+            // the verification sweep has its own, brighter sound
+            SortOperation::Compare { a, res, .. } if verifying => {
+                let a_f = a as f32 / len_f;
+                freq = a_f;
+                amp = if res { 0.6 } else { 0.3 };
+                pan = a_f;
+            }
+            SortOperation::Compare { a, b, .. } => {
+                let a_f = a as f32 / len_f;
+                let b_f = b as f32 / len_f;
+                freq = a_f * 0.5;
+                let freq_2 = b_f * 0.5;
+                amp = 0.4;
+                pan = a_f;
+                let pan_2 = b_f;
+                osc = OscillatorType::Tri;
+
+                second_event = Some(note(osc, freq_2, amp, pan_2));
+            }
+        }
+
+        let event = note(osc, freq, amp, pan);
+
+        if let Some(osc) = style.timbres.osc(op) {
+            let with_osc = |e: NoteEvent| e.with_type(osc);
+            return (with_osc(event), second_event.map(with_osc));
+        }
+
+        (event, second_event)
+    }
+
+    /// Adds any operations which have been streamed in to the capture.
+    fn receive_stream(&mut self) {
+        let (Some(stream), Some(cap)) =
+            (self.stream.as_ref(), self.capture.as_mut())
+        else {
+            return;
+        };
+
+        let finished = loop {
+            match stream.try_recv() {
+                Ok(StreamMessage::Operations(ops, timestamps)) => {
+                    cap.append_operations(&ops, timestamps.as_deref());
                 }
-                if let Some(event) = second_event {
-                    if event_sender.try_send(event).is_err() {
-                        break;
-                    }
+                Ok(StreamMessage::VerifyStart(start)) => {
+                    cap.set_verify_start(Some(start));
                 }
+                Ok(StreamMessage::Pointers(pointers)) => {
+                    cap.append_pointers(&pointers);
+                }
+                Ok(StreamMessage::Finished(truncated)) => break Some(truncated),
+                // if the sorting thread stopped without finishing the
+                // stream, then no more operations are coming either.
+                Err(TryRecvError::Disconnected) => break Some(false),
+                Err(TryRecvError::Empty) => break None,
             }
-        });
+        };
+
+        if let Some(truncated) = finished {
+            cap.finish(truncated);
+            self.op_density = cap.op_density(OP_DENSITY_BINS).into();
+            self.op_mix = cap.op_mix(OP_DENSITY_BINS).into();
+            self.stream = None;
+        }
+    }
+
+    /// Moves the current capture into the history, as the oldest capture if
+    /// `as_oldest`. Only complete sorts are kept, and the oldest capture is
+    /// dropped once the history is full.
+    fn archive_capture(&mut self, as_oldest: bool) {
+        if !self.keep_history {
+            return;
+        }
+
+        let Some(capture) = self.capture.take_if(|cap| {
+            cap.is_complete()
+                && !cap.algorithm().is_shuffle()
+                && cap.num_operations() > 0
+        })
+        else {
+            return;
+        };
+
+        if self.history.len() == MAX_HISTORY {
+            self.history.pop_back();
+        }
+
+        if as_oldest {
+            self.history.push_back(capture);
+        }
+        else {
+            self.history.push_front(capture);
+        }
+    }
+
+    /// Resynchronizes the pacing of playback with the capture's position, e.g.
+    /// after seeking.
+    fn reset_pacing(&mut self) {
+        self.time_progress = None;
+        self.curve_time = None;
+        self.stream_position = None;
+    }
+
+    /// Moves the playback position to `position` — see [`Self::seek()`] and
+    /// [`SortCapture::position()`].
+    pub fn seek_to_position(&mut self, position: usize) {
+        let Some(n) = self.capture.as_ref().map(SortCapture::num_operations)
+        else {
+            return;
+        };
+
+        if n == 0 {
+            return;
+        }
+
+        // `set_progress()` rounds up to the next operation, so the midpoint
+        // before the position is used to avoid any rounding error.
+        self.seek(((position as f32 - 0.5) / n as f32).max(0.0));
+    }
+
+    /// The proportion of the current capture which is played back each
+    /// second.
+    fn progress_per_second(&self) -> f32 {
+        if self.algorithm().is_some_and(SortingAlgorithm::is_shuffle) {
+            0.5
+        }
+        else {
+            self.playback_time.recip() * self.speed_mult
+        }
+    }
+
+    /// The time which has passed on the audio clock since the last update, so
+    /// that playback stays in step with the audio. The first update after
+    /// playback starts, or any update while the audio clock has stalled, uses
+    /// the frame's `delta_time` instead.
+    fn clock_delta(&mut self, delta_time: f32) -> f32 {
+        let Some(now) = self.audio.clock.now() else {
+            self.last_clock = None;
+            return delta_time;
+        };
+
+        // the clock may step backwards slightly, in which case playback waits
+        // for it to catch up.
+        let last = self.last_clock;
+        let now = last.map_or(now, |last| now.max(last));
+        self.last_clock = Some(now);
+
+        last.map_or(delta_time, |last| (now - last) as f32)
     }
 
-    fn map_freq(freq: f32) -> f32 {
+    /// The timbres which operations are sonified with: those which have been
+    /// set if there are any, and otherwise the sound preset of the capture's
+    /// algorithm, if it has one. The preset changes along with the capture.
+    fn timbres(&self) -> Timbres {
+        if !self.algorithm_sounds || !self.timbres.is_empty() {
+            return self.timbres;
+        }
+
+        self.algorithm()
+            .and_then(SortingAlgorithm::sound_preset)
+            .map_or(self.timbres, |(osc, kinds)| Timbres::preset(osc, kinds))
+    }
+
+    /// How the notes of operations sound — see [`NoteStyle`].
+    fn note_style(&self) -> NoteStyle {
+        NoteStyle {
+            timbres: self.timbres(),
+            tuning: self.tuning,
+            panning: self.panning,
+        }
+    }
+
+    /// The curve which playback follows — shuffles are always played back
+    /// linearly.
+    fn effective_curve(&self) -> PlaybackCurve {
+        if self.algorithm().is_some_and(SortingAlgorithm::is_shuffle) {
+            PlaybackCurve::Linear
+        }
+        else {
+            self.curve
+        }
+    }
+
+    fn map_freq(freq: f32, tuning: Tuning) -> f32 {
         const MIN_NOTE: f32 = 36.0;
         const MAX_NOTE: f32 = 104.0;
 
@@ -304,43 +1200,130 @@ impl Player {
         let n = 5.0;
         let x = ((n - 1.0) * freq.clamp(0.0, 1.0) + 1.0).log(n);
         let note = (MAX_NOTE - MIN_NOTE).mul_add(x, MIN_NOTE);
-        // let quantized = Audio::quantize_to_scale(&MINOR_SCALE, note, 59.0);
 
-        Audio::note_to_freq(note)
+        tuning.quantize(MidiNote(note)).to_freq()
     }
 }
 
 impl Updatable for Player {
     fn update(&mut self, _: &App, update: UpdateData) {
+        // the stream is received from even while paused, so that the capture
+        // is complete by the time playback resumes.
+        self.receive_stream();
+
         if !self.is_playing || self.capture.is_none() {
+            self.last_clock = None;
             return;
         }
 
+        let delta_time = match self.fixed_delta {
+            Some(delta_time) => delta_time,
+            None => self.clock_delta(update.delta_time),
+        };
+
+        // playback returns to the start of the loop once it reaches its end.
+        if let Some((start, Some(end))) = self.loop_points {
+            if self.capture.as_ref().is_some_and(|c| c.position() >= end) {
+                self.seek_to_position(start);
+                return;
+            }
+        }
+
+        let progress_per_second = self.progress_per_second();
+        let curve = self.effective_curve();
+        let is_shuffle =
+            self.algorithm().is_some_and(SortingAlgorithm::is_shuffle);
         let cap = unsafe { self.capture.as_mut().unwrap_unchecked() };
 
         if cap.is_done() {
             // println!("Sorting done");
-            self.ops_last_frame = [].into();
-            self.is_playing = false;
+            self.ops_last_frame = OperationSlice::default();
+
+            // playback waits for the stream to catch up.
+            if cap.is_complete() {
+                self.is_playing = false;
+            }
+
             return;
         }
 
-        let progress_per_second =
-            if matches!(cap.algorithm(), SortingAlgorithm::Shuffle) {
-                0.5
-            }
-            else {
-                self.playback_time.recip() * self.speed_mult
-            };
-        let progress_per_frame = progress_per_second * update.delta_time;
+        let progress_per_frame = progress_per_second * delta_time;
+        // the rate of progress at this point of the curve.
+        let mut rate = 1.0;
+
+        // the number of operations (and the total compute time) isn't known
+        // until the stream has finished, so streamed captures are played back
+        // at a constant number of operations per second until then, as if
+        // they will have around n log2 n operations (or n for shuffles).
+        let ops = if !cap.is_complete() {
+            let n = cap.len() as f32;
+            let expected_ops = if is_shuffle { n } else { n * n.log2() };
+            let num_ops = cap.num_operations() as f32;
+
+            let position = (self
+                .stream_position
+                .unwrap_or(cap.position() as f32)
+                + progress_per_frame * expected_ops)
+                .clamp(0.0, num_ops);
 
-        let curr_progress = cap.playback_progress();
+            self.stream_position = Some(position);
+            cap.set_progress(position / num_ops)
+        }
+        else if self.real_time && cap.has_timestamps() {
+            let time_progress = (self
+                .time_progress
+                .or_else(|| cap.time_progress().map(|p| curve.time_at(p)))
+                .unwrap_or(0.0)
+                + progress_per_frame)
+                .clamp(0.0, 1.0);
+
+            self.time_progress = Some(time_progress);
+            rate = curve.rate_at(time_progress);
+            cap.set_time_progress(curve.progress_at(time_progress))
+                .unwrap_or_default()
+        }
+        else if curve == PlaybackCurve::Linear {
+            let curr_progress = cap.playback_progress();
+            cap.set_progress(curr_progress + progress_per_frame)
+        }
+        else {
+            let time = (self
+                .curve_time
+                .unwrap_or_else(|| curve.time_at(cap.playback_progress()))
+                + progress_per_frame)
+                .clamp(0.0, 1.0);
 
-        self.ops_last_frame =
-            cap.set_progress(curr_progress + progress_per_frame);
+            self.curve_time = Some(time);
+            rate = curve.rate_at(time);
+            cap.set_progress(curve.progress_at(time))
+        };
 
-        if !self.ops_last_frame.is_empty() {
-            self.send_note_events(update.delta_time);
+        // the audio/visual offset is applied by sonifying the operations
+        // which are ahead of (or behind) the visual playback position.
+        let offset_ops = -(self.av_offset_ms * 0.001
+            * progress_per_second
+            * rate
+            * cap.num_operations() as f32) as isize;
+
+        let audio_range = cap.shifted_range(cap.last_range(), offset_ops);
+        let audio_ops = if offset_ops == 0 {
+            ops.clone()
+        }
+        else {
+            cap.operations_in(audio_range.clone())
+        };
+
+        self.ops_last_frame = self.op_filter.apply(&ops);
+
+        if audio_ops.is_empty() {
+            return;
+        }
+
+        if self.fixed_delta.is_none() {
+            self.send_note_events(&audio_ops, audio_range.start, delta_time);
+        }
+        else {
+            self.collect_notes(&audio_ops, audio_range.start, delta_time);
         }
     }
 }
@@ -0,0 +1,158 @@
+use super::*;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::ops::Range;
+
+/// The number of turns the spiral makes.
+const TURNS: f32 = 5.0;
+/// The radius at which the spiral starts, at its natural scale.
+const INNER_RADIUS: f32 = CIRCLE_RADIUS * 0.1;
+/// The distance between each turn of the spiral.
+const PITCH: f32 = (CIRCLE_RADIUS - INNER_RADIUS) / TURNS;
+/// The width of the spiral's band, as a proportion of [`PITCH`].
+const BAND_WIDTH: f32 = 0.85;
+/// The minimum number of pieces the spiral is drawn with, so that it stays
+/// smooth at low resolutions.
+const MIN_PIECES: usize = 720;
+/// The distance between the spiral and the selected region marker.
+const REGION_MARKER_GAP: f32 = 3.0;
+
+/// The array drawn as an Archimedean spiral of colored segments, running
+/// clockwise from the center outwards. This can be drawn in place of the
+/// [`ColorWheel`].
+///
+/// Neighboring elements sit next to each other along the spiral, while
+/// elements a turn apart sit next to each other across it, so blocks of the
+/// array (such as merge runs or radix buckets) show up as bands. Like the
+/// [`ScatterPlot`], the spiral mirrors the wheel which it is synced from (see
+/// [`Self::sync()`]).
+#[derive(Debug)]
+pub struct Spiral {
+    /// The color of each element's segment.
+    colors: Vec<Rgb<f32>>,
+    /// The range of elements which is marked as selected, if any.
+    region: Option<Range<usize>>,
+    /// The position of the center of the spiral in the window.
+    center: Vec2,
+    /// The scale which the spiral is drawn at, relative to its natural size.
+    scale: f32,
+}
+
+impl Spiral {
+    pub fn new() -> Self {
+        Self {
+            colors: vec![],
+            region: None,
+            center: Vec2::ZERO,
+            scale: 1.0,
+        }
+    }
+
+    /// Copies the colors and selected region of `wheel`.
+    pub fn sync(&mut self, wheel: &ColorWheel) {
+        let res = wheel.resolution();
+
+        self.colors.clear();
+        self.colors.extend((0..res).map(|slice| wheel.slice_color(slice)));
+        self.region = wheel.region();
+    }
+
+    /// Sets the position and scale which the spiral is drawn at.
+    pub fn set_transform(&mut self, center: Vec2, scale: f32) {
+        self.center = center;
+        self.scale = scale;
+    }
+
+    /// Returns the element at `pos` in the window, or `None` if `pos` isn't
+    /// on the spiral.
+    pub fn element_at(&self, pos: Vec2) -> Option<usize> {
+        let res = self.colors.len();
+        let pos = (pos - self.center) / self.scale;
+
+        // the proportion of a turn, starting from the top and going
+        // clockwise, as with the wheel.
+        let theta = pos.y.atan2(-pos.x);
+        let t = (theta - FRAC_PI_2).rem_euclid(TAU) / TAU;
+
+        // the turn which passes closest to `pos`.
+        let turn = ((pos.length() - INNER_RADIUS) / PITCH - t).round();
+        let turns = turn + t;
+        let dist = (pos.length() - Self::radius_at(turns)).abs();
+
+        ((0.0..TURNS).contains(&turns) && dist <= PITCH * BAND_WIDTH * 0.5)
+            .then(|| ((turns / TURNS * res as f32) as usize).min(res - 1))
+    }
+
+    /// The radius of the middle of the spiral's band after `turns` turns.
+    fn radius_at(turns: f32) -> f32 {
+        turns.mul_add(PITCH, INNER_RADIUS)
+    }
+
+    /// The point at `offset` from the middle of the spiral's band after
+    /// `turns` turns.
+    fn point(turns: f32, offset: f32) -> Vec3 {
+        let theta = turns.mul_add(TAU, FRAC_PI_2);
+        let (y, x) = theta.sin_cos();
+        let radius = Self::radius_at(turns) + offset;
+
+        vec3(-x * radius, y * radius, 0.0)
+    }
+
+    /// Returns `draw` transformed to the spiral's position and scale.
+    fn transform(&self, draw: &Draw) -> Draw {
+        draw.translate(self.center.extend(0.0)).scale(self.scale)
+    }
+}
+
+impl Updatable for Spiral {
+    fn update(&mut self, _: &App, _: UpdateData) {}
+}
+
+impl Drawable for Spiral {
+    fn draw(&self, draw: &Draw, _: UpdateData) {
+        let res = self.colors.len();
+
+        if res == 0 {
+            return;
+        }
+
+        let draw = self.transform(draw);
+        let half_width = PITCH * BAND_WIDTH * 0.5;
+        // each segment is split into pieces, so that long segments still
+        // follow the curve of the spiral.
+        let pieces = MIN_PIECES.div_ceil(res);
+        let turns_per_piece = TURNS / (res * pieces) as f32;
+
+        let points = self.colors.iter().enumerate().flat_map(|(i, &color)| {
+            (0..pieces).flat_map(move |p| {
+                let start = (i * pieces + p) as f32 * turns_per_piece;
+                let end = start + turns_per_piece;
+
+                let inner = |turns| Self::point(turns, -half_width);
+                let outer = |turns| Self::point(turns, half_width);
+                let (a, b) = (inner(start), outer(start));
+                let (c, d) = (inner(end), outer(end));
+
+                [a, b, d, a, d, c].map(|p| (p, color))
+            })
+        });
+
+        draw.mesh().points_colored(points).xy(Vec2::ZERO);
+
+        if let Some(region) = &self.region {
+            let offset = half_width + REGION_MARKER_GAP;
+            let turns_per_element = TURNS / res as f32;
+            let (start, end) = (
+                region.start as f32 * turns_per_element,
+                region.end as f32 * turns_per_element,
+            );
+            let steps = (region.len() * pieces).max(1);
+
+            let points = (0..=steps).map(|s| {
+                let t = s as f32 / steps as f32;
+                Self::point((end - start).mul_add(t, start), offset).truncate()
+            });
+
+            draw.polyline().weight(3.0).points(points).color(WHITE);
+        }
+    }
+}
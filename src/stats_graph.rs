@@ -0,0 +1,116 @@
+use super::*;
+
+/// The kinds of operation which are plotted, in the order they are drawn.
+const LINES: [OperationKind; 4] = [
+    OperationKind::Comparisons,
+    OperationKind::Writes,
+    OperationKind::Swaps,
+    OperationKind::Reads,
+];
+/// The color of the graph's border.
+const BORDER_COLOR: Rgb<f32> = Rgb {
+    red: 0.25,
+    green: 0.25,
+    blue: 0.25,
+    standard: std::marker::PhantomData,
+};
+
+/// A small plot of the number of each kind of operation performed so far
+/// against playback progress, which is drawn beneath the wheel.
+///
+/// The plot is built up in real time from the operations performed each
+/// frame (see [`Self::record()`]), so the slope of each line shows how busy
+/// the algorithm is with that kind of operation at each point of playback.
+/// All of the lines share the same scale.
+#[derive(Debug)]
+pub struct StatsGraph {
+    /// The playback progress at each frame, along with the number of each
+    /// kind of operation (in the order of [`LINES`]) performed by then, in
+    /// ascending order of progress.
+    samples: Vec<(f32, [usize; LINES.len()])>,
+    /// The area of the window which the graph covers.
+    rect: Rect,
+}
+
+impl StatsGraph {
+    pub fn new() -> Self {
+        Self { samples: vec![], rect: Rect::from_w_h(0.0, 0.0) }
+    }
+
+    /// Adds the operations performed in the last frame, which brought
+    /// playback to `progress`. If playback has gone backward, then the
+    /// samples after `progress` are removed instead, and if there is no
+    /// capture then the graph is cleared.
+    pub fn record(&mut self, progress: Option<f32>, ops: &OperationSlice) {
+        let Some(progress) = progress else {
+            self.samples.clear();
+            return;
+        };
+
+        let mut counts = match self.samples.last() {
+            // while paused, the same operations are provided every frame.
+            Some(&(last, _)) if progress == last => return,
+            Some(&(last, _)) if progress < last => {
+                let keep = self.samples.partition_point(|s| s.0 <= progress);
+                self.samples.truncate(keep);
+                return;
+            }
+            Some(&(_, counts)) => counts,
+            None => [0; LINES.len()],
+        };
+
+        for &op in ops.iter() {
+            let kind = OperationKind::of(op);
+
+            if let Some(i) = LINES.iter().position(|&k| k == kind) {
+                counts[i] += 1;
+            }
+        }
+
+        self.samples.push((progress, counts));
+    }
+
+    /// Sets the area of the window which the graph covers.
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+}
+
+impl Updatable for StatsGraph {
+    fn update(&mut self, _: &App, _: UpdateData) {}
+}
+
+impl Drawable for StatsGraph {
+    fn draw(&self, draw: &Draw, _: UpdateData) {
+        let Some(&(_, totals)) = self.samples.last() else {
+            return;
+        };
+
+        let rect = self.rect;
+
+        draw.rect()
+            .xy(rect.xy())
+            .wh(rect.wh())
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke_color(BORDER_COLOR);
+
+        // the counts only ever increase, so the latest sample is the largest.
+        let max = totals.iter().copied().max().unwrap_or(0).max(1) as f32;
+
+        for (i, &kind) in LINES.iter().enumerate() {
+            if totals[i] == 0 {
+                continue;
+            }
+
+            let points = self.samples.iter().map(|(progress, counts)| {
+                vec2(
+                    progress.clamp(0.0, 1.0).mul_add(rect.w(), rect.left()),
+                    (counts[i] as f32 / max).mul_add(rect.h(), rect.bottom()),
+                )
+            });
+
+            draw.polyline().weight(1.5).points(points).color(kind.color());
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! The colors of the window around the visuals.
+
+use super::*;
+
+/// The background and text colors of the window.
+///
+/// Themes can be loaded from a file where each line sets a color, e.g.
+/// `background = #202020` — see [`Self::parse()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// The colors at the top and bottom of the background, which are the same
+    /// for a solid background.
+    pub background: (Rgb<f32>, Rgb<f32>),
+    /// The color of the text.
+    pub text: Rgb<f32>,
+}
+
+impl Theme {
+    /// Parses a theme, where each line is a `key = value` pair. The keys are
+    /// `background`, whose value is a hex color (e.g. `#202020`) or two hex
+    /// colors separated by a comma for a vertical gradient from the top of
+    /// the window to the bottom, and `text`, whose value is a hex color.
+    /// Keys which aren't set keep their default colors, and blank lines and
+    /// anything after a `#` followed by a space are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first line which is invalid.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut theme = Self::default();
+
+        for (i, line) in text.lines().enumerate() {
+            // colors start with a `#`, so comments need a space after it.
+            let line = line.split("# ").next().unwrap_or_default().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            theme
+                .set(line)
+                .map_err(|e| format!("line {}: {e}", i + 1))?;
+        }
+
+        Ok(theme)
+    }
+
+    /// Reads and parses the theme at `path` — see [`Self::parse()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {path}: {e}"))?;
+
+        Self::parse(&text)
+    }
+
+    /// Draws the background across `rect`, which should cover the window.
+    pub fn draw_background(&self, draw: &Draw, rect: Rect) {
+        let (top, bottom) = self.background;
+
+        draw.background().color(top);
+
+        if top == bottom {
+            return;
+        }
+
+        let (tl, tr) = (rect.top_left(), rect.top_right());
+        let (bl, br) = (rect.bottom_left(), rect.bottom_right());
+
+        let points = [
+            (tl, top),
+            (tr, top),
+            (br, bottom),
+            (tl, top),
+            (br, bottom),
+            (bl, bottom),
+        ];
+
+        draw.mesh().points_colored(points.map(|(p, c)| (p.extend(0.0), c)));
+    }
+
+    /// Sets the value of a `key = value` pair.
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("expected `key = value`, found `{line}`"));
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        match key {
+            "background" => {
+                let colors = value
+                    .split(',')
+                    .map(|c| parse_hex_color(c.trim()))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                self.background = match colors[..] {
+                    [color] => (color, color),
+                    [top, bottom] => (top, bottom),
+                    _ => {
+                        return Err(format!(
+                            "expected one or two colors, found `{value}`"
+                        ))
+                    }
+                };
+            }
+            "text" => self.text = parse_hex_color(value)?,
+            _ => return Err(format!("unknown key `{key}`")),
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let black = Rgb::new(0.0, 0.0, 0.0);
+
+        Self { background: (black, black), text: Rgb::new(1.0, 1.0, 1.0) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_theme() {
+        let text = "# a light theme\nbackground = #ffffff, #808080\n\n\
+                    text = #000000  # black";
+        let theme = Theme::parse(text).unwrap();
+
+        assert_eq!(
+            theme,
+            Theme {
+                background: (
+                    Rgb::new(1.0, 1.0, 1.0),
+                    Rgb::new(128.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0)
+                ),
+                text: Rgb::new(0.0, 0.0, 0.0),
+            }
+        );
+        assert_eq!(
+            Theme::parse("text = #000000\nforeground = #ffffff").unwrap_err(),
+            "line 2: unknown key `foreground`"
+        );
+        assert!(
+            Theme::parse("background = #000000, #111111, #222222").is_err()
+        );
+    }
+}
@@ -3,54 +3,190 @@ use super::*;
 use crate::prelude::*;
 use nannou::text::*;
 
+/// The time which messages are shown for, in seconds — see [`Ui::notify()`].
+const NOTICE_DURATION: f32 = 3.0;
+/// The number of segments in the compressor's gain reduction meter.
+const METER_SEGMENTS: usize = 12;
+/// The gain reduction shown by each segment of the meter, in decibels.
+const METER_SEGMENT_DB: f32 = 2.0;
+
 #[derive(Clone, Copy, Debug)]
 pub struct UiData {
     pub algorithm: SortingAlgorithm,
+    pub distribution: Distribution,
+    /// The name of the palette which values are colored with.
+    pub palette: &'static str,
+    /// The amount which lighten and darken overlays are scaled by.
+    pub overlay_intensity: f32,
+    pub uniform_shuffle: bool,
+    pub shuffle_intensity: f32,
     pub data: Option<SortData>,
     pub resolution: usize,
     pub player_time: f32,
     pub speed: f32,
+    /// The index of the active speed preset, if the speed matches one.
+    pub speed_preset: Option<usize>,
+    pub curve: PlaybackCurve,
+    /// The scale which notes are quantized to.
+    pub tuning: Tuning,
+    /// How notes are placed in the stereo field.
+    pub panning: Panning,
+    /// The amplitude envelope of new notes.
+    pub adsr: Adsr,
+    /// The tone control on the output.
+    pub tone: ToneParams,
+    /// The echoes on the output.
+    pub delay: DelayParams,
+    /// The reverb on the output.
+    pub reverb: ReverbParams,
+    /// The operations which are highlighted and sonified.
+    pub op_filter: OperationFilter,
+    /// The operations which are sonified, out of those which are shown.
+    pub sound_filter: OperationFilter,
+    /// The playback progress between `0.0` and `1.0`, if there is a capture.
+    pub progress: Option<f32>,
+    /// The estimated playback time remaining in seconds, if it is known.
+    pub time_remaining: Option<f32>,
+    /// The start and end of the A–B loop as progress, if one is marked.
+    pub loop_points: Option<(f32, Option<f32>)>,
+    pub real_time: bool,
+    pub av_offset_ms: f32,
     pub num_voices: u32,
+    /// The polyphony cap, and what happens to new notes beyond it.
+    pub polyphony: Polyphony,
     pub dsp_load: f32,
+    /// The fraction of notes dropped to relieve the audio engine.
+    pub note_thinning: f32,
+    /// The gain reduction of the output compressor, in decibels.
+    pub gain_reduction_db: f32,
+    /// Whether the audio stream has died and is being rebuilt.
+    pub audio_lost: bool,
     pub sorted: bool,
     pub computing: bool,
+    /// The resolution which the wheel will be resized to once the current
+    /// sort has been computed, if a resize is pending.
+    pub pending_resolution: Option<usize>,
     pub shuffling: bool,
+    pub editing: bool,
+    /// The start and (exclusive) end of the selected region, if any.
+    pub region: Option<(usize, usize)>,
+    /// The opponent algorithm and its data, in race mode.
+    pub race: Option<(SortingAlgorithm, Option<SortData>)>,
+    /// The time until the next run in tour mode (or `None` while a run is in
+    /// progress), if tour mode is enabled.
+    pub tour: Option<Option<f32>>,
+    /// The number of captures in the player's history.
+    pub history_len: usize,
+    /// The number of algorithms in the playlist.
+    pub playlist_len: usize,
+    /// The number of actions recorded so far, if a macro is being recorded.
+    pub recorded_actions: Option<usize>,
 }
 
 #[derive(Debug)]
 pub struct Ui {
     text: String,
+    /// The area of the window which the text is drawn in.
+    rect: Rect,
+    /// The playback progress shown by the progress bar, if there is a
+    /// capture.
+    progress: Option<f32>,
+    /// The area of the window which the progress bar is drawn in.
+    progress_bar_rect: Rect,
+    /// The color of the text.
+    text_color: Rgb<f32>,
+    /// A message shown beneath the text, and when it was shown.
+    notice: Option<(String, Instant)>,
 }
 
 impl Ui {
-    pub const fn new() -> Self {
-        Self { text: String::new() }
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            rect: Rect::from_w_h(0.0, 0.0),
+            progress: None,
+            progress_bar_rect: Rect::from_w_h(0.0, 0.0),
+            text_color: Rgb::new(1.0, 1.0, 1.0),
+            notice: None,
+        }
+    }
+
+    /// Sets the color of the text — see [`Theme`].
+    pub fn set_text_color(&mut self, color: Rgb<f32>) {
+        self.text_color = color;
+    }
+
+    /// Sets the area of the window which the text is drawn in.
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    /// Sets the area of the window which the progress bar is drawn in.
+    pub fn set_progress_bar_rect(&mut self, rect: Rect) {
+        self.progress_bar_rect = rect;
+    }
+
+    /// Shows `message` beneath the text for [`NOTICE_DURATION`] seconds,
+    /// replacing any message which is already shown.
+    pub fn notify(&mut self, message: String) {
+        self.notice = Some((message, Instant::now()));
     }
 
     pub fn update_text(&mut self, ui_data: UiData) {
         let UiData {
             algorithm,
+            distribution,
+            palette,
+            overlay_intensity,
+            uniform_shuffle,
+            shuffle_intensity,
             data,
             resolution,
             player_time,
             speed,
+            speed_preset,
+            curve,
+            tuning,
+            panning,
+            adsr,
+            tone,
+            delay,
+            reverb,
+            op_filter,
+            sound_filter,
+            progress,
+            time_remaining,
+            loop_points,
+            real_time,
+            av_offset_ms,
             num_voices,
+            polyphony,
             dsp_load,
+            note_thinning,
+            gain_reduction_db,
+            audio_lost,
             sorted,
             computing,
+            pending_resolution,
             shuffling,
+            editing,
+            region,
+            history_len,
+            playlist_len,
+            tour,
+            race,
+            recorded_actions,
         } = ui_data;
 
-        let info = if computing {
-            String::from("Computing...")
-        }
-        else {
-            data.map_or_else(|| String::from("No data — no algorithm has been captured"), |data| {
-            let SortData { writes, reads, swaps, comparisons } = data;
-            format!(
-                "Writes: {writes}, reads: {reads}, swaps: {swaps}, comparisons: {comparisons}"
-            )
-        })
+        let info = match data {
+            // sorts are played back while they are computed.
+            Some(SortData { writes, reads, swaps, comparisons, truncated }) => format!(
+                "Writes: {writes}, reads: {reads}, swaps: {swaps}, comparisons: {comparisons}{}{}",
+                if computing { " (computing...)" } else { "" },
+                if truncated { " — stopped at the operation limit" } else { "" },
+            ),
+            None if computing => String::from("Computing..."),
+            None => String::from("No data — no algorithm has been captured"),
         };
         let algo = format!(
             "Algorithm: {}",
@@ -61,27 +197,187 @@ impl Ui {
                 algorithm.to_string()
             }
         );
-        let res = format!("{resolution} segments");
+        let distribution = match distribution {
+            Distribution::Shuffled if uniform_shuffle => {
+                format!("Distribution: {distribution} (Fisher–Yates)")
+            }
+            _ => format!("Distribution: {distribution}"),
+        };
+        let palette = format!("Palette: {palette}");
+        let intensity =
+            format!("Shuffle intensity: {:.0}%", shuffle_intensity * 100.0);
+        let res = match pending_resolution {
+            Some(pending) => format!(
+                "{resolution} segments (changing to {pending} once computed)"
+            ),
+            None => format!("{resolution} segments"),
+        };
         let sorted = format!("Sorted: {}", if sorted { "yes" } else { "no" });
+        let preset = speed_preset
+            .map_or_else(String::new, |idx| format!(" [preset {}]", idx + 1));
         let speed = format!(
-            "Speed: {speed:.2}x ({:.2}s playback time)",
-            player_time * speed.recip()
+            "Speed: {speed:.2}x{preset} ({:.2}s playback time, {curve}{})",
+            player_time * speed.recip(),
+            if real_time { ", real-time pacing" } else { "" }
+        );
+        self.progress = progress;
+
+        let progress = match (progress, loop_points) {
+            (Some(progress), Some((start, Some(end)))) => format!(
+                "Progress: {:.1}% (looping between {:.1}% and {:.1}%)",
+                progress * 100.0,
+                start * 100.0,
+                end * 100.0
+            ),
+            (Some(progress), Some((start, None))) => format!(
+                "Progress: {:.1}% (loop from {:.1}%)",
+                progress * 100.0,
+                start * 100.0
+            ),
+            (Some(progress), None) => {
+                format!("Progress: {:.1}%", progress * 100.0)
+            }
+            (None, _) => String::from("Progress: -"),
+        };
+        let remaining = time_remaining.map_or_else(
+            || String::from("Time remaining: -"),
+            |t| format!("Time remaining: {t:.1}s"),
+        );
+        let av_offset = format!("A/V offset: {av_offset_ms:+.0} ms");
+        let scale = format!("Scale: {tuning}");
+        let panning = format!("Panning: {panning}");
+        let envelope = format!("Envelope: {adsr}");
+        let tone = format!("Tone: {tone}");
+        let delay = format!("Delay: {delay}");
+        let reverb = format!("Reverb: {reverb}");
+        let voices = format!(
+            "Active voices: {num_voices}/{} ({})",
+            polyphony.max_voices, polyphony.steal
+        );
+        let dsp = if audio_lost {
+            String::from("Audio device lost — reconnecting...")
+        }
+        else if note_thinning > 0.0 {
+            format!(
+                "DSP load: {:.1}% (thinning notes by {:.0}%)",
+                dsp_load * 100.0,
+                note_thinning * 100.0
+            )
+        }
+        else {
+            format!("DSP load: {:.1}%", dsp_load * 100.0)
+        };
+        let compressor = format!(
+            "Compressor: {} {gain_reduction_db:.1} dB",
+            reduction_meter(gain_reduction_db)
         );
-        let voices =
-            format!("Active voices: {num_voices}/{}", super::audio::NUM_VOICES);
-        let dsp = format!("DSP load: {:.1}%", dsp_load * 100.0);
 
         self.text = format!(
-            "{algo}\n{res}\n{speed}\n{info}\n{sorted}\n{voices}\n{dsp}"
+            "{algo}\n{distribution}\n{palette}\n{intensity}\n{res}\n{speed}\n{progress}\n{remaining}\n{av_offset}\n{scale}\n{panning}\n{envelope}\n{tone}\n{delay}\n{reverb}\n{info}\n{sorted}\n{voices}\n{dsp}\n{compressor}"
         );
+
+        if overlay_intensity != 1.0 {
+            self.text.push_str(&format!(
+                "\nOverlay intensity: {:.0}%",
+                overlay_intensity * 100.0
+            ));
+        }
+
+        if !op_filter.shows_all() {
+            let hidden: Vec<_> =
+                op_filter.hidden().map(|k| k.to_string()).collect();
+            self.text.push_str(&format!("\nHidden: {}", hidden.join(", ")));
+        }
+
+        if !sound_filter.shows_all() {
+            let muted: Vec<_> =
+                sound_filter.hidden().map(|k| k.to_string()).collect();
+            self.text.push_str(&format!("\nMuted: {}", muted.join(", ")));
+        }
+
+        if let Some((start, end)) = region {
+            self.text.push_str(&format!(
+                "\nRegion: {start} to {} ({} elements)",
+                end - 1,
+                end - start
+            ));
+        }
+
+        match tour {
+            Some(Some(next_in)) => self.text.push_str(&format!(
+                "\nTour mode (next algorithm in {next_in:.1}s)"
+            )),
+            Some(None) => self.text.push_str("\nTour mode"),
+            None => {}
+        }
+
+        if let Some((opponent, data)) = race {
+            self.text.push_str(&format!("\nRacing against {opponent}"));
+
+            if let Some(SortData { writes, reads, swaps, comparisons, .. }) = data {
+                self.text.push_str(&format!(
+                    " (writes: {writes}, reads: {reads}, swaps: {swaps}, comparisons: {comparisons})"
+                ));
+            }
+        }
+
+        if history_len > 0 {
+            self.text.push_str(&format!(
+                "\nHistory: {history_len} previous capture{}",
+                if history_len == 1 { "" } else { "s" }
+            ));
+        }
+
+        if playlist_len > 0 {
+            self.text.push_str(&format!(
+                "\nPlaylist: {playlist_len} algorithm{}",
+                if playlist_len == 1 { "" } else { "s" }
+            ));
+        }
+
+        if editing {
+            self.text.push_str("\nEditing (drag across the wheel to set values)");
+        }
+
+        if let Some(n) = recorded_actions {
+            self.text.push_str(&format!("\nRecording macro ({n} actions)..."));
+        }
+
+        if let Some((message, shown_at)) = &self.notice {
+            if shown_at.elapsed().as_secs_f32() < NOTICE_DURATION {
+                self.text.push_str(&format!("\n\n{message}"));
+            }
+            else {
+                self.notice = None;
+            }
+        }
     }
 
     pub fn draw(&self, draw: &Draw) {
         draw.text(&self.text)
             .layout(&default_layout())
-            .xy(vec2(-135.0, -320.0))
-            .wh(vec2(500.0, 300.0))
-            .color(WHITE);
+            .xy(self.rect.xy())
+            .wh(self.rect.wh())
+            .color(self.text_color);
+
+        self.draw_progress_bar(draw);
+    }
+
+    /// Draws the progress bar beneath the wheel, which fills from left to
+    /// right as the capture is played back.
+    fn draw_progress_bar(&self, draw: &Draw) {
+        let Some(progress) = self.progress else {
+            return;
+        };
+
+        let rect = self.progress_bar_rect;
+        let filled_w = rect.w() * progress.clamp(0.0, 1.0);
+
+        draw.rect().xy(rect.xy()).wh(rect.wh()).color(rgb(0.15, 0.15, 0.15));
+        draw.rect()
+            .x_y(rect.left() + filled_w * 0.5, rect.y())
+            .w_h(filled_w, rect.h())
+            .color(rgb(0.85, 0.85, 0.85));
     }
 }
 
@@ -90,6 +386,17 @@ fn default_layout() -> Layout {
         justify: Justify::Left,
         font_size: 16,
         line_spacing: 3.0,
+        // the text is anchored to the top of its area.
+        y_align: Align::End,
         ..Default::default()
     }
 }
+
+/// Draws a text meter of `reduction_db` decibels of gain reduction, which
+/// fills a segment for every [`METER_SEGMENT_DB`] decibels.
+fn reduction_meter(reduction_db: f32) -> String {
+    let filled = ((reduction_db / METER_SEGMENT_DB).round() as usize)
+        .min(METER_SEGMENTS);
+
+    format!("[{}{}]", "|".repeat(filled), ".".repeat(METER_SEGMENTS - filled))
+}